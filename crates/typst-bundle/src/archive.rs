@@ -0,0 +1,241 @@
+use std::fmt::Display;
+use std::io::{Cursor, Write};
+
+use ecow::{EcoString, eco_format};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use typst_library::diag::StrResult;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::VirtualFs;
+
+/// An archive format into which a bundle can be written.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ArchiveFormat {
+    /// A ZIP archive with deflate compression.
+    Zip,
+    /// An uncompressed tarball.
+    Tar,
+    /// A gzip-compressed tarball.
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Determines the archive format from a file name, if it has one of the
+    /// supported extensions (`.zip`, `.tar`, `.tar.gz`, or `.tgz`).
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Settings for writing archives.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ArchiveOptions {
+    /// The modification time of all entries, in seconds since the Unix epoch.
+    ///
+    /// Together with the sorted entries, a fixed timestamp makes archives
+    /// reproducible byte-for-byte. ZIP archives cannot represent times before
+    /// 1980, so earlier times are clamped.
+    pub timestamp: i64,
+}
+
+/// Writes the files of a bundle into an archive.
+///
+/// The entries are sorted by path, so that the archive does not depend on
+/// the order in which the files were produced.
+#[typst_macros::time(name = "write archive")]
+pub fn archive(
+    fs: &VirtualFs,
+    format: ArchiveFormat,
+    options: &ArchiveOptions,
+) -> StrResult<Vec<u8>> {
+    let mut entries: Vec<_> = fs
+        .iter()
+        .map(|(path, data)| (path.get_without_slash(), data.as_slice()))
+        .collect();
+    entries.sort_by_key(|&(path, _)| path);
+
+    match format {
+        ArchiveFormat::Zip => write_zip(&entries, options),
+        ArchiveFormat::Tar => write_tar(Vec::new(), &entries, options),
+        ArchiveFormat::TarGz => {
+            // The gzip header's own timestamp is left at zero.
+            let encoder = GzEncoder::new(Vec::new(), Compression::default());
+            write_tar(encoder, &entries, options)?.finish().map_err(failed)
+        }
+    }
+}
+
+/// Writes the entries into a ZIP archive.
+fn write_zip(entries: &[(&str, &[u8])], options: &ArchiveOptions) -> StrResult<Vec<u8>> {
+    let modified = zip_datetime(options.timestamp);
+    let file_options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(modified)
+        .unix_permissions(0o644);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (path, data) in entries {
+        zip.start_file(*path, file_options).map_err(failed)?;
+        zip.write_all(data).map_err(failed)?;
+    }
+
+    Ok(zip.finish().map_err(failed)?.into_inner())
+}
+
+/// Writes the entries into a tarball.
+fn write_tar<W: Write>(
+    writer: W,
+    entries: &[(&str, &[u8])],
+    options: &ArchiveOptions,
+) -> StrResult<W> {
+    let mut builder = tar::Builder::new(writer);
+    for (path, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mtime(options.timestamp.max(0) as u64);
+        builder.append_data(&mut header, path, *data).map_err(failed)?;
+    }
+    builder.into_inner().map_err(failed)
+}
+
+/// Converts a Unix timestamp into a ZIP timestamp.
+fn zip_datetime(timestamp: i64) -> zip::DateTime {
+    time::OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|datetime| {
+            zip::DateTime::from_date_and_time(
+                datetime.year().try_into().ok()?,
+                datetime.month().into(),
+                datetime.day(),
+                datetime.hour(),
+                datetime.minute(),
+                datetime.second(),
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// The error message for a failure while writing an archive.
+#[cold]
+fn failed(err: impl Display) -> EcoString {
+    eco_format!("failed to write archive ({err})")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use typst_library::foundations::Bytes;
+    use typst_syntax::VirtualPath;
+
+    use super::*;
+
+    /// 2023-11-14T22:13:20Z
+    const TIMESTAMP: i64 = 1_700_000_000;
+
+    fn fs(files: &[(&str, &'static str)]) -> VirtualFs {
+        files
+            .iter()
+            .map(|&(path, data)| {
+                (VirtualPath::new(path).unwrap(), Bytes::from_string(data))
+            })
+            .collect()
+    }
+
+    fn tar_entries(data: &[u8]) -> Vec<(String, u64, String)> {
+        let mut archive = tar::Archive::new(data);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mtime = entry.header().mtime().unwrap();
+                let mut contents = String::new();
+                entry.read_to_string(&mut contents).unwrap();
+                (path, mtime, contents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_archive_is_reproducible() {
+        let a = fs(&[("b.txt", "B"), ("a/c.txt", "C")]);
+        let b = fs(&[("a/c.txt", "C"), ("b.txt", "B")]);
+        let options = ArchiveOptions { timestamp: TIMESTAMP };
+        for format in [ArchiveFormat::Zip, ArchiveFormat::Tar, ArchiveFormat::TarGz] {
+            let first = archive(&a, format, &options).unwrap();
+            assert_eq!(first, archive(&a, format, &options).unwrap(), "{format:?}");
+            assert_eq!(first, archive(&b, format, &options).unwrap(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_archive_tar() {
+        let fs = fs(&[("b.txt", "B"), ("a/c.txt", "C")]);
+        let options = ArchiveOptions { timestamp: TIMESTAMP };
+        let tar = archive(&fs, ArchiveFormat::Tar, &options).unwrap();
+        assert_eq!(
+            tar_entries(&tar),
+            [
+                ("a/c.txt".to_string(), TIMESTAMP as u64, "C".to_string()),
+                ("b.txt".to_string(), TIMESTAMP as u64, "B".to_string()),
+            ]
+        );
+
+        // The gzip header has no timestamp and wraps the same tarball.
+        let gz = archive(&fs, ArchiveFormat::TarGz, &options).unwrap();
+        assert_eq!(gz[4..8], [0; 4]);
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(gz.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, tar);
+    }
+
+    #[test]
+    fn test_archive_zip() {
+        let fs = fs(&[("b.txt", "B"), ("a/c.txt", "C")]);
+        let options = ArchiveOptions { timestamp: TIMESTAMP };
+        let zip = archive(&fs, ArchiveFormat::Zip, &options).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(zip)).unwrap();
+        let mut names = vec![];
+        for i in 0..archive.len() {
+            let file = archive.by_index(i).unwrap();
+            let modified = file.last_modified().unwrap();
+            assert_eq!(
+                (modified.year(), modified.month(), modified.day()),
+                (2023, 11, 14)
+            );
+            assert_eq!(
+                (modified.hour(), modified.minute(), modified.second()),
+                (22, 13, 20)
+            );
+            names.push(file.name().to_string());
+        }
+        assert_eq!(names, ["a/c.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn test_zip_datetime_clamps_to_1980() {
+        assert_eq!(zip_datetime(0), zip::DateTime::default());
+        assert_eq!(zip_datetime(-1), zip::DateTime::default());
+        assert_eq!(zip_datetime(315_532_800).year(), 1980);
+    }
+}
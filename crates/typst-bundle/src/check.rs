@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use ecow::{EcoString, EcoVec};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst_html::{HtmlDocument, HtmlElement, HtmlNode, attr};
+use typst_library::diag::{SourceDiagnostic, warning};
+use typst_library::foundations::{NativeElement, Packed};
+use typst_library::introspection::Introspector;
+use typst_library::model::{Destination, LinkElem, LinkTarget};
+use typst_syntax::{Span, VirtualPath};
+use url::Url;
+
+use crate::{Bundle, BundleDocument, BundleFile};
+
+/// Settings for checking links.
+#[derive(Default, Copy, Clone)]
+pub struct LinkCheckOptions<'a> {
+    /// Determines whether a file exists on the local file system.
+    ///
+    /// This is used to check `file://` links, receiving the local path that
+    /// the URL refers to. If `None`, such links are not checked.
+    pub file_exists: Option<&'a dyn Fn(&Path) -> bool>,
+}
+
+/// Checks a bundle for links whose destination does not exist.
+///
+/// This covers relative URLs to files and anchors within the bundle, both
+/// from `link` calls and from `src` and `href` attributes in HTML documents.
+/// Links to labels are already checked during compilation, so they always
+/// resolve. Returns a warning for each broken link.
+#[typst_macros::time(name = "check links")]
+pub fn check_links(
+    bundle: &Bundle,
+    options: &LinkCheckOptions,
+) -> EcoVec<SourceDiagnostic> {
+    let files: FxHashMap<&VirtualPath, Option<FxHashSet<&str>>> = bundle
+        .files
+        .iter()
+        .map(|(path, file)| {
+            let ids = match file {
+                BundleFile::Document(BundleDocument::Html(doc, _)) => {
+                    Some(ids(doc.root()))
+                }
+                _ => None,
+            };
+            (path, ids)
+        })
+        .collect();
+
+    let mut checker = Checker {
+        files: Some(&files),
+        options,
+        warnings: EcoVec::new(),
+    };
+    for (path, file) in bundle.files.iter() {
+        let BundleFile::Document(doc) = file else { continue };
+        let own = files[path].as_ref();
+        for (url, span) in references(doc) {
+            checker.check(Some(path), own, &url, span);
+        }
+    }
+
+    checker.warnings
+}
+
+/// Checks a standalone HTML document for links whose destination does not
+/// exist.
+///
+/// Since the document is not part of a bundle, only links to anchors within
+/// the document itself and `file://` links can be checked.
+#[typst_macros::time(name = "check links")]
+pub fn check_html_links(
+    document: &HtmlDocument,
+    options: &LinkCheckOptions,
+) -> EcoVec<SourceDiagnostic> {
+    let own = ids(document.root());
+    let mut references = vec![];
+    html_references(document.root(), &mut references);
+
+    let mut checker = Checker { files: None, options, warnings: EcoVec::new() };
+    for (url, span) in references {
+        checker.check(None, Some(&own), &url, span);
+    }
+
+    checker.warnings
+}
+
+/// Checks links against the files in a bundle.
+struct Checker<'a> {
+    /// The files in the bundle along with the IDs in the HTML documents.
+    /// `None` when checking a standalone document.
+    files: Option<&'a FxHashMap<&'a VirtualPath, Option<FxHashSet<&'a str>>>>,
+    options: &'a LinkCheckOptions<'a>,
+    warnings: EcoVec<SourceDiagnostic>,
+}
+
+impl Checker<'_> {
+    /// Checks a single link from the document at the path `base`, which has
+    /// the given IDs if it is an HTML document.
+    fn check(
+        &mut self,
+        base: Option<&VirtualPath>,
+        own: Option<&FxHashSet<&str>>,
+        url: &str,
+        span: Span,
+    ) {
+        let (rest, fragment) = match url.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (url, None),
+        };
+        let path = rest.split_once('?').map_or(rest, |(path, _)| path);
+
+        // A link to an anchor in the same document.
+        if path.is_empty() {
+            if let Some(fragment) = fragment
+                && let Some(ids) = own
+                && !fragment.is_empty()
+                && !ids.contains(decode(fragment).as_str())
+            {
+                self.warnings.push(warning!(
+                    span,
+                    "anchor `#{fragment}` does not exist in this document"
+                ));
+            }
+            return;
+        }
+
+        if path.starts_with("file://") {
+            let Some(exists) = self.options.file_exists else { return };
+            match Url::parse(url).ok().and_then(|url| url.to_file_path().ok()) {
+                Some(file) if !exists(&file) => self.warnings.push(warning!(
+                    span,
+                    "file `{}` does not exist",
+                    file.display()
+                )),
+                Some(_) => {}
+                None => self.warnings.push(warning!(
+                    span,
+                    "link destination `{url}` is not a valid local file URL"
+                )),
+            }
+            return;
+        }
+
+        // Skip links with a scheme (like `https:`) and protocol-relative
+        // links, which point outside of the bundle.
+        let first = path.split('/').next().unwrap_or_default();
+        if first.contains(':') || path.starts_with("//") {
+            return;
+        }
+
+        let (Some(files), Some(parent)) =
+            (self.files, base.and_then(VirtualPath::parent))
+        else {
+            return;
+        };
+
+        let Ok(target) = parent.join(&decode(path)) else {
+            self.warnings.push(warning!(
+                span,
+                "link destination `{url}` is outside of the bundle"
+            ));
+            return;
+        };
+
+        // Links to a directory are served with its index page.
+        let index = target.join("index.html").ok();
+        let Some((target, ids)) = files
+            .get_key_value(&target)
+            .or_else(|| files.get_key_value(index.as_ref()?))
+        else {
+            self.warnings.push(warning!(
+                span, "link destination `{url}` does not exist in the bundle";
+                hint: "it resolves to `{}`", target.get_with_slash();
+            ));
+            return;
+        };
+
+        if let Some(fragment) = fragment
+            && let Some(ids) = ids
+            && !fragment.is_empty()
+            && !ids.contains(decode(fragment).as_str())
+        {
+            self.warnings.push(warning!(
+                span,
+                "anchor `#{fragment}` does not exist in `{}`",
+                target.get_without_slash()
+            ));
+        }
+    }
+}
+
+/// Collects the URLs that a document refers to, along with the spans of the
+/// elements that contain them.
+fn references(doc: &BundleDocument) -> Vec<(EcoString, Span)> {
+    let mut references = vec![];
+    match doc {
+        BundleDocument::Html(doc, _) => html_references(doc.root(), &mut references),
+        BundleDocument::Paged(doc, _) => {
+            for elem in doc.introspector().query(&LinkElem::ELEM.select()) {
+                let Some(link) = elem.to_packed::<LinkElem>() else { continue };
+                if let Some(url) = url(link) {
+                    references.push((url, link.span()));
+                }
+            }
+        }
+    }
+    references
+}
+
+/// The URL a link element points to, if any.
+fn url(link: &Packed<LinkElem>) -> Option<EcoString> {
+    match &link.dest {
+        LinkTarget::Dest(Destination::Url(url)) => Some(url.clone().into_inner()),
+        _ => None,
+    }
+}
+
+/// Collects the values of all `href` and `src` attributes in an element.
+fn html_references(elem: &HtmlElement, references: &mut Vec<(EcoString, Span)>) {
+    for (key, value) in &elem.attrs.0 {
+        if *key == attr::href || *key == attr::src {
+            references.push((value.clone(), elem.span));
+        }
+    }
+    for child in &elem.children {
+        if let HtmlNode::Element(child) = child {
+            html_references(child, references);
+        }
+    }
+}
+
+/// Collects the IDs of all elements, which anchors in links can refer to.
+fn ids(root: &HtmlElement) -> FxHashSet<&str> {
+    let mut ids = FxHashSet::default();
+    let mut stack = vec![root];
+    while let Some(elem) = stack.pop() {
+        if let Some(id) = elem.attrs.get(attr::id) {
+            ids.insert(id.as_str());
+        }
+        stack.extend(elem.children.iter().filter_map(|child| match child {
+            HtmlNode::Element(child) => Some(child),
+            _ => None,
+        }));
+    }
+    ids
+}
+
+/// Decodes percent-encoded characters in a part of a URL.
+fn decode(part: &str) -> String {
+    percent_encoding::percent_decode_str(part)
+        .decode_utf8_lossy()
+        .into_owned()
+}
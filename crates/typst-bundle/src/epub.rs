@@ -0,0 +1,712 @@
+use std::fmt::{Display, Write as _};
+use std::io::{Cursor, Write};
+
+use comemo::Track;
+use ecow::{EcoString, eco_format};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rustc_hash::FxHashSet;
+use typst_html::{HtmlElement, HtmlNode, HtmlOptions, attr, tag};
+use typst_library::diag::{
+    At, ParallelCollectCombinedResult, SourceResult, StrResult, bail,
+};
+use typst_library::foundations::{Bytes, Datetime, Smart};
+use typst_library::model::{Document, DocumentInfo, LateLinkResolver, PagedFormat};
+use typst_library::text::{Font, FontStyle};
+use typst_syntax::{Span, VirtualPath};
+use xmlwriter::XmlWriter;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::export_::{
+    export_document, export_html, html_options, shared_stylesheet, stylesheet_path,
+};
+use crate::util::{collect_text, format_modified, heading_level, write_text_element};
+use crate::{Bundle, BundleDocument, BundleFile, BundleOptions};
+
+/// Settings for EPUB export.
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct EpubOptions {
+    /// A unique identifier for the publication, like an ISBN URN or a UUID
+    /// URN. If `None`, a UUID is derived from the title and authors.
+    pub identifier: Option<EcoString>,
+    /// When the publication was last modified. If `None`, the date of the
+    /// document is used, falling back to the Unix epoch.
+    pub modified: Option<Datetime>,
+    /// Fonts to package with the publication.
+    ///
+    /// They are declared through `@font-face` rules in the shared stylesheet
+    /// and their families become the default font of the chapters, in the
+    /// given order.
+    pub fonts: Vec<Font>,
+}
+
+/// Exports a bundle into an EPUB 3 publication.
+///
+/// The HTML documents in the bundle become the chapters of the publication,
+/// in the order in which they appear in the bundle. They are serialized as
+/// XHTML. All other files and the configured fonts are packaged as resources
+/// that the chapters can refer to. The publication's metadata is taken from the first HTML document
+/// and its table of contents is generated from the headings of all chapters.
+#[typst_macros::time(name = "export epub")]
+pub fn epub(bundle: &Bundle, options: &BundleOptions) -> SourceResult<Vec<u8>> {
+    let Some(info) = bundle.files.values().find_map(|file| match file {
+        BundleFile::Document(doc @ BundleDocument::Html(..)) => Some(doc.info()),
+        _ => None,
+    }) else {
+        bail!(
+            Span::detached(),
+            "EPUB export requires at least one HTML document";
+            hint: "add a `document` with a path that ends in `.html`";
+        );
+    };
+
+    let Some(title) = &info.title else {
+        bail!(
+            Span::detached(),
+            "EPUB export requires a document title";
+            hint: "set the title with `set document(title: [...])`";
+        );
+    };
+
+    for reserved in [NAV_PATH, PACKAGE_PATH] {
+        if bundle.files.contains_key(&VirtualPath::new(reserved).unwrap()) {
+            bail!(
+                Span::detached(),
+                "bundle already contains a file at {reserved}";
+                hint: "this path is used by EPUB export";
+            );
+        }
+    }
+
+    let html = HtmlOptions {
+        xhtml: true,
+        fragment: false,
+        ..options.html.clone()
+    };
+    let fonts = package_fonts(bundle, &options.epub.fonts)?;
+    let mut stylesheet = shared_stylesheet(bundle, &html)?;
+    if !fonts.is_empty() {
+        let (path, classes) = match stylesheet {
+            Some((path, classes)) => (path, Some(classes)),
+            None => (stylesheet_path(bundle)?, None),
+        };
+        let mut css = font_css(&fonts, &path);
+        if let Some(classes) = classes {
+            css.push_str(classes.as_str().unwrap_or_default());
+        }
+        stylesheet = Some((path, Bytes::from_string(css)));
+    }
+
+    let mut resources: Vec<Resource> = bundle
+        .files
+        .par_iter()
+        .map(|(path, file)| {
+            let link_resolver =
+                LateLinkResolver::new(Some(path), bundle.introspector.as_ref());
+            let resource = match file {
+                BundleFile::Document(BundleDocument::Html(doc, extras)) => {
+                    let href = stylesheet.as_ref().and_then(|(sheet_path, _)| {
+                        Some(sheet_path.relative_from(&path.parent()?))
+                    });
+                    let (root, mut chapter) = prepare_chapter(doc.root());
+                    chapter.title = doc.info().title.clone();
+                    Resource {
+                        path: path.clone(),
+                        data: export_html(
+                            &root,
+                            &html_options(&html, &extras.options),
+                            link_resolver.track(),
+                            href.as_deref(),
+                        )?,
+                        media_type: XHTML,
+                        chapter: Some(chapter),
+                    }
+                }
+                BundleFile::Document(doc @ BundleDocument::Paged(_, extras)) => {
+                    Resource {
+                        path: path.clone(),
+                        data: export_document(doc, options, link_resolver.track(), None)?,
+                        media_type: paged_media_type(extras.format),
+                        chapter: None,
+                    }
+                }
+                BundleFile::Asset(bytes) => Resource {
+                    path: path.clone(),
+                    data: bytes.clone(),
+                    media_type: asset_media_type(path),
+                    chapter: None,
+                },
+            };
+            Ok(resource)
+        })
+        .collect_combined_result()?;
+
+    resources.extend(fonts.into_iter().map(|(_, resource)| resource));
+
+    if let Some((path, css)) = stylesheet {
+        resources.push(Resource {
+            path,
+            data: css,
+            media_type: "text/css",
+            chapter: None,
+        });
+    }
+
+    let identifier = match &options.epub.identifier {
+        Some(identifier) => identifier.clone(),
+        None => derive_identifier(title, &info.author),
+    };
+
+    let modified = match (&options.epub.modified, info.date) {
+        (Some(modified), _) => Some(*modified),
+        (None, Smart::Custom(date)) => date,
+        (None, Smart::Auto) => None,
+    };
+
+    let package = write_package(info, title, &identifier, modified, &resources);
+    let nav = write_nav(info, title, &resources);
+    write_container(&package, &nav, &resources).at(Span::detached())
+}
+
+/// The media type of XHTML documents.
+const XHTML: &str = "application/xhtml+xml";
+
+/// The directory within the container that holds the publication's files.
+const CONTENT_DIR: &str = "EPUB";
+
+/// The path of the navigation document, relative to the content directory.
+const NAV_PATH: &str = "/nav.xhtml";
+
+/// The path of the package document, relative to the content directory.
+const PACKAGE_PATH: &str = "/content.opf";
+
+/// The directory that holds the packaged fonts, relative to the content
+/// directory.
+const FONTS_DIR: &str = "/fonts";
+
+/// A file in the publication.
+struct Resource {
+    /// The file's path within the bundle.
+    path: VirtualPath,
+    /// The exported file.
+    data: Bytes,
+    /// The file's media type.
+    media_type: &'static str,
+    /// Information about the chapter, if the file is one.
+    chapter: Option<Chapter>,
+}
+
+/// Information about a chapter that is relevant for the package and
+/// navigation documents.
+#[derive(Default)]
+struct Chapter {
+    /// The chapter's title.
+    title: Option<EcoString>,
+    /// The headings in the chapter.
+    headings: Vec<Heading>,
+    /// The manifest properties of the chapter, like `mathml`.
+    properties: Vec<&'static str>,
+}
+
+/// A heading in a chapter.
+struct Heading {
+    /// The heading's level, with 1 for `<h1>`.
+    level: usize,
+    /// The plain text of the heading.
+    text: EcoString,
+    /// The ID through which the heading can be linked to.
+    id: EcoString,
+}
+
+/// Prepares a chapter for packaging.
+///
+/// This assigns an ID to each heading, so that the table of contents can link
+/// to it, and determines the chapter's manifest properties.
+fn prepare_chapter(root: &HtmlElement) -> (HtmlElement, Chapter) {
+    let mut ids = FxHashSet::default();
+    collect_ids(root, &mut ids);
+
+    let mut root = root.clone();
+    let mut chapter = Chapter::default();
+    let mut next = 1;
+    visit(&mut root, &mut |elem| {
+        if let Some(level) = heading_level(elem.tag) {
+            let id = match elem.attrs.get(attr::id) {
+                Some(id) => id.clone(),
+                None => {
+                    let id = loop {
+                        let id = eco_format!("heading-{next}");
+                        next += 1;
+                        if ids.insert(id.clone()) {
+                            break id;
+                        }
+                    };
+                    elem.attrs.push(attr::id, id.clone());
+                    id
+                }
+            };
+
+            let mut text = String::new();
+            collect_text(elem, &mut text);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !text.is_empty() {
+                chapter.headings.push(Heading { level, text: text.into(), id });
+            }
+        }
+
+        let property = match elem.tag {
+            tag::script => "scripted",
+            tag::mathml::math => "mathml",
+            other if other.resolve().as_str() == "svg" => "svg",
+            _ if elem.children.iter().any(|node| matches!(node, HtmlNode::Frame(_))) => {
+                "svg"
+            }
+            _ => return,
+        };
+
+        if !chapter.properties.contains(&property) {
+            chapter.properties.push(property);
+        }
+    });
+
+    (root, chapter)
+}
+
+/// Calls the function for the element and all its descendant elements.
+fn visit(elem: &mut HtmlElement, f: &mut impl FnMut(&mut HtmlElement)) {
+    f(elem);
+    for child in elem.children.make_mut() {
+        if let HtmlNode::Element(child) = child {
+            visit(child, f);
+        }
+    }
+}
+
+/// Collects the IDs of the element and all its descendant elements.
+fn collect_ids(elem: &HtmlElement, ids: &mut FxHashSet<EcoString>) {
+    if let Some(id) = elem.attrs.get(attr::id) {
+        ids.insert(id.clone());
+    }
+    for child in &elem.children {
+        if let HtmlNode::Element(child) = child {
+            collect_ids(child, ids);
+        }
+    }
+}
+
+/// The media type of a paged document.
+fn paged_media_type(format: PagedFormat) -> &'static str {
+    match format {
+        PagedFormat::Pdf => "application/pdf",
+        PagedFormat::Png => "image/png",
+        PagedFormat::Jpg => "image/jpeg",
+        PagedFormat::Webp => "image/webp",
+        PagedFormat::Svg => "image/svg+xml",
+    }
+}
+
+/// Determines the media type of an asset from its extension.
+fn asset_media_type(path: &VirtualPath) -> &'static str {
+    let extension = path.extension().unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "html" | "xhtml" => XHTML,
+        "css" => "text/css",
+        "js" | "mjs" => "application/javascript",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Creates a resource for each of the fonts, skipping duplicates.
+fn package_fonts<'a>(
+    bundle: &Bundle,
+    fonts: &'a [Font],
+) -> SourceResult<Vec<(&'a Font, Resource)>> {
+    let mut seen = FxHashSet::default();
+    let mut packaged: Vec<(&Font, Resource)> = vec![];
+    for font in fonts {
+        if !seen.insert(font) {
+            continue;
+        }
+
+        let Some(data) = font_file(font) else {
+            bail!(
+                Span::detached(),
+                "failed to package font {}",
+                font.info().family;
+                hint: "the font collection may be malformed";
+            );
+        };
+
+        let (extension, media_type) = if data.starts_with(b"OTTO") {
+            ("otf", "font/otf")
+        } else {
+            ("ttf", "font/ttf")
+        };
+
+        let stem = font_file_stem(font);
+        let mut path = eco_format!("{FONTS_DIR}/{stem}.{extension}");
+        let mut n = 2;
+        while packaged.iter().any(|(_, other)| other.path.get_with_slash() == path) {
+            path = eco_format!("{FONTS_DIR}/{stem}-{n}.{extension}");
+            n += 1;
+        }
+
+        let path = VirtualPath::new(&path).unwrap();
+        if bundle.files.contains_key(&path) {
+            bail!(
+                Span::detached(),
+                "bundle already contains a file at {}",
+                path.get_with_slash();
+                hint: "this path is used for a font in EPUB export";
+            );
+        }
+
+        packaged.push((font, Resource { path, data, media_type, chapter: None }));
+    }
+    Ok(packaged)
+}
+
+/// The data of a standalone font file for the font.
+///
+/// A font from a collection is extracted into its own file.
+fn font_file(font: &Font) -> Option<Bytes> {
+    let data = font.data();
+    if !data.starts_with(b"ttcf") {
+        return Some(data.clone());
+    }
+    extract_face(data, font.index()).map(Bytes::new)
+}
+
+/// Extracts the face with the given index from a TrueType collection.
+///
+/// The tables are copied as-is, only their offsets change.
+fn extract_face(data: &[u8], index: u32) -> Option<Vec<u8>> {
+    let read_u16 =
+        |at: usize| data.get(at..at + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    let read_u32 = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+
+    let start = read_u32(12 + 4 * index as usize)? as usize;
+    let num_tables = read_u16(start + 4)? as usize;
+    let mut tables = Vec::with_capacity(num_tables);
+    for i in 0..num_tables {
+        let record = start + 12 + 16 * i;
+        let offset = read_u32(record + 8)? as usize;
+        let length = read_u32(record + 12)? as usize;
+        tables.push((data.get(record..record + 8)?, data.get(offset..offset + length)?));
+    }
+
+    // The header with the version and the binary search parameters stays the
+    // same since the number of tables does.
+    let mut out = data.get(start..start + 12)?.to_vec();
+    let mut offset = 12 + 16 * tables.len();
+    for (tag_and_checksum, table) in &tables {
+        out.extend_from_slice(tag_and_checksum);
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in &tables {
+        out.extend_from_slice(table);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    Some(out)
+}
+
+/// The file name of a packaged font, without extension.
+fn font_file_stem(font: &Font) -> String {
+    let name = font.post_script_name().unwrap_or_else(|| font.info().family.clone());
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .collect();
+    if stem.is_empty() { "font".into() } else { stem }
+}
+
+/// Writes `@font-face` rules for the packaged fonts and makes their families
+/// the default font of the chapters.
+fn font_css(fonts: &[(&Font, Resource)], stylesheet: &VirtualPath) -> String {
+    let dir = stylesheet.parent();
+    let mut css = String::new();
+    let mut families: Vec<&str> = vec![];
+    for (font, resource) in fonts {
+        let info = font.info();
+        let href = match &dir {
+            Some(dir) => resource.path.relative_from(dir),
+            None => resource.path.get_without_slash().into(),
+        };
+        let style = match info.variant.style {
+            FontStyle::Normal => "normal",
+            FontStyle::Italic => "italic",
+            FontStyle::Oblique => "oblique",
+        };
+        writeln!(
+            css,
+            "@font-face {{ font-family: {}; font-style: {style}; font-weight: {}; \
+             src: url(\"{href}\"); }}",
+            css_string(&info.family),
+            info.variant.weight.to_number(),
+        )
+        .unwrap();
+        if !families.contains(&info.family.as_str()) {
+            families.push(&info.family);
+        }
+    }
+
+    let families: Vec<String> = families.into_iter().map(css_string).collect();
+    writeln!(css, "body {{ font-family: {}; }}", families.join(", ")).unwrap();
+    css
+}
+
+/// Quotes a string for use in CSS.
+fn css_string(string: &str) -> String {
+    format!("\"{}\"", string.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Derives a UUID URN for the publication from its title and authors.
+fn derive_identifier(title: &str, authors: &[EcoString]) -> EcoString {
+    let mut hash = typst_utils::hash128(&(title, authors));
+    // Mark the UUID as a name-based one (version 5, RFC 4122 variant).
+    hash = (hash & !(0xf << 76)) | (0x5 << 76);
+    hash = (hash & !(0x3 << 62)) | (0x2 << 62);
+    let hex = format!("{hash:032x}");
+    eco_format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..],
+    )
+}
+
+/// Writes the package document, which lists the publication's metadata and
+/// files.
+fn write_package(
+    info: &DocumentInfo,
+    title: &str,
+    identifier: &str,
+    modified: Option<Datetime>,
+    resources: &[Resource],
+) -> String {
+    let lang = info.locale.unwrap_or_default().rfc_3066();
+
+    let mut xml = XmlWriter::new(xmlwriter::Options::default());
+    xml.write_declaration();
+    xml.start_element("package");
+    xml.write_attribute("xmlns", "http://www.idpf.org/2007/opf");
+    xml.write_attribute("version", "3.0");
+    xml.write_attribute("unique-identifier", "uid");
+    xml.write_attribute("xml:lang", &lang);
+
+    xml.start_element("metadata");
+    xml.write_attribute("xmlns:dc", "http://purl.org/dc/elements/1.1/");
+    xml.start_element("dc:identifier");
+    xml.write_attribute("id", "uid");
+    xml.write_text(identifier);
+    xml.end_element();
+    write_text_element(&mut xml, "dc:title", title);
+    write_text_element(&mut xml, "dc:language", &lang);
+    for author in &info.author {
+        write_text_element(&mut xml, "dc:creator", author);
+    }
+    if let Some(description) = &info.description {
+        write_text_element(&mut xml, "dc:description", description);
+    }
+    for keyword in &info.keywords {
+        write_text_element(&mut xml, "dc:subject", keyword);
+    }
+    if let Smart::Custom(Some(date)) = info.date
+        && let (Some(year), Some(month), Some(day)) =
+            (date.year(), date.month(), date.day())
+    {
+        write_text_element(
+            &mut xml,
+            "dc:date",
+            &format!("{year:04}-{month:02}-{day:02}"),
+        );
+    }
+    xml.start_element("meta");
+    xml.write_attribute("property", "dcterms:modified");
+    xml.write_text(&format_modified(modified));
+    xml.end_element();
+    xml.end_element();
+
+    xml.start_element("manifest");
+    xml.start_element("item");
+    xml.write_attribute("id", "nav");
+    xml.write_attribute("href", NAV_PATH.trim_start_matches('/'));
+    xml.write_attribute("media-type", XHTML);
+    xml.write_attribute("properties", "nav");
+    xml.end_element();
+    for (i, resource) in resources.iter().enumerate() {
+        xml.start_element("item");
+        xml.write_attribute("id", &format!("item-{i}"));
+        xml.write_attribute("href", resource.path.get_without_slash());
+        xml.write_attribute("media-type", resource.media_type);
+        if let Some(chapter) = &resource.chapter
+            && !chapter.properties.is_empty()
+        {
+            xml.write_attribute("properties", &chapter.properties.join(" "));
+        }
+        xml.end_element();
+    }
+    xml.end_element();
+
+    xml.start_element("spine");
+    for (i, resource) in resources.iter().enumerate() {
+        if resource.chapter.is_some() {
+            xml.start_element("itemref");
+            xml.write_attribute("idref", &format!("item-{i}"));
+            xml.end_element();
+        }
+    }
+    xml.end_element();
+
+    xml.end_document()
+}
+
+/// An entry in the table of contents.
+struct NavEntry<'a> {
+    level: usize,
+    text: &'a str,
+    href: EcoString,
+}
+
+/// Writes the navigation document with the table of contents.
+///
+/// Each chapter contributes its headings. Chapters without headings are
+/// listed with their title or path instead.
+fn write_nav(info: &DocumentInfo, title: &str, resources: &[Resource]) -> String {
+    let mut entries = vec![];
+    for resource in resources {
+        let Some(chapter) = &resource.chapter else { continue };
+        let path = resource.path.get_without_slash();
+        if chapter.headings.is_empty() {
+            let text = chapter.title.as_deref().unwrap_or(path);
+            entries.push(NavEntry { level: 1, text, href: path.into() });
+        }
+        for heading in &chapter.headings {
+            entries.push(NavEntry {
+                level: heading.level,
+                text: &heading.text,
+                href: eco_format!("{path}#{}", heading.id),
+            });
+        }
+    }
+
+    let lang = info.locale.unwrap_or_default().rfc_3066();
+
+    let mut xml = XmlWriter::new(xmlwriter::Options::default());
+    xml.write_declaration();
+    xml.start_element("html");
+    xml.write_attribute("xmlns", "http://www.w3.org/1999/xhtml");
+    xml.write_attribute("xmlns:epub", "http://www.idpf.org/2007/ops");
+    xml.write_attribute("lang", &lang);
+    xml.write_attribute("xml:lang", &lang);
+    xml.start_element("head");
+    xml.start_element("meta");
+    xml.write_attribute("charset", "utf-8");
+    xml.end_element();
+    write_text_element(&mut xml, "title", title);
+    xml.end_element();
+    xml.start_element("body");
+    xml.start_element("nav");
+    xml.write_attribute("epub:type", "toc");
+    xml.write_attribute("id", "toc");
+    write_nav_entries(&mut xml, &entries);
+    xml.end_element();
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Writes entries of the table of contents as a nested list.
+///
+/// Each entry contains the following entries with a higher level.
+fn write_nav_entries(xml: &mut XmlWriter, entries: &[NavEntry]) {
+    xml.start_element("ol");
+    let mut i = 0;
+    while i < entries.len() {
+        let entry = &entries[i];
+        let end = entries[i + 1..]
+            .iter()
+            .position(|next| next.level <= entry.level)
+            .map_or(entries.len(), |offset| i + 1 + offset);
+
+        xml.start_element("li");
+        xml.start_element("a");
+        xml.write_attribute("href", &entry.href);
+        xml.write_text(entry.text);
+        xml.end_element();
+        if end > i + 1 {
+            write_nav_entries(xml, &entries[i + 1..end]);
+        }
+        xml.end_element();
+
+        i = end;
+    }
+    xml.end_element();
+}
+
+/// The container file that points reading systems to the package document.
+const CONTAINER_XML: &str = "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">
+  <rootfiles>
+    <rootfile full-path=\"EPUB/content.opf\" media-type=\"application/oebps-package+xml\"/>
+  </rootfiles>
+</container>
+";
+
+/// Writes the ZIP container of the publication.
+///
+/// The `mimetype` file must come first and be stored uncompressed, so that
+/// the container can be identified. All timestamps are fixed to keep the
+/// output reproducible.
+fn write_container(
+    package: &str,
+    nav: &str,
+    resources: &[Resource],
+) -> StrResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options =
+        SimpleFileOptions::default().last_modified_time(zip::DateTime::default());
+    let stored = options.compression_method(CompressionMethod::Stored);
+    let deflated = options.compression_method(CompressionMethod::Deflated);
+
+    let mut add = |name: &str, options: SimpleFileOptions, data: &[u8]| {
+        zip.start_file(name, options).map_err(failed)?;
+        zip.write_all(data).map_err(failed)
+    };
+
+    add("mimetype", stored, b"application/epub+zip")?;
+    add("META-INF/container.xml", deflated, CONTAINER_XML.as_bytes())?;
+    add(&format!("{CONTENT_DIR}{PACKAGE_PATH}"), deflated, package.as_bytes())?;
+    add(&format!("{CONTENT_DIR}{NAV_PATH}"), deflated, nav.as_bytes())?;
+    for resource in resources {
+        let name = format!("{CONTENT_DIR}/{}", resource.path.get_without_slash());
+        add(&name, deflated, &resource.data)?;
+    }
+
+    Ok(zip.finish().map_err(failed)?.into_inner())
+}
+
+/// The error message for a failure while writing the container.
+#[cold]
+fn failed(err: impl Display) -> EcoString {
+    eco_format!("failed to write EPUB container ({err})")
+}
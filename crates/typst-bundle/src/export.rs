@@ -1,42 +1,167 @@
+use std::borrow::Cow;
+
 use comemo::{Track, Tracked};
 use ecow::EcoString;
 use indexmap::IndexMap;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use rustc_hash::FxBuildHasher;
-use typst_html::{HtmlElement, HtmlOptions};
+use rustc_hash::{FxBuildHasher, FxHashMap};
+use typst_html::{HtmlElement, HtmlOptions, HtmlProfile, HtmlStyleMode, Stylesheet};
 use typst_layout::PagedDocument;
-use typst_library::diag::{At, ParallelCollectCombinedResult, SourceResult};
+use typst_library::diag::{
+    At, HintedStrResult, ParallelCollectCombinedResult, SourceResult, bail,
+};
 use typst_library::foundations::Bytes;
 use typst_library::introspection::Location;
-use typst_library::model::{LateLinkResolver, PagedFormat};
-use typst_pdf::PdfOptions;
-use typst_render::RenderOptions;
+use typst_library::model::{
+    DocumentFormat, ExportOptions, LateLinkResolver, PagedFormat,
+};
+use typst_pdf::{PdfOptions, PdfStandards};
+use typst_render::{RasterFormat, RenderOptions};
 use typst_svg::SvgOptions;
 use typst_syntax::{Span, VirtualPath};
+use typst_utils::Scalar;
 
-use crate::{Bundle, BundleDocument, BundleFile};
+use crate::fingerprint::{MANIFEST_PATH, fingerprint_paths, manifest};
+use crate::{Bundle, BundleDocument, BundleFile, EpubOptions};
 
 /// A raw mapping from paths to bytes.
 pub type VirtualFs = IndexMap<VirtualPath, Bytes, FxBuildHasher>;
 
+/// The paths that changed between two exports of a bundle.
+///
+/// This is used to avoid needless work when a bundle is exported repeatedly,
+/// for instance in `typst watch`, and to inform live-reloading consumers about
+/// what changed.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct BundleChanges {
+    /// Files that did not exist before.
+    pub added: Vec<VirtualPath>,
+    /// Files that exist in both exports, but with different contents.
+    pub changed: Vec<VirtualPath>,
+    /// Files that no longer exist.
+    pub removed: Vec<VirtualPath>,
+}
+
+impl BundleChanges {
+    /// Determines the changes between two exports.
+    ///
+    /// Without a previous export, all files are considered added.
+    pub fn between(previous: Option<&VirtualFs>, current: &VirtualFs) -> Self {
+        let mut changes = Self::default();
+        for (path, data) in current {
+            match previous.and_then(|previous| previous.get(path)) {
+                None => changes.added.push(path.clone()),
+                Some(old) if old != data => changes.changed.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        if let Some(previous) = previous {
+            changes.removed = previous
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned()
+                .collect();
+        }
+        changes
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// Exports a bundle into a raw virtual file system.
 #[typst_macros::time(name = "export bundle")]
 pub fn export(bundle: &Bundle, options: &BundleOptions) -> SourceResult<VirtualFs> {
-    bundle
+    let stylesheet = shared_stylesheet(bundle, &options.html)?;
+    let renamed = if options.fingerprint {
+        fingerprint_paths(bundle, stylesheet.as_ref())?
+    } else {
+        FxHashMap::default()
+    };
+    let rename = |path: &VirtualPath| renamed.get(path).unwrap_or(path).clone();
+
+    let mut fs: VirtualFs = bundle
         .files
         .par_iter()
         .map(|(path, file)| {
             let data = match file {
                 BundleFile::Document(doc) => {
-                    let link_resolver =
+                    let mut link_resolver =
                         LateLinkResolver::new(Some(path), bundle.introspector.as_ref());
-                    export_document(doc, options, link_resolver.track())
+                    if options.fingerprint {
+                        link_resolver = link_resolver.with_renamed(&renamed);
+                    }
+                    let href = stylesheet.as_ref().and_then(|(sheet_path, _)| {
+                        Some(rename(sheet_path).relative_from(&path.parent()?))
+                    });
+                    export_document(doc, options, link_resolver.track(), href.as_deref())
                 }
                 BundleFile::Asset(bytes) => Ok(bytes.clone()),
             };
-            data.map(|data| (path.clone(), data))
+            data.map(|data| (rename(path), data))
         })
-        .collect_combined_result()
+        .collect_combined_result()?;
+
+    if let Some((path, css)) = stylesheet {
+        fs.insert(rename(&path), css);
+    }
+
+    if options.fingerprint {
+        fs.insert(VirtualPath::new(MANIFEST_PATH).unwrap(), manifest(&renamed));
+    }
+
+    Ok(fs)
+}
+
+/// The path of the stylesheet that is shared by all HTML documents in a bundle
+/// when generated styles are written as classes.
+const STYLESHEET_PATH: &str = "/typst.css";
+
+/// Collects the generated styles of all HTML documents in the bundle into one
+/// stylesheet, if they should be written as classes.
+///
+/// Since class names are derived from the styles, identical styles in
+/// different documents share a class.
+pub(crate) fn shared_stylesheet(
+    bundle: &Bundle,
+    options: &HtmlOptions,
+) -> SourceResult<Option<(VirtualPath, Bytes)>> {
+    if options.styles != HtmlStyleMode::Classes
+        || options.profile == HtmlProfile::Semantic
+        || options.fragment
+    {
+        return Ok(None);
+    }
+
+    let mut sheet = Stylesheet::new();
+    for file in bundle.files.values() {
+        if let BundleFile::Document(BundleDocument::Html(doc, _)) = file {
+            sheet.collect(doc.root());
+        }
+    }
+
+    if sheet.is_empty() {
+        return Ok(None);
+    }
+
+    let path = stylesheet_path(bundle)?;
+    Ok(Some((path, Bytes::from_string(sheet.to_css()))))
+}
+
+/// The path of the shared stylesheet, which must not be taken by a file of
+/// the bundle.
+pub(crate) fn stylesheet_path(bundle: &Bundle) -> SourceResult<VirtualPath> {
+    let path = VirtualPath::new(STYLESHEET_PATH).unwrap();
+    if bundle.files.contains_key(&path) {
+        bail!(
+            Span::detached(),
+            "bundle already contains a file at {STYLESHEET_PATH}";
+            hint: "this path is used for the stylesheet with the generated styles";
+        );
+    }
+    Ok(path)
 }
 
 /// Settings for bundle export.
@@ -46,31 +171,159 @@ pub struct BundleOptions {
     pub html: HtmlOptions,
     /// Options for exporting PDF documents.
     pub pdf: PdfOptions,
-    /// Options for exporting PNG documents.
+    /// Options for exporting PNG, JPEG, and WebP documents.
     pub png: RenderOptions,
     /// Options for exporting SVG documents.
     pub svg: SvgOptions,
+    /// Options for exporting the bundle as an EPUB publication.
+    pub epub: EpubOptions,
+    /// Whether to rename assets to include a hash of their contents.
+    ///
+    /// References to the assets in documents are rewritten accordingly and a
+    /// manifest with the new paths is added to the bundle. Fingerprinted
+    /// paths change whenever the contents do, so that browsers can cache the
+    /// assets indefinitely. This does not apply to EPUB export.
+    pub fingerprint: bool,
 }
 
 /// Exports a single document.
-fn export_document(
+pub(crate) fn export_document(
     doc: &BundleDocument,
     options: &BundleOptions,
     link_resolver: Tracked<LateLinkResolver>,
+    stylesheet: Option<&str>,
 ) -> SourceResult<Bytes> {
     match doc {
-        BundleDocument::Paged(doc, extras) => match extras.format {
-            PagedFormat::Pdf => {
-                export_pdf(doc, &options.pdf, &extras.anchors, link_resolver)
-            }
-            PagedFormat::Png => export_png(doc, &options.png),
-            PagedFormat::Svg => {
-                export_svg(doc, &options.svg, &extras.anchors, link_resolver)
+        BundleDocument::Paged(doc, extras) => {
+            let overrides = &extras.options;
+            let raster = |format| {
+                export_raster(doc, &raster_options(&options.png, overrides), format)
+            };
+            match extras.format {
+                PagedFormat::Pdf => {
+                    let pdf = pdf_options(&options.pdf, overrides).at(Span::detached())?;
+                    export_pdf(doc, &pdf, &extras.anchors, link_resolver)
+                }
+                PagedFormat::Png => raster(RasterFormat::Png),
+                PagedFormat::Jpg => raster(RasterFormat::Jpg),
+                PagedFormat::Webp => raster(RasterFormat::Webp),
+                PagedFormat::Svg => {
+                    let svg = svg_options(&options.svg, overrides);
+                    export_svg(doc, &svg, &extras.anchors, link_resolver)
+                }
             }
-        },
-        BundleDocument::Html(doc) => {
-            export_html(doc.root(), &options.html, link_resolver)
         }
+        BundleDocument::Html(doc, extras) => {
+            let html = html_options(&options.html, &extras.options);
+            export_html(doc.root(), &html, link_resolver, stylesheet)
+        }
+    }
+}
+
+/// Ensures that the export options of a document apply to its format.
+pub(crate) fn check_export_options(
+    options: &ExportOptions,
+    format: DocumentFormat,
+) -> HintedStrResult<()> {
+    let pdf = format == DocumentFormat::Paged(PagedFormat::Pdf);
+    let raster = matches!(
+        format,
+        DocumentFormat::Paged(PagedFormat::Png | PagedFormat::Jpg | PagedFormat::Webp)
+    );
+
+    for (name, set, supported, formats) in [
+        ("standards", options.standards.is_some(), pdf, "PDF"),
+        ("tagged", options.tagged.is_some(), pdf, "PDF"),
+        ("pages", options.pages.is_some(), pdf, "PDF"),
+        ("ppi", options.ppi.is_some(), raster, "PNG, JPEG, and WebP"),
+        ("pretty", options.pretty.is_some(), !raster, "HTML, PDF, and SVG"),
+    ] {
+        if set && !supported {
+            bail!(
+                "export option `{name}` is not supported for {} documents",
+                format_name(format);
+                hint: "it is only supported for {formats} documents";
+            );
+        }
+    }
+
+    if let Some(standards) = &options.standards {
+        PdfStandards::new(standards)?;
+    }
+
+    Ok(())
+}
+
+/// The human-readable name of a document format.
+fn format_name(format: DocumentFormat) -> &'static str {
+    match format {
+        DocumentFormat::Paged(PagedFormat::Pdf) => "PDF",
+        DocumentFormat::Paged(PagedFormat::Png) => "PNG",
+        DocumentFormat::Paged(PagedFormat::Svg) => "SVG",
+        DocumentFormat::Paged(PagedFormat::Jpg) => "JPEG",
+        DocumentFormat::Paged(PagedFormat::Webp) => "WebP",
+        DocumentFormat::Html => "HTML",
+    }
+}
+
+/// Applies a document's export options to the bundle's PDF options.
+fn pdf_options<'a>(
+    options: &'a PdfOptions,
+    overrides: &ExportOptions,
+) -> HintedStrResult<Cow<'a, PdfOptions>> {
+    if overrides == &ExportOptions::default() {
+        return Ok(Cow::Borrowed(options));
+    }
+
+    let mut options = options.clone();
+    if let Some(standards) = &overrides.standards {
+        options.standards = PdfStandards::new(standards)?;
+    }
+    if let Some(tagged) = overrides.tagged {
+        options.tagged = tagged;
+    }
+    if let Some(pages) = &overrides.pages {
+        options.page_ranges = Some(pages.clone());
+    }
+    if let Some(pretty) = overrides.pretty {
+        options.pretty = pretty;
+    }
+    Ok(Cow::Owned(options))
+}
+
+/// Applies a document's export options to the bundle's raster options.
+fn raster_options<'a>(
+    options: &'a RenderOptions,
+    overrides: &ExportOptions,
+) -> Cow<'a, RenderOptions> {
+    match overrides.ppi {
+        Some(ppi) => Cow::Owned(RenderOptions {
+            pixel_per_pt: Scalar::new(ppi.get() / 72.0),
+            ..options.clone()
+        }),
+        None => Cow::Borrowed(options),
+    }
+}
+
+/// Applies a document's export options to the bundle's SVG options.
+fn svg_options<'a>(
+    options: &'a SvgOptions,
+    overrides: &ExportOptions,
+) -> Cow<'a, SvgOptions> {
+    match overrides.pretty {
+        Some(pretty) => Cow::Owned(SvgOptions { pretty, ..options.clone() }),
+        None => Cow::Borrowed(options),
+    }
+}
+
+/// Applies a document's export options to the bundle's HTML options.
+pub(crate) fn html_options<'a>(
+    options: &'a HtmlOptions,
+    overrides: &ExportOptions,
+) -> Cow<'a, HtmlOptions> {
+    match overrides.pretty {
+        Some(pretty) => Cow::Owned(HtmlOptions { pretty, ..options.clone() }),
+        None => Cow::Borrowed(options),
     }
 }
 
@@ -86,14 +339,17 @@ fn export_pdf(
     typst_pdf::pdf_in_bundle(doc, options, anchors, link_resolver).map(Bytes::new)
 }
 
-/// Exports a PNG document.
+/// Exports a raster image document.
 #[comemo::memoize]
-#[typst_macros::time(name = "export png")]
-fn export_png(doc: &PagedDocument, options: &RenderOptions) -> SourceResult<Bytes> {
-    typst_render::render(&doc.pages()[0], options)
-        .encode_png()
+#[typst_macros::time(name = "export raster")]
+fn export_raster(
+    doc: &PagedDocument,
+    options: &RenderOptions,
+    format: RasterFormat,
+) -> SourceResult<Bytes> {
+    let pixmap = typst_render::render(&doc.pages()[0], options);
+    typst_render::encode(&pixmap, format, options)
         .map(Bytes::new)
-        .map_err(|_| "failed to encode PNG")
         .at(Span::detached())
 }
 
@@ -133,10 +389,57 @@ fn export_svg(
 /// built (for linking), which means it's not 100% derived from the document.
 #[comemo::memoize]
 #[typst_macros::time(name = "export html")]
-fn export_html(
+pub(crate) fn export_html(
     root: &HtmlElement,
     options: &HtmlOptions,
     link_resolver: Tracked<LateLinkResolver>,
+    stylesheet: Option<&str>,
 ) -> SourceResult<Bytes> {
-    typst_html::html_in_bundle(root, options, link_resolver).map(Bytes::from_string)
+    typst_html::html_in_bundle(root, options, link_resolver, stylesheet)
+        .map(Bytes::from_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fs(files: &[(&str, &'static str)]) -> VirtualFs {
+        files
+            .iter()
+            .map(|&(path, data)| {
+                (VirtualPath::new(path).unwrap(), Bytes::from_string(data))
+            })
+            .collect()
+    }
+
+    fn paths(paths: &[&str]) -> Vec<VirtualPath> {
+        paths.iter().map(|path| VirtualPath::new(path).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_bundle_changes_without_previous() {
+        let current = fs(&[("/a.html", "A"), ("/b.css", "B")]);
+        let changes = BundleChanges::between(None, &current);
+        assert_eq!(changes.added, paths(&["/a.html", "/b.css"]));
+        assert!(changes.changed.is_empty());
+        assert!(changes.removed.is_empty());
+    }
+
+    #[test]
+    fn test_bundle_changes_between() {
+        let previous = fs(&[("/a.html", "A"), ("/b.css", "B"), ("/old/c.png", "C")]);
+        let current = fs(&[("/a.html", "A"), ("/b.css", "B2"), ("/d.js", "D")]);
+        let changes = BundleChanges::between(Some(&previous), &current);
+        assert_eq!(changes.added, paths(&["/d.js"]));
+        assert_eq!(changes.changed, paths(&["/b.css"]));
+        assert_eq!(changes.removed, paths(&["/old/c.png"]));
+        assert!(!changes.is_empty());
+    }
+
+    #[test]
+    fn test_bundle_changes_unchanged() {
+        let previous = fs(&[("/a.html", "A")]);
+        let changes = BundleChanges::between(Some(&previous), &previous);
+        assert!(changes.is_empty());
+    }
 }
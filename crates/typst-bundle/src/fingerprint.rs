@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use ecow::{eco_format, eco_vec};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst_library::diag::{SourceResult, bail};
+use typst_library::foundations::{Bytes, NativeElement, Selector};
+use typst_library::introspection::Introspector;
+use typst_library::model::{FeedElem, SearchIndexElem, SitemapElem};
+use typst_syntax::{Span, VirtualPath};
+
+use crate::generate::GeneratedAsset;
+use crate::{Bundle, BundleFile};
+
+/// The path of the manifest that maps the original paths of fingerprinted
+/// files to their new ones.
+pub(crate) const MANIFEST_PATH: &str = "/manifest.json";
+
+/// Determines new paths with a hash of the contents for all assets in the
+/// bundle and, if present, the shared stylesheet.
+///
+/// Documents keep their paths because they are usually the entry points that
+/// other sites link to. The same goes for generated assets like sitemaps and
+/// feeds, which are found by well-known paths.
+pub(crate) fn fingerprint_paths(
+    bundle: &Bundle,
+    stylesheet: Option<&(VirtualPath, Bytes)>,
+) -> SourceResult<FxHashMap<VirtualPath, VirtualPath>> {
+    let selector = Selector::Or(eco_vec![
+        SitemapElem::ELEM.select(),
+        FeedElem::ELEM.select(),
+        SearchIndexElem::ELEM.select(),
+    ]);
+    let generated = bundle.introspector.query(&selector);
+    let generated: FxHashSet<_> = generated
+        .iter()
+        .filter_map(GeneratedAsset::from_content)
+        .map(GeneratedAsset::path)
+        .collect();
+
+    let assets = bundle.files.iter().filter_map(|(path, file)| match file {
+        BundleFile::Asset(data) if !generated.contains(path) => Some((path, data)),
+        BundleFile::Asset(_) | BundleFile::Document(_) => None,
+    });
+
+    let mut renamed = FxHashMap::default();
+    for (path, data) in assets.chain(stylesheet.map(|(path, data)| (path, data))) {
+        let new = fingerprinted(path, data);
+        if bundle.files.contains_key(&new) {
+            bail!(
+                Span::detached(),
+                "bundle already contains a file at {}", new.get_with_slash();
+                hint: "this path is used for the fingerprinted version of {}",
+                path.get_with_slash();
+            );
+        }
+        renamed.insert(path.clone(), new);
+    }
+
+    if bundle.files.contains_key(&VirtualPath::new(MANIFEST_PATH).unwrap()) {
+        bail!(
+            Span::detached(),
+            "bundle already contains a file at {MANIFEST_PATH}";
+            hint: "this path is used for the manifest of fingerprinted files";
+        );
+    }
+
+    Ok(renamed)
+}
+
+/// Inserts a short hash of the data before the extension of the path, e.g.
+/// turning `style.css` into `style.3f9a1c2b.css`.
+fn fingerprinted(path: &VirtualPath, data: &[u8]) -> VirtualPath {
+    let hash = eco_format!("{:032x}", typst_utils::hash128(data));
+    let hash = &hash[..8];
+    let name = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => eco_format!("{stem}.{hash}.{ext}"),
+        _ => eco_format!("{}.{hash}", path.file_name().unwrap_or_default()),
+    };
+    // The parent exists and the name is a valid segment because the path
+    // belongs to a file.
+    path.parent().unwrap().join(&name).unwrap()
+}
+
+/// Creates a JSON manifest that maps the original paths of fingerprinted files
+/// to their new ones, so that external tools can find them.
+pub(crate) fn manifest(renamed: &FxHashMap<VirtualPath, VirtualPath>) -> Bytes {
+    let map: BTreeMap<&str, &str> = renamed
+        .iter()
+        .map(|(from, to)| (from.get_without_slash(), to.get_without_slash()))
+        .collect();
+    let mut json = serde_json::to_vec_pretty(&map).unwrap();
+    json.push(b'\n');
+    Bytes::new(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> VirtualPath {
+        VirtualPath::new(path).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprinted() {
+        let hash = &eco_format!("{:032x}", typst_utils::hash128(b"data"))[..8];
+        assert_eq!(
+            fingerprinted(&path("/img/logo.png"), b"data"),
+            path(&format!("/img/logo.{hash}.png")),
+        );
+        assert_eq!(
+            fingerprinted(&path("/archive.tar.gz"), b"data"),
+            path(&format!("/archive.tar.{hash}.gz")),
+        );
+        assert_eq!(
+            fingerprinted(&path("/LICENSE"), b"data"),
+            path(&format!("/LICENSE.{hash}"))
+        );
+        assert_ne!(
+            fingerprinted(&path("/img/logo.png"), b"data"),
+            fingerprinted(&path("/img/logo.png"), b"other"),
+        );
+    }
+
+    #[test]
+    fn test_manifest() {
+        let renamed: FxHashMap<_, _> = [
+            (path("/b.css"), path("/b.22222222.css")),
+            (path("/a/c.png"), path("/a/c.11111111.png")),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            manifest(&renamed).as_str().unwrap(),
+            "{\n  \"a/c.png\": \"a/c.11111111.png\",\n  \"b.css\": \"b.22222222.css\"\n}\n",
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use ecow::{EcoString, eco_format};
+use serde_json::json;
+use typst_html::{HtmlElement, HtmlNode, attr, tag};
+use typst_library::diag::{SourceResult, bail};
+use typst_library::foundations::{
+    Bytes, Content, Datetime, Packed, Selector, Smart, StyleChain,
+};
+use typst_library::introspection::Location;
+use typst_library::model::{
+    Document, DocumentElem, FeedElem, SearchIndexElem, SitemapElem, percent_encode_path,
+};
+use typst_syntax::{Span, VirtualPath};
+use xmlwriter::XmlWriter;
+
+use crate::BundleDocument;
+use crate::util::{collect_text, format_modified, heading_level, write_text_element};
+
+/// An asset whose contents are derived from the bundle's documents.
+#[derive(Copy, Clone)]
+pub(crate) enum GeneratedAsset<'a> {
+    Sitemap(&'a Packed<SitemapElem>),
+    Feed(&'a Packed<FeedElem>),
+    SearchIndex(&'a Packed<SearchIndexElem>),
+}
+
+impl<'a> GeneratedAsset<'a> {
+    /// Checks whether the content is a generated asset.
+    pub fn from_content(content: &'a Content) -> Option<Self> {
+        if let Some(elem) = content.to_packed::<SitemapElem>() {
+            Some(Self::Sitemap(elem))
+        } else if let Some(elem) = content.to_packed::<FeedElem>() {
+            Some(Self::Feed(elem))
+        } else {
+            content.to_packed::<SearchIndexElem>().map(Self::SearchIndex)
+        }
+    }
+
+    /// The path of the asset in the bundle.
+    pub fn path(self) -> &'a VirtualPath {
+        match self {
+            Self::Sitemap(elem) => elem.path.as_ref(),
+            Self::Feed(elem) => elem.path.as_ref(),
+            Self::SearchIndex(elem) => elem.path.as_ref(),
+        }
+    }
+
+    /// The location of the asset's element.
+    pub fn location(self) -> Location {
+        match self {
+            Self::Sitemap(elem) => elem.location(),
+            Self::Feed(elem) => elem.location(),
+            Self::SearchIndex(elem) => elem.location(),
+        }
+        .unwrap()
+    }
+
+    /// Creates the contents of the asset.
+    pub fn generate(
+        self,
+        styles: StyleChain,
+        documents: &[SourceDocument],
+    ) -> SourceResult<Bytes> {
+        match self {
+            Self::Sitemap(elem) => sitemap(elem, styles, documents),
+            Self::Feed(elem) => feed(elem, styles, documents),
+            Self::SearchIndex(elem) => search_index(elem, styles, documents),
+        }
+    }
+}
+
+/// A compiled document from which assets can be generated.
+pub(crate) struct SourceDocument<'a> {
+    pub elem: &'a Packed<DocumentElem>,
+    pub styles: StyleChain<'a>,
+    pub path: &'a VirtualPath,
+    pub doc: &'a BundleDocument,
+}
+
+/// Generates a sitemap with the selected documents.
+fn sitemap(
+    elem: &Packed<SitemapElem>,
+    styles: StyleChain,
+    documents: &[SourceDocument],
+) -> SourceResult<Bytes> {
+    let base = require_base(elem.span(), elem.base.get_ref(styles), "sitemap")?;
+
+    let mut xml = XmlWriter::new(xmlwriter::Options::default());
+    xml.write_declaration();
+    xml.start_element("urlset");
+    xml.write_attribute("xmlns", "http://www.sitemaps.org/schemas/sitemap/0.9");
+    for source in select(documents, elem.documents.get_ref(styles)) {
+        xml.start_element("url");
+        write_text_element(&mut xml, "loc", &url(base, source.path));
+        if let Some(date) = date(source) {
+            let lastmod = eco_format!(
+                "{:04}-{:02}-{:02}",
+                date.year().unwrap_or(1970),
+                date.month().unwrap_or(1),
+                date.day().unwrap_or(1),
+            );
+            write_text_element(&mut xml, "lastmod", &lastmod);
+        }
+        xml.end_element();
+    }
+    xml.end_element();
+
+    Ok(Bytes::from_string(xml.end_document()))
+}
+
+/// Generates an Atom feed with the selected documents as entries.
+fn feed(
+    elem: &Packed<FeedElem>,
+    styles: StyleChain,
+    documents: &[SourceDocument],
+) -> SourceResult<Bytes> {
+    let base = require_base(elem.span(), elem.base.get_ref(styles), "feed")?;
+    let Some(title) = elem.title.get_ref(styles) else {
+        bail!(
+            elem.span(), "feed requires a title";
+            hint: "try setting `title` to the name of the website";
+        );
+    };
+
+    let mut entries = vec![];
+    for source in select(documents, elem.documents.get_ref(styles)) {
+        let Some(date) = date(source) else {
+            bail!(
+                source.elem.span(),
+                "document `{}` has no date", source.path.get_without_slash();
+                hint: "documents in a feed must have a date";
+                hint: "try setting the `date` of the document";
+            );
+        };
+        entries.push((source, date));
+    }
+
+    // Newest entries first.
+    entries.sort_by_key(|(_, date)| std::cmp::Reverse(sort_key(date)));
+
+    let self_url = url(base, elem.path.as_ref());
+    let mut xml = XmlWriter::new(xmlwriter::Options::default());
+    xml.write_declaration();
+    xml.start_element("feed");
+    xml.write_attribute("xmlns", "http://www.w3.org/2005/Atom");
+    write_text_element(&mut xml, "title", title);
+    write_text_element(&mut xml, "id", &self_url);
+    xml.start_element("link");
+    xml.write_attribute("rel", "self");
+    xml.write_attribute("href", &self_url);
+    xml.end_element();
+    let updated = entries.first().map(|&(_, date)| date);
+    write_text_element(&mut xml, "updated", &format_modified(updated));
+
+    for (source, date) in entries {
+        let info = source.doc.info();
+        let url = url(base, source.path);
+        xml.start_element("entry");
+        let title = info.title.as_deref().unwrap_or(source.path.get_without_slash());
+        write_text_element(&mut xml, "title", title);
+        write_text_element(&mut xml, "id", &url);
+        xml.start_element("link");
+        xml.write_attribute("href", &url);
+        xml.end_element();
+        write_text_element(&mut xml, "updated", &format_modified(Some(date)));
+        for author in &info.author {
+            xml.start_element("author");
+            write_text_element(&mut xml, "name", author);
+            xml.end_element();
+        }
+        if let Some(description) = &info.description {
+            write_text_element(&mut xml, "summary", description);
+        }
+        xml.end_element();
+    }
+    xml.end_element();
+
+    Ok(Bytes::from_string(xml.end_document()))
+}
+
+/// Generates a JSON index of the text in the selected HTML documents.
+fn search_index(
+    elem: &Packed<SearchIndexElem>,
+    styles: StyleChain,
+    documents: &[SourceDocument],
+) -> SourceResult<Bytes> {
+    let entries: Vec<_> = select(documents, elem.documents.get_ref(styles))
+        .filter_map(|source| {
+            let BundleDocument::Html(doc, _) = source.doc else { return None };
+            let mut sections = vec![];
+            index_sections(doc.root(), &mut sections);
+            let sections: Vec<_> = sections
+                .into_iter()
+                .map(|section| {
+                    json!({
+                        "heading": section.heading,
+                        "anchor": section.anchor.as_deref(),
+                        "text": section.text,
+                    })
+                })
+                .collect();
+            Some(json!({
+                "path": source.path.get_without_slash(),
+                "title": doc.info().title.as_deref(),
+                "sections": sections,
+            }))
+        })
+        .collect();
+
+    Ok(Bytes::new(serde_json::to_vec(&entries).unwrap()))
+}
+
+/// A part of a document that starts at a heading.
+struct Section {
+    heading: Option<String>,
+    anchor: Option<EcoString>,
+    text: String,
+}
+
+/// Collects the headings and paragraphs of an element into sections.
+fn index_sections(elem: &HtmlElement, sections: &mut Vec<Section>) {
+    for child in &elem.children {
+        let HtmlNode::Element(child) = child else { continue };
+        if heading_level(child.tag).is_some() {
+            let mut heading = String::new();
+            collect_text(child, &mut heading);
+            sections.push(Section {
+                heading: Some(heading),
+                anchor: child.attrs.get(attr::id).cloned(),
+                text: String::new(),
+            });
+        } else if child.tag == tag::p {
+            if sections.is_empty() {
+                sections.push(Section {
+                    heading: None,
+                    anchor: None,
+                    text: String::new(),
+                });
+            }
+            let section = sections.last_mut().unwrap();
+            if !section.text.is_empty() {
+                section.text.push(' ');
+            }
+            collect_text(child, &mut section.text);
+        } else {
+            index_sections(child, sections);
+        }
+    }
+}
+
+/// Yields the documents that match the selector.
+fn select<'a, 'b>(
+    documents: &'b [SourceDocument<'a>],
+    selector: &'b Smart<Selector>,
+) -> impl Iterator<Item = &'b SourceDocument<'a>> {
+    documents.iter().filter(move |source| match selector {
+        Smart::Auto => true,
+        Smart::Custom(selector) => {
+            selector.matches(source.elem.pack_ref(), Some(source.styles))
+        }
+    })
+}
+
+/// Ensures that a base URL was configured.
+fn require_base<'a>(
+    span: Span,
+    base: &'a Option<EcoString>,
+    name: &str,
+) -> SourceResult<&'a str> {
+    let Some(base) = base else {
+        bail!(
+            span, "{name} requires a base URL";
+            hint: "try setting `base` to the URL at which the bundle is published";
+        );
+    };
+    Ok(base.trim_end_matches('/'))
+}
+
+/// The absolute URL of a file in the bundle.
+fn url(base: &str, path: &VirtualPath) -> EcoString {
+    eco_format!("{base}/{}", percent_encode_path(path.get_without_slash()))
+}
+
+/// The date of a document, if it has one that includes a calendar date.
+fn date(source: &SourceDocument) -> Option<Datetime> {
+    match source.doc.info().date {
+        Smart::Custom(Some(date)) if date.year().is_some() => Some(date),
+        _ => None,
+    }
+}
+
+/// A key by which dates can be sorted chronologically.
+fn sort_key(date: &Datetime) -> (Option<i32>, [Option<u8>; 5]) {
+    (date.year(), [date.month(), date.day(), date.hour(), date.minute(), date.second()])
+}
@@ -16,6 +16,7 @@ use typst_library::introspection::{
 use typst_library::model::{AssetElem, DocumentElem, LinkElem, Numbering};
 use typst_syntax::VirtualPath;
 
+use crate::generate::GeneratedAsset;
 use crate::{BundleDocument, Item};
 
 /// An introspector implementation for bundles.
@@ -160,7 +161,7 @@ impl Introspector for BundleIntrospector {
         } else if let Some(asset) = content.to_packed::<AssetElem>() {
             Some(asset.path.as_ref())
         } else {
-            None
+            GeneratedAsset::from_content(content).map(GeneratedAsset::path)
         }
     }
 }
@@ -240,7 +241,7 @@ impl BundleIntrospectorBuilder {
                     .discover_elements(doc.introspector().elements(), |_| pos);
                 ChildIntrospector::Paged(doc.introspector().clone())
             }
-            BundleDocument::Html(doc) => {
+            BundleDocument::Html(doc, _) => {
                 self.elements
                     .discover_elements(doc.introspector().elements(), |_| pos);
                 ChildIntrospector::Html(doc.introspector().clone())
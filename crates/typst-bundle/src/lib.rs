@@ -1,13 +1,26 @@
 //! Multi-file output for Typst.
 
+#[path = "archive.rs"]
+mod archive_;
+mod check;
+#[path = "epub.rs"]
+mod epub_;
 #[path = "export.rs"]
 mod export_;
+mod fingerprint;
+mod generate;
 mod introspect;
 mod link;
+mod util;
 
+use crate::export_::check_export_options;
+use crate::generate::{GeneratedAsset, SourceDocument};
 use crate::introspect::BundleIntrospector;
 
-pub use self::export_::{BundleOptions, VirtualFs, export};
+pub use self::archive_::{ArchiveFormat, ArchiveOptions, archive};
+pub use self::check::{LinkCheckOptions, check_html_links, check_links};
+pub use self::epub_::{EpubOptions, epub};
+pub use self::export_::{BundleChanges, BundleOptions, VirtualFs, export};
 
 use std::collections::hash_map::Entry;
 use std::sync::Arc;
@@ -27,7 +40,8 @@ use typst_library::introspection::{
     Introspector, Location, Locator, SplitLocator, Tag, TagElem,
 };
 use typst_library::model::{
-    AssetElem, Document, DocumentElem, DocumentFormat, DocumentInfo, PagedFormat,
+    AssetElem, Document, DocumentElem, DocumentFormat, DocumentInfo, ExportOptions,
+    PagedFormat,
 };
 use typst_library::routines::{Arenas, Pair, RealizationKind};
 use typst_library::{Feature, Library, World};
@@ -88,14 +102,14 @@ pub enum BundleDocument {
     /// A document in one of the paged formats.
     Paged(Box<PagedDocument>, PagedExtras),
     /// A document in the HTML format.
-    Html(Box<HtmlDocument>),
+    Html(Box<HtmlDocument>, HtmlExtras),
 }
 
 impl Document for BundleDocument {
     fn info(&self) -> &DocumentInfo {
         match self {
             BundleDocument::Paged(doc, _) => doc.info(),
-            BundleDocument::Html(doc) => doc.info(),
+            BundleDocument::Html(doc, _) => doc.info(),
         }
     }
 }
@@ -111,6 +125,17 @@ pub struct PagedExtras {
     /// Not all export targets support this (e.g. PNG), in which case it can
     /// simply be ignored.
     pub anchors: Vec<(Location, EcoString)>,
+    /// Options that take precedence over the bundle's export options for this
+    /// document.
+    pub options: ExportOptions,
+}
+
+/// Extra data relevant for exporting an HTML document in a bundle.
+#[derive(Debug, Clone, Hash)]
+pub struct HtmlExtras {
+    /// Options that take precedence over the bundle's export options for this
+    /// document.
+    pub options: ExportOptions,
 }
 
 /// Produces a bundle from content.
@@ -174,7 +199,14 @@ fn bundle_impl(
         styles,
     )?;
 
-    let children = collect(&children, &mut engine, &mut locator)?;
+    let (children, generated) = collect(&children, &mut engine, &mut locator)?;
+    let document_elems: Vec<_> = children
+        .iter()
+        .filter_map(|child| match child {
+            Child::Document(document, styles, _) => Some((*document, *styles)),
+            _ => None,
+        })
+        .collect();
 
     let mut items = engine
         .parallelize(children, |engine, child| -> SourceResult<_> {
@@ -196,7 +228,32 @@ fn bundle_impl(
 
     let mut introspector = BundleIntrospector::new(&items);
     let targets = introspector.link_targets();
-    let anchors = crate::link::create_link_anchors(&mut items, &targets);
+    let mut anchors = crate::link::create_link_anchors(&mut items, &targets);
+
+    // Generated assets are derived from the compiled documents, so they can
+    // only be created now.
+    let documents: Vec<_> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Document(path, doc, _) => Some((path, doc)),
+            _ => None,
+        })
+        .zip(document_elems)
+        .map(|((path, doc), (elem, styles))| SourceDocument { elem, styles, path, doc })
+        .collect();
+    let generated = generated
+        .into_iter()
+        .map(|(asset, styles)| {
+            let data = asset.generate(styles, &documents)?;
+            Ok(Item::Asset(asset.path().clone(), data, asset.location()))
+        })
+        .collect::<SourceResult<Vec<_>>>()?;
+    for item in &generated {
+        if let Item::Asset(.., loc) = item {
+            anchors.insert(*loc, EcoString::new());
+        }
+    }
+    items.extend(generated);
     introspector.set_anchors(anchors);
 
     let mut files = IndexMap::default();
@@ -225,6 +282,9 @@ enum Child<'a> {
     Document(&'a Packed<DocumentElem>, StyleChain<'a>, Locator<'a>),
 }
 
+/// A generated asset along with the styles it was realized with.
+type Generated<'a> = (GeneratedAsset<'a>, StyleChain<'a>);
+
 /// The processed version of a [`Child`].
 enum Item {
     Tag(Tag),
@@ -233,12 +293,16 @@ enum Item {
 }
 
 /// Collects all documents and assets in the bundle.
+///
+/// Generated assets are returned separately because they can only be created
+/// once the documents are compiled.
 fn collect<'a>(
     children: &'a [Pair<'a>],
     engine: &mut Engine,
     locator: &mut SplitLocator<'a>,
-) -> SourceResult<Vec<Child<'a>>> {
+) -> SourceResult<(Vec<Child<'a>>, Vec<Generated<'a>>)> {
     let mut items = Vec::new();
+    let mut generated = Vec::new();
     let mut errors = EcoVec::new();
     let mut seen = FxHashMap::default();
 
@@ -252,6 +316,9 @@ fn collect<'a>(
         } else if let Some(elem) = elem.to_packed::<DocumentElem>() {
             items.push(Child::Document(elem, *styles, locator.next(&elem.span())));
             elem.path.as_ref()
+        } else if let Some(asset) = GeneratedAsset::from_content(elem) {
+            generated.push((asset, *styles));
+            asset.path()
         } else {
             errors.push(error!(
                 elem.span(), "{} is not allowed at the top-level in bundle export",
@@ -281,7 +348,7 @@ fn collect<'a>(
         return Err(errors);
     }
 
-    Ok(items)
+    Ok((items, generated))
 }
 
 /// Compiles a single document.
@@ -292,6 +359,8 @@ fn compile_document<'a>(
     locator: Locator<'a>,
 ) -> SourceResult<BundleDocument> {
     let format = document.determine_format(styles).at(document.span())?;
+    let options = document.export.get_cloned(styles);
+    check_export_options(&options, format).at(document.span())?;
     let target = TargetElem::target.set(format.target()).wrap();
     let styles = styles.chain(&target);
     Ok(match format {
@@ -304,7 +373,7 @@ fn compile_document<'a>(
             )?;
 
             let num_pages = doc.pages().len();
-            if num_pages != 1 && matches!(format, PagedFormat::Png | PagedFormat::Svg) {
+            if num_pages != 1 && !matches!(format, PagedFormat::Pdf) {
                 bail!(
                     document.span(),
                     "expected document to have a single page";
@@ -315,7 +384,7 @@ fn compile_document<'a>(
 
             BundleDocument::Paged(
                 Box::new(doc),
-                PagedExtras { format, anchors: Vec::new() },
+                PagedExtras { format, anchors: Vec::new(), options },
             )
         }
         DocumentFormat::Html => {
@@ -334,7 +403,7 @@ fn compile_document<'a>(
                 locator,
                 styles,
             )?;
-            BundleDocument::Html(Box::new(doc))
+            BundleDocument::Html(Box::new(doc), HtmlExtras { options })
         }
     })
 }
@@ -0,0 +1,54 @@
+//! Helpers shared by the EPUB export and generated assets.
+
+use ecow::{EcoString, eco_format};
+use typst_html::{HtmlElement, HtmlNode, HtmlTag, tag};
+use typst_library::foundations::Datetime;
+use xmlwriter::XmlWriter;
+
+/// Collects the text within an element.
+pub fn collect_text(elem: &HtmlElement, text: &mut String) {
+    for child in &elem.children {
+        match child {
+            HtmlNode::Text(piece, _) => text.push_str(piece),
+            HtmlNode::Element(child) => collect_text(child, text),
+            HtmlNode::Tag(_) | HtmlNode::Frame(_) => {}
+        }
+    }
+}
+
+/// The level of a heading element.
+pub fn heading_level(tag: HtmlTag) -> Option<usize> {
+    Some(match tag {
+        tag::h1 => 1,
+        tag::h2 => 2,
+        tag::h3 => 3,
+        tag::h4 => 4,
+        tag::h5 => 5,
+        tag::h6 => 6,
+        _ => return None,
+    })
+}
+
+/// Formats a datetime in the format that EPUB and Atom require for
+/// modification dates.
+pub fn format_modified(datetime: Option<Datetime>) -> EcoString {
+    let Some(datetime) = datetime else {
+        return "1970-01-01T00:00:00Z".into();
+    };
+    eco_format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        datetime.year().unwrap_or(1970),
+        datetime.month().unwrap_or(1),
+        datetime.day().unwrap_or(1),
+        datetime.hour().unwrap_or(0),
+        datetime.minute().unwrap_or(0),
+        datetime.second().unwrap_or(0),
+    )
+}
+
+/// Writes an element that only contains text.
+pub fn write_text_element(xml: &mut XmlWriter, name: &str, text: &str) {
+    xml.start_element(name);
+    xml.write_text(text);
+    xml.end_element();
+}
@@ -11,12 +11,14 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use clap::builder::styling::{AnsiColor, Effects};
-use clap::builder::{Styles, TypedValueParser, ValueParser};
+use clap::builder::{
+    PossibleValue, PossibleValuesParser, Styles, TypedValueParser, ValueParser,
+};
 use clap::{ArgAction, Args, ColorChoice, Parser, Subcommand, ValueEnum, ValueHint};
 use clap_complete::Shell;
 use semver::Version;
 use serde::Serialize;
-use typst_utils::display_possible_values;
+use typst_utils::{PdfStandard, display_possible_values, parse_page_range};
 
 /// The character typically used to separate path components
 /// in environment variables.
@@ -342,7 +344,11 @@ pub struct CompileArgs {
 
     /// One (or multiple comma-separated) PDF standards that Typst will enforce
     /// conformance with.
-    #[arg(long = "pdf-standard", value_delimiter = ',')]
+    #[arg(
+        long = "pdf-standard",
+        value_delimiter = ',',
+        value_parser = pdf_standard_value_parser()
+    )]
     pub pdf_standard: Vec<PdfStandard>,
 
     /// By default, even when not producing a `PDF/UA-1` document, a tagged PDF
@@ -352,10 +358,183 @@ pub struct CompileArgs {
     #[arg(long = "no-pdf-tags")]
     pub no_pdf_tags: bool,
 
-    /// The PPI (pixels per inch) to use for PNG export.
+    /// Reduces the size of the PDF by optimizing raster images according to a
+    /// profile.
+    ///
+    /// Images whose resolution exceeds what the profile needs for the size at
+    /// which they are placed are downsampled. Images without transparency are
+    /// re-encoded as JPEG and images with identical pixel data are only
+    /// embedded once.
+    #[arg(long = "pdf-optimize", value_name = "PROFILE", default_value_t)]
+    pub pdf_optimize: PdfOptimize,
+
+    /// Which labelled elements become named destinations in PDF export, so
+    /// that they can be linked to from other documents and systems.
+    ///
+    /// Takes a selector like for `typst query`, e.g. 'heading' or
+    /// 'selector(heading).or(figure)'. Defaults to 'heading'.
+    #[arg(long = "pdf-destinations", value_name = "SELECTOR")]
+    pub pdf_destinations: Option<String>,
+
+    /// File path to which a JSON file mapping the PDF's named destinations to
+    /// their page and position will be written. Use `-` to write to stdout.
+    #[clap(
+        long = "pdf-link-targets",
+        value_name = "PATH",
+        value_parser = output_value_parser(),
+        value_hint = ValueHint::FilePath,
+    )]
+    pub pdf_link_targets: Option<Output>,
+
+    /// Writes the presenter notes of the document (see `pdf.note`) to a
+    /// `.pdfpc` file for presenter consoles like pdfpc.
+    ///
+    /// Without a path, the file is written next to the PDF with the same file
+    /// stem, where presenter consoles look for it. Use `-` to write to stdout.
+    #[clap(
+        long = "pdfpc",
+        value_name = "PATH",
+        num_args = 0..=1,
+        value_parser = output_value_parser(),
+        value_hint = ValueHint::FilePath,
+    )]
+    pub pdfpc: Option<Option<Output>>,
+
+    /// Groups consecutive pages with the same page number into one slide in
+    /// the presenter notes, with each page being an overlay of the slide.
+    #[arg(long = "pdfpc-overlays")]
+    pub pdfpc_overlays: bool,
+
+    /// Attaches the presenter notes to the PDF as a `.pdfpc` file.
+    #[arg(long = "pdf-embed-notes")]
+    pub pdf_embed_notes: bool,
+
+    /// Writes a bundle into a single archive instead of a directory.
+    ///
+    /// If not specified, the archive format is inferred from the output path's
+    /// extension (`.zip`, `.tar`, `.tar.gz`, or `.tgz`). Required to write a
+    /// bundle to stdout. Timestamps within the archive are taken from
+    /// `--creation-timestamp`, defaulting to the Unix epoch, so that archives
+    /// are reproducible.
+    #[arg(long = "bundle-archive", value_name = "FORMAT")]
+    pub bundle_archive: Option<BundleArchive>,
+
+    /// Removes files from the bundle output directory that an earlier
+    /// compilation produced, but the current one no longer does.
+    ///
+    /// The files of the last compilation are recorded in a
+    /// `.typst-bundle.json` file in the output directory, so that this also
+    /// works across `typst watch` sessions.
+    #[arg(long = "bundle-prune")]
+    pub bundle_prune: bool,
+
+    /// Renames the assets of a bundle to include a hash of their contents,
+    /// rewrites references to them, and adds a `manifest.json` that maps the
+    /// original paths to the new ones.
+    #[arg(long = "bundle-fingerprint")]
+    pub bundle_fingerprint: bool,
+
+    /// Warns about links whose destination does not exist, in HTML and bundle
+    /// export.
+    ///
+    /// This covers relative links to files and anchors within a bundle, as
+    /// well as links to anchors within the same HTML document.
+    #[arg(long = "check-links")]
+    pub check_links: bool,
+
+    /// Also checks that `file://` links point to existing files on this
+    /// machine.
+    #[arg(long = "check-file-links", requires = "check_links")]
+    pub check_file_links: bool,
+
+    /// Only outputs the contents of the `<body>` element in HTML export, for
+    /// embedding into an existing page.
+    #[arg(long = "html-fragment")]
+    pub html_fragment: bool,
+
+    /// File path to which the CSS required by an HTML fragment, e.g. for
+    /// equations, will be written. Use `-` to write to stdout.
+    #[clap(
+        long = "html-styles",
+        value_name = "PATH",
+        value_parser = output_value_parser(),
+        value_hint = ValueHint::FilePath,
+        requires = "html_fragment",
+    )]
+    pub html_styles: Option<Output>,
+
+    /// How to write the styles that Typst generates in HTML export.
+    ///
+    /// With 'classes', elements with the same styles share a generated CSS
+    /// class, which is defined in a `<style>` element or, in bundle export, in
+    /// a shared `typst.css` file. Site authors can then override the styles.
+    #[arg(long = "html-style-mode", value_name = "MODE", default_value_t)]
+    pub html_style_mode: HtmlStyleMode,
+
+    /// Which kind of markup to emit in HTML export.
+    ///
+    /// With 'semantic', Typst emits no generated styles, sizes, or
+    /// presentational elements, so that the HTML can be styled externally.
+    #[arg(long = "html-profile", value_name = "PROFILE", default_value_t)]
+    pub html_profile: HtmlProfile,
+
+    /// The unique identifier of the publication in EPUB export, like an ISBN
+    /// URN (`urn:isbn:...`). By default, a UUID is derived from the document's
+    /// title and authors.
+    #[arg(long = "epub-identifier", value_name = "ID")]
+    pub epub_identifier: Option<String>,
+
+    /// A font family to package with the publication in EPUB export. Can be
+    /// given multiple times.
+    ///
+    /// All fonts of the family are packaged and declared in the stylesheet.
+    /// The families become the default font of the chapters, in the given
+    /// order.
+    #[arg(long = "epub-font", value_name = "FAMILY")]
+    pub epub_fonts: Vec<String>,
+
+    /// How to write text in SVG export.
+    ///
+    /// With 'text', text can be searched, selected, copied, and read by screen
+    /// readers, at the cost of embedding the used fonts. Glyphs that can't be
+    /// written as text fall back to outlines.
+    #[arg(long = "svg-text", value_name = "MODE", default_value_t)]
+    pub svg_text: SvgTextMode,
+
+    /// The PPI (pixels per inch) to use for PNG, JPEG, and WebP export.
     #[arg(long = "ppi", default_value_t = 144.0)]
     pub ppi: f64,
 
+    /// Overrides the background of pages in PNG, JPEG, and WebP export.
+    ///
+    /// Either 'transparent' or a hex color like '#ffeedd'. By default, the
+    /// page's fill is used. JPEG files do not support transparency, so
+    /// transparent areas become white.
+    #[arg(long = "background", value_name = "COLOR", value_parser = parse_background)]
+    pub background: Option<Background>,
+
+    /// The quality of JPEG files, from 1 (smallest) to 100 (best). Defaults
+    /// to 90.
+    ///
+    /// Only applies to JPEG output and to JPEG documents in bundles. PNG and
+    /// WebP files are always compressed losslessly, so this is rejected for
+    /// other output formats.
+    #[arg(
+        long = "jpeg-quality",
+        value_name = "QUALITY",
+        value_parser = clap::value_parser!(u8).range(1..=100),
+    )]
+    pub jpeg_quality: Option<u8>,
+
+    /// Records the PPI in PNG and JPEG files, so that they are printed at the
+    /// document's physical size.
+    #[arg(long = "embed-ppi")]
+    pub embed_ppi: bool,
+
+    /// Tags PNG, JPEG, and WebP files as being in the sRGB color space.
+    #[arg(long = "srgb-profile")]
+    pub srgb_profile: bool,
+
     /// File path to which a Makefile with the current compilation's
     /// dependencies will be written.
     #[clap(long = "make-deps", value_name = "PATH", hide = true)]
@@ -391,6 +570,11 @@ pub struct CompileArgs {
     /// apart from file names and line numbers.
     #[arg(long = "timings", value_name = "OUTPUT_JSON")]
     pub timings: Option<PathBuf>,
+
+    /// Prints additional information about the exported output, such as the
+    /// effect of `--pdf-optimize`.
+    #[arg(long = "verbose")]
+    pub verbose: bool,
 }
 
 /// Arguments for the construction of a world. Shared by compile, watch, eval, and
@@ -497,6 +681,23 @@ pub struct ServerArgs {
     #[clap(long)]
     pub no_serve: bool,
 
+    /// Serves a live preview of the pages when exporting to PDF, PNG, JPEG,
+    /// WebP, or SVG. (HTML export is always served unless `--no-serve` is
+    /// passed.)
+    #[clap(long, conflicts_with = "no_serve")]
+    pub serve: bool,
+
+    /// A command that is run when clicking on a page in the preview of a
+    /// paged document, to open the clicked position in an editor.
+    ///
+    /// The placeholders `{file}`, `{line}`, and `{column}` are replaced with
+    /// the path of the source file and the one-based line and column of the
+    /// position. The template is split into arguments like by a shell, so
+    /// arguments with spaces can be quoted. Example:
+    /// `code --goto {file}:{line}:{column}`.
+    #[clap(long, requires = "serve", value_name = "TEMPLATE")]
+    pub editor_command: Option<String>,
+
     /// Disables the injected live reload script for HTML export. The HTML that
     /// is written to disk isn't affected either way.
     #[clap(long)]
@@ -591,15 +792,19 @@ impl Write for OpenOutput<'_> {
 pub enum OutputFormat {
     Pdf,
     Png,
+    Jpg,
+    Webp,
     Svg,
     Html,
     Bundle,
+    Epub,
+    Docx,
 }
 
 impl OutputFormat {
     /// Whether this format results in a `PagedDocument`.
     pub fn is_paged(&self) -> bool {
-        matches!(self, Self::Pdf | Self::Png | Self::Svg)
+        matches!(self, Self::Pdf | Self::Png | Self::Jpg | Self::Webp | Self::Svg)
     }
 }
 
@@ -649,68 +854,75 @@ pub enum Feature {
     Html,
     Bundle,
     A11yExtras,
+    Docx,
 }
 
 display_possible_values!(Feature);
 
-/// A PDF standard that Typst can enforce conformance with.
+/// A profile for optimizing raster images in PDF export.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum PdfOptimize {
+    /// Embeds images as-is.
+    #[default]
+    None,
+    /// Downsamples to 96 PPI and uses low JPEG quality, for viewing on screen.
+    Screen,
+    /// Downsamples to 150 PPI and uses medium JPEG quality, for e-readers.
+    Ebook,
+    /// Downsamples to 300 PPI and uses high JPEG quality, for printing.
+    Print,
+}
+
+display_possible_values!(PdfOptimize);
+
+/// An archive format for bundle export.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, ValueEnum)]
-#[allow(non_camel_case_types)]
-pub enum PdfStandard {
-    /// PDF 1.4.
-    #[value(name = "1.4")]
-    V_1_4,
-    /// PDF 1.5.
-    #[value(name = "1.5")]
-    V_1_5,
-    /// PDF 1.6.
-    #[value(name = "1.6")]
-    V_1_6,
-    /// PDF 1.7.
-    #[value(name = "1.7")]
-    V_1_7,
-    /// PDF 2.0.
-    #[value(name = "2.0")]
-    V_2_0,
-    /// PDF/A-1b.
-    #[value(name = "a-1b")]
-    A_1b,
-    /// PDF/A-1a.
-    #[value(name = "a-1a")]
-    A_1a,
-    /// PDF/A-2b.
-    #[value(name = "a-2b")]
-    A_2b,
-    /// PDF/A-2u.
-    #[value(name = "a-2u")]
-    A_2u,
-    /// PDF/A-2a.
-    #[value(name = "a-2a")]
-    A_2a,
-    /// PDF/A-3b.
-    #[value(name = "a-3b")]
-    A_3b,
-    /// PDF/A-3u.
-    #[value(name = "a-3u")]
-    A_3u,
-    /// PDF/A-3a.
-    #[value(name = "a-3a")]
-    A_3a,
-    /// PDF/A-4.
-    #[value(name = "a-4")]
-    A_4,
-    /// PDF/A-4f.
-    #[value(name = "a-4f")]
-    A_4f,
-    /// PDF/A-4e.
-    #[value(name = "a-4e")]
-    A_4e,
-    /// PDF/UA-1.
-    #[value(name = "ua-1")]
-    UA_1,
-}
-
-display_possible_values!(PdfStandard);
+pub enum BundleArchive {
+    /// A ZIP archive.
+    Zip,
+    /// An uncompressed tarball.
+    Tar,
+    /// A gzip-compressed tarball.
+    TarGz,
+}
+
+display_possible_values!(BundleArchive);
+
+/// How to write generated styles in HTML export.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum HtmlStyleMode {
+    /// Writes styles as inline `style` attributes.
+    #[default]
+    Inline,
+    /// Writes styles as shared CSS classes.
+    Classes,
+}
+
+display_possible_values!(HtmlStyleMode);
+
+/// Which kind of markup to emit in HTML export.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum HtmlProfile {
+    /// Emits semantic markup with generated styles.
+    #[default]
+    Full,
+    /// Emits purely semantic markup.
+    Semantic,
+}
+
+display_possible_values!(HtmlProfile);
+
+/// How to write text in SVG export.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
+pub enum SvgTextMode {
+    /// Writes glyphs as outlines.
+    #[default]
+    Outlines,
+    /// Writes selectable text with embedded fonts.
+    Text,
+}
+
+display_possible_values!(SvgTextMode);
 
 /// Output file format for query and info commands
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ValueEnum)]
@@ -731,39 +943,20 @@ display_possible_values!(SerializationFormat);
 pub struct Pages(pub RangeInclusive<Option<NonZeroUsize>>);
 
 impl FromStr for Pages {
-    type Err = &'static str;
+    type Err = String;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
-        match value.split('-').map(str::trim).collect::<Vec<_>>().as_slice() {
-            [] | [""] => Err("page export range must not be empty"),
-            [single_page] => {
-                let page_number = parse_page_number(single_page)?;
-                Ok(Pages(Some(page_number)..=Some(page_number)))
-            }
-            ["", ""] => Err("page export range must have start or end"),
-            [start, ""] => Ok(Pages(Some(parse_page_number(start)?)..=None)),
-            ["", end] => Ok(Pages(None..=Some(parse_page_number(end)?))),
-            [start, end] => {
-                let start = parse_page_number(start)?;
-                let end = parse_page_number(end)?;
-                if start > end {
-                    Err("page export range must end at a page after the start")
-                } else {
-                    Ok(Pages(Some(start)..=Some(end)))
-                }
-            }
-            [_, _, _, ..] => Err("page export range must have a single hyphen"),
-        }
+        parse_page_range(value).map(Pages)
     }
 }
 
-/// Parses a single page number.
-fn parse_page_number(value: &str) -> Result<NonZeroUsize, &'static str> {
-    if value == "0" {
-        Err("page numbers start at one")
-    } else {
-        NonZeroUsize::from_str(value).map_err(|_| "not a valid page number")
-    }
+/// The clap value parser used by `CompileArgs.pdf_standard`, which lists the
+/// standards shared with the library as possible values.
+fn pdf_standard_value_parser() -> impl TypedValueParser<Value = PdfStandard> {
+    let values = PdfStandard::ALL.map(|standard| {
+        PossibleValue::new(standard.name()).help(standard.display_name())
+    });
+    PossibleValuesParser::new(values).map(|name| name.parse::<PdfStandard>().unwrap())
 }
 
 /// The clap value parser used by `SharedArgs.input`
@@ -793,6 +986,51 @@ fn output_value_parser() -> impl TypedValueParser<Value = Output> {
     })
 }
 
+/// An overridden background for raster image export.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Background {
+    /// No background.
+    Transparent,
+    /// A solid color, given by its RGBA components.
+    Color([u8; 4]),
+}
+
+/// Parses the `--background` argument.
+fn parse_background(raw: &str) -> Result<Background, String> {
+    if raw == "transparent" {
+        return Ok(Background::Transparent);
+    }
+
+    let hex = raw.strip_prefix('#').unwrap_or(raw);
+    let digits = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<_>>>();
+    let rgba = match (digits, hex.len()) {
+        (Some(d), 3 | 4) => {
+            let mut rgba = [u8::MAX; 4];
+            for (v, d) in rgba.iter_mut().zip(d) {
+                *v = d * 17;
+            }
+            rgba
+        }
+        (Some(d), 6 | 8) => {
+            let mut rgba = [u8::MAX; 4];
+            for (v, d) in rgba.iter_mut().zip(d.chunks(2)) {
+                *v = d[0] * 16 + d[1];
+            }
+            rgba
+        }
+        _ => {
+            return Err(format!(
+                "invalid color '{raw}' (expected 'transparent' or a hex color like '#ffeedd')"
+            ));
+        }
+    };
+
+    Ok(Background::Color(rgba))
+}
+
 /// Parses key/value pairs split by the first equal sign.
 ///
 /// This function will return an error if the argument contains no equals sign
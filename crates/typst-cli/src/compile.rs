@@ -1,39 +1,68 @@
 use std::ffi::OsStr;
+use std::hash::Hash;
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
 use std::path::Path;
 
 use chrono::{DateTime, Datelike, Timelike, Utc};
-use ecow::eco_format;
+use ecow::{EcoString, EcoVec, eco_format};
 use parking_lot::RwLock;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use typst::World;
 use typst::diag::{
     At, HintedStrResult, HintedString, SourceDiagnostic, SourceResult, StrResult, Warned,
     bail,
 };
-use typst::foundations::{Datetime, Smart};
+use typst::foundations::{Bytes, Datetime, Selector, Smart};
 use typst::layout::PageRanges;
-use typst::syntax::Span;
-use typst_bundle::{Bundle, BundleOptions, VirtualFs};
+use typst::syntax::{Span, VirtualPath};
+use typst::text::Font;
+use typst::visualize::Color;
+use typst_bundle::{
+    ArchiveFormat, ArchiveOptions, Bundle, BundleChanges, BundleOptions, EpubOptions,
+    LinkCheckOptions, VirtualFs,
+};
+use typst_docx::DocxDocument;
 use typst_html::{HtmlDocument, HtmlOptions};
 use typst_kit::diagnostics::DiagnosticWorld;
 use typst_kit::timer::Timer;
 use typst_layout::{Page, PagedDocument};
-use typst_pdf::{PdfOptions, PdfStandards, Timestamp};
-use typst_render::RenderOptions;
-use typst_svg::SvgOptions;
+use typst_pdf::{
+    PdfImageOptions, PdfImageReport, PdfOptions, PdfPresenterOptions, PdfStandard,
+    PdfStandards, Timestamp,
+};
+use typst_render::{RasterFormat, RenderOptions};
+use typst_svg::{SvgLinks, SvgOptions};
 use typst_utils::Scalar;
 
 use crate::args::{
-    CompileArgs, CompileCommand, DepsFormat, DiagnosticFormat, Input, Output,
-    OutputFormat, PdfStandard, WatchCommand,
+    Background, BundleArchive, CompileArgs, CompileCommand, DepsFormat, DiagnosticFormat,
+    HtmlProfile, HtmlStyleMode, Input, Output, OutputFormat, PdfOptimize, SvgTextMode,
+    WatchCommand,
 };
 use crate::deps::write_deps;
+use crate::query::eval_selector;
 use crate::watch::Status;
 use crate::world::SystemWorld;
 use crate::{set_failed, terminal};
 
+#[cfg(feature = "http-server")]
+use std::sync::Arc;
+
+#[cfg(feature = "http-server")]
+use typst::introspection::{DocumentPosition, PagedPosition};
+#[cfg(feature = "http-server")]
+use typst::syntax::FileId;
+#[cfg(feature = "http-server")]
+use typst_ide::{Jump, JumpInDocument};
 #[cfg(feature = "http-server")]
 use typst_kit::server::HttpServer;
 
+#[cfg(feature = "http-server")]
+use crate::print_error;
+#[cfg(feature = "http-server")]
+use crate::world::SourceSnapshot;
+
 /// Execute a compilation command.
 pub fn compile(command: &'static CompileCommand) -> HintedStrResult<()> {
     let mut timer = Timer::new_or_placeholder(command.args.timings.clone());
@@ -74,18 +103,72 @@ pub struct CompileConfig {
     pub pdf_standards: PdfStandards,
     /// Whether to write PDF (accessibility) tags.
     pub tagged: bool,
+    /// How to optimize raster images in PDF export.
+    pub pdf_images: PdfImageOptions,
+    /// A selector for the elements that become named destinations in PDF
+    /// export, as passed on the command line.
+    pub pdf_destinations: Option<String>,
+    /// The evaluated selector for named destinations.
+    pub pdf_named_destinations: Smart<Selector>,
+    /// A destination to write the PDF's link targets to.
+    pub pdf_link_targets: Option<Output>,
+    /// A destination to write the presenter notes to.
+    pub pdfpc: Option<Output>,
+    /// How to export presenter notes.
+    pub pdf_presenter: PdfPresenterOptions,
+    /// Whether to only output the contents of the `<body>` in HTML export.
+    pub html_fragment: bool,
+    /// A destination to write the styles required by an HTML fragment to.
+    pub html_styles: Option<Output>,
+    /// How to write generated styles in HTML export.
+    pub html_style_mode: HtmlStyleMode,
+    /// Which kind of markup to emit in HTML export.
+    pub html_profile: HtmlProfile,
+    /// The unique identifier of an EPUB publication.
+    pub epub_identifier: Option<EcoString>,
+    /// The font families to package with an EPUB publication.
+    pub epub_fonts: Vec<EcoString>,
+    /// The archive to write a bundle into, if any.
+    pub bundle_archive: Option<ArchiveFormat>,
     /// A destination to write a list of dependencies to.
     pub deps: Option<Output>,
     /// The format to use for dependencies.
     pub deps_format: DepsFormat,
-    /// The PPI (pixels per inch) to use for PNG export.
+    /// How to write text in SVG export.
+    pub svg_text: SvgTextMode,
+    /// The PPI (pixels per inch) to use for PNG, JPEG, and WebP export.
     pub ppi: f64,
+    /// Overrides the page background in raster image export.
+    pub background: Option<Background>,
+    /// The quality of JPEG files.
+    pub jpeg_quality: u8,
+    /// Whether to record the PPI in raster image files.
+    pub embed_ppi: bool,
+    /// Whether to tag raster image files as sRGB.
+    pub srgb_profile: bool,
     /// The export cache for images, used for caching output files in `typst
     /// watch` sessions with images.
     pub export_cache: ExportCache,
+    /// The files of the previous bundle export, used to detect changes and
+    /// stale files in `typst watch` sessions.
+    pub bundle_cache: RwLock<Option<VirtualFs>>,
+    /// Whether to remove stale files from the bundle output directory.
+    pub bundle_prune: bool,
+    /// Whether to rename bundle assets to include a hash of their contents.
+    pub bundle_fingerprint: bool,
+    /// Whether to warn about links whose destination does not exist.
+    pub check_links: bool,
+    /// Whether to also check `file://` links against the file system.
+    pub check_file_links: bool,
+    /// Whether to print additional information about the exported output.
+    pub verbose: bool,
     /// Server for `typst watch` to HTML.
     #[cfg(feature = "http-server")]
     pub server: Option<HttpServer>,
+    /// The command that opens a source position in an editor when clicking on
+    /// the served preview of a paged document.
+    #[cfg(feature = "http-server")]
+    pub editor_command: Option<String>,
 }
 
 impl CompileConfig {
@@ -114,8 +197,25 @@ impl CompileConfig {
             match output.extension() {
                 Some(ext) if ext.eq_ignore_ascii_case("pdf") => OutputFormat::Pdf,
                 Some(ext) if ext.eq_ignore_ascii_case("png") => OutputFormat::Png,
+                Some(ext)
+                    if ext.eq_ignore_ascii_case("jpg")
+                        || ext.eq_ignore_ascii_case("jpeg") =>
+                {
+                    OutputFormat::Jpg
+                }
+                Some(ext) if ext.eq_ignore_ascii_case("webp") => OutputFormat::Webp,
                 Some(ext) if ext.eq_ignore_ascii_case("svg") => OutputFormat::Svg,
                 Some(ext) if ext.eq_ignore_ascii_case("html") => OutputFormat::Html,
+                Some(ext) if ext.eq_ignore_ascii_case("epub") => OutputFormat::Epub,
+                Some(ext) if ext.eq_ignore_ascii_case("docx") => OutputFormat::Docx,
+                _ if output
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .and_then(ArchiveFormat::from_file_name)
+                    .is_some() =>
+                {
+                    OutputFormat::Bundle
+                }
                 _ => bail!(
                     "could not infer output format for path {}.\n\
                      consider providing the format manually with `--format/-f`",
@@ -134,13 +234,22 @@ impl CompileConfig {
                 match output_format {
                     OutputFormat::Pdf => "pdf",
                     OutputFormat::Png => "png",
+                    OutputFormat::Jpg => "jpg",
+                    OutputFormat::Webp => "webp",
                     OutputFormat::Svg => "svg",
                     OutputFormat::Html => "html",
                     OutputFormat::Bundle => "",
+                    OutputFormat::Epub => "epub",
+                    OutputFormat::Docx => "docx",
                 },
             ))
         });
 
+        let bundle_archive = args.bundle_archive.map(Into::into).or_else(|| {
+            let Output::Path(path) = &output else { return None };
+            ArchiveFormat::from_file_name(path.file_name()?.to_str()?)
+        });
+
         let pages = args.pages.as_ref().map(|export_ranges| {
             PageRanges::new(export_ranges.iter().map(|r| r.0.clone()).collect())
         });
@@ -156,15 +265,16 @@ impl CompileConfig {
         }
 
         if !tagged {
-            const ACCESSIBLE: &[(PdfStandard, &str)] = &[
-                (PdfStandard::A_1a, "PDF/A-1a"),
-                (PdfStandard::A_2a, "PDF/A-2a"),
-                (PdfStandard::A_3a, "PDF/A-3a"),
-                (PdfStandard::UA_1, "PDF/UA-1"),
+            const ACCESSIBLE: &[PdfStandard] = &[
+                PdfStandard::A_1a,
+                PdfStandard::A_2a,
+                PdfStandard::A_3a,
+                PdfStandard::Ua_1,
             ];
 
-            for (standard, name) in ACCESSIBLE {
+            for standard in ACCESSIBLE {
                 if args.pdf_standard.contains(standard) {
+                    let name = standard.display_name();
                     if args.no_pdf_tags {
                         bail!("cannot disable PDF tags when exporting a {name} document");
                     } else {
@@ -177,14 +287,13 @@ impl CompileConfig {
             }
         }
 
-        let pdf_standards = PdfStandards::new(
-            &args.pdf_standard.iter().copied().map(Into::into).collect::<Vec<_>>(),
-        )?;
+        let pdf_standards = PdfStandards::new(&args.pdf_standard)?;
 
         #[cfg(feature = "http-server")]
         let server = if let Some(command) = watch
             && !command.server.no_serve
-            && matches!(output_format, OutputFormat::Html | OutputFormat::Bundle)
+            && (matches!(output_format, OutputFormat::Html | OutputFormat::Bundle)
+                || (command.server.serve && output_format.is_paged()))
         {
             Some(HttpServer::new(
                 &eco_format!("{input}"),
@@ -221,6 +330,67 @@ impl CompileConfig {
             _ => {}
         }
 
+        match (&output, &args.pdf_link_targets, watch) {
+            (_, Some(Output::Stdout), Some(_)) => {
+                bail!("cannot write link targets to stdout in watch mode")
+            }
+            (Output::Stdout, Some(Output::Stdout), _) => {
+                bail!("cannot write both output and link targets to stdout")
+            }
+            _ => {}
+        }
+
+        if args.jpeg_quality.is_some()
+            && !matches!(output_format, OutputFormat::Jpg | OutputFormat::Bundle)
+        {
+            bail!(
+                "--jpeg-quality only applies to JPEG output";
+                hint: "the output format is {output_format}";
+                hint: "PNG and WebP files are always compressed losslessly";
+            );
+        }
+
+        if (args.pdfpc.is_some() || args.pdf_embed_notes)
+            && output_format != OutputFormat::Pdf
+        {
+            bail!(
+                "presenter notes can only be written for PDF output";
+                hint: "the output format is {output_format}";
+            );
+        }
+
+        let pdfpc = match &args.pdfpc {
+            None => None,
+            Some(Some(dest)) => Some(dest.clone()),
+            Some(None) => match &output {
+                Output::Path(path) => Some(Output::Path(path.with_extension("pdfpc"))),
+                Output::Stdout => bail!(
+                    "cannot determine where to write presenter notes";
+                    hint: "pass a path to --pdfpc when writing the document to stdout";
+                ),
+            },
+        };
+
+        match (&output, &pdfpc, watch) {
+            (_, Some(Output::Stdout), Some(_)) => {
+                bail!("cannot write presenter notes to stdout in watch mode")
+            }
+            (Output::Stdout, Some(Output::Stdout), _) => {
+                bail!("cannot write both output and presenter notes to stdout")
+            }
+            _ => {}
+        }
+
+        match (&output, &args.html_styles, watch) {
+            (_, Some(Output::Stdout), Some(_)) => {
+                bail!("cannot write styles to stdout in watch mode")
+            }
+            (Output::Stdout, Some(Output::Stdout), _) => {
+                bail!("cannot write both output and styles to stdout")
+            }
+            _ => {}
+        }
+
         Ok(Self {
             warnings,
             watching: watch.is_some(),
@@ -231,6 +401,22 @@ impl CompileConfig {
             pages,
             pdf_standards,
             tagged,
+            pdf_images: args.pdf_optimize.into(),
+            pdf_destinations: args.pdf_destinations.clone(),
+            pdf_named_destinations: Smart::Auto,
+            pdf_link_targets: args.pdf_link_targets.clone(),
+            pdfpc,
+            pdf_presenter: PdfPresenterOptions {
+                embed: args.pdf_embed_notes,
+                overlays: args.pdfpc_overlays,
+            },
+            html_fragment: args.html_fragment,
+            html_styles: args.html_styles.clone(),
+            html_style_mode: args.html_style_mode,
+            html_profile: args.html_profile,
+            epub_identifier: args.epub_identifier.as_deref().map(Into::into),
+            epub_fonts: args.epub_fonts.iter().map(Into::into).collect(),
+            bundle_archive,
             creation_timestamp: args
                 .world
                 .creation_timestamp
@@ -239,14 +425,30 @@ impl CompileConfig {
                         .ok_or("creation timestamp is out of range")
                 })
                 .transpose()?,
+            svg_text: args.svg_text,
             ppi: args.ppi,
+            background: args.background.clone(),
+            jpeg_quality: args
+                .jpeg_quality
+                .unwrap_or(RenderOptions::default().jpeg_quality),
+            embed_ppi: args.embed_ppi,
+            srgb_profile: args.srgb_profile,
             diagnostic_format: args.process.diagnostic_format,
             open: args.open.clone(),
             export_cache: ExportCache::new(),
+            bundle_cache: RwLock::new(None),
+            bundle_prune: args.bundle_prune,
+            bundle_fingerprint: args.bundle_fingerprint,
+            check_links: args.check_links,
+            check_file_links: args.check_file_links,
             deps,
             deps_format,
+            verbose: args.verbose,
             #[cfg(feature = "http-server")]
             server,
+            #[cfg(feature = "http-server")]
+            editor_command: watch
+                .and_then(|command| command.server.editor_command.clone()),
         })
     }
 }
@@ -264,6 +466,12 @@ pub fn compile_once(
         Status::Compiling.print(config).unwrap();
     }
 
+    // The selector can only be evaluated with access to the standard library.
+    if let Some(selector) = &config.pdf_destinations {
+        config.pdf_named_destinations =
+            Smart::Custom(eval_selector(&*world, selector)?.0);
+    }
+
     let Warned { output, mut warnings } = compile_and_export(world, config);
 
     // Add static warnings (for deprecated CLI flags and such).
@@ -319,41 +527,253 @@ fn compile_and_export(
     config: &mut CompileConfig,
 ) -> Warned<SourceResult<Vec<Output>>> {
     match config.output_format {
-        OutputFormat::Pdf | OutputFormat::Png | OutputFormat::Svg => {
+        OutputFormat::Pdf
+        | OutputFormat::Png
+        | OutputFormat::Jpg
+        | OutputFormat::Webp
+        | OutputFormat::Svg => {
             let Warned { output, warnings } = typst::compile::<PagedDocument>(world);
+            #[cfg(feature = "http-server")]
+            if let Ok(document) = &output {
+                serve_paged(world, document, config);
+            }
             let result = output.and_then(|document| export_paged(&document, config));
             Warned { output: result, warnings }
         }
         OutputFormat::Html => {
-            let Warned { output, warnings } = typst::compile::<HtmlDocument>(world);
-            let result = output.and_then(|document| export_html(&document, config));
-            Warned {
-                output: result.map(|()| vec![config.output.clone()]),
-                warnings,
+            let Warned { output, mut warnings } = typst::compile::<HtmlDocument>(world);
+            if let Ok(document) = &output {
+                warnings.extend(check_links(config, |options| {
+                    typst_bundle::check_html_links(document, options)
+                }));
             }
+            let result = output.and_then(|document| {
+                #[cfg_attr(not(feature = "http-server"), allow(unused_variables))]
+                let html = export_html(&document, config)?;
+                #[cfg(feature = "http-server")]
+                serve_html(world, &document, config, html);
+                Ok(vec![config.output.clone()])
+            });
+            Warned { output: result, warnings }
         }
         OutputFormat::Bundle => {
-            let Warned { output, warnings } = typst::compile::<Bundle>(world);
+            let Warned { output, mut warnings } = typst::compile::<Bundle>(world);
+            if let Ok(bundle) = &output {
+                warnings.extend(check_links(config, |options| {
+                    typst_bundle::check_links(bundle, options)
+                }));
+            }
             let result = output.and_then(|bundle| export_bundle(bundle, config));
             Warned { output: result, warnings }
         }
+        OutputFormat::Epub => {
+            let Warned { output, mut warnings } = typst::compile::<Bundle>(world);
+            if let Ok(bundle) = &output {
+                warnings.extend(check_links(config, |options| {
+                    typst_bundle::check_links(bundle, options)
+                }));
+            }
+            let result = output.and_then(|bundle| export_epub(world, &bundle, config));
+            Warned {
+                output: result.map(|()| vec![config.output.clone()]),
+                warnings,
+            }
+        }
+        OutputFormat::Docx => {
+            let Warned { output, warnings } = typst::compile::<DocxDocument>(world);
+            let result = output.and_then(|document| export_docx(&document, config));
+            Warned {
+                output: result.map(|()| vec![config.output.clone()]),
+                warnings,
+            }
+        }
     }
 }
 
-/// Export to HTML.
-fn export_html(document: &HtmlDocument, config: &CompileConfig) -> SourceResult<()> {
-    let options = HtmlOptions { pretty: config.pretty };
+/// Checks compiled output for links whose destination does not exist, if
+/// requested.
+fn check_links(
+    config: &CompileConfig,
+    check: impl FnOnce(&LinkCheckOptions) -> EcoVec<SourceDiagnostic>,
+) -> EcoVec<SourceDiagnostic> {
+    if !config.check_links {
+        return EcoVec::new();
+    }
+
+    let file_exists = |path: &Path| path.exists();
+    let options = LinkCheckOptions {
+        file_exists: config
+            .check_file_links
+            .then_some(&file_exists as &dyn Fn(&Path) -> bool),
+    };
+    check(&options)
+}
+
+/// Export to HTML. Returns the encoded HTML.
+fn export_html(document: &HtmlDocument, config: &CompileConfig) -> SourceResult<String> {
+    let options = html_options(config);
     let html = typst_html::html(document, &options)?;
     let result = config.output.write(html.as_bytes());
 
-    #[cfg(feature = "http-server")]
-    if let Some(server) = &config.server {
-        server.set_html(html);
+    if let Some(dest) = &config.html_styles {
+        let fragment = typst_html::html_fragment(document, &options)?;
+        dest.write(fragment.styles.as_bytes())
+            .map_err(|err| eco_format!("failed to write styles ({err})"))
+            .at(Span::detached())?;
     }
 
     result
         .map_err(|err| eco_format!("failed to write HTML file ({err})"))
-        .at(Span::detached())
+        .at(Span::detached())?;
+
+    Ok(html)
+}
+
+/// Serves the encoded HTML of a document, if the server is running.
+///
+/// Connected browsers are patched with the changes to the previous version of
+/// the document and can be scrolled to positions in the source code.
+#[cfg(feature = "http-server")]
+fn serve_html(
+    world: &mut SystemWorld,
+    document: &HtmlDocument,
+    config: &CompileConfig,
+    html: String,
+) {
+    let Some(server) = &config.server else { return };
+    let on_jump = jump_handler(Arc::new(world.snapshot()), Arc::new(document.clone()));
+    server.set_html_document(html, document, &html_options(config), Some(on_jump));
+}
+
+/// Serves a preview of the pages, if the server is running.
+///
+/// If an editor command is configured, clicks on the pages open the
+/// corresponding position in the source code.
+#[cfg(feature = "http-server")]
+fn serve_paged(
+    world: &mut SystemWorld,
+    document: &PagedDocument,
+    config: &CompileConfig,
+) {
+    let Some(server) = &config.server else { return };
+
+    let options = svg_options(config);
+    let pages: Vec<_> = document
+        .pages()
+        .iter()
+        .map(|page| typst_svg::svg(page, &options))
+        .collect();
+
+    let snapshot = Arc::new(world.snapshot());
+    let document = Arc::new(document.clone());
+    let on_jump = jump_handler(snapshot.clone(), document.clone());
+    let on_click = config.editor_command.clone().map(|command| {
+        move |position: PagedPosition| {
+            if let Some(Jump::File(id, offset)) =
+                typst_ide::jump_from_click(&*snapshot, &*document, &position)
+                && let Err(err) = open_in_editor(&command, &snapshot, id, offset)
+            {
+                print_error(&format!("failed to run editor command ({err})")).ok();
+            }
+        }
+    });
+
+    server.set_pages(&eco_format!("{}", config.input), pages, on_click, Some(on_jump));
+}
+
+/// Creates a handler that finds the position in a served document that
+/// corresponds to a one-based line and column in a source file.
+#[cfg(feature = "http-server")]
+fn jump_handler<D>(
+    snapshot: Arc<SourceSnapshot>,
+    document: Arc<D>,
+) -> impl Fn(&Path, usize, usize) -> Option<DocumentPosition> + Send + Sync + 'static
+where
+    D: JumpInDocument + Send + Sync + 'static,
+    D::Position: Into<DocumentPosition>,
+{
+    move |path, line, column| {
+        let (source, cursor) = snapshot.cursor(path, line, column)?;
+        let positions = typst_ide::jump_from_cursor(&*document, &source, cursor);
+        positions.into_iter().next().map(Into::into)
+    }
+}
+
+/// Runs the editor command for a position in a source file.
+#[cfg(feature = "http-server")]
+fn open_in_editor(
+    template: &str,
+    snapshot: &SourceSnapshot,
+    id: FileId,
+    offset: usize,
+) -> io::Result<()> {
+    let path = snapshot
+        .path(id)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unknown source file"))?;
+    let source = snapshot.source(id).map_err(io::Error::other)?;
+    let (line, column) = source.lines().byte_to_line_column(offset).unwrap_or((0, 0));
+
+    let mut args = editor_args(template, path, line + 1, column + 1)?.into_iter();
+    let program = args
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty command"))?;
+    // The editor's output would interfere with the watch status.
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    // Wait for the editor in the background so that it doesn't linger as a
+    // zombie process once it exits.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Fills in the placeholders of an editor command for a file and a one-based
+/// line and column.
+///
+/// The template is split like by a shell so that arguments can be quoted.
+#[cfg(feature = "http-server")]
+fn editor_args(
+    template: &str,
+    path: &Path,
+    line: usize,
+    column: usize,
+) -> io::Result<Vec<String>> {
+    let words = shell_words::split(template)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let file = path.to_string_lossy();
+    let (line, column) = (line.to_string(), column.to_string());
+    let placeholders = [
+        ("{file}", file.as_ref()),
+        ("{line}", line.as_str()),
+        ("{column}", column.as_str()),
+    ];
+
+    // Replace all placeholders in one pass so that a file path containing
+    // braces stays intact.
+    let fill = |word: String| {
+        let mut filled = String::new();
+        let mut rest = word.as_str();
+        while let Some(i) = rest.find('{') {
+            filled.push_str(&rest[..i]);
+            rest = &rest[i..];
+            match placeholders.iter().find(|(key, _)| rest.starts_with(key)) {
+                Some((key, value)) => {
+                    filled.push_str(value);
+                    rest = &rest[key.len()..];
+                }
+                None => {
+                    filled.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        filled.push_str(rest);
+        filled
+    };
+
+    Ok(words.into_iter().map(fill).collect())
 }
 
 /// Export to a paged target format.
@@ -366,19 +786,46 @@ fn export_paged(
             export_pdf(document, config).map(|()| vec![config.output.clone()])
         }
         OutputFormat::Png => {
-            export_image(document, config, ImageExportFormat::Png).at(Span::detached())
+            let fmt = ImageExportFormat::Raster(RasterFormat::Png);
+            export_image(document, config, fmt).at(Span::detached())
+        }
+        OutputFormat::Jpg => {
+            let fmt = ImageExportFormat::Raster(RasterFormat::Jpg);
+            export_image(document, config, fmt).at(Span::detached())
+        }
+        OutputFormat::Webp => {
+            let fmt = ImageExportFormat::Raster(RasterFormat::Webp);
+            export_image(document, config, fmt).at(Span::detached())
         }
         OutputFormat::Svg => {
             export_image(document, config, ImageExportFormat::Svg).at(Span::detached())
         }
-        OutputFormat::Html | OutputFormat::Bundle => unreachable!(),
+        OutputFormat::Html
+        | OutputFormat::Bundle
+        | OutputFormat::Epub
+        | OutputFormat::Docx => unreachable!(),
     }
 }
 
 /// Export to a PDF.
 fn export_pdf(document: &PagedDocument, config: &CompileConfig) -> SourceResult<()> {
     let options = pdf_options(config);
-    let buffer = typst_pdf::pdf(document, &options)?;
+    let (buffer, report) = typst_pdf::pdf_with_report(document, &options)?;
+    if let Some(dest) = &config.pdf_link_targets {
+        write_link_targets(document, &options, dest)
+            .map_err(|err| eco_format!("failed to write link targets ({err})"))
+            .at(Span::detached())?;
+    }
+    if let Some(dest) = &config.pdfpc {
+        write_pdfpc(document, &options, dest)
+            .map_err(|err| eco_format!("failed to write presenter notes ({err})"))
+            .at(Span::detached())?;
+    }
+    if config.verbose {
+        print_image_report(&report)
+            .map_err(|err| eco_format!("failed to print image report ({err})"))
+            .at(Span::detached())?;
+    }
     config
         .output
         .write(&buffer)
@@ -387,44 +834,266 @@ fn export_pdf(document: &PagedDocument, config: &CompileConfig) -> SourceResult<
     Ok(())
 }
 
+/// Writes a JSON file that maps the PDF's named destinations to their page
+/// and position.
+fn write_link_targets(
+    document: &PagedDocument,
+    options: &PdfOptions,
+    dest: &Output,
+) -> StrResult<()> {
+    let targets = typst_pdf::link_targets(document, options);
+    let json = serde_json::to_string_pretty(&targets).map_err(|e| eco_format!("{e}"))?;
+    dest.write(json.as_bytes()).map_err(|e| eco_format!("{e}"))
+}
+
+/// Writes the presenter notes in the `.pdfpc` format.
+fn write_pdfpc(
+    document: &PagedDocument,
+    options: &PdfOptions,
+    dest: &Output,
+) -> StrResult<()> {
+    let pdfpc = typst_pdf::pdfpc(document, options);
+    let json = serde_json::to_string_pretty(&pdfpc).map_err(|e| eco_format!("{e}"))?;
+    dest.write(json.as_bytes()).map_err(|e| eco_format!("{e}"))
+}
+
+/// Prints statistics about the raster images in an exported PDF.
+fn print_image_report(report: &PdfImageReport) -> io::Result<()> {
+    /// Formats a number of bytes in a human-readable way.
+    fn size(bytes: usize) -> String {
+        const KI: f64 = 1024.0;
+        const MI: f64 = KI * KI;
+        let bytes = bytes as f64;
+        if bytes >= MI {
+            format!("{:.1} MiB", bytes / MI)
+        } else {
+            format!("{:.1} KiB", bytes / KI)
+        }
+    }
+
+    let mut out = terminal::out();
+    writeln!(
+        out,
+        "pdf images: {} embedded, {} downsampled, {} re-encoded as JPEG, \
+         {} deduplicated ({} -> {})",
+        report.images,
+        report.downsampled,
+        report.reencoded,
+        report.deduplicated,
+        size(report.original_bytes),
+        size(report.optimized_bytes),
+    )
+}
+
 /// Export to a bundle, a collection of files in a directory.
 fn export_bundle(bundle: Bundle, config: &CompileConfig) -> SourceResult<Vec<Output>> {
-    let options = BundleOptions {
-        html: html_options(config),
-        pdf: pdf_options(config),
-        png: png_options(config),
-        svg: svg_options(config),
+    let fs = typst_bundle::export(&bundle, &bundle_options(config))?;
+
+    // The cache is only replaced once the export succeeded, so that a failed
+    // write is retried next time. Without a cache, we fall back to the files
+    // that an earlier session wrote into the output directory.
+    let previous = match (&*config.bundle_cache.read(), &config.output) {
+        (Some(cached), _) => Some(cached.clone()),
+        (None, Output::Path(root)) if config.bundle_archive.is_none() => {
+            read_bundle_manifest(root)
+        }
+        _ => None,
     };
-
-    let fs = typst_bundle::export(&bundle, &options)?;
-    let root = match &config.output {
-        Output::Path(path) => path,
-        Output::Stdout => {
-            bail!(Span::detached(), "cannot write bundle to standard output")
+    let changes = BundleChanges::between(previous.as_ref(), &fs);
+    let outputs = match (&config.output, config.bundle_archive) {
+        (output, Some(format)) => {
+            let options = ArchiveOptions {
+                timestamp: config.creation_timestamp.map_or(0, |time| time.timestamp()),
+            };
+            let buffer =
+                typst_bundle::archive(&fs, format, &options).at(Span::detached())?;
+            output
+                .write(&buffer)
+                .map_err(|err| eco_format!("failed to write archive ({err})"))
+                .at(Span::detached())?;
+            vec![output.clone()]
         }
+        (Output::Path(root), None) => {
+            let stale =
+                if config.bundle_prune { changes.removed.as_slice() } else { &[] };
+            write_virtual_fs(root, &fs, previous.as_ref(), stale).at(Span::detached())?
+        }
+        (Output::Stdout, None) => bail!(
+            Span::detached(),
+            "cannot write bundle to standard output";
+            hint: "pass `--bundle-archive` to write an archive instead";
+        ),
     };
 
-    let outputs = write_virtual_fs(root, &fs).at(Span::detached())?;
+    if config.verbose {
+        print_bundle_changes(&changes)
+            .map_err(|err| eco_format!("failed to print bundle changes ({err})"))
+            .at(Span::detached())?;
+    }
 
     #[cfg(feature = "http-server")]
     if let Some(server) = &config.server {
-        server.set_bundle(bundle, fs);
+        server.set_bundle(bundle, fs.clone(), &changes);
     }
 
+    *config.bundle_cache.write() = Some(fs);
+
     Ok(outputs)
 }
 
+/// Prints which files of a bundle were added, changed, and removed.
+fn print_bundle_changes(changes: &BundleChanges) -> io::Result<()> {
+    let mut out = terminal::out();
+    writeln!(
+        out,
+        "bundle: {} added, {} changed, {} removed",
+        changes.added.len(),
+        changes.changed.len(),
+        changes.removed.len(),
+    )?;
+    for (sign, paths) in
+        [('+', &changes.added), ('~', &changes.changed), ('-', &changes.removed)]
+    {
+        for path in paths {
+            writeln!(out, "  {sign} {}", path.get_with_slash())?;
+        }
+    }
+    Ok(())
+}
+
+/// Export a bundle to an EPUB publication.
+fn export_epub(
+    world: &SystemWorld,
+    bundle: &Bundle,
+    config: &CompileConfig,
+) -> SourceResult<()> {
+    let mut options = bundle_options(config);
+    options.epub.fonts = epub_fonts(world, &config.epub_fonts).at(Span::detached())?;
+    let buffer = typst_bundle::epub(bundle, &options)?;
+    config
+        .output
+        .write(&buffer)
+        .map_err(|err| eco_format!("failed to write EPUB file ({err})"))
+        .at(Span::detached())
+}
+
+/// Looks up all fonts of the families that should be packaged with an EPUB
+/// publication.
+fn epub_fonts(world: &SystemWorld, families: &[EcoString]) -> HintedStrResult<Vec<Font>> {
+    let mut fonts = vec![];
+    for family in families {
+        let len = fonts.len();
+        fonts.extend(
+            world
+                .book()
+                .select_family(&family.to_lowercase())
+                .filter_map(|id| world.font(id)),
+        );
+        if fonts.len() == len {
+            bail!(
+                "unknown font family: {family}";
+                hint: "run `typst fonts` to list the available fonts";
+            );
+        }
+    }
+    Ok(fonts)
+}
+
+/// Export to a DOCX file.
+fn export_docx(document: &DocxDocument, config: &CompileConfig) -> SourceResult<()> {
+    let buffer = typst_docx::docx(document)?;
+    config
+        .output
+        .write(&buffer)
+        .map_err(|err| eco_format!("failed to write DOCX file ({err})"))
+        .at(Span::detached())
+}
+
+/// Creates options for bundle and EPUB export.
+fn bundle_options(config: &CompileConfig) -> BundleOptions {
+    // As with PDF, a timestamp provided through the CLI takes precedence over
+    // the current time.
+    let modified = convert_datetime(config.creation_timestamp.unwrap_or_else(Utc::now));
+    BundleOptions {
+        html: html_options(config),
+        pdf: pdf_options(config),
+        png: png_options(config),
+        svg: svg_options(config),
+        epub: EpubOptions {
+            identifier: config.epub_identifier.clone(),
+            modified,
+            // Looked up in the world by `export_epub`.
+            fonts: vec![],
+        },
+        fingerprint: config.bundle_fingerprint,
+    }
+}
+
+/// The file in a bundle's output directory that lists the paths of the files
+/// that the last export wrote.
+const BUNDLE_MANIFEST: &str = "/.typst-bundle.json";
+
+/// Reads the files that an earlier export wrote into the output directory,
+/// as listed in its manifest.
+fn read_bundle_manifest(root: &Path) -> Option<VirtualFs> {
+    let manifest = VirtualPath::new(BUNDLE_MANIFEST).ok()?.realize(root).ok()?;
+    let paths: Vec<String> =
+        serde_json::from_slice(&std::fs::read(manifest).ok()?).ok()?;
+    Some(
+        paths
+            .iter()
+            .filter_map(|path| {
+                let path = VirtualPath::new(path).ok()?;
+                let data = std::fs::read(path.realize(root).ok()?).ok()?;
+                Some((path, Bytes::new(data)))
+            })
+            .collect(),
+    )
+}
+
 /// Writes a bundle's files to disk.
-fn write_virtual_fs(root: &Path, fs: &VirtualFs) -> StrResult<Vec<Output>> {
+///
+/// Files whose contents are already on disk are not written again. The
+/// `stale` files, which a previous export produced but the current one did
+/// not, are deleted, together with directories that become empty. Finally,
+/// the paths of the files are recorded in a manifest, so that stale files can
+/// also be found by later sessions.
+fn write_virtual_fs(
+    root: &Path,
+    fs: &VirtualFs,
+    previous: Option<&VirtualFs>,
+    stale: &[VirtualPath],
+) -> StrResult<Vec<Output>> {
+    let manifest = VirtualPath::new(BUNDLE_MANIFEST).unwrap();
+    if fs.contains_key(&manifest) {
+        bail!(
+            "bundle already contains a file at {BUNDLE_MANIFEST}";
+            hint: "this path is used to keep track of the files in the output directory";
+        );
+    }
+
     std::fs::create_dir_all(root)
         .map_err(|err| eco_format!("failed to create output directory ({err})"))?;
 
-    fs.par_iter()
+    let outputs = fs
+        .par_iter()
         .map(|(path, data)| {
             let realized = path
                 .realize(root)
                 .map_err(|err| eco_format!("failed to realize path ({err})"))?;
 
+            // If the file did not change since the previous export, we don't
+            // even need to read it back, as long as it still exists.
+            let unchanged = previous
+                .and_then(|previous| previous.get(path))
+                .is_some_and(|old| old == data)
+                && realized.exists();
+            if unchanged
+                || std::fs::read(&realized).is_ok_and(|old| old == data.as_slice())
+            {
+                return Ok(Output::Path(realized));
+            }
+
             if let Some(parent) = realized.parent() {
                 std::fs::create_dir_all(parent)
                     .map_err(|err| eco_format!("failed to create directory ({err})"))?;
@@ -434,7 +1103,39 @@ fn write_virtual_fs(root: &Path, fs: &VirtualFs) -> StrResult<Vec<Output>> {
                 .map_err(|err| eco_format!("failed to write file ({err})"))?;
             Ok(Output::Path(realized))
         })
-        .collect()
+        .collect::<StrResult<Vec<_>>>()?;
+
+    for path in stale {
+        let realized = path
+            .realize(root)
+            .map_err(|err| eco_format!("failed to realize path ({err})"))?;
+        match std::fs::remove_file(&realized) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => bail!("failed to remove stale file ({err})"),
+        }
+
+        // Removing a directory fails if it is not empty, which is when we stop.
+        let mut dir = realized.parent();
+        while let Some(parent) = dir
+            && parent != root
+            && std::fs::remove_dir(parent).is_ok()
+        {
+            dir = parent.parent();
+        }
+    }
+
+    let realized = manifest
+        .realize(root)
+        .map_err(|err| eco_format!("failed to realize path ({err})"))?;
+    let paths: Vec<&str> = fs.keys().map(VirtualPath::get_with_slash).collect();
+    let data = serde_json::to_vec_pretty(&paths).unwrap();
+    if std::fs::read(&realized).ok().as_deref() != Some(data.as_slice()) {
+        std::fs::write(&realized, data)
+            .map_err(|err| eco_format!("failed to write bundle manifest ({err})"))?;
+    }
+
+    Ok(outputs)
 }
 
 /// Convert [`chrono::DateTime`] to [`Datetime`]
@@ -454,7 +1155,7 @@ fn convert_datetime<Tz: chrono::TimeZone>(
 /// An image format to export in.
 #[derive(Copy, Clone)]
 enum ImageExportFormat {
-    Png,
+    Raster(RasterFormat),
     Svg,
 }
 
@@ -493,6 +1194,32 @@ fn export_image(
         bail!("cannot export multiple images {err}");
     }
 
+    // Links between pages become links between the files. This only works if
+    // all pages end up in the same directory.
+    let links = match (fmt, &config.output) {
+        (ImageExportFormat::Svg, Output::Path(path)) => {
+            let per_page = can_handle_multiple
+                && !path.parent().and_then(Path::to_str).is_some_and(|parent| {
+                    output_template::has_indexable_template(parent)
+                });
+            Some(SvgLinks::new(document, |number| {
+                let i = number.get() - 1;
+                let exported = exported_pages.iter().any(|(j, _)| *j == i);
+                if !exported || !per_page {
+                    return None;
+                }
+                let path = output_template::format(
+                    path.to_str().unwrap_or_default(),
+                    number.get(),
+                    document.pages().len(),
+                );
+                let name = Path::new(&path).file_name()?.to_str()?;
+                Some(name.into())
+            }))
+        }
+        _ => None,
+    };
+
     // The results are collected in a `Vec<()>` which does not allocate.
     exported_pages
         .par_iter()
@@ -516,7 +1243,7 @@ fn export_image(
                     // If the frame is in the cache, skip it.
                     // If the file does not exist, always create it.
                     if config.watching
-                        && config.export_cache.is_cached(*i, page)
+                        && config.export_cache.is_cached(*i, &(page, &links))
                         && path.exists()
                     {
                         return Ok(Output::Path(path.to_path_buf()));
@@ -527,7 +1254,8 @@ fn export_image(
                 Output::Stdout => Output::Stdout,
             };
 
-            export_image_page(config, page, &output, fmt)?;
+            let number = NonZeroUsize::new(i + 1).unwrap();
+            export_image_page(config, page, number, links.as_ref(), &output, fmt)?;
             Ok(output)
         })
         .collect::<StrResult<Vec<Output>>>()
@@ -566,23 +1294,26 @@ mod output_template {
 fn export_image_page(
     config: &CompileConfig,
     page: &Page,
+    number: NonZeroUsize,
+    links: Option<&SvgLinks>,
     output: &Output,
     fmt: ImageExportFormat,
 ) -> StrResult<()> {
     match fmt {
-        ImageExportFormat::Png => {
+        ImageExportFormat::Raster(format) => {
             let options = png_options(config);
             let pixmap = typst_render::render(page, &options);
-            let buf = pixmap
-                .encode_png()
-                .map_err(|err| eco_format!("failed to encode PNG file ({err})"))?;
-            output
-                .write(&buf)
-                .map_err(|err| eco_format!("failed to write PNG file ({err})"))?;
+            let buf = typst_render::encode(&pixmap, format, &options)?;
+            output.write(&buf).map_err(|err| {
+                eco_format!("failed to write {} file ({err})", format.name())
+            })?;
         }
         ImageExportFormat::Svg => {
             let options = svg_options(config);
-            let svg = typst_svg::svg(page, &options);
+            let svg = match links {
+                Some(links) => typst_svg::svg_with_links(page, number, &options, links),
+                None => typst_svg::svg(page, &options),
+            };
             output
                 .write(svg.as_bytes())
                 .map_err(|err| eco_format!("failed to write SVG file ({err})"))?;
@@ -593,7 +1324,13 @@ fn export_image_page(
 
 /// Creates options for HTML export.
 fn html_options(config: &CompileConfig) -> HtmlOptions {
-    HtmlOptions { pretty: config.pretty }
+    HtmlOptions {
+        pretty: config.pretty,
+        fragment: config.html_fragment,
+        styles: config.html_style_mode.into(),
+        profile: config.html_profile.into(),
+        xhtml: false,
+    }
 }
 
 /// Creates options for PDF export.
@@ -621,19 +1358,37 @@ fn pdf_options(config: &CompileConfig) -> PdfOptions {
         standards: config.pdf_standards.clone(),
         tagged: config.tagged,
         pretty: config.pretty,
+        images: config.pdf_images,
+        named_destinations: config.pdf_named_destinations.clone(),
+        presenter: config.pdf_presenter,
     }
 }
 
 /// Creates options for SVG export.
 fn svg_options(config: &CompileConfig) -> SvgOptions {
-    SvgOptions { render_bleed: false, pretty: config.pretty }
+    SvgOptions {
+        render_bleed: false,
+        pretty: config.pretty,
+        text: config.svg_text.into(),
+    }
 }
 
-/// Creates options for PNG export.
+/// Creates options for PNG, JPEG, and WebP export.
 fn png_options(config: &CompileConfig) -> RenderOptions {
     RenderOptions {
         pixel_per_pt: Scalar::new(config.ppi / 72.0),
         render_bleed: false,
+        thumbnail: false,
+        background: match &config.background {
+            None => Smart::Auto,
+            Some(Background::Transparent) => Smart::Custom(None),
+            Some(Background::Color([r, g, b, a])) => {
+                Smart::Custom(Some(Color::from_u8(*r, *g, *b, *a)))
+            }
+        },
+        jpeg_quality: config.jpeg_quality,
+        embed_ppi: config.embed_ppi,
+        srgb_profile: config.srgb_profile,
     }
 }
 
@@ -657,7 +1412,7 @@ impl ExportCache {
 
     /// Returns true if the entry is cached and appends the new hash to the
     /// cache (for the next compilation).
-    pub fn is_cached(&self, i: usize, page: &Page) -> bool {
+    pub fn is_cached(&self, i: usize, page: &impl Hash) -> bool {
         let hash = typst::utils::hash128(page);
 
         let mut cache = self.cache.upgradable_read();
@@ -732,26 +1487,77 @@ pub fn print_diagnostics(
     )
 }
 
-impl From<PdfStandard> for typst_pdf::PdfStandard {
-    fn from(standard: PdfStandard) -> Self {
-        match standard {
-            PdfStandard::V_1_4 => typst_pdf::PdfStandard::V_1_4,
-            PdfStandard::V_1_5 => typst_pdf::PdfStandard::V_1_5,
-            PdfStandard::V_1_6 => typst_pdf::PdfStandard::V_1_6,
-            PdfStandard::V_1_7 => typst_pdf::PdfStandard::V_1_7,
-            PdfStandard::V_2_0 => typst_pdf::PdfStandard::V_2_0,
-            PdfStandard::A_1b => typst_pdf::PdfStandard::A_1b,
-            PdfStandard::A_1a => typst_pdf::PdfStandard::A_1a,
-            PdfStandard::A_2b => typst_pdf::PdfStandard::A_2b,
-            PdfStandard::A_2u => typst_pdf::PdfStandard::A_2u,
-            PdfStandard::A_2a => typst_pdf::PdfStandard::A_2a,
-            PdfStandard::A_3b => typst_pdf::PdfStandard::A_3b,
-            PdfStandard::A_3u => typst_pdf::PdfStandard::A_3u,
-            PdfStandard::A_3a => typst_pdf::PdfStandard::A_3a,
-            PdfStandard::A_4 => typst_pdf::PdfStandard::A_4,
-            PdfStandard::A_4f => typst_pdf::PdfStandard::A_4f,
-            PdfStandard::A_4e => typst_pdf::PdfStandard::A_4e,
-            PdfStandard::UA_1 => typst_pdf::PdfStandard::Ua_1,
+impl From<HtmlStyleMode> for typst_html::HtmlStyleMode {
+    fn from(mode: HtmlStyleMode) -> Self {
+        match mode {
+            HtmlStyleMode::Inline => Self::Inline,
+            HtmlStyleMode::Classes => Self::Classes,
+        }
+    }
+}
+
+impl From<BundleArchive> for ArchiveFormat {
+    fn from(archive: BundleArchive) -> Self {
+        match archive {
+            BundleArchive::Zip => Self::Zip,
+            BundleArchive::Tar => Self::Tar,
+            BundleArchive::TarGz => Self::TarGz,
+        }
+    }
+}
+
+impl From<HtmlProfile> for typst_html::HtmlProfile {
+    fn from(profile: HtmlProfile) -> Self {
+        match profile {
+            HtmlProfile::Full => Self::Full,
+            HtmlProfile::Semantic => Self::Semantic,
         }
     }
 }
+
+impl From<SvgTextMode> for typst_svg::SvgTextMode {
+    fn from(mode: SvgTextMode) -> Self {
+        match mode {
+            SvgTextMode::Outlines => Self::Outlines,
+            SvgTextMode::Text => Self::Text,
+        }
+    }
+}
+
+impl From<PdfOptimize> for PdfImageOptions {
+    fn from(profile: PdfOptimize) -> Self {
+        let (max_ppi, jpeg_quality) = match profile {
+            PdfOptimize::None => return Self::default(),
+            PdfOptimize::Screen => (96.0, 60),
+            PdfOptimize::Ebook => (150.0, 75),
+            PdfOptimize::Print => (300.0, 90),
+        };
+        Self {
+            max_ppi: Some(Scalar::new(max_ppi)),
+            jpeg_quality: Some(jpeg_quality),
+            deduplicate: true,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http-server"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_args() {
+        let path = Path::new("/my project/{line}.typ");
+        let args = |template| editor_args(template, path, 3, 14).unwrap();
+        assert_eq!(
+            args("code --goto {file}:{line}:{column}"),
+            ["code", "--goto", "/my project/{line}.typ:3:14"]
+        );
+        assert_eq!(
+            args(r#""my editor" '+call cursor({line}, {column})' {file}"#),
+            ["my editor", "+call cursor(3, 14)", "/my project/{line}.typ"]
+        );
+        assert_eq!(args("vim {unknown} {{line}}"), ["vim", "{unknown}", "{3}"]);
+        assert!(args("").is_empty());
+        assert!(editor_args("code 'unclosed", path, 1, 1).is_err());
+    }
+}
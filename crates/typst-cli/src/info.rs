@@ -111,16 +111,18 @@ struct Features {
     html: bool,
     bundle: bool,
     a11y_extras: bool,
+    docx: bool,
 }
 
 impl Features {
     /// Return the runtime features with human readable information.
     fn features(&self) -> impl Iterator<Item = KeyValDesc<'_>> {
-        let Self { html, bundle, a11y_extras } = self;
+        let Self { html, bundle, a11y_extras, docx } = self;
         [
             ("html", html, "Experimental HTML export"),
             ("bundle", bundle, "Experimental bundle export"),
             ("a11y-extras", a11y_extras, "Experimental accessibility additions"),
+            ("docx", docx, "Experimental DOCX export"),
         ]
         .into_iter()
         .map(|(key, val, desc)| KeyValDesc { key, val: Value::Bool(*val), desc })
@@ -427,7 +429,12 @@ fn parse_bool(cmd: &Command, val: &str, key: &'static str) -> Option<bool> {
 /// Turns a comma separated list of feature names into a well typed struct of
 /// feature flags.
 fn parse_features(feature_list: &str) -> StrResult<Features> {
-    let mut features = Features { html: false, bundle: false, a11y_extras: false };
+    let mut features = Features {
+        html: false,
+        bundle: false,
+        a11y_extras: false,
+        docx: false,
+    };
 
     for feature in feature_list.split(',').filter(|s| !s.is_empty()) {
         match Feature::from_str(feature, true) {
@@ -435,6 +442,7 @@ fn parse_features(feature_list: &str) -> StrResult<Features> {
                 Feature::Html => features.html = true,
                 Feature::Bundle => features.bundle = true,
                 Feature::A11yExtras => features.a11y_extras = true,
+                Feature::Docx => features.docx = true,
             },
             Err(_) => {
                 crate::print_error(&format!("unknown runtime feature: `{feature}`"))
@@ -74,14 +74,23 @@ fn retrieve(
     command: &QueryCommand,
     introspector: &dyn Introspector,
 ) -> HintedStrResult<Vec<Content>> {
-    let selector = eval_string(
+    let selector = eval_selector(world, &command.selector)?;
+    Ok(introspector.query(&selector.0).into_iter().collect::<Vec<_>>())
+}
+
+/// Evaluates a selector passed on the command line.
+pub fn eval_selector(
+    world: &dyn World,
+    selector: &str,
+) -> HintedStrResult<LocatableSelector> {
+    eval_string(
         world.track(),
         world.library(),
         // TODO: propagate warnings
         Sink::new().track_mut(),
         EmptyIntrospector.track(),
         Context::none().track(),
-        &command.selector,
+        selector,
         SpanMode::Uniform(Span::detached()),
         SyntaxMode::Code,
         Scope::default(),
@@ -94,9 +103,7 @@ fn retrieve(
         }
         message
     })?
-    .cast::<LocatableSelector>()?;
-
-    Ok(introspector.query(&selector.0).into_iter().collect::<Vec<_>>())
+    .cast::<LocatableSelector>()
 }
 
 /// Format the query result in the output format.
@@ -2,7 +2,7 @@ use std::error;
 use std::fmt;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 use ecow::{EcoString, eco_format};
 use typst::diag::{FileError, FileResult};
@@ -21,14 +21,17 @@ use typst_kit::packages::SystemPackages;
 
 use crate::args::{Feature, Input, ProcessArgs, WorldArgs};
 
+/// Fonts that are only discovered once they are needed.
+type LazyFonts = LazyLock<FontStore, Box<dyn Fn() -> FontStore + Send + Sync>>;
+
 /// A world that provides access to the operating system.
 pub struct SystemWorld {
     /// The working directory.
     workdir: Option<PathBuf>,
     /// Typst's standard library.
-    library: LazyHash<Library>,
+    library: Arc<LazyHash<Library>>,
     /// Metadata about discovered fonts and lazily loaded fonts.
-    fonts: LazyLock<FontStore, Box<dyn Fn() -> FontStore + Send + Sync>>,
+    fonts: Arc<LazyFonts>,
     /// Maps file ids to source files and buffers.
     files: FileStore<SystemFiles>,
     /// The current datetime if requested. This is stored here to ensure it is
@@ -75,10 +78,10 @@ impl SystemWorld {
 
         Ok(Self {
             workdir: std::env::current_dir().ok(),
-            library: LazyHash::new(library),
-            fonts: LazyLock::new(Box::new(|| {
+            library: Arc::new(LazyHash::new(library)),
+            fonts: Arc::new(LazyLock::new(Box::new(|| {
                 crate::fonts::discover_fonts(&world_args.font)
-            })),
+            }))),
             files: FileStore::new(SystemFiles::new(input, world_args)?),
             now,
         })
@@ -100,6 +103,30 @@ impl SystemWorld {
         deps.filter_map(|id| loader.resolve(id).ok())
     }
 
+    /// Captures the sources of the last compilation, so that positions in its
+    /// output can be mapped back to them while the world moves on.
+    #[cfg(feature = "http-server")]
+    pub fn snapshot(&mut self) -> SourceSnapshot {
+        // Only Typst files are considered, to avoid parsing other files like
+        // data and images as sources.
+        let main = self.main();
+        let (loader, deps) = self.files.dependencies();
+        let ids: Vec<_> = deps
+            .filter(|&id| id == main || id.vpath().extension() == Some("typ"))
+            .filter_map(|id| Some((id, loader.resolve(id).ok()?)))
+            .collect();
+        let sources = ids
+            .into_iter()
+            .filter_map(|(id, path)| Some((id, (self.files.source(id).ok()?, path))))
+            .collect();
+        SourceSnapshot {
+            library: self.library.clone(),
+            fonts: self.fonts.clone(),
+            main,
+            sources,
+        }
+    }
+
     /// Reset the compilation state in preparation of a new compilation.
     pub fn reset(&mut self) {
         self.files.reset();
@@ -110,7 +137,7 @@ impl SystemWorld {
     ///
     /// Does nothing if the fonts were already scanned.
     pub fn scan_fonts(&mut self) {
-        LazyLock::force(&self.fonts);
+        LazyLock::force(&*self.fonts);
     }
 }
 
@@ -164,6 +191,85 @@ impl DiagnosticWorld for SystemWorld {
     }
 }
 
+/// The sources of a past compilation of a [`SystemWorld`].
+///
+/// Other files are not available.
+#[cfg(feature = "http-server")]
+pub struct SourceSnapshot {
+    library: Arc<LazyHash<Library>>,
+    fonts: Arc<LazyFonts>,
+    main: FileId,
+    sources: std::collections::HashMap<FileId, (Source, PathBuf)>,
+}
+
+#[cfg(feature = "http-server")]
+impl SourceSnapshot {
+    /// The path of a source file on disk.
+    pub fn path(&self, id: FileId) -> Option<&Path> {
+        self.sources.get(&id).map(|(_, path)| path.as_path())
+    }
+
+    /// Finds the source file at a path on disk and the byte offset of a
+    /// one-based line and column in it.
+    pub fn cursor(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+    ) -> Option<(Source, usize)> {
+        let path = path.canonicalize().ok()?;
+        let (source, _) = self
+            .sources
+            .values()
+            .find(|(_, other)| other.canonicalize().is_ok_and(|other| other == path))?;
+        let cursor = source
+            .lines()
+            .line_column_to_byte(line.checked_sub(1)?, column.saturating_sub(1))?;
+        Some((source.clone(), cursor))
+    }
+}
+
+#[cfg(feature = "http-server")]
+impl World for SourceSnapshot {
+    fn library(&self) -> &LazyHash<Library> {
+        &self.library
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        self.fonts.book()
+    }
+
+    fn main(&self) -> FileId {
+        self.main
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        match self.sources.get(&id) {
+            Some((source, _)) => Ok(source.clone()),
+            None => Err(FileError::NotSource),
+        }
+    }
+
+    fn file(&self, _: FileId) -> FileResult<Bytes> {
+        Err(FileError::Other(Some("file is not part of the snapshot".into())))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.fonts.font(index)
+    }
+
+    fn today(&self, _: Option<Duration>) -> Option<Datetime> {
+        None
+    }
+}
+
+#[cfg(feature = "http-server")]
+impl typst_ide::IdeWorld for SourceSnapshot {
+    fn upcast(&self) -> &dyn World {
+        self
+    }
+}
+
 /// Static `FileId` allocated for stdin. This is to ensure that stdin can live
 /// in the project root without colliding with any real on-disk file.
 static STDIN_ID: LazyLock<FileId> = LazyLock::new(|| {
@@ -352,6 +458,7 @@ impl From<Feature> for typst::Feature {
             Feature::Html => typst::Feature::Html,
             Feature::Bundle => typst::Feature::Bundle,
             Feature::A11yExtras => typst::Feature::A11yExtras,
+            Feature::Docx => typst::Feature::Docx,
         }
     }
 }
@@ -97,6 +97,106 @@ fn test_deps() {
     output.stdout.must_contain("tiger.jpg").must_contain("main.typ");
 }
 
+#[test]
+fn test_bundle_archive_reproducible() {
+    let project = tempfs();
+    let main = project
+        .write("main.typ", "#document(\"b.svg\")[B]\n#asset(\"a/c.txt\", bytes(\"C\"))");
+    for name in ["out.zip", "out.tar", "out.tar.gz"] {
+        let compile = || {
+            exec()
+                .arg("compile")
+                .arg(&main)
+                .arg(project.resolve(name))
+                .arg("--features")
+                .arg("bundle")
+                .env_remove("SOURCE_DATE_EPOCH")
+                .must_succeed();
+            project.read(name).0
+        };
+        let first = compile();
+        assert!(first == compile(), "{name} differs between runs");
+    }
+}
+
+#[test]
+fn test_bundle_archive_creation_timestamp() {
+    // 2023-11-14T22:13:20Z
+    let timestamp = 1_700_000_000;
+    let project = tempfs();
+    let main = project.write("main.typ", "#asset(\"a.txt\", bytes(\"A\"))");
+    for name in ["out.zip", "out.tar"] {
+        exec()
+            .arg("compile")
+            .arg(&main)
+            .arg(project.resolve(name))
+            .arg("--features")
+            .arg("bundle")
+            .arg("--creation-timestamp")
+            .arg(timestamp.to_string())
+            .must_succeed();
+    }
+
+    let tar = project.read("out.tar").0;
+    let mut tar = tar::Archive::new(tar.as_slice());
+    for entry in tar.entries().unwrap() {
+        assert_eq!(entry.unwrap().header().mtime().unwrap(), timestamp);
+    }
+
+    let zip = std::io::Cursor::new(project.read("out.zip").0);
+    let mut zip = zip::ZipArchive::new(zip).unwrap();
+    let modified = zip.by_index(0).unwrap().last_modified().unwrap();
+    assert_eq!(
+        (modified.year(), modified.month(), modified.day(), modified.hour()),
+        (2023, 11, 14, 22)
+    );
+}
+
+#[test]
+fn test_bundle_prune() {
+    let project = tempfs();
+    let out = project.resolve("out");
+    let compile = |source: &str, prune: bool| {
+        let main = project.write("main.typ", source);
+        let mut command = exec();
+        command
+            .arg("compile")
+            .arg(&main)
+            .arg(&out)
+            .arg("--format")
+            .arg("bundle")
+            .arg("--features")
+            .arg("bundle");
+        if prune {
+            command.arg("--bundle-prune");
+        }
+        command.must_succeed();
+    };
+
+    compile(
+        "#asset(\"a.txt\", bytes(\"A\"))\n#asset(\"dir/b.txt\", bytes(\"B\"))",
+        false,
+    );
+    project.write("out/user.txt", "mine");
+    assert!(out.join("dir/b.txt").exists());
+    project.read("out/.typst-bundle.json").must_contain("/dir/b.txt");
+
+    // Without pruning, the file that is no longer produced stays.
+    compile("#asset(\"a.txt\", bytes(\"A\"))", false);
+    assert!(out.join("dir/b.txt").exists());
+
+    // The manifest of the previous process is used to find stale files. Files
+    // that Typst did not produce are kept.
+    compile(
+        "#asset(\"a.txt\", bytes(\"A\"))\n#asset(\"dir/b.txt\", bytes(\"B\"))",
+        false,
+    );
+    compile("#asset(\"a.txt\", bytes(\"A2\"))", true);
+    assert!(!out.join("dir").exists());
+    assert!(out.join("user.txt").exists());
+    project.read("out/a.txt").must_match_lines(["A2"]);
+}
+
 #[test]
 fn test_path_resolved() {
     let project = tempfs();
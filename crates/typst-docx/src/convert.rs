@@ -0,0 +1,1473 @@
+use base64::Engine as _;
+use ecow::{EcoString, eco_format};
+use rustc_hash::{FxHashMap, FxHashSet};
+use typst_html::{HtmlElement, HtmlFrame, HtmlNode, attr, tag};
+use typst_layout::Page;
+use typst_library::diag::{StrResult, bail};
+use typst_library::foundations::{Bytes, Content, Smart};
+use typst_library::layout::{Abs, Frame, FrameItem, Point, Sides, Size};
+use typst_library::visualize::{ExchangeFormat, Image, RasterImage, SvgImage};
+use typst_render::{RasterFormat, RenderOptions};
+use typst_svg::SvgOptions;
+use typst_syntax::Span;
+use typst_utils::Scalar;
+use xmlwriter::XmlWriter;
+
+use crate::DocxDocument;
+use crate::math;
+use crate::package::{self, MediaFormat, twips};
+
+/// The resolution at which frames are rasterized for word processors that
+/// can't display the SVG version.
+const FALLBACK_PIXEL_PER_PT: f64 = 3.0;
+
+/// The paragraph styles of the heading levels. Level one is the document
+/// title.
+const HEADINGS: [&str; 10] = [
+    "Title", "Heading1", "Heading2", "Heading3", "Heading4", "Heading5", "Heading6",
+    "Heading7", "Heading8", "Heading9",
+];
+
+/// The paragraph styles of the outline levels.
+const TOC: [&str; 9] =
+    ["TOC1", "TOC2", "TOC3", "TOC4", "TOC5", "TOC6", "TOC7", "TOC8", "TOC9"];
+
+/// The parts of a DOCX file that depend on the document's content.
+pub struct Parts {
+    /// The main document part.
+    pub document: String,
+    /// The relationships of the main document part, in addition to the fixed
+    /// ones.
+    pub document_rels: Vec<Relationship>,
+    /// The footnotes part.
+    pub footnotes: String,
+    /// The relationships of the footnotes part.
+    pub footnote_rels: Vec<Relationship>,
+    /// The lists, each of which gets its own numbering instance.
+    pub lists: Vec<List>,
+    /// The embedded media files.
+    pub media: Vec<Media>,
+}
+
+/// A relationship from a part to an image or a hyperlink target.
+pub struct Relationship {
+    /// The ID through which the part refers to the target.
+    pub id: EcoString,
+    /// The target of the relationship.
+    pub target: EcoString,
+    /// Whether the target is an external hyperlink rather than an image.
+    pub external: bool,
+}
+
+/// A list in the document.
+pub struct List {
+    /// Whether the list is numbered rather than bulleted.
+    pub ordered: bool,
+    /// The nesting level of the list.
+    pub level: usize,
+    /// The number of the first item.
+    pub start: u64,
+}
+
+/// An embedded media file.
+pub struct Media {
+    /// The path of the file, relative to the `word` directory.
+    pub path: EcoString,
+    /// The file's data.
+    pub data: Bytes,
+    /// The file's format.
+    pub format: MediaFormat,
+}
+
+/// Converts the HTML DOM of a document into WordprocessingML.
+pub fn convert(document: &DocxDocument) -> StrResult<Parts> {
+    let root = document.html().root();
+    let body = root
+        .children
+        .iter()
+        .find_map(|node| match node {
+            HtmlNode::Element(elem) if elem.tag == tag::body => Some(elem),
+            _ => None,
+        })
+        .unwrap_or(root);
+
+    let mut converter = Converter::new(document);
+    converter.collect_notes(body);
+
+    let mut xml = package::writer();
+    xml.write_declaration();
+    xml.start_element("w:document");
+    package::write_namespaces(&mut xml);
+    xml.start_element("w:body");
+    converter.blocks(&mut xml, &body.children, &mut Block::default())?;
+    converter.flush_trailing(&mut xml);
+    converter.section(&mut xml);
+    xml.end_element();
+    xml.end_element();
+    let document_rels = std::mem::take(&mut converter.rels);
+    let document_xml = xml.end_document();
+
+    let footnotes = converter.footnotes()?;
+    Ok(Parts {
+        document: document_xml,
+        document_rels,
+        footnotes,
+        footnote_rels: converter.rels,
+        lists: converter.lists,
+        media: converter.media,
+    })
+}
+
+/// Holds the state of the conversion.
+struct Converter<'a> {
+    /// The document that is being converted.
+    document: &'a DocxDocument,
+    /// The entries of the footnote section, by their ID.
+    notes: FxHashMap<&'a str, &'a HtmlElement>,
+    /// The footnote entries that were referenced, in order. Footnote IDs start
+    /// at one because zero and minus one are taken by the separators.
+    referenced: Vec<&'a HtmlElement>,
+    /// Whether the footnotes are currently being written.
+    in_note: bool,
+    /// The relationships of the part that is currently being written.
+    rels: Vec<Relationship>,
+    /// The lists that were encountered so far.
+    lists: Vec<List>,
+    /// The embedded media files.
+    media: Vec<Media>,
+    /// Media paths by a hash of their data.
+    media_paths: FxHashMap<u128, EcoString>,
+    /// Bookmark names by HTML ID.
+    bookmarks: FxHashMap<EcoString, EcoString>,
+    /// The bookmark names that are already in use.
+    names: FxHashSet<EcoString>,
+    /// Bookmarks of block-level elements that are waiting for the next
+    /// paragraph.
+    pending: Vec<EcoString>,
+    /// The next ID for bookmarks and drawings.
+    next_id: usize,
+    /// What was last written at the block level.
+    last: Last,
+}
+
+/// What was last written at the block level.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Last {
+    Nothing,
+    Paragraph,
+    Table,
+}
+
+/// The block-level context in which paragraphs are written.
+#[derive(Debug, Default, Clone)]
+struct Block {
+    /// The paragraph style.
+    style: Option<&'static str>,
+    /// The list and level with which the next paragraph is numbered.
+    numbering: Option<(usize, usize)>,
+    /// The indentation of paragraphs, in list levels.
+    indent: usize,
+    /// The nesting depth of lists.
+    depth: usize,
+    /// Whether paragraphs are centered.
+    center: bool,
+    /// Whether paragraphs have a hanging indent.
+    hanging: bool,
+    /// Whether the next paragraph starts with the footnote's mark.
+    note_mark: bool,
+    /// Whether list items are entries of an outline.
+    outline: bool,
+    /// Whether list items are bibliography entries, and if so, whether they
+    /// have a hanging indent.
+    bibliography: Option<bool>,
+    /// The formatting of text.
+    format: Format,
+}
+
+impl Block {
+    /// Creates the context for a nested element.
+    ///
+    /// The nested element takes over the numbering and footnote mark, so that
+    /// they end up in its first paragraph.
+    fn nested(&mut self) -> Self {
+        let nested = self.clone();
+        self.numbering = None;
+        self.note_mark = false;
+        nested
+    }
+}
+
+/// The formatting of a run of text.
+#[derive(Debug, Default, Copy, Clone)]
+struct Format {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    strike: bool,
+    underline: bool,
+    highlight: bool,
+    subscript: bool,
+    superscript: bool,
+    /// Whether the text is part of a hyperlink.
+    link: bool,
+}
+
+impl<'a> Converter<'a> {
+    fn new(document: &'a DocxDocument) -> Self {
+        Self {
+            document,
+            notes: FxHashMap::default(),
+            referenced: vec![],
+            in_note: false,
+            rels: vec![],
+            lists: vec![],
+            media: vec![],
+            media_paths: FxHashMap::default(),
+            bookmarks: FxHashMap::default(),
+            names: FxHashSet::default(),
+            pending: vec![],
+            next_id: 1,
+            last: Last::Nothing,
+        }
+    }
+
+    /// Collects the entries of the footnote sections.
+    fn collect_notes(&mut self, elem: &'a HtmlElement) {
+        if elem.tag == tag::section && has_role(elem, "doc-endnotes") {
+            for list in elements(elem).filter(|list| list.tag == tag::ol) {
+                for item in elements(list).filter(|item| item.tag == tag::li) {
+                    if let Some(id) = item.attrs.get(attr::id) {
+                        self.notes.insert(id.as_str(), item);
+                    }
+                }
+            }
+            return;
+        }
+
+        for child in elements(elem) {
+            self.collect_notes(child);
+        }
+    }
+
+    /// Writes the footnotes part with all referenced footnotes.
+    fn footnotes(&mut self) -> StrResult<String> {
+        self.in_note = true;
+        self.last = Last::Nothing;
+
+        let mut xml = package::writer();
+        xml.write_declaration();
+        xml.start_element("w:footnotes");
+        package::write_namespaces(&mut xml);
+
+        for (id, kind) in [(-1, "separator"), (0, "continuationSeparator")] {
+            xml.start_element("w:footnote");
+            xml.write_attribute("w:type", kind);
+            xml.write_attribute("w:id", &id);
+            xml.start_element("w:p");
+            xml.start_element("w:pPr");
+            xml.start_element("w:spacing");
+            xml.write_attribute("w:after", "0");
+            xml.end_element();
+            xml.end_element();
+            xml.start_element("w:r");
+            xml.start_element(&format!("w:{kind}"));
+            xml.end_element();
+            xml.end_element();
+            xml.end_element();
+            xml.end_element();
+        }
+
+        for i in 0..self.referenced.len() {
+            let note = self.referenced[i];
+            xml.start_element("w:footnote");
+            xml.write_attribute("w:id", &(i + 1));
+            let mut block = Block {
+                style: Some("FootnoteText"),
+                note_mark: true,
+                ..Block::default()
+            };
+            self.last = Last::Nothing;
+            self.blocks(&mut xml, &note.children, &mut block)?;
+            if self.last != Last::Paragraph {
+                self.empty_paragraph(&mut xml);
+            }
+            xml.end_element();
+        }
+
+        xml.end_element();
+        Ok(xml.end_document())
+    }
+
+    /// Writes block-level content.
+    ///
+    /// Consecutive inline nodes are grouped into paragraphs.
+    fn blocks(
+        &mut self,
+        xml: &mut XmlWriter,
+        nodes: &'a [HtmlNode],
+        block: &mut Block,
+    ) -> StrResult<()> {
+        let mut inline = vec![];
+        for node in nodes {
+            match node {
+                HtmlNode::Tag(_) => {}
+                HtmlNode::Element(elem) if is_block(elem) => {
+                    self.paragraph(xml, &inline, block)?;
+                    inline.clear();
+                    self.block(xml, elem, block)?;
+                }
+                _ => inline.push(node),
+            }
+        }
+        self.paragraph(xml, &inline, block)
+    }
+
+    /// Writes a block-level element.
+    fn block(
+        &mut self,
+        xml: &mut XmlWriter,
+        elem: &'a HtmlElement,
+        block: &mut Block,
+    ) -> StrResult<()> {
+        if let Some(id) = elem.attrs.get(attr::id) {
+            let name = self.bookmark(id);
+            self.pending.push(name);
+        }
+
+        let mut nested = block.nested();
+        match elem.tag {
+            tag::head | tag::style | tag::script | tag::template => {}
+            tag::h1 | tag::h2 | tag::h3 | tag::h4 | tag::h5 | tag::h6 => {
+                let level = heading_level(elem).unwrap_or(1);
+                nested.style = Some(HEADINGS[level - 1]);
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::div if has_role(elem, "heading") => {
+                let level = elem
+                    .attrs
+                    .get(attr::aria_level)
+                    .and_then(|level| level.parse::<usize>().ok())
+                    .unwrap_or(1);
+                nested.style = Some(HEADINGS[level.clamp(1, HEADINGS.len()) - 1]);
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::ul | tag::ol => self.list(xml, elem, &mut nested)?,
+            tag::dl => {
+                for child in &elem.children {
+                    let mut item = nested.clone();
+                    match child {
+                        HtmlNode::Element(term) if term.tag == tag::dt => {
+                            item.format.bold = true;
+                        }
+                        HtmlNode::Element(desc) if desc.tag == tag::dd => {
+                            item.indent += 1;
+                        }
+                        _ => {}
+                    }
+                    self.blocks(xml, std::slice::from_ref(child), &mut item)?;
+                }
+            }
+            tag::table => self.table(xml, elem, &nested)?,
+            tag::figure => {
+                nested.center = true;
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::figcaption => {
+                nested.style = Some("Caption");
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::blockquote => {
+                nested.style = Some("Quote");
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::pre => {
+                nested.style = Some("SourceCode");
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::hr => self.rule(xml),
+            tag::section if has_role(elem, "doc-endnotes") => {}
+            tag::section if has_role(elem, "doc-bibliography") => {
+                let hanging = elem
+                    .attrs
+                    .get(attr::class)
+                    .is_some_and(|class| class.split(' ').any(|c| c == "hanging-indent"));
+                nested.bibliography = Some(hanging);
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::nav if has_role(elem, "doc-toc") => {
+                nested.outline = true;
+                self.blocks(xml, &elem.children, &mut nested)?;
+            }
+            tag::mathml::math => self.display_math(xml, elem, &mut nested)?,
+            _ => self.blocks(xml, &elem.children, &mut nested)?,
+        }
+
+        Ok(())
+    }
+
+    /// Writes a bulleted or numbered list.
+    ///
+    /// Each list gets its own numbering instance, so that numbering restarts
+    /// for every list. Outlines and bibliographies are written as plain
+    /// paragraphs as their entries already contain their numbers.
+    fn list(
+        &mut self,
+        xml: &mut XmlWriter,
+        elem: &'a HtmlElement,
+        block: &mut Block,
+    ) -> StrResult<()> {
+        let level = block.depth;
+        let numbering = if block.outline || block.bibliography.is_some() {
+            None
+        } else {
+            self.lists.push(List {
+                ordered: elem.tag == tag::ol,
+                level: level.min(8),
+                start: elem
+                    .attrs
+                    .get(attr::start)
+                    .and_then(|start| start.parse().ok())
+                    .unwrap_or(1),
+            });
+            Some(self.lists.len())
+        };
+
+        for child in &elem.children {
+            let HtmlNode::Element(item) = child else { continue };
+            if item.tag != tag::li {
+                self.blocks(xml, std::slice::from_ref(child), block)?;
+                continue;
+            }
+
+            if let Some(id) = item.attrs.get(attr::id) {
+                let name = self.bookmark(id);
+                self.pending.push(name);
+            }
+
+            let mut nested = block.clone();
+            nested.depth = level + 1;
+            if block.outline {
+                nested.style = Some(TOC[level.min(TOC.len() - 1)]);
+            } else if let Some(hanging) = block.bibliography {
+                nested.style = Some("Bibliography");
+                nested.hanging = hanging;
+            } else {
+                nested.style = Some("ListParagraph");
+                nested.numbering = numbering.map(|num| (num, level.min(8)));
+                nested.indent = level + 1;
+            }
+
+            self.blocks(xml, &item.children, &mut nested)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a table.
+    ///
+    /// Cells that span multiple rows are continued with merged cells in the
+    /// rows below them.
+    fn table(
+        &mut self,
+        xml: &mut XmlWriter,
+        elem: &'a HtmlElement,
+        block: &Block,
+    ) -> StrResult<()> {
+        // Adjacent tables would be merged by word processors.
+        if self.last == Last::Table {
+            self.empty_paragraph(xml);
+        }
+
+        let mut rows: Vec<(bool, &HtmlElement)> = vec![];
+        for child in elements(elem) {
+            match child.tag {
+                tag::tr => rows.push((false, child)),
+                tag::thead | tag::tbody | tag::tfoot => {
+                    let header = child.tag == tag::thead;
+                    rows.extend(
+                        elements(child)
+                            .filter(|row| row.tag == tag::tr)
+                            .map(|row| (header, row)),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        // Places the cells in the grid, taking spans into account.
+        let mut occupied: Vec<Vec<bool>> = vec![vec![]; rows.len()];
+        let mut slots: Vec<Vec<Slot>> = (0..rows.len()).map(|_| vec![]).collect();
+        for (y, (_, row)) in rows.iter().enumerate() {
+            let mut x = 0;
+            for cell in elements(row).filter(|cell| matches!(cell.tag, tag::td | tag::th))
+            {
+                while occupied[y].get(x).copied().unwrap_or(false) {
+                    x += 1;
+                }
+
+                let span = |key| {
+                    cell.attrs
+                        .get(key)
+                        .and_then(|span| span.parse::<usize>().ok())
+                        .unwrap_or(1)
+                        .max(1)
+                };
+                let colspan = span(attr::colspan);
+                let rowspan = span(attr::rowspan).min(rows.len() - y);
+
+                for (dy, covered) in occupied[y..y + rowspan].iter_mut().enumerate() {
+                    if covered.len() < x + colspan {
+                        covered.resize(x + colspan, false);
+                    }
+                    covered[x..x + colspan].fill(true);
+                    slots[y + dy].push(Slot {
+                        x,
+                        colspan,
+                        cell: (dy == 0).then_some(cell),
+                        merged: rowspan > 1,
+                    });
+                }
+
+                x += colspan;
+            }
+        }
+
+        let columns = occupied.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        let width = self.document.page.text_width() / columns as f64;
+
+        xml.start_element("w:tbl");
+        xml.start_element("w:tblPr");
+        xml.start_element("w:tblStyle");
+        xml.write_attribute("w:val", "TableGrid");
+        xml.end_element();
+        xml.start_element("w:tblW");
+        xml.write_attribute("w:w", "0");
+        xml.write_attribute("w:type", "auto");
+        xml.end_element();
+        if block.center {
+            xml.start_element("w:jc");
+            xml.write_attribute("w:val", "center");
+            xml.end_element();
+        }
+        xml.end_element();
+
+        xml.start_element("w:tblGrid");
+        for _ in 0..columns {
+            xml.start_element("w:gridCol");
+            xml.write_attribute("w:w", &twips(width));
+            xml.end_element();
+        }
+        xml.end_element();
+
+        for ((header, _), mut row) in rows.iter().zip(slots) {
+            row.sort_by_key(|slot| slot.x);
+            xml.start_element("w:tr");
+            if *header {
+                xml.start_element("w:trPr");
+                xml.start_element("w:tblHeader");
+                xml.end_element();
+                xml.end_element();
+            }
+
+            for slot in row {
+                xml.start_element("w:tc");
+                xml.start_element("w:tcPr");
+                xml.start_element("w:tcW");
+                xml.write_attribute("w:w", &twips(width * slot.colspan as f64));
+                xml.write_attribute("w:type", "dxa");
+                xml.end_element();
+                if slot.colspan > 1 {
+                    xml.start_element("w:gridSpan");
+                    xml.write_attribute("w:val", &slot.colspan);
+                    xml.end_element();
+                }
+                if slot.merged {
+                    xml.start_element("w:vMerge");
+                    if slot.cell.is_some() {
+                        xml.write_attribute("w:val", "restart");
+                    }
+                    xml.end_element();
+                }
+                xml.end_element();
+
+                self.last = Last::Nothing;
+                if let Some(cell) = slot.cell {
+                    let mut nested = Block::default();
+                    nested.format.bold = cell.tag == tag::th;
+                    self.blocks(xml, &cell.children, &mut nested)?;
+                }
+                if self.last != Last::Paragraph {
+                    self.empty_paragraph(xml);
+                }
+                xml.end_element();
+            }
+
+            xml.end_element();
+        }
+
+        xml.end_element();
+        self.last = Last::Table;
+        Ok(())
+    }
+
+    /// Writes a horizontal rule as an empty paragraph with a bottom border.
+    fn rule(&mut self, xml: &mut XmlWriter) {
+        xml.start_element("w:p");
+        xml.start_element("w:pPr");
+        xml.start_element("w:pBdr");
+        xml.start_element("w:bottom");
+        xml.write_attribute("w:val", "single");
+        xml.write_attribute("w:sz", "6");
+        xml.write_attribute("w:space", "1");
+        xml.write_attribute("w:color", "auto");
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        self.flush_bookmarks(xml);
+        xml.end_element();
+        self.last = Last::Paragraph;
+    }
+
+    /// Writes a paragraph with inline content.
+    ///
+    /// Nothing is written if there is only whitespace.
+    fn paragraph(
+        &mut self,
+        xml: &mut XmlWriter,
+        nodes: &[&'a HtmlNode],
+        block: &mut Block,
+    ) -> StrResult<()> {
+        if nodes.iter().all(|node| match node {
+            HtmlNode::Text(text, _) => text.trim().is_empty(),
+            _ => false,
+        }) {
+            return Ok(());
+        }
+
+        xml.start_element("w:p");
+        self.paragraph_props(xml, block);
+        self.flush_bookmarks(xml);
+        if std::mem::take(&mut block.note_mark) {
+            xml.start_element("w:r");
+            write_run_style(xml, "FootnoteReference");
+            xml.start_element("w:footnoteRef");
+            xml.end_element();
+            xml.end_element();
+        }
+        for node in nodes {
+            self.inline(xml, node, block.format)?;
+        }
+        xml.end_element();
+
+        self.last = Last::Paragraph;
+        Ok(())
+    }
+
+    /// Writes a paragraph without content.
+    fn empty_paragraph(&mut self, xml: &mut XmlWriter) {
+        xml.start_element("w:p");
+        self.flush_bookmarks(xml);
+        xml.end_element();
+        self.last = Last::Paragraph;
+    }
+
+    /// Writes the properties of a paragraph.
+    fn paragraph_props(&mut self, xml: &mut XmlWriter, block: &mut Block) {
+        let numbering = block.numbering.take();
+        let indent = if block.hanging {
+            Some((720, 720))
+        } else if numbering.is_none() && block.indent > 0 {
+            Some((720 * block.indent, 0))
+        } else {
+            None
+        };
+
+        if block.style.is_none()
+            && numbering.is_none()
+            && indent.is_none()
+            && !block.center
+        {
+            return;
+        }
+
+        xml.start_element("w:pPr");
+        if let Some(style) = block.style {
+            xml.start_element("w:pStyle");
+            xml.write_attribute("w:val", style);
+            xml.end_element();
+        }
+        if let Some((num, level)) = numbering {
+            xml.start_element("w:numPr");
+            xml.start_element("w:ilvl");
+            xml.write_attribute("w:val", &level);
+            xml.end_element();
+            xml.start_element("w:numId");
+            xml.write_attribute("w:val", &num);
+            xml.end_element();
+            xml.end_element();
+        }
+        if let Some((left, hanging)) = indent {
+            xml.start_element("w:ind");
+            xml.write_attribute("w:left", &left);
+            if hanging > 0 {
+                xml.write_attribute("w:hanging", &hanging);
+            }
+            xml.end_element();
+        }
+        if block.center {
+            xml.start_element("w:jc");
+            xml.write_attribute("w:val", "center");
+            xml.end_element();
+        }
+        xml.end_element();
+    }
+
+    /// Writes inline content into the current paragraph.
+    fn inline(
+        &mut self,
+        xml: &mut XmlWriter,
+        node: &'a HtmlNode,
+        mut format: Format,
+    ) -> StrResult<()> {
+        let elem = match node {
+            HtmlNode::Tag(_) => return Ok(()),
+            HtmlNode::Text(text, _) => {
+                self.run(xml, text, format);
+                return Ok(());
+            }
+            HtmlNode::Frame(frame) => return self.html_frame(xml, frame),
+            HtmlNode::Element(elem) => elem,
+        };
+
+        if let Some(id) = elem.attrs.get(attr::id) {
+            let name = self.bookmark(id);
+            self.write_bookmark(xml, &name);
+        }
+
+        match elem.tag {
+            tag::strong | tag::b => format.bold = true,
+            tag::em | tag::i => format.italic = true,
+            tag::code | tag::kbd | tag::samp => format.code = true,
+            tag::s | tag::del => format.strike = true,
+            tag::u | tag::ins => format.underline = true,
+            tag::mark => format.highlight = true,
+            tag::sub => format.subscript = true,
+            tag::sup if has_role(elem, "doc-backlink") => return Ok(()),
+            tag::sup if has_role(elem, "doc-noteref") => {
+                if let Some(id) = self.note_ref(elem) {
+                    xml.start_element("w:r");
+                    write_run_style(xml, "FootnoteReference");
+                    xml.start_element("w:footnoteReference");
+                    xml.write_attribute("w:id", &id);
+                    xml.end_element();
+                    xml.end_element();
+                    return Ok(());
+                }
+                // The note's entry is not part of the output, so the mark
+                // can't link to it.
+                format.superscript = true;
+                self.run(xml, &plain_text(elem), format);
+                return Ok(());
+            }
+            tag::sup => format.superscript = true,
+            tag::br => {
+                xml.start_element("w:r");
+                xml.start_element("w:br");
+                xml.end_element();
+                xml.end_element();
+                return Ok(());
+            }
+            tag::wbr | tag::style | tag::script | tag::template => return Ok(()),
+            tag::img => return self.image(xml, elem),
+            tag::mathml::math => return self.math(xml, elem),
+            tag::a if !format.link => {
+                if let Some(href) = elem.attrs.get(attr::href) {
+                    format.link = true;
+                    xml.start_element("w:hyperlink");
+                    if let Some(id) = href.strip_prefix('#') {
+                        let name = self.bookmark(id);
+                        xml.write_attribute("w:anchor", &name);
+                    } else {
+                        let id = self.relationship(href.clone(), true);
+                        xml.write_attribute("r:id", &id);
+                    }
+                    for child in &elem.children {
+                        self.inline(xml, child, format)?;
+                    }
+                    xml.end_element();
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+
+        for child in &elem.children {
+            self.inline(xml, child, format)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a run of text.
+    fn run(&mut self, xml: &mut XmlWriter, text: &str, format: Format) {
+        xml.start_element("w:r");
+        write_run_props(xml, format);
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                xml.start_element("w:br");
+                xml.end_element();
+            }
+            for (k, piece) in line.split('\t').enumerate() {
+                if k > 0 {
+                    xml.start_element("w:tab");
+                    xml.end_element();
+                }
+                if !piece.is_empty() {
+                    write_text(xml, "w:t", piece);
+                }
+            }
+        }
+        xml.end_element();
+    }
+
+    /// Writes an inline equation.
+    fn math(&mut self, xml: &mut XmlWriter, elem: &'a HtmlElement) -> StrResult<()> {
+        xml.start_element("m:oMath");
+        math::write_math(xml, &elem.children);
+        xml.end_element();
+        Ok(())
+    }
+
+    /// Writes a block-level equation into its own paragraph.
+    fn display_math(
+        &mut self,
+        xml: &mut XmlWriter,
+        elem: &'a HtmlElement,
+        block: &mut Block,
+    ) -> StrResult<()> {
+        xml.start_element("w:p");
+        self.paragraph_props(xml, block);
+        self.flush_bookmarks(xml);
+        xml.start_element("m:oMathPara");
+        self.math(xml, elem)?;
+        xml.end_element();
+        xml.end_element();
+        self.last = Last::Paragraph;
+        Ok(())
+    }
+
+    /// Registers a reference to a footnote and returns its footnote ID.
+    ///
+    /// Returns `None` if the reference can't become a footnote reference,
+    /// because the note was already referenced or is referenced from within
+    /// a footnote.
+    fn note_ref(&mut self, elem: &'a HtmlElement) -> Option<usize> {
+        if self.in_note {
+            return None;
+        }
+
+        let href = find_href(elem)?;
+        let note = *self.notes.get(href.strip_prefix('#')?)?;
+        if self.referenced.iter().any(|other| std::ptr::eq(*other, note)) {
+            return None;
+        }
+
+        self.referenced.push(note);
+        Some(self.referenced.len())
+    }
+
+    /// Writes an image.
+    ///
+    /// PNG, JPEG, and GIF images are embedded as is. Other formats are
+    /// embedded like frames.
+    fn image(&mut self, xml: &mut XmlWriter, elem: &'a HtmlElement) -> StrResult<()> {
+        let alt = elem.attrs.get(attr::alt).map(EcoString::as_str);
+        let Some((format, data)) =
+            elem.attrs.get(attr::src).and_then(|src| decode_data_url(src))
+        else {
+            if let Some(alt) = alt {
+                self.run(xml, alt, Format::default());
+            }
+            return Ok(());
+        };
+
+        // The `width` and `height` attributes hold the natural size of the
+        // image in CSS pixels.
+        let pixels = |key| {
+            elem.attrs
+                .get(key)
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+                .unwrap_or(1.0)
+        };
+        let natural = Size::new(
+            Abs::pt(pixels(attr::width) * 0.75),
+            Abs::pt(pixels(attr::height) * 0.75),
+        );
+        let size = self.fit(natural, css_width(elem, self.document.text.size));
+
+        let kind = match format {
+            "image/png" => MediaFormat::Png,
+            "image/jpeg" => MediaFormat::Jpg,
+            "image/gif" => MediaFormat::Gif,
+            other => {
+                // Formats that Word can't embed directly are converted.
+                let image = match other {
+                    "image/svg+xml" => SvgImage::new(data)
+                        .map(Image::plain)
+                        .map_err(|_| "failed to decode SVG image")?,
+                    "image/webp" => RasterImage::plain(data, ExchangeFormat::Webp)
+                        .map(Image::plain)?,
+                    other => bail!("unsupported image format in docx export: {other}"),
+                };
+                let mut frame = Frame::soft(size);
+                frame
+                    .push(Point::zero(), FrameItem::Image(image, size, Span::detached()));
+                return self.frame(xml, &frame, size, alt);
+            }
+        };
+
+        let raster = self.embed(kind, data);
+        self.drawing(xml, &raster, None, size, alt);
+        Ok(())
+    }
+
+    /// Writes a frame that was embedded into the HTML.
+    fn html_frame(&mut self, xml: &mut XmlWriter, frame: &'a HtmlFrame) -> StrResult<()> {
+        if let Some(id) = &frame.id {
+            let name = self.bookmark(id);
+            self.write_bookmark(xml, &name);
+        }
+        let size = self.fit(frame.inner.size(), None);
+        self.frame(xml, &frame.inner, size, None)
+    }
+
+    /// Writes a frame as an SVG image with a PNG fallback for word processors
+    /// that don't support SVG.
+    fn frame(
+        &mut self,
+        xml: &mut XmlWriter,
+        frame: &Frame,
+        size: Size,
+        alt: Option<&str>,
+    ) -> StrResult<()> {
+        let page = Page {
+            frame: frame.clone(),
+            bleed: Sides::default(),
+            fill: Smart::Custom(None),
+            numbering: None,
+            supplement: Content::empty(),
+            number: 1,
+        };
+
+        let options = RenderOptions {
+            pixel_per_pt: Scalar::new(FALLBACK_PIXEL_PER_PT),
+            background: Smart::Custom(None),
+            ..RenderOptions::default()
+        };
+        let pixmap = typst_render::render(&page, &options);
+        let png = typst_render::encode(&pixmap, RasterFormat::Png, &options)?;
+        let svg = typst_svg::svg(&page, &SvgOptions::default());
+
+        let raster = self.embed(MediaFormat::Png, Bytes::new(png));
+        let vector = self.embed(MediaFormat::Svg, Bytes::from_string(svg));
+        self.drawing(xml, &raster, Some(&vector), size, alt);
+        Ok(())
+    }
+
+    /// Writes an inline drawing of an embedded image.
+    fn drawing(
+        &mut self,
+        xml: &mut XmlWriter,
+        raster: &str,
+        vector: Option<&str>,
+        size: Size,
+        alt: Option<&str>,
+    ) {
+        let id = self.next_id();
+        let name = eco_format!("Picture {id}");
+        let (cx, cy) = (emus(size.x), emus(size.y));
+
+        xml.start_element("w:r");
+        xml.start_element("w:drawing");
+        xml.start_element("wp:inline");
+        for side in ["distT", "distB", "distL", "distR"] {
+            xml.write_attribute(side, "0");
+        }
+        xml.start_element("wp:extent");
+        xml.write_attribute("cx", &cx);
+        xml.write_attribute("cy", &cy);
+        xml.end_element();
+        xml.start_element("wp:docPr");
+        xml.write_attribute("id", &id);
+        xml.write_attribute("name", &name);
+        if let Some(alt) = alt {
+            xml.write_attribute("descr", alt);
+        }
+        xml.end_element();
+        xml.start_element("a:graphic");
+        xml.start_element("a:graphicData");
+        xml.write_attribute(
+            "uri",
+            "http://schemas.openxmlformats.org/drawingml/2006/picture",
+        );
+        xml.start_element("pic:pic");
+        xml.start_element("pic:nvPicPr");
+        xml.start_element("pic:cNvPr");
+        xml.write_attribute("id", &id);
+        xml.write_attribute("name", &name);
+        xml.end_element();
+        xml.start_element("pic:cNvPicPr");
+        xml.end_element();
+        xml.end_element();
+        xml.start_element("pic:blipFill");
+        xml.start_element("a:blip");
+        xml.write_attribute("r:embed", raster);
+        if let Some(vector) = vector {
+            xml.start_element("a:extLst");
+            xml.start_element("a:ext");
+            xml.write_attribute("uri", "{96DAC541-7B7A-43D3-8B79-37D633B846F1}");
+            xml.start_element("asvg:svgBlip");
+            xml.write_attribute("r:embed", vector);
+            xml.end_element();
+            xml.end_element();
+            xml.end_element();
+        }
+        xml.end_element();
+        xml.start_element("a:stretch");
+        xml.start_element("a:fillRect");
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.start_element("pic:spPr");
+        xml.start_element("a:xfrm");
+        xml.start_element("a:off");
+        xml.write_attribute("x", "0");
+        xml.write_attribute("y", "0");
+        xml.end_element();
+        xml.start_element("a:ext");
+        xml.write_attribute("cx", &cx);
+        xml.write_attribute("cy", &cy);
+        xml.end_element();
+        xml.end_element();
+        xml.start_element("a:prstGeom");
+        xml.write_attribute("prst", "rect");
+        xml.start_element("a:avLst");
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+        xml.end_element();
+    }
+
+    /// Scales a size to the requested width and shrinks it to fit into the
+    /// text area, keeping its aspect ratio.
+    fn fit(&self, natural: Size, width: Option<CssWidth>) -> Size {
+        let available = self.document.page.text_width();
+        let mut target = match width {
+            Some(CssWidth::Abs(abs)) => abs,
+            Some(CssWidth::Ratio(ratio)) => available * ratio,
+            None => natural.x,
+        };
+        target = target.min(available);
+        if natural.x <= Abs::zero() {
+            return natural;
+        }
+        Size::new(target, natural.y * (target / natural.x))
+    }
+
+    /// Adds a media file to the package, unless it already exists, and
+    /// returns the ID of a relationship to it from the current part.
+    fn embed(&mut self, format: MediaFormat, data: Bytes) -> EcoString {
+        let hash = typst_utils::hash128(&data);
+        let path = match self.media_paths.get(&hash) {
+            Some(path) => path.clone(),
+            None => {
+                let path = eco_format!(
+                    "media/image{}.{}",
+                    self.media.len() + 1,
+                    format.extension()
+                );
+                self.media.push(Media { path: path.clone(), data, format });
+                self.media_paths.insert(hash, path.clone());
+                path
+            }
+        };
+        self.relationship(path, false)
+    }
+
+    /// Returns the ID of a relationship from the current part, adding the
+    /// relationship if necessary.
+    fn relationship(&mut self, target: EcoString, external: bool) -> EcoString {
+        if let Some(rel) = self
+            .rels
+            .iter()
+            .find(|rel| rel.target == target && rel.external == external)
+        {
+            return rel.id.clone();
+        }
+
+        let id = eco_format!("rId{}", self.rels.len() + 1);
+        self.rels.push(Relationship { id: id.clone(), target, external });
+        id
+    }
+
+    /// Returns the bookmark name for an HTML ID.
+    ///
+    /// Bookmark names may only contain letters, digits, and underscores and
+    /// are limited to 40 characters.
+    fn bookmark(&mut self, id: &str) -> EcoString {
+        if let Some(name) = self.bookmarks.get(id) {
+            return name.clone();
+        }
+
+        let mut base: EcoString = id
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
+            .take(36)
+            .collect();
+        if !base.starts_with(char::is_alphabetic) {
+            base = eco_format!("_{base}");
+        }
+
+        let mut name = base.clone();
+        let mut counter = 1;
+        while self.names.contains(&name) {
+            name = eco_format!("{base}{counter}");
+            counter += 1;
+        }
+
+        self.names.insert(name.clone());
+        self.bookmarks.insert(id.into(), name.clone());
+        name
+    }
+
+    /// Writes a bookmark that marks a position.
+    fn write_bookmark(&mut self, xml: &mut XmlWriter, name: &str) {
+        let id = self.next_id();
+        xml.start_element("w:bookmarkStart");
+        xml.write_attribute("w:id", &id);
+        xml.write_attribute("w:name", name);
+        xml.end_element();
+        xml.start_element("w:bookmarkEnd");
+        xml.write_attribute("w:id", &id);
+        xml.end_element();
+    }
+
+    /// Writes the bookmarks that are waiting for a paragraph.
+    fn flush_bookmarks(&mut self, xml: &mut XmlWriter) {
+        for name in std::mem::take(&mut self.pending) {
+            self.write_bookmark(xml, &name);
+        }
+    }
+
+    /// Writes an empty paragraph if bookmarks are still waiting at the end
+    /// of the body.
+    fn flush_trailing(&mut self, xml: &mut XmlWriter) {
+        if !self.pending.is_empty() || self.last == Last::Table {
+            self.empty_paragraph(xml);
+        }
+    }
+
+    /// Writes the section properties with the page setup.
+    fn section(&mut self, xml: &mut XmlWriter) {
+        let page = &self.document.page;
+        xml.start_element("w:sectPr");
+        xml.start_element("w:footnotePr");
+        xml.start_element("w:numFmt");
+        xml.write_attribute("w:val", "decimal");
+        xml.end_element();
+        xml.end_element();
+        xml.start_element("w:pgSz");
+        xml.write_attribute("w:w", &twips(page.size.x));
+        xml.write_attribute("w:h", &twips(page.size.y));
+        if page.size.x > page.size.y {
+            xml.write_attribute("w:orient", "landscape");
+        }
+        xml.end_element();
+        xml.start_element("w:pgMar");
+        xml.write_attribute("w:top", &twips(page.margin.top));
+        xml.write_attribute("w:right", &twips(page.margin.right));
+        xml.write_attribute("w:bottom", &twips(page.margin.bottom));
+        xml.write_attribute("w:left", &twips(page.margin.left));
+        xml.write_attribute("w:header", &twips(page.margin.top / 2.0));
+        xml.write_attribute("w:footer", &twips(page.margin.bottom / 2.0));
+        xml.write_attribute("w:gutter", "0");
+        xml.end_element();
+        if page.columns.get() > 1 {
+            xml.start_element("w:cols");
+            xml.write_attribute("w:num", &page.columns);
+            xml.write_attribute("w:space", "720");
+            xml.end_element();
+        }
+        xml.end_element();
+    }
+
+    /// Returns a fresh ID for a bookmark or drawing.
+    fn next_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+/// A cell of a table, placed in the grid.
+struct Slot<'a> {
+    /// The column of the cell.
+    x: usize,
+    /// The number of columns the cell spans.
+    colspan: usize,
+    /// The cell, or `None` if this continues a cell from a row above.
+    cell: Option<&'a HtmlElement>,
+    /// Whether the cell spans multiple rows.
+    merged: bool,
+}
+
+/// A width requested through CSS.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CssWidth {
+    Abs(Abs),
+    Ratio(f64),
+}
+
+/// Parses the width of an element from its CSS properties.
+///
+/// Only simple lengths and percentages are supported.
+fn css_width(elem: &HtmlElement, font_size: Abs) -> Option<CssWidth> {
+    let css = elem.css.to_inline().to_string();
+    let value = css.split(';').find_map(|declaration| {
+        let (property, value) = declaration.split_once(':')?;
+        (property.trim() == "width").then(|| value.trim().to_string())
+    })?;
+
+    let number = |suffix| value.strip_suffix(suffix)?.parse::<f64>().ok();
+    if let Some(percent) = number("%") {
+        Some(CssWidth::Ratio(percent / 100.0))
+    } else if let Some(pt) = number("pt") {
+        Some(CssWidth::Abs(Abs::pt(pt)))
+    } else {
+        number("em").map(|em| CssWidth::Abs(font_size * em))
+    }
+}
+
+/// Decodes a base64 data URL into its media type and data.
+fn decode_data_url(url: &str) -> Option<(&str, Bytes)> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let media_type = header.strip_suffix(";base64")?;
+    let data = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+    Some((media_type, Bytes::new(data)))
+}
+
+/// Finds the `href` of the first link within an element.
+fn find_href(elem: &HtmlElement) -> Option<&EcoString> {
+    if elem.tag == tag::a {
+        return elem.attrs.get(attr::href);
+    }
+    elements(elem).find_map(find_href)
+}
+
+/// The text within an element and its descendants.
+pub(crate) fn plain_text(elem: &HtmlElement) -> EcoString {
+    let mut text = EcoString::new();
+    for node in &elem.children {
+        match node {
+            HtmlNode::Text(t, _) => text.push_str(t),
+            HtmlNode::Element(child) => text.push_str(&plain_text(child)),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Iterates over the child elements of an element.
+pub(crate) fn elements(elem: &HtmlElement) -> impl Iterator<Item = &HtmlElement> {
+    elem.children.iter().filter_map(|node| match node {
+        HtmlNode::Element(child) => Some(child),
+        _ => None,
+    })
+}
+
+/// Whether an element has the given ARIA role.
+fn has_role(elem: &HtmlElement, role: &str) -> bool {
+    elem.attrs.get(attr::role).is_some_and(|r| r == role)
+}
+
+/// The level of a heading element, with one for `<h1>`.
+fn heading_level(elem: &HtmlElement) -> Option<usize> {
+    Some(match elem.tag {
+        tag::h1 => 1,
+        tag::h2 => 2,
+        tag::h3 => 3,
+        tag::h4 => 4,
+        tag::h5 => 5,
+        tag::h6 => 6,
+        _ => return None,
+    })
+}
+
+/// Whether an element is written at the block level.
+fn is_block(elem: &HtmlElement) -> bool {
+    match elem.tag {
+        tag::mathml::math => {
+            elem.attrs.get(attr::mathml::display).is_some_and(|d| d == "block")
+        }
+        tag::address
+        | tag::article
+        | tag::aside
+        | tag::blockquote
+        | tag::dd
+        | tag::details
+        | tag::div
+        | tag::dl
+        | tag::dt
+        | tag::fieldset
+        | tag::figcaption
+        | tag::figure
+        | tag::footer
+        | tag::form
+        | tag::h1
+        | tag::h2
+        | tag::h3
+        | tag::h4
+        | tag::h5
+        | tag::h6
+        | tag::head
+        | tag::header
+        | tag::hgroup
+        | tag::hr
+        | tag::li
+        | tag::main
+        | tag::menu
+        | tag::nav
+        | tag::ol
+        | tag::p
+        | tag::pre
+        | tag::script
+        | tag::search
+        | tag::section
+        | tag::style
+        | tag::summary
+        | tag::table
+        | tag::template
+        | tag::ul => true,
+        _ => false,
+    }
+}
+
+/// Writes the properties of a run.
+fn write_run_props(xml: &mut XmlWriter, format: Format) {
+    let style = if format.link {
+        Some("Hyperlink")
+    } else if format.code {
+        Some("VerbatimChar")
+    } else {
+        None
+    };
+
+    let flags =
+        [(format.bold, "w:b"), (format.italic, "w:i"), (format.strike, "w:strike")];
+    if style.is_none()
+        && !flags.iter().any(|(on, _)| *on)
+        && !format.highlight
+        && !format.underline
+        && !format.subscript
+        && !format.superscript
+    {
+        return;
+    }
+
+    xml.start_element("w:rPr");
+    if let Some(style) = style {
+        xml.start_element("w:rStyle");
+        xml.write_attribute("w:val", style);
+        xml.end_element();
+    }
+    for (on, name) in flags {
+        if on {
+            xml.start_element(name);
+            xml.end_element();
+        }
+    }
+    if format.highlight {
+        xml.start_element("w:highlight");
+        xml.write_attribute("w:val", "yellow");
+        xml.end_element();
+    }
+    if format.underline {
+        xml.start_element("w:u");
+        xml.write_attribute("w:val", "single");
+        xml.end_element();
+    }
+    if format.subscript || format.superscript {
+        xml.start_element("w:vertAlign");
+        xml.write_attribute(
+            "w:val",
+            if format.superscript { "superscript" } else { "subscript" },
+        );
+        xml.end_element();
+    }
+    xml.end_element();
+}
+
+/// Writes run properties that only consist of a character style.
+fn write_run_style(xml: &mut XmlWriter, style: &str) {
+    xml.start_element("w:rPr");
+    xml.start_element("w:rStyle");
+    xml.write_attribute("w:val", style);
+    xml.end_element();
+    xml.end_element();
+}
+
+/// Writes a text element whose whitespace is preserved.
+///
+/// Characters that are not allowed in XML are dropped.
+pub(crate) fn write_text(xml: &mut XmlWriter, name: &str, text: &str) {
+    xml.start_element(name);
+    xml.write_attribute("xml:space", "preserve");
+    if text.chars().any(is_invalid_xml_char) {
+        let text: String = text.chars().filter(|&c| !is_invalid_xml_char(c)).collect();
+        xml.write_text(&text);
+    } else {
+        xml.write_text(text);
+    }
+    xml.end_element();
+}
+
+/// Whether a character may not appear in XML.
+fn is_invalid_xml_char(c: char) -> bool {
+    matches!(
+        c,
+        '\0'..='\x08' | '\x0B' | '\x0C' | '\x0E'..='\x1F' | '\u{FFFE}' | '\u{FFFF}'
+    )
+}
+
+/// Converts a length into English Metric Units.
+fn emus(abs: Abs) -> i64 {
+    (abs.to_pt() * 12700.0).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_data_url() {
+        let (format, data) = decode_data_url("data:image/png;base64,iVBORw==").unwrap();
+        assert_eq!(format, "image/png");
+        assert_eq!(data.as_slice(), b"\x89PNG");
+        assert!(decode_data_url("https://example.com/image.png").is_none());
+        assert!(decode_data_url("data:image/png,plain").is_none());
+    }
+
+    #[test]
+    fn test_is_invalid_xml_char() {
+        assert!(is_invalid_xml_char('\x07'));
+        assert!(!is_invalid_xml_char('\t'));
+        assert!(!is_invalid_xml_char('\n'));
+        assert!(!is_invalid_xml_char('ä'));
+    }
+}
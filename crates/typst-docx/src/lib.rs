@@ -0,0 +1,253 @@
+//! Typst's DOCX exporter.
+//!
+//! Unlike PDF or SVG export, DOCX export does not work from laid-out frames.
+//! Instead, the document is realized for the HTML target and the resulting DOM
+//! is translated into WordprocessingML, so that the exported file has real
+//! paragraphs, lists, and tables that can be edited in a word processor.
+
+mod convert;
+mod math;
+mod package;
+
+use std::num::NonZeroUsize;
+
+use typst_html::HtmlDocument;
+use typst_library::Feature;
+use typst_library::diag::{At, SourceResult, bail, warning};
+use typst_library::engine::Engine;
+use typst_library::foundations::{
+    Content, NativeElement, Output, Resolve, SequenceElem, Smart, Style, StyleChain,
+    StyledElem, Styles, Target,
+};
+use typst_library::introspection::Introspector;
+use typst_library::layout::{Abs, Length, PageElem, Paper, Rel, Sides, Size};
+use typst_library::model::{Document, DocumentInfo, ParElem, ParbreakElem};
+use typst_library::text::{SpaceElem, TextElem};
+use typst_syntax::Span;
+use typst_utils::LazyHash;
+
+/// Exports a document into a DOCX file.
+///
+/// The document's content is converted from its HTML DOM. The page setup and
+/// the default text style come from the set rules at the top level of the
+/// document.
+#[typst_macros::time(name = "docx")]
+pub fn docx(document: &DocxDocument) -> SourceResult<Vec<u8>> {
+    let parts = convert::convert(document).at(Span::detached())?;
+    package::write(document, &parts).at(Span::detached())
+}
+
+/// A document that is ready for DOCX export.
+#[derive(Debug, Clone)]
+pub struct DocxDocument {
+    /// The document realized for the HTML target.
+    html: HtmlDocument,
+    /// The page setup of the document.
+    page: PageSetup,
+    /// The default text style of the document.
+    text: TextSetup,
+}
+
+impl DocxDocument {
+    /// The HTML document from which the DOCX file is created.
+    pub fn html(&self) -> &HtmlDocument {
+        &self.html
+    }
+}
+
+impl Document for DocxDocument {
+    fn info(&self) -> &DocumentInfo {
+        self.html.info()
+    }
+}
+
+impl Output for DocxDocument {
+    fn introspector(&self) -> &dyn Introspector {
+        self.html.introspector().as_ref()
+    }
+
+    fn target() -> Target {
+        Target::Html
+    }
+
+    fn create(
+        engine: &mut Engine,
+        content: &Content,
+        styles: StyleChain,
+    ) -> SourceResult<Self> {
+        let features = &engine.library.features;
+        if !features.is_enabled(Feature::Docx) || !features.is_enabled(Feature::Html) {
+            bail!(
+                Span::detached(),
+                "docx export is only available when `--features docx,html` is passed";
+                hint: "docx export is experimental";
+                hint: "it is built on HTML export, which is experimental as well";
+            );
+        }
+
+        // Page set rules are not meaningful for HTML, so they are taken out
+        // before realization instead of being ignored with a warning.
+        let mut setup = vec![];
+        let content = extract_setup(engine, content, Some(&mut setup))
+            .unwrap_or_else(|| content.clone());
+        let setup: Styles = setup.into_iter().collect();
+        let chain = styles.chain(&setup);
+
+        let html = typst_html::html_document(engine, &content, styles)?;
+        Ok(Self {
+            html,
+            page: PageSetup::resolve(chain),
+            text: TextSetup::resolve(chain),
+        })
+    }
+}
+
+/// The page setup of a DOCX document, derived from page set rules.
+#[derive(Debug, Clone, PartialEq)]
+struct PageSetup {
+    /// The size of the page.
+    size: Size,
+    /// The page margins.
+    margin: Sides<Abs>,
+    /// The number of columns.
+    columns: NonZeroUsize,
+}
+
+impl PageSetup {
+    /// Resolves the page setup from the document-level styles.
+    ///
+    /// Word documents can't have pages that grow with their content, so an
+    /// automatic width or height falls back to the respective A4 dimension.
+    fn resolve(styles: StyleChain) -> Self {
+        let width = styles.resolve(PageElem::width).unwrap_or(Paper::A4.width());
+        let height = styles.resolve(PageElem::height).unwrap_or(Paper::A4.height());
+        let mut size = Size::new(width, height);
+        if styles.get(PageElem::flipped) {
+            std::mem::swap(&mut size.x, &mut size.y);
+        }
+
+        // Mirrors the default margins of paged layout.
+        let default = Rel::<Length>::from((2.5 / 21.0) * width.min(height));
+        let margin = styles
+            .get(PageElem::margin)
+            .unwrap_or_default()
+            .sides
+            .map(|side| side.and_then(Smart::custom).unwrap_or(default))
+            .resolve(styles)
+            .relative_to(size);
+
+        Self {
+            size,
+            margin,
+            columns: styles.get(PageElem::columns),
+        }
+    }
+
+    /// The width that is available for content.
+    fn text_width(&self) -> Abs {
+        (self.size.x - self.margin.left - self.margin.right).max(Abs::zero())
+    }
+}
+
+/// The default text style of a DOCX document, derived from text and paragraph
+/// set rules.
+#[derive(Debug, Clone, PartialEq)]
+struct TextSetup {
+    /// The primary font family.
+    font: Option<String>,
+    /// The font size.
+    size: Abs,
+    /// The spacing between paragraphs.
+    spacing: Abs,
+    /// Whether paragraphs are justified.
+    justify: bool,
+}
+
+impl TextSetup {
+    /// Resolves the text setup from the document-level styles.
+    fn resolve(styles: StyleChain) -> Self {
+        Self {
+            font: styles
+                .get_ref(TextElem::font)
+                .0
+                .first()
+                .map(|family| family.as_str().into()),
+            size: styles.resolve(TextElem::size),
+            spacing: styles.resolve(ParElem::spacing),
+            justify: styles.get(ParElem::justify),
+        }
+    }
+}
+
+/// Collects the styles that are relevant for the setup of the document from
+/// the top level of the content and strips page styles from it.
+///
+/// The set rules at the top level of a document, including those from a
+/// template's `show` rule, end up in a leading chain of styled content. A set
+/// rule styles the remainder of its sequence, so a sequence only continues the
+/// chain if its single child other than spaces and paragraph breaks does. Only
+/// styles from this chain form the setup. Word can't change the page setup
+/// within a section, so page styles outside of the chain are stripped with a
+/// warning. Returns `None` if the content does not contain page styles and can
+/// be used as is.
+fn extract_setup(
+    engine: &mut Engine,
+    content: &Content,
+    mut setup: Option<&mut Vec<LazyHash<Style>>>,
+) -> Option<Content> {
+    if let Some(sequence) = content.to_packed::<SequenceElem>() {
+        let is_space =
+            |child: &&Content| child.is::<SpaceElem>() || child.is::<ParbreakElem>();
+        let leading =
+            sequence.children.iter().filter(|child| !is_space(child)).count() == 1;
+
+        let mut changed = false;
+        let mut children = Vec::with_capacity(sequence.children.len());
+        for child in &sequence.children {
+            let setup = if leading { setup.as_deref_mut() } else { None };
+            match extract_setup(engine, child, setup) {
+                Some(stripped) => {
+                    changed = true;
+                    children.push(stripped);
+                }
+                None => children.push(child.clone()),
+            }
+        }
+        changed.then(|| Content::sequence(children).spanned(content.span()))
+    } else if let Some(styled) = content.to_packed::<StyledElem>() {
+        let mut kept = vec![];
+        for style in styled.styles.as_slice() {
+            let elem = style.element();
+            match &mut setup {
+                Some(setup) if elem == Some(PageElem::ELEM) => setup.push(style.clone()),
+                Some(setup)
+                    if elem == Some(TextElem::ELEM) || elem == Some(ParElem::ELEM) =>
+                {
+                    setup.push(style.clone());
+                    kept.push(style.clone());
+                }
+                None if elem == Some(PageElem::ELEM) => engine.sink.warn(warning!(
+                    style.span(),
+                    "page set rule was ignored during DOCX export";
+                    hint: "the page setup is only taken from set rules at the start \
+                           of the document";
+                )),
+                _ => kept.push(style.clone()),
+            }
+        }
+
+        let child = extract_setup(engine, &styled.child, setup);
+        if child.is_none() && kept.len() == styled.styles.as_slice().len() {
+            return None;
+        }
+
+        let child = child.unwrap_or_else(|| styled.child.clone());
+        Some(
+            child
+                .styled_with_map(kept.into_iter().collect())
+                .spanned(content.span()),
+        )
+    } else {
+        None
+    }
+}
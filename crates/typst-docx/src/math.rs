@@ -0,0 +1,247 @@
+//! Conversion of MathML into Office Math (OMML).
+
+use typst_html::{HtmlElement, HtmlNode, attr, tag};
+use xmlwriter::XmlWriter;
+
+use crate::convert::{elements, plain_text, write_text};
+
+/// Writes the OMML for a sequence of MathML nodes.
+pub fn write_math(xml: &mut XmlWriter, nodes: &[HtmlNode]) {
+    for node in nodes {
+        match node {
+            HtmlNode::Element(elem) => write_elem(xml, elem),
+            HtmlNode::Text(text, _) if !text.trim().is_empty() => {
+                write_run(xml, text, Some("p"));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Writes the OMML for a MathML element.
+fn write_elem(xml: &mut XmlWriter, elem: &HtmlElement) {
+    let args: Vec<&HtmlElement> = elements(elem).collect();
+    let arg = |i: usize| args.get(i).copied();
+    match elem.tag {
+        tag::mathml::mi => {
+            let text = plain_text(elem);
+            let style =
+                match elem.attrs.get(attr::mathml::mathvariant).map(|v| v.as_str()) {
+                    Some("normal") => Some("p"),
+                    Some("bold") => Some("b"),
+                    Some("bold-italic") => Some("bi"),
+                    Some("italic") => None,
+                    _ if text.chars().count() == 1 => None,
+                    _ => Some("p"),
+                };
+            write_run(xml, &text, style);
+        }
+        tag::mathml::mn | tag::mathml::mo | tag::mathml::mtext | tag::mathml::ms => {
+            let style =
+                match elem.attrs.get(attr::mathml::mathvariant).map(|v| v.as_str()) {
+                    Some("bold") => "b",
+                    Some("bold-italic") => "bi",
+                    Some("italic") => "i",
+                    _ => "p",
+                };
+            write_run(xml, &plain_text(elem), Some(style));
+        }
+        tag::mathml::mspace => {
+            let width = elem
+                .attrs
+                .get(attr::mathml::width)
+                .and_then(|width| width.strip_suffix("em")?.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let space = if width >= 1.0 {
+                "\u{2003}"
+            } else if width >= 0.5 {
+                "\u{2002}"
+            } else if width > 0.0 {
+                "\u{2009}"
+            } else {
+                return;
+            };
+            write_run(xml, space, Some("p"));
+        }
+        tag::mathml::mfrac => {
+            xml.start_element("m:f");
+            let bar = elem.attrs.get(attr::mathml::linethickness);
+            if bar.is_some_and(|thickness| thickness.trim_start_matches('0').is_empty()) {
+                xml.start_element("m:fPr");
+                write_val(xml, "m:type", "noBar");
+                xml.end_element();
+            }
+            write_arg(xml, "m:num", arg(0));
+            write_arg(xml, "m:den", arg(1));
+            xml.end_element();
+        }
+        tag::mathml::msup => {
+            xml.start_element("m:sSup");
+            write_arg(xml, "m:e", arg(0));
+            write_arg(xml, "m:sup", arg(1));
+            xml.end_element();
+        }
+        tag::mathml::msub => {
+            xml.start_element("m:sSub");
+            write_arg(xml, "m:e", arg(0));
+            write_arg(xml, "m:sub", arg(1));
+            xml.end_element();
+        }
+        tag::mathml::msubsup => {
+            xml.start_element("m:sSubSup");
+            write_arg(xml, "m:e", arg(0));
+            write_arg(xml, "m:sub", arg(1));
+            write_arg(xml, "m:sup", arg(2));
+            xml.end_element();
+        }
+        tag::mathml::msqrt => {
+            xml.start_element("m:rad");
+            xml.start_element("m:radPr");
+            write_val(xml, "m:degHide", "1");
+            xml.end_element();
+            write_arg(xml, "m:deg", None);
+            xml.start_element("m:e");
+            write_math(xml, &elem.children);
+            xml.end_element();
+            xml.end_element();
+        }
+        tag::mathml::mroot => {
+            xml.start_element("m:rad");
+            write_arg(xml, "m:deg", arg(1));
+            write_arg(xml, "m:e", arg(0));
+            xml.end_element();
+        }
+        tag::mathml::mover
+            if elem.attrs.get(attr::mathml::accent).is_some_and(|a| a == "true")
+                && arg(1).is_some_and(|over| over.tag == tag::mathml::mo) =>
+        {
+            xml.start_element("m:acc");
+            xml.start_element("m:accPr");
+            write_val(xml, "m:chr", &plain_text(args[1]));
+            xml.end_element();
+            write_arg(xml, "m:e", arg(0));
+            xml.end_element();
+        }
+        tag::mathml::mover => write_limit(xml, "m:limUpp", arg(0), arg(1)),
+        tag::mathml::munder => write_limit(xml, "m:limLow", arg(0), arg(1)),
+        tag::mathml::munderover => {
+            xml.start_element("m:limUpp");
+            xml.start_element("m:e");
+            write_limit(xml, "m:limLow", arg(0), arg(1));
+            xml.end_element();
+            write_arg(xml, "m:lim", arg(2));
+            xml.end_element();
+        }
+        tag::mathml::mmultiscripts => write_multiscripts(xml, &args),
+        tag::mathml::mtable => {
+            xml.start_element("m:m");
+            for row in args.iter().filter(|row| row.tag == tag::mathml::mtr) {
+                xml.start_element("m:mr");
+                for cell in elements(row) {
+                    write_arg(xml, "m:e", Some(cell));
+                }
+                xml.end_element();
+            }
+            xml.end_element();
+        }
+        tag::mathml::mphantom => {
+            xml.start_element("m:phant");
+            xml.start_element("m:e");
+            write_math(xml, &elem.children);
+            xml.end_element();
+            xml.end_element();
+        }
+        // The first child holds the presentation markup, while the
+        // annotations hold alternative representations.
+        tag::mathml::semantics => {
+            if let Some(first) = arg(0) {
+                write_elem(xml, first);
+            }
+        }
+        tag::mathml::annotation | tag::mathml::annotation_xml => {}
+        _ => write_math(xml, &elem.children),
+    }
+}
+
+/// Writes a base with scripts before and after it.
+///
+/// Only the first pair of scripts on each side is kept as OMML has no
+/// equivalent for more of them.
+fn write_multiscripts(xml: &mut XmlWriter, args: &[&HtmlElement]) {
+    let split = args.iter().position(|arg| arg.tag == tag::mathml::mprescripts);
+    let (post, pre) = match split {
+        Some(i) => (&args[..i], &args[i + 1..]),
+        None => (args, &[][..]),
+    };
+
+    let base = |xml: &mut XmlWriter| {
+        if post.len() > 1 {
+            xml.start_element("m:sSubSup");
+            write_arg(xml, "m:e", post.first().copied());
+            write_arg(xml, "m:sub", post.get(1).copied());
+            write_arg(xml, "m:sup", post.get(2).copied());
+            xml.end_element();
+        } else if let Some(first) = post.first() {
+            write_elem(xml, first);
+        }
+    };
+
+    if pre.is_empty() {
+        base(xml);
+        return;
+    }
+
+    xml.start_element("m:sPre");
+    write_arg(xml, "m:sub", pre.first().copied());
+    write_arg(xml, "m:sup", pre.get(1).copied());
+    xml.start_element("m:e");
+    base(xml);
+    xml.end_element();
+    xml.end_element();
+}
+
+/// Writes a base with a limit above or below it.
+fn write_limit(
+    xml: &mut XmlWriter,
+    name: &str,
+    base: Option<&HtmlElement>,
+    limit: Option<&HtmlElement>,
+) {
+    xml.start_element(name);
+    write_arg(xml, "m:e", base);
+    write_arg(xml, "m:lim", limit);
+    xml.end_element();
+}
+
+/// Writes an argument of an OMML object. A missing argument is written as an
+/// empty element.
+fn write_arg(xml: &mut XmlWriter, name: &str, arg: Option<&HtmlElement>) {
+    xml.start_element(name);
+    if let Some(arg) = arg {
+        write_elem(xml, arg);
+    }
+    xml.end_element();
+}
+
+/// Writes a math run with an optional style (`p` for plain, `b` for bold,
+/// `i` for italic, `bi` for bold italic).
+fn write_run(xml: &mut XmlWriter, text: &str, style: Option<&str>) {
+    if text.is_empty() {
+        return;
+    }
+    xml.start_element("m:r");
+    if let Some(style) = style {
+        xml.start_element("m:rPr");
+        write_val(xml, "m:sty", style);
+        xml.end_element();
+    }
+    write_text(xml, "m:t", text);
+    xml.end_element();
+}
+
+/// Writes an element with a single `m:val` attribute.
+fn write_val(xml: &mut XmlWriter, name: &str, val: &str) {
+    xml.start_element(name);
+    xml.write_attribute("m:val", val);
+    xml.end_element();
+}
@@ -0,0 +1,708 @@
+//! Writing of the parts of a DOCX file and the ZIP package that holds them.
+
+use std::fmt::Display;
+use std::io::{Cursor, Write};
+
+use ecow::{EcoString, eco_format};
+use typst_library::diag::StrResult;
+use typst_library::foundations::{Datetime, Smart};
+use typst_library::layout::Abs;
+use typst_library::model::Document;
+use xmlwriter::XmlWriter;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::DocxDocument;
+use crate::convert::{Parts, Relationship};
+
+/// The namespaces that are declared on the root of the document and footnotes
+/// parts.
+const NAMESPACES: &[(&str, &str)] = &[
+    ("w", "http://schemas.openxmlformats.org/wordprocessingml/2006/main"),
+    ("r", "http://schemas.openxmlformats.org/officeDocument/2006/relationships"),
+    ("m", "http://schemas.openxmlformats.org/officeDocument/2006/math"),
+    ("wp", "http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"),
+    ("a", "http://schemas.openxmlformats.org/drawingml/2006/main"),
+    ("pic", "http://schemas.openxmlformats.org/drawingml/2006/picture"),
+    ("asvg", "http://schemas.microsoft.com/office/drawing/2016/SVG/main"),
+];
+
+const NS_W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const NS_RELS: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+const WML: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml";
+
+/// The font family used for source code.
+const MONOSPACE: &str = "DejaVu Sans Mono";
+
+/// The format of an embedded media file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MediaFormat {
+    Png,
+    Jpg,
+    Gif,
+    Svg,
+}
+
+impl MediaFormat {
+    /// The file extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpg => "jpeg",
+            Self::Gif => "gif",
+            Self::Svg => "svg",
+        }
+    }
+
+    /// The MIME type of the format.
+    fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpg => "image/jpeg",
+            Self::Gif => "image/gif",
+            Self::Svg => "image/svg+xml",
+        }
+    }
+}
+
+/// Creates a writer for an XML part.
+pub fn writer() -> XmlWriter {
+    XmlWriter::new(xmlwriter::Options {
+        indent: xmlwriter::Indent::None,
+        attributes_indent: xmlwriter::Indent::None,
+        use_single_quote: false,
+    })
+}
+
+/// Declares the namespaces of the document and footnotes parts on the element
+/// that was just started.
+pub fn write_namespaces(xml: &mut XmlWriter) {
+    for (prefix, uri) in NAMESPACES {
+        xml.write_attribute(&format!("xmlns:{prefix}"), uri);
+    }
+}
+
+/// Writes the DOCX package.
+///
+/// All timestamps are fixed to keep the output reproducible.
+pub fn write(document: &DocxDocument, parts: &Parts) -> StrResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default()
+        .last_modified_time(zip::DateTime::default())
+        .compression_method(CompressionMethod::Deflated);
+
+    let mut add = |name: &str, data: &[u8]| {
+        zip.start_file(name, options).map_err(failed)?;
+        zip.write_all(data).map_err(failed)
+    };
+
+    let fixed = [
+        ("rIdStyles", "styles", "styles.xml"),
+        ("rIdNumbering", "numbering", "numbering.xml"),
+        ("rIdFootnotes", "footnotes", "footnotes.xml"),
+        ("rIdSettings", "settings", "settings.xml"),
+    ];
+
+    add("[Content_Types].xml", content_types(parts).as_bytes())?;
+    add("_rels/.rels", package_rels().as_bytes())?;
+    add("docProps/core.xml", core(document).as_bytes())?;
+    add("word/document.xml", parts.document.as_bytes())?;
+    add(
+        "word/_rels/document.xml.rels",
+        part_rels(&fixed, &parts.document_rels).as_bytes(),
+    )?;
+    add("word/styles.xml", styles(document).as_bytes())?;
+    add("word/numbering.xml", numbering(parts).as_bytes())?;
+    add("word/settings.xml", settings().as_bytes())?;
+    add("word/footnotes.xml", parts.footnotes.as_bytes())?;
+    if !parts.footnote_rels.is_empty() {
+        add(
+            "word/_rels/footnotes.xml.rels",
+            part_rels(&[], &parts.footnote_rels).as_bytes(),
+        )?;
+    }
+    for media in &parts.media {
+        add(&format!("word/{}", media.path), &media.data)?;
+    }
+
+    Ok(zip.finish().map_err(failed)?.into_inner())
+}
+
+/// Writes the content types of the package's parts.
+fn content_types(parts: &Parts) -> String {
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("Types");
+    xml.write_attribute(
+        "xmlns",
+        "http://schemas.openxmlformats.org/package/2006/content-types",
+    );
+
+    let mut defaults = vec![
+        ("rels", "application/vnd.openxmlformats-package.relationships+xml"),
+        ("xml", "application/xml"),
+    ];
+    for media in &parts.media {
+        let default = (media.format.extension(), media.format.mime_type());
+        if !defaults.contains(&default) {
+            defaults.push(default);
+        }
+    }
+    for (extension, content_type) in defaults {
+        xml.start_element("Default");
+        xml.write_attribute("Extension", extension);
+        xml.write_attribute("ContentType", content_type);
+        xml.end_element();
+    }
+
+    let overrides = [
+        ("/word/document.xml", "document.main"),
+        ("/word/styles.xml", "styles"),
+        ("/word/numbering.xml", "numbering"),
+        ("/word/settings.xml", "settings"),
+        ("/word/footnotes.xml", "footnotes"),
+    ];
+    for (part, kind) in overrides {
+        xml.start_element("Override");
+        xml.write_attribute("PartName", part);
+        xml.write_attribute("ContentType", &format!("{WML}.{kind}+xml"));
+        xml.end_element();
+    }
+    xml.start_element("Override");
+    xml.write_attribute("PartName", "/docProps/core.xml");
+    xml.write_attribute(
+        "ContentType",
+        "application/vnd.openxmlformats-package.core-properties+xml",
+    );
+    xml.end_element();
+
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Writes the relationships of the package to its main parts.
+fn package_rels() -> String {
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("Relationships");
+    xml.write_attribute("xmlns", NS_RELS);
+    write_rel(
+        &mut xml,
+        "rIdDocument",
+        &format!("{REL}/officeDocument"),
+        "word/document.xml",
+    );
+    write_rel(
+        &mut xml,
+        "rIdCore",
+        "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties",
+        "docProps/core.xml",
+    );
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Writes the relationships of a part, consisting of fixed ones to other
+/// parts and those to images and hyperlinks.
+fn part_rels(fixed: &[(&str, &str, &str)], rels: &[Relationship]) -> String {
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("Relationships");
+    xml.write_attribute("xmlns", NS_RELS);
+    for (id, kind, target) in fixed {
+        write_rel(&mut xml, id, &format!("{REL}/{kind}"), target);
+    }
+    for rel in rels {
+        xml.start_element("Relationship");
+        xml.write_attribute("Id", &rel.id);
+        if rel.external {
+            xml.write_attribute("Type", &format!("{REL}/hyperlink"));
+            xml.write_attribute("Target", &rel.target);
+            xml.write_attribute("TargetMode", "External");
+        } else {
+            xml.write_attribute("Type", &format!("{REL}/image"));
+            xml.write_attribute("Target", &rel.target);
+        }
+        xml.end_element();
+    }
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Writes a relationship to another part of the package.
+fn write_rel(xml: &mut XmlWriter, id: &str, kind: &str, target: &str) {
+    xml.start_element("Relationship");
+    xml.write_attribute("Id", id);
+    xml.write_attribute("Type", kind);
+    xml.write_attribute("Target", target);
+    xml.end_element();
+}
+
+/// Writes the document's metadata.
+fn core(document: &DocxDocument) -> String {
+    let info = document.html().info();
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("cp:coreProperties");
+    xml.write_attribute(
+        "xmlns:cp",
+        "http://schemas.openxmlformats.org/package/2006/metadata/core-properties",
+    );
+    xml.write_attribute("xmlns:dc", "http://purl.org/dc/elements/1.1/");
+    xml.write_attribute("xmlns:dcterms", "http://purl.org/dc/terms/");
+    xml.write_attribute("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance");
+
+    let mut write = |name: &str, value: &str| {
+        xml.start_element(name);
+        xml.write_text(value);
+        xml.end_element();
+    };
+
+    if let Some(title) = &info.title {
+        write("dc:title", title);
+    }
+    if !info.author.is_empty() {
+        write("dc:creator", &info.author.join(", "));
+    }
+    if let Some(description) = &info.description {
+        write("dc:description", description);
+    }
+    if !info.keywords.is_empty() {
+        write("cp:keywords", &info.keywords.join(", "));
+    }
+    write("dc:language", &info.locale.unwrap_or_default().rfc_3066());
+
+    if let Smart::Custom(Some(date)) = info.date {
+        xml.start_element("dcterms:created");
+        xml.write_attribute("xsi:type", "dcterms:W3CDTF");
+        xml.write_text(&format_date(date));
+        xml.end_element();
+    }
+
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Formats a date in the W3CDTF format of the core properties.
+fn format_date(date: Datetime) -> EcoString {
+    eco_format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        date.year().unwrap_or(1970),
+        date.month().unwrap_or(1),
+        date.day().unwrap_or(1),
+        date.hour().unwrap_or(0),
+        date.minute().unwrap_or(0),
+        date.second().unwrap_or(0),
+    )
+}
+
+/// Writes the styles part.
+///
+/// The default text style comes from the document's set rules. The other
+/// styles are derived from it, roughly following Typst's defaults.
+fn styles(document: &DocxDocument) -> String {
+    let text = &document.text;
+    let lang = document.html().info().locale.unwrap_or_default().rfc_3066();
+
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("w:styles");
+    xml.write_attribute("xmlns:w", NS_W);
+
+    xml.start_element("w:docDefaults");
+    xml.start_element("w:rPrDefault");
+    xml.start_element("w:rPr");
+    if let Some(font) = &text.font {
+        write_fonts(&mut xml, font);
+    }
+    write_val(&mut xml, "w:sz", half_points(text.size));
+    write_val(&mut xml, "w:szCs", half_points(text.size));
+    xml.start_element("w:lang");
+    xml.write_attribute("w:val", &lang);
+    xml.end_element();
+    xml.end_element();
+    xml.end_element();
+    xml.start_element("w:pPrDefault");
+    xml.start_element("w:pPr");
+    xml.start_element("w:spacing");
+    xml.write_attribute("w:after", &twips(text.spacing));
+    xml.end_element();
+    if text.justify {
+        write_val(&mut xml, "w:jc", "both");
+    }
+    xml.end_element();
+    xml.end_element();
+    xml.end_element();
+
+    let style = Style::paragraph;
+    let heading = |id, name, scale: f64| Style {
+        bold: true,
+        size: Some(text.size * scale),
+        ..style(id, name)
+    };
+
+    let mut styles = vec![
+        Style { default: true, ..style("Normal", "Normal") },
+        Style {
+            keep_next: true,
+            outline: None,
+            ..heading("Title", "Title", 1.7)
+        },
+        Style {
+            outline: Some(0),
+            ..heading("Heading1", "heading 1", 1.4)
+        },
+        Style {
+            outline: Some(1),
+            ..heading("Heading2", "heading 2", 1.2)
+        },
+        Style {
+            outline: Some(2),
+            ..heading("Heading3", "heading 3", 1.0)
+        },
+        Style {
+            outline: Some(3),
+            ..heading("Heading4", "heading 4", 1.0)
+        },
+        Style {
+            outline: Some(4),
+            ..heading("Heading5", "heading 5", 1.0)
+        },
+        Style {
+            outline: Some(5),
+            ..heading("Heading6", "heading 6", 1.0)
+        },
+        Style {
+            outline: Some(6),
+            ..heading("Heading7", "heading 7", 1.0)
+        },
+        Style {
+            outline: Some(7),
+            ..heading("Heading8", "heading 8", 1.0)
+        },
+        Style {
+            outline: Some(8),
+            ..heading("Heading9", "heading 9", 1.0)
+        },
+        Style { indent: Some(720), ..style("Quote", "Quote") },
+        Style { center: true, ..style("Caption", "caption") },
+        Style {
+            after: Some(Abs::zero()),
+            ..style("ListParagraph", "List Paragraph")
+        },
+        Style {
+            size: Some(text.size * 0.8),
+            after: Some(Abs::zero()),
+            ..style("FootnoteText", "footnote text")
+        },
+        style("Bibliography", "Bibliography"),
+        Style {
+            font: Some(MONOSPACE),
+            size: Some(text.size * 0.8),
+            after: Some(Abs::zero()),
+            ..style("SourceCode", "Source Code")
+        },
+    ];
+    for level in 1..=9 {
+        styles.push(Style {
+            id: TOC_IDS[level - 1],
+            name: TOC_NAMES[level - 1],
+            indent: Some(240 * (level - 1)),
+            after: Some(Abs::zero()),
+            ..style("", "")
+        });
+    }
+    styles.extend([
+        Style {
+            color: Some("0563C1"),
+            underline: true,
+            ..Style::character("Hyperlink", "Hyperlink")
+        },
+        Style {
+            superscript: true,
+            ..Style::character("FootnoteReference", "footnote reference")
+        },
+        Style {
+            font: Some(MONOSPACE),
+            ..Style::character("VerbatimChar", "Verbatim Char")
+        },
+    ]);
+
+    for style in &styles {
+        style.write(&mut xml);
+    }
+    write_table_style(&mut xml);
+
+    xml.end_element();
+    xml.end_document()
+}
+
+const TOC_IDS: [&str; 9] =
+    ["TOC1", "TOC2", "TOC3", "TOC4", "TOC5", "TOC6", "TOC7", "TOC8", "TOC9"];
+const TOC_NAMES: [&str; 9] =
+    ["toc 1", "toc 2", "toc 3", "toc 4", "toc 5", "toc 6", "toc 7", "toc 8", "toc 9"];
+
+/// A paragraph or character style.
+#[derive(Debug, Default, Clone)]
+struct Style {
+    id: &'static str,
+    name: &'static str,
+    /// Whether this is a character style rather than a paragraph style.
+    character: bool,
+    /// Whether this is the default paragraph style.
+    default: bool,
+    bold: bool,
+    underline: bool,
+    superscript: bool,
+    center: bool,
+    keep_next: bool,
+    font: Option<&'static str>,
+    size: Option<Abs>,
+    color: Option<&'static str>,
+    /// The left indent, in twips.
+    indent: Option<usize>,
+    /// The spacing after paragraphs.
+    after: Option<Abs>,
+    /// The outline level, starting at zero.
+    outline: Option<usize>,
+}
+
+impl Style {
+    /// A paragraph style based on the default one.
+    fn paragraph(id: &'static str, name: &'static str) -> Self {
+        Self { id, name, ..Self::default() }
+    }
+
+    /// A character style.
+    fn character(id: &'static str, name: &'static str) -> Self {
+        Self { id, name, character: true, ..Self::default() }
+    }
+
+    /// Writes the style's definition.
+    fn write(&self, xml: &mut XmlWriter) {
+        xml.start_element("w:style");
+        xml.write_attribute(
+            "w:type",
+            if self.character { "character" } else { "paragraph" },
+        );
+        if self.default {
+            xml.write_attribute("w:default", "1");
+        }
+        xml.write_attribute("w:styleId", self.id);
+        write_val(xml, "w:name", self.name);
+        if !self.default && !self.character {
+            write_val(xml, "w:basedOn", "Normal");
+            write_val(xml, "w:next", "Normal");
+        }
+        xml.start_element("w:qFormat");
+        xml.end_element();
+
+        if !self.character
+            && (self.keep_next
+                || self.outline.is_some()
+                || self.after.is_some()
+                || self.indent.is_some()
+                || self.center)
+        {
+            xml.start_element("w:pPr");
+            if self.keep_next || self.outline.is_some() {
+                xml.start_element("w:keepNext");
+                xml.end_element();
+            }
+            if let Some(after) = self.after {
+                xml.start_element("w:spacing");
+                xml.write_attribute("w:after", &twips(after));
+                xml.end_element();
+            }
+            if let Some(indent) = self.indent {
+                xml.start_element("w:ind");
+                xml.write_attribute("w:left", &indent);
+                xml.end_element();
+            }
+            if self.center {
+                write_val(xml, "w:jc", "center");
+            }
+            if let Some(level) = self.outline {
+                write_val(xml, "w:outlineLvl", level);
+            }
+            xml.end_element();
+        }
+
+        if self.bold
+            || self.underline
+            || self.superscript
+            || self.font.is_some()
+            || self.size.is_some()
+            || self.color.is_some()
+        {
+            xml.start_element("w:rPr");
+            if let Some(font) = self.font {
+                write_fonts(xml, font);
+            }
+            if self.bold {
+                xml.start_element("w:b");
+                xml.end_element();
+            }
+            if let Some(color) = self.color {
+                write_val(xml, "w:color", color);
+            }
+            if let Some(size) = self.size {
+                write_val(xml, "w:sz", half_points(size));
+                write_val(xml, "w:szCs", half_points(size));
+            }
+            if self.underline {
+                write_val(xml, "w:u", "single");
+            }
+            if self.superscript {
+                write_val(xml, "w:vertAlign", "superscript");
+            }
+            xml.end_element();
+        }
+
+        xml.end_element();
+    }
+}
+
+/// Writes the style of tables, which have a thin grid.
+fn write_table_style(xml: &mut XmlWriter) {
+    xml.start_element("w:style");
+    xml.write_attribute("w:type", "table");
+    xml.write_attribute("w:styleId", "TableGrid");
+    write_val(xml, "w:name", "Table Grid");
+    xml.start_element("w:pPr");
+    xml.start_element("w:spacing");
+    xml.write_attribute("w:after", "0");
+    xml.end_element();
+    xml.end_element();
+    xml.start_element("w:tblPr");
+    xml.start_element("w:tblBorders");
+    for side in ["top", "left", "bottom", "right", "insideH", "insideV"] {
+        xml.start_element(&format!("w:{side}"));
+        xml.write_attribute("w:val", "single");
+        xml.write_attribute("w:sz", "4");
+        xml.write_attribute("w:space", "0");
+        xml.write_attribute("w:color", "auto");
+        xml.end_element();
+    }
+    xml.end_element();
+    xml.start_element("w:tblCellMar");
+    for side in ["left", "right"] {
+        xml.start_element(&format!("w:{side}"));
+        xml.write_attribute("w:w", "108");
+        xml.write_attribute("w:type", "dxa");
+        xml.end_element();
+    }
+    xml.end_element();
+    xml.end_element();
+    xml.end_element();
+}
+
+/// Writes the numbering part.
+///
+/// There is one abstract definition for bulleted and one for numbered lists.
+/// Each list refers to one of them through its own numbering instance.
+fn numbering(parts: &Parts) -> String {
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("w:numbering");
+    xml.write_attribute("xmlns:w", NS_W);
+
+    for (id, ordered) in [(0, false), (1, true)] {
+        xml.start_element("w:abstractNum");
+        xml.write_attribute("w:abstractNumId", &id);
+        write_val(&mut xml, "w:multiLevelType", "multilevel");
+        for level in 0..9 {
+            xml.start_element("w:lvl");
+            xml.write_attribute("w:ilvl", &level);
+            write_val(&mut xml, "w:start", 1);
+            if ordered {
+                write_val(&mut xml, "w:numFmt", "decimal");
+                write_val(&mut xml, "w:lvlText", format!("%{}.", level + 1));
+            } else {
+                write_val(&mut xml, "w:numFmt", "bullet");
+                write_val(&mut xml, "w:lvlText", ["•", "‣", "–"][level % 3]);
+            }
+            write_val(&mut xml, "w:lvlJc", "left");
+            xml.start_element("w:pPr");
+            xml.start_element("w:ind");
+            xml.write_attribute("w:left", &(720 * (level + 1)));
+            xml.write_attribute("w:hanging", "360");
+            xml.end_element();
+            xml.end_element();
+            xml.end_element();
+        }
+        xml.end_element();
+    }
+
+    for (i, list) in parts.lists.iter().enumerate() {
+        xml.start_element("w:num");
+        xml.write_attribute("w:numId", &(i + 1));
+        write_val(&mut xml, "w:abstractNumId", usize::from(list.ordered));
+        xml.start_element("w:lvlOverride");
+        xml.write_attribute("w:ilvl", &list.level);
+        write_val(&mut xml, "w:startOverride", list.start);
+        xml.end_element();
+        xml.end_element();
+    }
+
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Writes the settings part.
+fn settings() -> String {
+    let mut xml = writer();
+    xml.write_declaration();
+    xml.start_element("w:settings");
+    xml.write_attribute("xmlns:w", NS_W);
+    write_val(&mut xml, "w:defaultTabStop", 720);
+    xml.start_element("w:footnotePr");
+    for id in [-1, 0] {
+        xml.start_element("w:footnote");
+        xml.write_attribute("w:id", &id);
+        xml.end_element();
+    }
+    xml.end_element();
+    xml.start_element("w:compat");
+    xml.start_element("w:compatSetting");
+    xml.write_attribute("w:name", "compatibilityMode");
+    xml.write_attribute("w:uri", "http://schemas.microsoft.com/office/word");
+    xml.write_attribute("w:val", "15");
+    xml.end_element();
+    xml.end_element();
+    xml.end_element();
+    xml.end_document()
+}
+
+/// Writes the font family of a run for all scripts.
+fn write_fonts(xml: &mut XmlWriter, font: &str) {
+    xml.start_element("w:rFonts");
+    for script in ["w:ascii", "w:hAnsi", "w:eastAsia", "w:cs"] {
+        xml.write_attribute(script, font);
+    }
+    xml.end_element();
+}
+
+/// Writes an element with a single `w:val` attribute.
+fn write_val(xml: &mut XmlWriter, name: &str, val: impl Display) {
+    xml.start_element(name);
+    xml.write_attribute("w:val", &val);
+    xml.end_element();
+}
+
+/// Converts a font size into half-points.
+fn half_points(abs: Abs) -> i64 {
+    (abs.to_pt() * 2.0).round() as i64
+}
+
+/// Converts a length into twentieths of a point.
+pub fn twips(abs: Abs) -> i64 {
+    (abs.to_pt() * 20.0).round() as i64
+}
+
+/// The error message for a failure while writing the package.
+#[cold]
+fn failed(err: impl Display) -> EcoString {
+    eco_format!("failed to write DOCX file ({err})")
+}
@@ -1,5 +1,5 @@
 pub use encode::{Properties, ToCss};
-pub use resolve::resolve_inline_styles;
+pub use resolve::{HtmlStyleMode, Stylesheet, resolve_attrs};
 
 mod encode;
 mod resolve;
@@ -1,38 +1,158 @@
-use ecow::eco_format;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write;
 
-use crate::{HtmlElement, HtmlNode, attr};
+use ecow::{EcoString, eco_format};
 
-/// Turns CSS properties on all elements in the DOM into inline `style`
-/// attributes.
+use crate::css::Properties;
+use crate::{HtmlAttrs, HtmlElement, HtmlNode, attr};
+
+/// How CSS properties that Typst generates are written.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HtmlStyleMode {
+    /// As inline `style` attributes on each element.
+    #[default]
+    Inline,
+    /// As classes that are defined in a generated stylesheet.
+    ///
+    /// Elements with the same properties share a class, which keeps pages
+    /// small and lets site authors override the styles.
+    Classes,
+}
+
+/// Determines the attributes of an element with its CSS properties applied.
+///
+/// Depending on the mode, the properties are either prepended to the `style`
+/// attribute or referenced through a generated class.
+pub fn resolve_attrs(elem: &HtmlElement, mode: HtmlStyleMode) -> Cow<'_, HtmlAttrs> {
+    if elem.css.is_empty() {
+        return Cow::Borrowed(&elem.attrs);
+    }
+
+    let mut attrs = elem.attrs.clone();
+    match mode {
+        HtmlStyleMode::Inline => {
+            // TODO: Use to_eco_string once merged:
+            // https://github.com/typst/ecow/pull/60
+            let mut generated = eco_format!("{}", elem.css.to_inline());
+            if let Some(style) = attrs.get_mut(attr::style) {
+                if !style.is_empty() {
+                    generated.push_str("; ");
+                }
+                // TODO: Use insert_str once merged:
+                // https://github.com/typst/ecow/pull/59
+                generated.push_str(style);
+                *style = generated;
+            } else {
+                attrs.push(attr::style, generated);
+            }
+        }
+        HtmlStyleMode::Classes => {
+            let name = class_name(&elem.css);
+            if let Some(class) = attrs.get_mut(attr::class) {
+                if !class.is_empty() {
+                    class.push(' ');
+                }
+                class.push_str(&name);
+            } else {
+                attrs.push(attr::class, name);
+            }
+        }
+    }
+
+    Cow::Owned(attrs)
+}
+
+/// A stylesheet with generated classes for CSS properties.
 ///
-/// This by itself would not need a separate pass over the DOM, but it will be
-/// supplanted by more advanced CSS handling, so it makes sense to already
-/// organize the code like this.
-pub fn resolve_inline_styles(root: &mut HtmlElement) {
-    visit_elem(root);
+/// Class names are derived from the properties, so they are the same across
+/// all documents of a bundle and across compilations.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct Stylesheet {
+    rules: BTreeMap<EcoString, Properties>,
 }
 
-fn visit_elem(elem: &mut HtmlElement) {
-    if !elem.css.is_empty() {
-        // TODO: Use to_eco_string once merged:
-        // https://github.com/typst/ecow/pull/60
-        let mut generated = eco_format!("{}", elem.css.to_inline());
-        if let Some(style) = elem.attrs.get_mut(attr::style) {
-            if !style.is_empty() {
-                generated.push_str("; ");
+impl Stylesheet {
+    /// Creates an empty stylesheet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds classes for the CSS properties of all elements in the DOM.
+    pub fn collect(&mut self, root: &HtmlElement) {
+        if !root.css.is_empty() {
+            self.rules
+                .entry(class_name(&root.css))
+                .or_insert_with(|| root.css.clone());
+        }
+
+        for child in &root.children {
+            if let HtmlNode::Element(elem) = child {
+                self.collect(elem);
             }
-            // TODO: Use insert_str once merged:
-            // https://github.com/typst/ecow/pull/59
-            generated.push_str(style);
-            *style = generated;
-        } else {
-            elem.attrs.push(attr::style, generated);
         }
     }
 
-    for child in elem.children.make_mut().iter_mut() {
-        if let HtmlNode::Element(elem) = child {
-            visit_elem(elem);
+    /// Whether the stylesheet has no classes.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Encodes the stylesheet as CSS, with one rule per line.
+    pub fn to_css(&self) -> String {
+        let mut css = String::new();
+        for (name, props) in &self.rules {
+            writeln!(css, ".{name} {{ {}; }}", props.to_inline()).unwrap();
         }
+        css
+    }
+}
+
+/// The generated class name for a set of CSS properties.
+///
+/// Uses the full 128-bit hash so that distinct property sets practically
+/// never share a class.
+fn class_name(props: &Properties) -> EcoString {
+    eco_format!("typst-{:032x}", typst_utils::hash128(props))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag;
+
+    #[test]
+    fn test_resolve_attrs() {
+        let css = Properties::new().with("display", "block");
+        let elem = HtmlElement::new(tag::div)
+            .with_attr(attr::class, "note")
+            .with_attr(attr::style, "color: red")
+            .with_css(css.clone());
+
+        let inline = resolve_attrs(&elem, HtmlStyleMode::Inline);
+        assert_eq!(inline.get(attr::style).unwrap(), "display: block; color: red");
+        assert_eq!(inline.get(attr::class).unwrap(), "note");
+
+        let classes = resolve_attrs(&elem, HtmlStyleMode::Classes);
+        let name = class_name(&css);
+        assert_eq!(classes.get(attr::style).unwrap(), "color: red");
+        assert_eq!(*classes.get(attr::class).unwrap(), eco_format!("note {name}"));
+
+        let mut sheet = Stylesheet::new();
+        sheet.collect(
+            &HtmlElement::new(tag::body)
+                .with_children(ecow::eco_vec![elem.clone().into(), elem.into(),]),
+        );
+        assert_eq!(sheet.to_css(), format!(".{name} {{ display: block; }}\n"));
+    }
+
+    #[test]
+    fn test_class_name() {
+        let block = Properties::new().with("display", "block");
+        let inline = Properties::new().with("display", "inline");
+        let name = class_name(&block);
+        assert_eq!(name.len(), "typst-".len() + 32);
+        assert_eq!(name, class_name(&block.clone()));
+        assert_ne!(name, class_name(&inline));
     }
 }
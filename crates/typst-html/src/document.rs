@@ -1,8 +1,10 @@
+use std::sync::Arc;
+
 use comemo::{Track, Tracked, TrackedMut};
 use ecow::{EcoVec, eco_vec};
 use typst_library::diag::{SourceResult, bail, error};
 use typst_library::engine::{Engine, Route, Sink, Traced};
-use typst_library::foundations::{Content, NativeElement, StyleChain, Styles};
+use typst_library::foundations::{Content, NativeElement, Selector, StyleChain, Styles};
 use typst_library::introspection::{
     Introspector, Locator, LocatorLink, QueryIntrospection,
 };
@@ -15,7 +17,7 @@ use typst_utils::{LazyHash, Protected};
 
 use crate::convert::{ConversionLevel, Whitespace};
 use crate::mathml::EQUATION_CSS_STYLES;
-use crate::{HtmlDocument, HtmlElement, HtmlNode, attr, css, tag};
+use crate::{HtmlDocument, HtmlElement, HtmlNode, attr, tag};
 
 /// Produce an HTML document from content.
 ///
@@ -185,24 +187,27 @@ fn html_document_common(
         StyleChain::new(&Styles::root(&children, styles)),
     )?;
 
-    // Since `finalize_dom` might have inserted more DOM nodes that have styles,
-    // the styles must be resolved last.
-    css::resolve_inline_styles(output.root_mut());
-
     let has_equations = !engine
         .introspect(QueryIntrospection(EquationElem::ELEM.select(), Span::detached()))
         .is_empty();
 
     if has_equations {
         let root = output.root_mut();
+        let span = root.span;
 
+        // The styles are also placed into the head for fragment export, which
+        // extracts them from there.
         let head = root.children.make_mut().iter_mut().find_map(|node| match node {
             HtmlNode::Element(elem) if elem.tag == tag::head => Some(elem),
             _ => None,
         });
 
-        // TODO: this becomes an error when html fragments are supported
-        let head = head.expect("head to be present in document output");
+        let Some(head) = head else {
+            bail!(
+                span, "equations require a `<head>` element";
+                hint: "add a `head` element to the custom `html` element";
+            );
+        };
 
         head.children.push(
             HtmlElement::new(tag::style)
@@ -250,6 +255,12 @@ impl HtmlOutput {
     pub fn root_node(&self) -> &HtmlNode {
         &self.nodes[self.root_index]
     }
+
+    /// Creates output that consists of just a root element.
+    #[cfg(test)]
+    pub(crate) fn from_root(root: HtmlElement) -> Self {
+        Self { nodes: eco_vec![root.into()], root_index: 0 }
+    }
 }
 
 /// Wrap the user generated HTML in `<html>`, `<body>` or both if needed.
@@ -271,11 +282,11 @@ fn finalize_dom(
         let tag = elem.tag;
         match (tag, count) {
             (tag::html, 1) => {
-                footnotes_unsupported_with_custom_dom(engine)?;
+                unlisted_footnotes_with_custom_dom(engine)?;
                 return Ok(HtmlOutput { nodes, root_index: idx });
             }
             (tag::body, 1) => {
-                footnotes_unsupported_with_custom_dom(engine)?;
+                unlisted_footnotes_with_custom_dom(engine)?;
                 needs_body = false;
             }
             (tag::html | tag::body, _) => bail!(
@@ -361,11 +372,28 @@ fn head_element(info: &DocumentInfo) -> HtmlElement {
     HtmlElement::new(tag::head).with_children(children)
 }
 
-/// Fails with an error if there are footnotes.
-fn footnotes_unsupported_with_custom_dom(engine: &mut Engine) -> SourceResult<()> {
-    let markers = engine
-        .introspect(QueryIntrospection(FootnoteMarker::ELEM.select(), Span::detached()));
+/// Fails with an error if there are footnotes that are not listed by any
+/// `footnote.entries` element.
+///
+/// With a custom `<html>` or `<body>` element, we cannot add the footnote
+/// listing ourselves, so footnotes after the last user-placed listing would
+/// go missing.
+fn unlisted_footnotes_with_custom_dom(engine: &mut Engine) -> SourceResult<()> {
+    let containers = engine.introspect(QueryIntrospection(
+        FootnoteContainer::ELEM.select(),
+        Span::detached(),
+    ));
+
+    let mut selector = FootnoteMarker::ELEM.select();
+    if let Some(last) = containers.last().and_then(|container| container.location()) {
+        selector = Selector::After {
+            selector: Arc::new(selector),
+            start: Arc::new(last.into()),
+            inclusive: false,
+        };
+    }
 
+    let markers = engine.introspect(QueryIntrospection(selector, Span::detached()));
     if markers.is_empty() {
         return Ok(());
     }
@@ -375,9 +403,11 @@ fn footnotes_unsupported_with_custom_dom(engine: &mut Engine) -> SourceResult<()
         .map(|marker| {
             error!(
                 marker.span(),
-                "footnotes are not currently supported in combination \
-                 with a custom `<html>` or `<body>` element";
-                hint: "you can still use footnotes with a custom footnote show rule";
+                "footnote is not listed anywhere in the custom `<html>` or \
+                 `<body>` element";
+                hint: "use `footnote.entries()` within the custom element to \
+                       list the footnotes";
+                hint: "you can also use footnotes with a custom footnote show rule";
             )
         })
         .collect())
@@ -1,12 +1,15 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 
 use comemo::{Track, Tracked};
-use ecow::{EcoString, eco_format};
+use ecow::{EcoString, eco_format, eco_vec};
 use typst_library::diag::{At, SourceResult, StrResult, bail};
 use typst_library::foundations::Repr;
 use typst_library::model::LateLinkResolver;
 use typst_syntax::Span;
 
+use crate::css::{self, HtmlStyleMode, Stylesheet};
+use crate::profile::{HtmlProfile, apply_profile};
 use crate::{
     HtmlDocument, HtmlElement, HtmlFrame, HtmlNode, HtmlTag, attr, charsets, property,
     tag,
@@ -17,30 +20,213 @@ use crate::{
 pub struct HtmlOptions {
     /// Whether to format the HTML in a human-readable way.
     pub pretty: bool,
+    /// Whether to only output the contents of the `<body>` element instead of
+    /// a full document, for embedding into an existing page.
+    ///
+    /// Styles that the contents require, for example for equations, are not
+    /// part of the output then. Use [`html_fragment`] to retrieve them.
+    pub fragment: bool,
+    /// How to write the CSS properties that Typst generates.
+    ///
+    /// With [`HtmlStyleMode::Classes`], the generated stylesheet is placed
+    /// into the document's `<head>`, or, for fragments, returned through
+    /// [`html_fragment`].
+    pub styles: HtmlStyleMode,
+    /// Which kind of markup to emit.
+    ///
+    /// With [`HtmlProfile::Semantic`], generated styles are omitted
+    /// regardless of [`styles`](Self::styles).
+    pub profile: HtmlProfile,
+    /// Whether to use the XML serialization of HTML (XHTML), as required by
+    /// formats like EPUB.
+    ///
+    /// Void elements are then self-closing, attributes are never shortened,
+    /// and the `<html>` and `<math>` elements declare their namespaces.
+    pub xhtml: bool,
+}
+
+/// An HTML document encoded as a fragment for embedding into a host page.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct HtmlFragment {
+    /// The contents of the document's `<body>` element, without the
+    /// footnotes.
+    pub body: String,
+    /// The section with the document's footnotes, if there are any.
+    pub footnotes: Option<String>,
+    /// The CSS that the body and footnotes require. The host page should
+    /// include it in a `<style>` element or stylesheet.
+    pub styles: String,
 }
 
 /// Encodes an HTML document into a string.
 pub fn html(document: &HtmlDocument, options: &HtmlOptions) -> SourceResult<String> {
     let link_resolver = LateLinkResolver::new(None, document.introspector().as_ref());
-    let w = Writer::new(link_resolver.track(), options.pretty);
-    html_impl(w, document.root())
+    let w = Writer::new(link_resolver.track(), options);
+    let root = apply_profile(document.root(), options.profile);
+    if options.fragment {
+        body_impl(w, &root)
+    } else {
+        html_impl(w, with_stylesheet(&root, options, None)?.as_ref())
+    }
+}
+
+/// Encodes an HTML document into a fragment, with the footnotes and required
+/// styles separated from the body's contents.
+///
+/// This ignores [`HtmlOptions::fragment`].
+pub fn html_fragment(
+    document: &HtmlDocument,
+    options: &HtmlOptions,
+) -> SourceResult<HtmlFragment> {
+    let link_resolver = LateLinkResolver::new(None, document.introspector().as_ref());
+    let root = apply_profile(document.root(), options.profile);
+    let root = root.as_ref();
+    let body = find_child(root, tag::body)?;
+
+    let (notes, contents): (Vec<_>, Vec<_>) =
+        body.children.iter().cloned().partition(|node| {
+            matches!(node, HtmlNode::Element(elem)
+                if elem.attrs.get(attr::role).is_some_and(|role| role == "doc-endnotes"))
+        });
+
+    let mut w = Writer::new(link_resolver.track(), options);
+    write_fragment(&mut w, &contents, body.pre_span)?;
+    let body_html = w.buf;
+
+    let footnotes = if notes.is_empty() {
+        None
+    } else {
+        let mut w = Writer::new(link_resolver.track(), options);
+        write_fragment(&mut w, &notes, body.pre_span)?;
+        Some(w.buf)
+    };
+
+    let mut styles = String::new();
+    if let Ok(head) = find_child(root, tag::head) {
+        for node in &head.children {
+            if let HtmlNode::Element(elem) = node
+                && elem.tag == tag::style
+            {
+                styles.push_str(&collect_raw_text(elem)?);
+                styles.push('\n');
+            }
+        }
+    }
+
+    if options.styles == HtmlStyleMode::Classes {
+        let mut sheet = Stylesheet::new();
+        sheet.collect(root);
+        styles.push_str(&sheet.to_css());
+    }
+
+    Ok(HtmlFragment { body: body_html, footnotes, styles })
 }
 
 /// Encodes an HTML root element into a string as part of a bundle.
 ///
 /// See `export_html` in `typst-bundle` for more details on why this takes the
 /// root element instead of the document.
+///
+/// The `stylesheet` is the relative URL of a shared stylesheet for the whole
+/// bundle, which is then linked to instead of embedding the document's own
+/// stylesheet.
 pub fn html_in_bundle(
     root: &HtmlElement,
     options: &HtmlOptions,
     link_resolver: Tracked<LateLinkResolver>,
+    stylesheet: Option<&str>,
+) -> SourceResult<String> {
+    let w = Writer::new(link_resolver, options);
+    let root = apply_profile(root, options.profile);
+    if options.fragment {
+        body_impl(w, &root)
+    } else {
+        html_impl(w, with_stylesheet(&root, options, stylesheet)?.as_ref())
+    }
+}
+
+/// Prepares the root element of a document for encoding with [`html`], by
+/// applying the profile and adding the generated stylesheet.
+pub(crate) fn prepare_root(
+    document: &HtmlDocument,
+    options: &HtmlOptions,
+) -> SourceResult<HtmlElement> {
+    let root = apply_profile(document.root(), options.profile);
+    if options.fragment {
+        return Ok(root.into_owned());
+    }
+    Ok(with_stylesheet(&root, options, None)?.into_owned())
+}
+
+/// Encodes an element of a document prepared with [`prepare_root`] in the
+/// same way as [`html`] does. If `inner` is true, only the element's children
+/// are encoded.
+pub(crate) fn encode_element(
+    document: &HtmlDocument,
+    element: &HtmlElement,
+    options: &HtmlOptions,
+    inner: bool,
 ) -> SourceResult<String> {
-    let w = Writer::new(link_resolver, options.pretty);
-    html_impl(w, root)
+    let link_resolver = LateLinkResolver::new(None, document.introspector().as_ref());
+    let mut w = Writer::new(link_resolver.track(), options);
+    if inner {
+        write_fragment(&mut w, &element.children, element.pre_span)?;
+    } else {
+        write_element(&mut w, element)?;
+    }
+    Ok(w.buf)
+}
+
+/// Adds a `<link>` to the given stylesheet URL to the root's `<head>` element.
+/// Without a URL, the generated stylesheet is added inline if the options ask
+/// for classes.
+fn with_stylesheet<'a>(
+    root: &'a HtmlElement,
+    options: &HtmlOptions,
+    href: Option<&str>,
+) -> SourceResult<Cow<'a, HtmlElement>> {
+    let elem = match href {
+        Some(href) => HtmlElement::new(tag::link)
+            .with_attr(attr::rel, "stylesheet")
+            .with_attr(attr::href, href),
+        None if options.styles != HtmlStyleMode::Classes
+            || options.profile == HtmlProfile::Semantic =>
+        {
+            return Ok(Cow::Borrowed(root));
+        }
+        None => {
+            let mut sheet = Stylesheet::new();
+            sheet.collect(root);
+            if sheet.is_empty() {
+                return Ok(Cow::Borrowed(root));
+            }
+            HtmlElement::new(tag::style).with_children(eco_vec![HtmlNode::Text(
+                sheet.to_css().trim_end().into(),
+                Span::detached(),
+            )])
+        }
+    };
+
+    let mut root = root.clone();
+    let span = root.span;
+    let Some(head) = root.children.make_mut().iter_mut().find_map(|node| match node {
+        HtmlNode::Element(elem) if elem.tag == tag::head => Some(elem),
+        _ => None,
+    }) else {
+        bail!(
+            span, "generated styles require a `<head>` element";
+            hint: "add a `head` element to the custom `html` element";
+        );
+    };
+    head.children.push(elem.into());
+    Ok(Cow::Owned(root))
 }
 
 /// The shared implementation of [`html`] and [`html_in_bundle`].
 fn html_impl(mut w: Writer, root: &HtmlElement) -> SourceResult<String> {
+    if w.xhtml {
+        w.buf.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    }
     w.buf.push_str("<!DOCTYPE html>");
     write_indent(&mut w);
     write_element(&mut w, root)?;
@@ -50,6 +236,25 @@ fn html_impl(mut w: Writer, root: &HtmlElement) -> SourceResult<String> {
     Ok(w.buf)
 }
 
+/// Encodes the contents of the root's `<body>` element.
+fn body_impl(mut w: Writer, root: &HtmlElement) -> SourceResult<String> {
+    let body = find_child(root, tag::body)?;
+    write_fragment(&mut w, &body.children, body.pre_span)?;
+    Ok(w.buf)
+}
+
+/// Finds the child element of the root with the given tag.
+fn find_child(root: &HtmlElement, tag: HtmlTag) -> SourceResult<&HtmlElement> {
+    root.children
+        .iter()
+        .find_map(|node| match node {
+            HtmlNode::Element(elem) if elem.tag == tag => Some(elem),
+            _ => None,
+        })
+        .ok_or_else(|| eco_format!("document has no `{}` element", tag.resolve()))
+        .at(root.span)
+}
+
 /// Encodes HTML.
 struct Writer<'a> {
     /// The output buffer.
@@ -61,16 +266,25 @@ struct Writer<'a> {
     link_resolver: Tracked<'a, LateLinkResolver<'a>>,
     /// Whether pretty printing is enabled.
     pretty: bool,
+    /// How to write generated CSS properties.
+    styles: HtmlStyleMode,
+    /// Whether to use the XML serialization.
+    xhtml: bool,
 }
 
 impl<'a> Writer<'a> {
     /// Creates a new writer.
-    fn new(link_resolver: Tracked<'a, LateLinkResolver<'a>>, pretty: bool) -> Self {
+    fn new(
+        link_resolver: Tracked<'a, LateLinkResolver<'a>>,
+        options: &HtmlOptions,
+    ) -> Self {
         Self {
             buf: String::new(),
             level: 0,
             link_resolver,
-            pretty,
+            pretty: options.pretty,
+            styles: options.styles,
+            xhtml: options.xhtml,
         }
     }
 }
@@ -113,13 +327,26 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     w.buf.push('<');
     w.buf.push_str(&element.tag.resolve());
 
-    for (attr, value) in &element.attrs.0 {
+    let attrs = css::resolve_attrs(element, w.styles);
+    for (attr, value) in &attrs.0 {
         w.buf.push(' ');
         w.buf.push_str(&attr.resolve());
 
+        // Point references to files that were renamed during bundle export
+        // to their new paths.
+        let rewritten = if *attr == attr::href || *attr == attr::src {
+            w.link_resolver.rewrite_url(value)
+        } else {
+            None
+        };
+        let value = rewritten.as_ref().unwrap_or(value);
+
         // If the string is empty, we can use shorthand syntax.
         // `<elem attr="">..</div` is equivalent to `<elem attr>..</div>`
-        if !value.is_empty() {
+        // XML does not know this syntax, though.
+        if w.xhtml && value.is_empty() {
+            w.buf.push_str("=\"\"");
+        } else if !value.is_empty() {
             w.buf.push('=');
             w.buf.push('"');
             for c in value.chars() {
@@ -133,13 +360,23 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
         }
     }
 
-    if tag::is_foreign_self_closing(element.tag) {
+    if w.xhtml
+        && let Some(namespace) = xml_namespace(element.tag)
+        && !attrs.0.iter().any(|(attr, _)| attr.resolve().as_str() == "xmlns")
+    {
+        w.buf.push_str(" xmlns=\"");
+        w.buf.push_str(namespace);
+        w.buf.push('"');
+    }
+
+    let void = tag::is_void(element.tag) || tag::is_foreign_self_closing(element.tag);
+    if tag::is_foreign_self_closing(element.tag) || (w.xhtml && void) {
         w.buf.push('/');
     }
 
     w.buf.push('>');
 
-    if tag::is_void(element.tag) || tag::is_foreign_self_closing(element.tag) {
+    if void {
         if !element.children.is_empty() {
             bail!(element.span, "HTML void elements must not have children");
         }
@@ -151,7 +388,9 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
         w.buf.push('\n');
     }
 
-    if tag::is_raw(element.tag) {
+    // In XML, the contents of raw text elements are parsed like normal text
+    // and need to be escaped like it.
+    if tag::is_raw(element.tag) && !w.xhtml {
         write_raw(w, element)?;
     } else if tag::is_escapable_raw(element.tag) {
         write_escapable_raw(w, element)?;
@@ -166,6 +405,15 @@ fn write_element(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     Ok(())
 }
 
+/// The XML namespace that an element must declare in XHTML, if any.
+fn xml_namespace(tag: HtmlTag) -> Option<&'static str> {
+    match tag {
+        tag::html => Some("http://www.w3.org/1999/xhtml"),
+        tag::mathml::math => Some("http://www.w3.org/1998/Math/MathML"),
+        _ => None,
+    }
+}
+
 /// Encodes the children of an element.
 fn write_children(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     let pretty = w.pretty;
@@ -201,6 +449,33 @@ fn write_children(w: &mut Writer, element: &HtmlElement) -> SourceResult<()> {
     Ok(())
 }
 
+/// Encodes top-level nodes of a fragment.
+///
+/// This works like `write_children`, but without the indentation and
+/// surrounding newlines of an enclosing element.
+fn write_fragment(w: &mut Writer, nodes: &[HtmlNode], escape: bool) -> SourceResult<()> {
+    let mut indent = false;
+    for node in nodes {
+        let pretty_around = match node {
+            HtmlNode::Tag(_) => continue,
+            HtmlNode::Element(child) => w.pretty && wants_pretty_around(child),
+            HtmlNode::Text(..) | HtmlNode::Frame(_) => false,
+        };
+
+        if !w.buf.is_empty() && (indent || pretty_around) {
+            write_indent(w);
+        }
+        write_node(w, node, escape)?;
+        indent = pretty_around;
+    }
+
+    if w.pretty && !w.buf.is_empty() {
+        w.buf.push('\n');
+    }
+
+    Ok(())
+}
+
 /// Whether the first character in the element is a newline.
 fn starts_with_newline(element: &HtmlElement) -> bool {
     for child in &element.children {
@@ -400,3 +675,104 @@ fn write_frame(w: &mut Writer, frame: &HtmlFrame) {
     );
     w.buf.push_str(&svg);
 }
+
+#[cfg(test)]
+mod tests {
+    use typst_library::model::DocumentInfo;
+
+    use super::*;
+    use crate::document::HtmlOutput;
+
+    fn elem(tag: HtmlTag, children: impl IntoIterator<Item = HtmlNode>) -> HtmlElement {
+        HtmlElement::new(tag).with_children(children.into_iter().collect())
+    }
+
+    fn text(text: &str) -> HtmlNode {
+        HtmlNode::Text(text.into(), Span::detached())
+    }
+
+    fn document(
+        head: impl IntoIterator<Item = HtmlNode>,
+        body: impl IntoIterator<Item = HtmlNode>,
+    ) -> HtmlDocument {
+        let root =
+            elem(tag::html, [elem(tag::head, head).into(), elem(tag::body, body).into()]);
+        HtmlDocument::new(HtmlOutput::from_root(root), DocumentInfo::default())
+    }
+
+    fn paragraphs() -> [HtmlNode; 2] {
+        [elem(tag::p, [text("Hello")]).into(), elem(tag::p, [text("World")]).into()]
+    }
+
+    fn endnotes() -> HtmlNode {
+        elem(tag::section, [elem(tag::ol, [elem(tag::li, [text("Note")]).into()]).into()])
+            .with_attr(attr::role, "doc-endnotes")
+            .into()
+    }
+
+    #[test]
+    fn test_html_fragment_option() {
+        let doc = document([], paragraphs());
+        let options = HtmlOptions { fragment: true, ..Default::default() };
+        assert_eq!(html(&doc, &options).unwrap(), "<p>Hello</p><p>World</p>");
+
+        let options = HtmlOptions { fragment: true, pretty: true, ..Default::default() };
+        assert_eq!(html(&doc, &options).unwrap(), "<p>Hello</p>\n<p>World</p>\n");
+
+        let full = html(&doc, &HtmlOptions::default()).unwrap();
+        assert!(full.starts_with("<!DOCTYPE html>"));
+        assert!(full.contains("<body><p>Hello</p><p>World</p></body>"));
+    }
+
+    #[test]
+    fn test_html_fragment_separates_styles_and_footnotes() {
+        let style = elem(tag::style, [text("math { color: red }")]).into();
+        let doc = document(
+            [elem(tag::meta, []).with_attr(attr::charset, "utf-8").into(), style],
+            paragraphs().into_iter().chain([endnotes()]),
+        );
+
+        let fragment = html_fragment(&doc, &HtmlOptions::default()).unwrap();
+        assert_eq!(fragment.body, "<p>Hello</p><p>World</p>");
+        assert_eq!(
+            fragment.footnotes.as_deref(),
+            Some(r#"<section role="doc-endnotes"><ol><li>Note</li></ol></section>"#),
+        );
+        assert_eq!(fragment.styles, "math { color: red }\n");
+    }
+
+    #[test]
+    fn test_html_fragment_without_styles_and_footnotes() {
+        let doc = document([], paragraphs());
+        let fragment = html_fragment(&doc, &HtmlOptions::default()).unwrap();
+        assert_eq!(fragment.body, "<p>Hello</p><p>World</p>");
+        assert_eq!(fragment.footnotes, None);
+        assert_eq!(fragment.styles, "");
+    }
+
+    #[test]
+    fn test_xhtml_void_and_raw_elements() {
+        let doc = document(
+            [],
+            [
+                elem(tag::p, [text("A"), elem(tag::br, []).into(), text("B")]).into(),
+                elem(tag::script, [text("if (a < b && c) {}")]).into(),
+            ],
+        );
+
+        let options = HtmlOptions { xhtml: true, ..Default::default() };
+        assert_eq!(
+            html(&doc, &options).unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\"><head></head><body>\
+             <p>A<br/>B</p><script>if (a &lt; b &amp;&amp; c) {}</script>\
+             </body></html>",
+        );
+
+        let options = HtmlOptions { fragment: true, ..Default::default() };
+        assert_eq!(
+            html(&doc, &options).unwrap(),
+            "<p>A<br>B</p><script>if (a < b && c) {}</script>",
+        );
+    }
+}
@@ -14,14 +14,19 @@ mod fragment;
 mod introspect;
 mod link;
 mod mathml;
+mod patch;
+mod profile;
 mod rules;
 mod typed;
 
+pub use self::css::{HtmlStyleMode, Stylesheet};
 pub use self::document::{html_document, html_document_for_bundle};
 pub use self::dom::*;
-pub use self::encode::{HtmlOptions, html, html_in_bundle};
+pub use self::encode::{HtmlFragment, HtmlOptions, html, html_fragment, html_in_bundle};
 pub use self::introspect::HtmlIntrospector;
 pub use self::link::create_link_anchors;
+pub use self::patch::{HtmlPatch, html_patches};
+pub use self::profile::HtmlProfile;
 pub use self::rules::{html_mathml_body, html_span_filled, register};
 
 use ecow::EcoString;
@@ -0,0 +1,337 @@
+use typst_library::diag::SourceResult;
+
+use crate::encode::{encode_element, prepare_root};
+use crate::{HtmlDocument, HtmlElement, HtmlNode, HtmlOptions, tag};
+
+/// The maximum number of patches before a diff is considered too large to be
+/// worth applying.
+const MAX_PATCHES: usize = 32;
+
+/// A change to an element in the `<body>` of an HTML document.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct HtmlPatch {
+    /// Indices that lead from the `<body>` to the element. At each level, only
+    /// elements are counted, not text.
+    pub path: Vec<usize>,
+    /// Whether only the element's contents are replaced instead of the element
+    /// as a whole.
+    pub inner: bool,
+    /// The new HTML for the element or its contents, encoded like by
+    /// [`html`](crate::html).
+    pub html: String,
+}
+
+/// Determines the changes that turn the `<body>` of the encoded `old` document
+/// into that of the encoded `new` one.
+///
+/// Returns `None` if the documents differ outside of the `<body>` or if the
+/// changes are so extensive that a full reload is preferable.
+#[typst_macros::time(name = "html patches")]
+pub fn html_patches(
+    old: &HtmlDocument,
+    new: &HtmlDocument,
+    options: &HtmlOptions,
+) -> SourceResult<Option<Vec<HtmlPatch>>> {
+    let old_root = prepare_root(old, options)?;
+    let new_root = prepare_root(new, options)?;
+    if !same_shell(&old_root, &new_root) {
+        return Ok(None);
+    }
+
+    let (Some(old_body), Some(new_body)) = (body(&old_root), body(&new_root)) else {
+        return Ok(None);
+    };
+
+    let mut differ = Differ { document: new, options, patches: vec![] };
+    if !differ.diff(old_body, new_body, &mut vec![])? {
+        return Ok(None);
+    }
+
+    // The body itself can't be replaced without losing the page's state.
+    if differ
+        .patches
+        .iter()
+        .any(|patch| patch.path.is_empty() && !patch.inner)
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(differ.patches))
+}
+
+/// Collects the patches for a document.
+struct Differ<'a> {
+    document: &'a HtmlDocument,
+    options: &'a HtmlOptions,
+    patches: Vec<HtmlPatch>,
+}
+
+impl Differ<'_> {
+    /// Diffs two versions of the element at `path`. Returns `false` if there
+    /// are too many patches.
+    fn diff(
+        &mut self,
+        old: &HtmlElement,
+        new: &HtmlElement,
+        path: &mut Vec<usize>,
+    ) -> SourceResult<bool> {
+        if !same_element_shell(old, new) {
+            return self.patch(new, path, false);
+        }
+
+        let old_children = significant(old);
+        let new_children = significant(new);
+        if old_children.len() != new_children.len()
+            || old_children
+                .iter()
+                .zip(&new_children)
+                .any(|(a, b)| !same_kind_and_leaf(a, b))
+        {
+            // The contents of raw text elements are encoded differently, so
+            // those elements are replaced as a whole.
+            let raw = tag::is_raw(new.tag)
+                || tag::is_escapable_raw(new.tag)
+                || new.tag == tag::pre;
+            return self.patch(new, path, !raw);
+        }
+
+        let mut index = 0;
+        for (a, b) in old_children.iter().zip(&new_children) {
+            match (a, b) {
+                (HtmlNode::Element(a), HtmlNode::Element(b)) => {
+                    // Tables without a header or footer contain their rows
+                    // directly. Browsers wrap those in an implicit `tbody`,
+                    // which the path must go through to match their DOM.
+                    let implicit_tbody = new.tag == tag::table && b.tag == tag::tr;
+                    if implicit_tbody {
+                        path.push(0);
+                    }
+                    path.push(index);
+                    let ok = self.diff(a, b, path)?;
+                    path.pop();
+                    if implicit_tbody {
+                        path.pop();
+                    }
+                    if !ok {
+                        return Ok(false);
+                    }
+                    index += 1;
+                }
+                // Frames are encoded as `<svg>` elements.
+                (HtmlNode::Frame(_), _) => index += 1,
+                _ => {}
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Adds a patch for the element at `path`. Returns `false` if there are
+    /// too many patches.
+    fn patch(
+        &mut self,
+        element: &HtmlElement,
+        path: &[usize],
+        inner: bool,
+    ) -> SourceResult<bool> {
+        if self.patches.len() >= MAX_PATCHES {
+            return Ok(false);
+        }
+        let html = encode_element(self.document, element, self.options, inner)?;
+        self.patches.push(HtmlPatch { path: path.to_vec(), inner, html });
+        Ok(true)
+    }
+}
+
+/// Finds the `<body>` element of a root.
+fn body(root: &HtmlElement) -> Option<&HtmlElement> {
+    root.children.iter().find_map(|node| match node {
+        HtmlNode::Element(elem) if elem.tag == tag::body => Some(elem),
+        _ => None,
+    })
+}
+
+/// Whether two roots are the same except for their `<body>`.
+fn same_shell(old: &HtmlElement, new: &HtmlElement) -> bool {
+    let old_children = significant(old);
+    let new_children = significant(new);
+    same_element_shell(old, new)
+        && old_children.len() == new_children.len()
+        && old_children.iter().zip(&new_children).all(|(a, b)| match (a, b) {
+            (HtmlNode::Element(a), HtmlNode::Element(b))
+                if a.tag == tag::body && b.tag == tag::body =>
+            {
+                true
+            }
+            _ => same_node(a, b),
+        })
+}
+
+/// The children of an element that end up in the output.
+fn significant(element: &HtmlElement) -> Vec<&HtmlNode> {
+    element
+        .children
+        .iter()
+        .filter(|node| !matches!(node, HtmlNode::Tag(_)))
+        .collect()
+}
+
+/// Whether two elements have the same tag and attributes, regardless of their
+/// children.
+fn same_element_shell(a: &HtmlElement, b: &HtmlElement) -> bool {
+    a.tag == b.tag && a.attrs == b.attrs && a.css == b.css && a.pre_span == b.pre_span
+}
+
+/// Whether two nodes are of the same kind and, if they are not elements,
+/// equal. Spans are ignored because they change with edits that don't affect
+/// the output.
+fn same_kind_and_leaf(a: &HtmlNode, b: &HtmlNode) -> bool {
+    match (a, b) {
+        (HtmlNode::Element(_), HtmlNode::Element(_)) => true,
+        (HtmlNode::Text(a, _), HtmlNode::Text(b, _)) => a == b,
+        (HtmlNode::Frame(a), HtmlNode::Frame(b)) => {
+            typst_utils::hash128(a) == typst_utils::hash128(b)
+        }
+        _ => false,
+    }
+}
+
+/// Whether two nodes are equal, including their descendants.
+fn same_node(a: &HtmlNode, b: &HtmlNode) -> bool {
+    match (a, b) {
+        (HtmlNode::Element(a), HtmlNode::Element(b)) => {
+            let a_children = significant(a);
+            let b_children = significant(b);
+            same_element_shell(a, b)
+                && a_children.len() == b_children.len()
+                && a_children.iter().zip(&b_children).all(|(a, b)| same_node(a, b))
+        }
+        _ => same_kind_and_leaf(a, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ecow::eco_vec;
+    use typst_library::model::DocumentInfo;
+    use typst_syntax::{Source, Span};
+
+    use super::*;
+    use crate::HtmlTag;
+    use crate::attr;
+    use crate::document::HtmlOutput;
+
+    fn elem(tag: HtmlTag, children: impl IntoIterator<Item = HtmlNode>) -> HtmlNode {
+        HtmlElement::new(tag)
+            .with_children(children.into_iter().collect())
+            .into()
+    }
+
+    fn text(text: &str) -> HtmlNode {
+        HtmlNode::Text(text.into(), Span::detached())
+    }
+
+    fn document(body: impl IntoIterator<Item = HtmlNode>) -> HtmlDocument {
+        let HtmlNode::Element(root) =
+            elem(tag::html, [elem(tag::head, []), elem(tag::body, body)])
+        else {
+            unreachable!()
+        };
+        HtmlDocument::new(HtmlOutput::from_root(root), DocumentInfo::default())
+    }
+
+    fn row(cells: &[&str]) -> HtmlNode {
+        elem(tag::tr, cells.iter().map(|cell| elem(tag::td, [text(cell)])))
+    }
+
+    fn table(rows: &[&[&str]]) -> HtmlNode {
+        elem(tag::table, rows.iter().map(|cells| row(cells)))
+    }
+
+    #[test]
+    fn test_html_patches_table_cell() {
+        let options = HtmlOptions::default();
+        let old =
+            document([elem(tag::p, [text("Intro")]), table(&[&["A", "B"], &["C", "D"]])]);
+        let new =
+            document([elem(tag::p, [text("Intro")]), table(&[&["A", "B"], &["C", "E"]])]);
+
+        // The path goes through the `tbody` that browsers insert around the
+        // rows of the table.
+        let patches = html_patches(&old, &new, &options).unwrap().unwrap();
+        assert_eq!(
+            patches,
+            [HtmlPatch {
+                path: vec![1, 0, 1, 1],
+                inner: true,
+                html: "E".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_html_patches_table_cell_with_header() {
+        let options = HtmlOptions::default();
+        let with_header = |cell| {
+            elem(
+                tag::table,
+                [
+                    elem(tag::thead, [row(&["H"])]),
+                    elem(tag::tbody, [row(&["A"]), row(&[cell])]),
+                ],
+            )
+        };
+        let old = document([with_header("B")]);
+        let new = document([with_header("C")]);
+
+        // An explicit `tbody` is counted like any other element.
+        let patches = html_patches(&old, &new, &options).unwrap().unwrap();
+        assert_eq!(
+            patches,
+            [HtmlPatch {
+                path: vec![0, 1, 1, 0],
+                inner: true,
+                html: "C".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_html_patches_table_rows() {
+        let options = HtmlOptions::default();
+        let old = document([table(&[&["A", "B"], &["C", "D"]])]);
+        let new = document([table(&[&["A"]])]);
+
+        // A changed number of rows replaces the contents of the table, into
+        // which browsers insert the `tbody` again.
+        let patches = html_patches(&old, &new, &options).unwrap().unwrap();
+        assert_eq!(
+            patches,
+            [HtmlPatch {
+                path: vec![0],
+                inner: true,
+                html: "<tr><td>A</td></tr>".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_same_node_ignores_spans() {
+        let span = Source::detached("Hello").root().span();
+        let p = |text: &str, span: Span| -> HtmlNode {
+            HtmlElement::new(tag::p)
+                .with_attr(attr::class, "note")
+                .with_children(eco_vec![HtmlNode::Text(text.into(), span)])
+                .spanned(span)
+                .into()
+        };
+
+        assert!(same_node(&p("Hello", Span::detached()), &p("Hello", span)));
+        assert!(!same_node(&p("Hello", span), &p("World", span)));
+
+        let HtmlNode::Element(a) = p("Hello", span) else { unreachable!() };
+        let HtmlNode::Element(b) = p("Hello", span) else { unreachable!() };
+        let b = b.with_attr(attr::id, "intro");
+        assert!(!same_element_shell(&a, &b));
+    }
+}
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+
+use ecow::EcoVec;
+
+use crate::css::Properties;
+use crate::mathml::EQUATION_CSS_STYLES;
+use crate::{HtmlElement, HtmlNode, tag};
+
+/// Which kind of markup HTML export emits.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HtmlProfile {
+    /// Semantic markup plus the presentational styles that approximate the
+    /// look of the document.
+    #[default]
+    Full,
+    /// Purely semantic markup, for pages that are styled externally.
+    ///
+    /// Typst then emits none of its generated CSS, neither inline nor in
+    /// stylesheets. This excludes sizes from the layout (like the width of an
+    /// image) and presentational `<span>` elements from text styling (like
+    /// underlines and small capitals). Attributes, including accessibility
+    /// attributes like `role` and `alt`, are retained, as are styles that were
+    /// explicitly added through `html.elem`.
+    Semantic,
+}
+
+/// Removes presentational markup from the root element if the profile asks
+/// for it.
+pub fn apply_profile(root: &HtmlElement, profile: HtmlProfile) -> Cow<'_, HtmlElement> {
+    match profile {
+        HtmlProfile::Full => Cow::Borrowed(root),
+        HtmlProfile::Semantic => {
+            let mut root = root.clone();
+            strip(&mut root);
+            Cow::Owned(root)
+        }
+    }
+}
+
+/// Recursively removes generated CSS from an element and its descendants.
+fn strip(elem: &mut HtmlElement) {
+    elem.css = Properties::new();
+
+    if !elem.children.iter().any(is_presentational) {
+        for child in elem.children.make_mut() {
+            if let HtmlNode::Element(child) = child {
+                strip(child);
+            }
+        }
+        return;
+    }
+
+    let mut children = EcoVec::with_capacity(elem.children.len());
+    for child in std::mem::take(&mut elem.children) {
+        match child {
+            HtmlNode::Element(mut child) => {
+                if is_generated_style(&child) {
+                    continue;
+                }
+
+                // A presentational span has no meaning of its own, so its
+                // children take its place.
+                let unwrap = is_presentational_span(&child);
+                strip(&mut child);
+                if unwrap {
+                    children.extend(child.children);
+                } else {
+                    children.push(child.into());
+                }
+            }
+            other => children.push(other),
+        }
+    }
+    elem.children = children;
+}
+
+/// Whether stripping the node changes its parent's children.
+fn is_presentational(node: &HtmlNode) -> bool {
+    let HtmlNode::Element(elem) = node else { return false };
+    is_generated_style(elem) || is_presentational_span(elem)
+}
+
+/// Whether the element is a `<span>` that only carries generated CSS, like
+/// the ones for underlines or preserved whitespace.
+fn is_presentational_span(elem: &HtmlElement) -> bool {
+    elem.tag == tag::span && elem.attrs.0.is_empty() && !elem.css.is_empty()
+}
+
+/// Whether the element is a `<style>` element with the styles that Typst adds
+/// to the head for equations.
+fn is_generated_style(elem: &HtmlElement) -> bool {
+    elem.tag == tag::style
+        && matches!(
+            elem.children.as_slice(),
+            [HtmlNode::Text(text, _)] if *text == *EQUATION_CSS_STYLES
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use ecow::eco_vec;
+    use typst_syntax::Span;
+
+    use super::*;
+    use crate::attr;
+
+    #[test]
+    fn test_semantic_profile() {
+        let text = |s: &str| HtmlNode::Text(s.into(), Span::detached());
+        let underline = HtmlElement::new(tag::span)
+            .with_css(Properties::new().with("text-decoration", "underline"))
+            .with_children(eco_vec![text("world")]);
+        let img = HtmlElement::new(tag::img)
+            .with_attr(attr::alt, "A cat")
+            .with_css(Properties::new().with("width", "50%"));
+        let section = HtmlElement::new(tag::section)
+            .with_attr(attr::role, "doc-endnotes")
+            .with_css(Properties::new().with("display", "block"))
+            .with_children(eco_vec![text("Hello "), underline.into(), img.into()]);
+
+        let stripped = apply_profile(&section, HtmlProfile::Semantic);
+        assert!(stripped.css.is_empty());
+        assert_eq!(stripped.attrs.get(attr::role).unwrap(), "doc-endnotes");
+        assert_eq!(stripped.children.len(), 3);
+        assert!(matches!(&stripped.children[1], HtmlNode::Text(s, _) if s == "world"));
+        let HtmlNode::Element(img) = &stripped.children[2] else { panic!() };
+        assert!(img.css.is_empty());
+        assert_eq!(img.attrs.get(attr::alt).unwrap(), "A cat");
+
+        assert!(matches!(apply_profile(&section, HtmlProfile::Full), Cow::Borrowed(_)));
+    }
+}
@@ -347,21 +347,63 @@ const FOOTNOTE_RULE: ShowFn<FootnoteElem> = |elem, engine, styles| {
 const FOOTNOTE_MARKER_RULE: ShowFn<FootnoteMarker> = |_, _, _| Ok(Content::empty());
 
 const FOOTNOTE_CONTAINER_RULE: ShowFn<FootnoteContainer> = |elem, engine, _| {
-    let mut selector = FootnoteElem::ELEM.select();
+    let loc = elem.location().unwrap();
 
     // In bundle export, we only want the footnotes in the current document.
-    if let Some(doc_location) =
-        engine.introspect(DocumentIntrospection(elem.location().unwrap(), elem.span()))
-    {
-        selector = Selector::Within {
-            selector: Arc::new(FootnoteElem::ELEM.select()),
+    let doc_location = engine.introspect(DocumentIntrospection(loc, elem.span()));
+    let within = |selector: Selector| match doc_location {
+        Some(doc_location) => Selector::Within {
+            selector: Arc::new(selector),
             ancestor: Arc::new(doc_location.into()),
+        },
+        None => selector,
+    };
+
+    // A listing shows the footnotes since the previous listing, which allows
+    // for per-section endnotes.
+    let containers = engine.introspect(QueryIntrospection(
+        within(FootnoteContainer::ELEM.select()),
+        elem.span(),
+    ));
+    let prev = containers
+        .iter()
+        .filter_map(|container| container.location())
+        .take_while(|&container| container != loc)
+        .last();
+
+    let mut selector = Selector::Before {
+        selector: Arc::new(within(FootnoteElem::ELEM.select())),
+        end: Arc::new(loc.into()),
+        inclusive: false,
+    };
+    if let Some(prev) = prev {
+        selector = Selector::After {
+            selector: Arc::new(selector),
+            start: Arc::new(prev.into()),
+            inclusive: false,
         };
     }
 
-    // Create entries for all footnotes in the document.
+    // Footnotes within footnotes are realized inside of a listing, so they
+    // are positioned after it. They belong to the listing of their parent,
+    // which also lists them recursively.
+    let nested = |ancestor: Selector| Selector::Within {
+        selector: Arc::new(FootnoteElem::ELEM.select()),
+        ancestor: Arc::new(ancestor),
+    };
+    let foreign = engine.introspect(QueryIntrospection(
+        nested(FootnoteContainer::ELEM.select()),
+        elem.span(),
+    ));
+    let own = engine.introspect(QueryIntrospection(nested(loc.into()), elem.span()));
+
+    // Create entries for the footnotes.
     let notes = engine.introspect(QueryIntrospection(selector, elem.span()));
-    let items = notes.into_iter().filter_map(|note| {
+    let notes = notes
+        .into_iter()
+        .filter(|note| !foreign.iter().any(|other| other.location() == note.location()))
+        .chain(own);
+    let items = notes.filter_map(|note| {
         let note = note.into_packed::<FootnoteElem>().unwrap();
         if note.is_ref() {
             return None;
@@ -790,20 +832,18 @@ const IMAGE_RULE: ShowFn<ImageElem> = |elem, engine, styles| {
     attrs.push(attr::width, cast(image.width()));
     attrs.push(attr::height, cast(image.height()));
 
+    // The semantic profile omits all of these properties when encoding.
     let mut css = css::Properties::build((engine, elem.span()));
 
-    // TODO: Exclude in semantic profile.
     if let Some(value) = typst_svg::convert_image_scaling(image.scaling()) {
         css.push("image-rendering", value);
     }
 
-    // TODO: Exclude in semantic profile?
     match elem.width.get(styles) {
         Smart::Auto => {}
         Smart::Custom(rel) => css.push("width", rel),
     }
 
-    // TODO: Exclude in semantic profile?
     match elem.height.get(styles) {
         Sizing::Auto => {}
         Sizing::Rel(rel) => css.push("height", rel),
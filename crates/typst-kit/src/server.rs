@@ -2,24 +2,130 @@
 
 #![cfg(feature = "http-server")]
 
+use std::collections::VecDeque;
 use std::io::{self, Write};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::num::NonZeroUsize;
+use std::path::Path;
 use std::sync::Arc;
 
 use ecow::eco_format;
 use parking_lot::{Condvar, Mutex, MutexGuard};
 use percent_encoding::percent_decode_str;
-use tiny_http::{Header, Request, Response, StatusCode};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, StatusCode};
+use typst_html::{HtmlDocument, HtmlOptions, HtmlPatch};
 use typst_library::diag::{StrResult, bail};
 use typst_library::foundations::Bytes;
+use typst_library::introspection::{DocumentPosition, PagedPosition};
+use typst_library::layout::{Abs, Point};
 
 type Router = Box<dyn Fn(&str) -> Option<HttpBody> + Send + Sync>;
-type RouterBucket = Bucket<Router>;
+type ClickHandler = Arc<dyn Fn(PagedPosition) + Send + Sync>;
+type JumpHandler =
+    Arc<dyn Fn(&Path, usize, usize) -> Option<DocumentPosition> + Send + Sync>;
+type RouterBucket = Bucket<Live>;
+
+/// What the server currently serves, along with the events for connected
+/// browsers.
+struct Live {
+    /// The served contents.
+    served: Served,
+    /// The recent events.
+    events: Events,
+}
+
+/// What the server currently serves.
+struct Served {
+    /// Handles requests.
+    router: Router,
+    /// Increases with each update of the contents.
+    ///
+    /// Pages know the version they were served with, so that they only apply
+    /// patches to that exact version and reload otherwise.
+    version: u64,
+    /// Handles clicks on the pages of a paged document.
+    on_click: Option<ClickHandler>,
+    /// Finds the position in the served document that corresponds to a
+    /// position in a source file.
+    on_jump: Option<JumpHandler>,
+}
+
+/// A server-sent event.
+#[derive(Clone)]
+struct Event {
+    /// The name of the event.
+    ///
+    /// - A `reload` event makes browsers reload the page. For bundles, its
+    ///   data lists the paths that changed, so that browsers only need to
+    ///   reload when they display one of them. Otherwise, it's empty, and all
+    ///   browsers reload.
+    /// - A `patch` event carries changes to the `<body>` of the page, along
+    ///   with the versions before and after them.
+    /// - A `scroll` event carries a position in the document to scroll to.
+    name: &'static str,
+    /// The event's data, in JSON.
+    data: String,
+}
+
+impl Event {
+    /// An event that makes all browsers reload.
+    fn reload() -> Self {
+        Self { name: "reload", data: String::new() }
+    }
+}
+
+/// The most recent events, in the order they happened.
+///
+/// Each connected browser keeps track of how many events it has received, so
+/// that it receives all of them, even if multiple events happen in quick
+/// succession.
+#[derive(Default)]
+struct Events {
+    /// The number of events that happened so far.
+    count: u64,
+    /// The last few events.
+    recent: VecDeque<Event>,
+}
+
+impl Events {
+    /// How many events are kept for browsers that are lagging behind.
+    const CAPACITY: usize = 16;
+
+    /// Adds an event.
+    fn push(&mut self, event: Event) {
+        if self.recent.len() == Self::CAPACITY {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(event);
+        self.count += 1;
+    }
+
+    /// The events that happened after the first `seen` ones.
+    ///
+    /// If some of them were already dropped, this returns a single reload
+    /// event instead.
+    fn since(&self, seen: u64) -> Vec<Event> {
+        let missed = (self.count - seen) as usize;
+        if missed > self.recent.len() {
+            return vec![Event::reload()];
+        }
+        self.recent.range(self.recent.len() - missed..).cloned().collect()
+    }
+}
+
+/// The previously served contents, which patches are computed against.
+enum Previous {
+    None,
+    Html(HtmlDocument, HtmlOptions),
+    Pages(Vec<String>),
+}
 
 /// Serves HTML with live reload.
 pub struct HttpServer {
     addr: SocketAddr,
     bucket: Arc<RouterBucket>,
+    previous: Mutex<Previous>,
 }
 
 impl HttpServer {
@@ -28,7 +134,15 @@ impl HttpServer {
         let (addr, server) = start_server(port)?;
 
         let placeholder = PLACEHOLDER_HTML.replace("{INPUT}", title);
-        let bucket = Arc::new(Bucket::new(html_single_fs(placeholder)));
+        let bucket = Arc::new(Bucket::new(Live {
+            served: Served {
+                router: html_single_fs(placeholder),
+                version: 0,
+                on_click: None,
+                on_jump: None,
+            },
+            events: Events::default(),
+        }));
         let bucket2 = bucket.clone();
 
         std::thread::spawn(move || {
@@ -37,7 +151,7 @@ impl HttpServer {
             }
         });
 
-        Ok(Self { addr, bucket })
+        Ok(Self { addr, bucket, previous: Mutex::new(Previous::None) })
     }
 
     /// The address that we serve the HTML on.
@@ -48,13 +162,135 @@ impl HttpServer {
     /// Updates the served contents to a page of HTML served on `/`, triggering
     /// a reload in all connected browsers.
     pub fn set_html(&self, html: String) {
-        self.bucket.put(html_single_fs(html));
+        *self.previous.lock() = Previous::None;
+        self.serve(
+            Served {
+                router: html_single_fs(html),
+                version: self.next_version(),
+                on_click: None,
+                on_jump: None,
+            },
+            Some(Event::reload()),
+        );
+    }
+
+    /// Updates the served contents to the encoded HTML of a document, served
+    /// on `/`.
+    ///
+    /// Instead of reloading, connected browsers patch the changed parts of
+    /// the page, as determined by comparing the document to the previously
+    /// served one. They still reload if the changes are extensive.
+    ///
+    /// `on_jump` receives the path of a source file and a one-based line and
+    /// column in it and returns the corresponding position in the document,
+    /// to which connected browsers then scroll.
+    pub fn set_html_document<J>(
+        &self,
+        html: String,
+        document: &HtmlDocument,
+        options: &HtmlOptions,
+        on_jump: Option<J>,
+    ) where
+        J: Fn(&Path, usize, usize) -> Option<DocumentPosition> + Send + Sync + 'static,
+    {
+        let mut previous = self.previous.lock();
+        let patches = match &*previous {
+            Previous::Html(old, old_options) if old_options == options => {
+                typst_html::html_patches(old, document, options).ok().flatten()
+            }
+            _ => None,
+        };
+        *previous = Previous::Html(document.clone(), options.clone());
+
+        let version = self.next_version();
+        self.serve(
+            Served {
+                router: html_single_fs(html),
+                version,
+                on_click: None,
+                on_jump: on_jump.map(|f| Arc::new(f) as JumpHandler),
+            },
+            Some(
+                patches
+                    .map_or_else(Event::reload, |patches| patch_event(version, &patches)),
+            ),
+        );
+    }
+
+    /// Updates the served contents to a viewer for the pages of a paged
+    /// document, given as SVGs.
+    ///
+    /// Connected browsers replace the pages that changed. If the number of
+    /// pages changed, they reload.
+    ///
+    /// When a page is clicked in the viewer, `on_click` is called with the
+    /// clicked position, e.g. to jump to the corresponding source code.
+    /// `on_jump` works like for [`set_html_document`](Self::set_html_document).
+    pub fn set_pages<C, J>(
+        &self,
+        title: &str,
+        pages: Vec<String>,
+        on_click: Option<C>,
+        on_jump: Option<J>,
+    ) where
+        C: Fn(PagedPosition) + Send + Sync + 'static,
+        J: Fn(&Path, usize, usize) -> Option<DocumentPosition> + Send + Sync + 'static,
+    {
+        let mut html = String::new();
+        for (i, svg) in pages.iter().enumerate() {
+            html.push_str(&format!("<div class=\"page\" data-page=\"{}\">", i + 1));
+            html.push_str(svg);
+            html.push_str("</div>");
+        }
+        let html = PAGED_VIEWER_HTML
+            .replace("{TITLE}", &escape_html(title))
+            .replace("{PAGES}", &html);
+
+        let mut previous = self.previous.lock();
+        let patches = match &*previous {
+            Previous::Pages(old) if old.len() == pages.len() => Some(
+                old.iter()
+                    .zip(&pages)
+                    .enumerate()
+                    .filter(|(_, (old, new))| old != new)
+                    .map(|(i, (_, new))| HtmlPatch {
+                        path: vec![i],
+                        inner: true,
+                        html: new.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            _ => None,
+        };
+        *previous = Previous::Pages(pages);
+
+        let version = self.next_version();
+        self.serve(
+            Served {
+                router: html_single_fs(html),
+                version,
+                on_click: on_click.map(|f| Arc::new(f) as ClickHandler),
+                on_jump: on_jump.map(|f| Arc::new(f) as JumpHandler),
+            },
+            Some(
+                patches
+                    .map_or_else(Event::reload, |patches| patch_event(version, &patches)),
+            ),
+        );
     }
 
     /// Updates the served contents to a bundle.
+    ///
+    /// Connected browsers are informed about the changed paths and reload if
+    /// they are affected. If nothing changed, no reload is triggered at all.
     #[cfg(feature = "bundle")]
-    pub fn set_bundle(&self, bundle: typst_bundle::Bundle, fs: typst_bundle::VirtualFs) {
-        self.set_router(move |route| {
+    pub fn set_bundle(
+        &self,
+        bundle: typst_bundle::Bundle,
+        fs: typst_bundle::VirtualFs,
+        changes: &typst_bundle::BundleChanges,
+    ) {
+        let router = Box::new(move |route: &str| {
             let path = typst_syntax::VirtualPath::new(route).ok()?;
             let with_index = path.join("index.html").unwrap();
             for path in [path, with_index] {
@@ -62,7 +298,7 @@ impl HttpServer {
                 let body = if matches!(
                     bundle.files.get(&path),
                     Some(typst_bundle::BundleFile::Document(
-                        typst_bundle::BundleDocument::Html(_)
+                        typst_bundle::BundleDocument::Html(..)
                     ))
                 ) && let Ok(string) = data.as_str()
                 {
@@ -75,6 +311,23 @@ impl HttpServer {
 
             None
         });
+
+        let notify = !changes.is_empty();
+        fn paths(paths: &[typst_syntax::VirtualPath]) -> Vec<&str> {
+            paths.iter().map(|path| path.get_with_slash()).collect()
+        }
+        let changes = serde_json::json!({
+            "added": paths(&changes.added),
+            "changed": paths(&changes.changed),
+            "removed": paths(&changes.removed),
+        })
+        .to_string();
+
+        *self.previous.lock() = Previous::None;
+        let version =
+            if notify { self.next_version() } else { self.bucket.get().served.version };
+        let served = Served { router, version, on_click: None, on_jump: None };
+        self.serve(served, notify.then_some(Event { name: "reload", data: changes }));
     }
 
     /// Updates the content handler, triggering a reload in all connected browsers.
@@ -82,10 +335,52 @@ impl HttpServer {
     where
         R: Fn(&str) -> Option<HttpBody> + Send + Sync + 'static,
     {
-        self.bucket.put(Box::new(router));
+        *self.previous.lock() = Previous::None;
+        self.serve(
+            Served {
+                router: Box::new(router),
+                version: self.next_version(),
+                on_click: None,
+                on_jump: None,
+            },
+            Some(Event::reload()),
+        );
+    }
+
+    /// Replaces the served contents and, if given, sends an event to all
+    /// connected browsers.
+    fn serve(&self, served: Served, event: Option<Event>) {
+        self.bucket.update(|live| {
+            live.served = served;
+            if let Some(event) = event {
+                live.events.push(event);
+            }
+        });
+    }
+
+    /// The version for the next update of the served contents.
+    fn next_version(&self) -> u64 {
+        self.bucket.get().served.version + 1
     }
 }
 
+/// Creates an event that makes browsers apply patches to the previous version
+/// to get to the given one.
+fn patch_event(version: u64, patches: &[HtmlPatch]) -> Event {
+    let patches: Vec<_> = patches
+        .iter()
+        .map(|patch| {
+            json!({
+                "path": patch.path,
+                "inner": patch.inner,
+                "html": patch.html,
+            })
+        })
+        .collect();
+    let data = json!({ "from": version - 1, "to": version, "patches": patches });
+    Event { name: "patch", data: data.to_string() }
+}
+
 /// Creates a handler that serves just one HTML page at `/`.
 fn html_single_fs(html: String) -> Router {
     Box::new(move |route| (route == "/").then(|| HttpBody::Html(html.clone())))
@@ -153,23 +448,64 @@ fn handle(req: Request, reload: bool, bucket: &Arc<RouterBucket>) -> io::Result<
     };
 
     if path == "/__events" {
-        return handle_events(req, bucket.clone());
+        return handle_events(req, bucket.clone(), parse_version(&url));
+    }
+
+    if path == "/__click" {
+        // Other websites open in the browser must not be able to trigger the
+        // click handler, which may run an editor command.
+        if !is_same_origin_post(&req) {
+            return req.respond(Response::empty(StatusCode(403)));
+        }
+
+        // Release the lock before handling the click.
+        let on_click = bucket.get().served.on_click.clone();
+        if let Some(on_click) = on_click
+            && let Some(position) = parse_click(&url)
+        {
+            on_click(position);
+        }
+        return req.respond(Response::empty(StatusCode(204)));
     }
 
-    let fs = bucket.get();
-    let Some(body) = fs(path.as_ref()) else {
+    if path == "/__jump" {
+        // Other websites must not be able to scroll the preview.
+        if !is_same_origin_post(&req) {
+            return req.respond(Response::empty(StatusCode(403)));
+        }
+
+        // Release the lock before resolving the position.
+        let on_jump = bucket.get().served.on_jump.clone();
+        let Some(position) = on_jump.and_then(|on_jump| {
+            let (path, line, column) = parse_jump(&url)?;
+            on_jump(Path::new(&path), line, column)
+        }) else {
+            return req.respond(Response::empty(StatusCode(404)));
+        };
+        bucket.update(|live| {
+            live.events
+                .push(Event { name: "scroll", data: position_json(&position) })
+        });
+        return req.respond(Response::empty(StatusCode(204)));
+    }
+
+    let live = bucket.get();
+    let version = live.served.version;
+    let Some(body) = (live.served.router)(path.as_ref()) else {
         return req.respond(Response::empty(StatusCode(404)));
     };
 
-    handle_body(req, reload, body)
+    handle_body(req, reload.then_some(version), body)
 }
 
 /// Handles for the `/` route. Serves the compiled HTML.
-fn handle_body(req: Request, reload: bool, mut body: HttpBody) -> io::Result<()> {
+///
+/// If live reload is enabled, `version` is the version of the served contents.
+fn handle_body(req: Request, version: Option<u64>, mut body: HttpBody) -> io::Result<()> {
     let (data, mime) = match &mut body {
         HttpBody::Html(html) => {
-            if reload {
-                inject_live_reload_script(html);
+            if let Some(version) = version {
+                inject_live_reload_script(html, version);
             }
             (html.as_bytes(), Some("text/html"))
         }
@@ -185,17 +521,28 @@ fn handle_body(req: Request, reload: bool, mut body: HttpBody) -> io::Result<()>
 }
 
 /// Handler for the `/__events` route.
-fn handle_events(req: Request, bucket: Arc<RouterBucket>) -> io::Result<()> {
+///
+/// `version` is the version of the contents that the connecting page
+/// displays.
+fn handle_events(
+    req: Request,
+    bucket: Arc<RouterBucket>,
+    version: Option<u64>,
+) -> io::Result<()> {
     std::thread::spawn(move || {
         // When this returns an error, the client is disconnected and we can
         // terminate the thread.
-        let _ = handle_events_blocking(req, &bucket);
+        let _ = handle_events_blocking(req, &bucket, version);
     });
     Ok(())
 }
 
 /// Event stream for the `/events` route.
-fn handle_events_blocking(req: Request, bucket: &RouterBucket) -> io::Result<()> {
+fn handle_events_blocking(
+    req: Request,
+    bucket: &RouterBucket,
+    version: Option<u64>,
+) -> io::Result<()> {
     let mut writer = req.into_writer();
     let writer: &mut dyn Write = &mut *writer;
 
@@ -208,21 +555,125 @@ fn handle_events_blocking(req: Request, bucket: &RouterBucket) -> io::Result<()>
     write!(writer, "\r\n")?;
     writer.flush()?;
 
+    // The contents may have changed between serving the page and connecting
+    // to the events. Such a page would miss the update, so it reloads.
+    let (mut seen, behind) = {
+        let live = bucket.get();
+        let behind = version.is_some_and(|version| version < live.served.version);
+        (live.events.count, behind)
+    };
+    if behind {
+        write_events(writer, &[Event::reload()])?;
+    }
+
     // If the user closes the browser tab, this loop will terminate once it
     // tries to write to the dead socket for the first time.
     loop {
-        bucket.wait();
-        // Trigger a server-sent event. The browser is listening to it via
-        // an `EventSource` listener` (see `inject_script`).
-        write!(writer, "event: reload\ndata:\n\n")?;
-        writer.flush()?;
+        let events = {
+            let live = bucket.wait_while(|live| live.events.count == seen);
+            let events = live.events.since(seen);
+            seen = live.events.count;
+            events
+        };
+
+        write_events(writer, &events)?;
+    }
+}
+
+/// Triggers server-sent events. The browser is listening to them via
+/// `EventSource` listeners (see `LIVE_RELOAD_SCRIPT`).
+fn write_events(writer: &mut dyn Write, events: &[Event]) -> io::Result<()> {
+    for Event { name, data } in events {
+        write!(writer, "event: {name}\ndata:{data}\n\n")?;
+    }
+    writer.flush()
+}
+
+/// Whether a request is a `POST` request that doesn't come from a page on
+/// another website.
+///
+/// Browsers send the `Origin` header with all `POST` requests, so it can be
+/// compared to the `Host` the request was sent to. Requests from other tools,
+/// like an editor, don't have an `Origin` header.
+fn is_same_origin_post(req: &Request) -> bool {
+    let header = |name: &'static str| {
+        req.headers()
+            .iter()
+            .find(|header| header.field.equiv(name))
+            .map(|header| header.value.as_str())
+    };
+    *req.method() == Method::Post
+        && header("Origin").is_none_or(|origin| {
+            origin
+                .strip_prefix("http://")
+                .is_some_and(|host| Some(host) == header("Host"))
+        })
+}
+
+/// Parses the version of the contents that a page displays from the query of
+/// an `/__events` request.
+fn parse_version(url: &url::Url) -> Option<u64> {
+    url.query_pairs()
+        .find(|(key, _)| key == "version")
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// Parses the position of a click in the paged viewer from the query of a
+/// `/__click` request. The coordinates are given in points.
+fn parse_click(url: &url::Url) -> Option<PagedPosition> {
+    let (mut page, mut x, mut y) = (None, None, None);
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "page" => page = value.parse::<NonZeroUsize>().ok(),
+            "x" => x = value.parse::<f64>().ok(),
+            "y" => y = value.parse::<f64>().ok(),
+            _ => {}
+        }
     }
+    let point = Point::new(Abs::pt(x?), Abs::pt(y?));
+    Some(PagedPosition { page: page?, point })
 }
 
-/// Injects the live reload script into a string of HTML.
-fn inject_live_reload_script(html: &mut String) {
+/// Parses the source position from the query of a `/__jump` request, which
+/// consists of a file path and a one-based line and column.
+fn parse_jump(url: &url::Url) -> Option<(String, usize, usize)> {
+    let (mut file, mut line, mut column) = (None, None, None);
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "file" => file = Some(value.into_owned()),
+            "line" => line = value.parse::<usize>().ok(),
+            "column" => column = value.parse::<usize>().ok(),
+            _ => {}
+        }
+    }
+    Some((file?, line?, column.unwrap_or(1)))
+}
+
+/// Encodes a position in a served document for a `scroll` event.
+fn position_json(position: &DocumentPosition) -> String {
+    match position {
+        DocumentPosition::Paged(position) => json!({
+            "page": position.page.get(),
+            "x": position.point.x.to_pt(),
+            "y": position.point.y.to_pt(),
+        }),
+        DocumentPosition::Html(position) => json!({
+            "element": position.element().collect::<Vec<_>>(),
+        }),
+    }
+    .to_string()
+}
+
+/// Escapes text for use in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Injects the live reload script for a version of the served contents into a
+/// string of HTML.
+fn inject_live_reload_script(html: &mut String, version: u64) {
     let pos = html.rfind("</body>").unwrap_or(html.len());
-    html.insert_str(pos, LIVE_RELOAD_SCRIPT);
+    html.insert_str(pos, &LIVE_RELOAD_SCRIPT.replace("{VERSION}", &version.to_string()));
 }
 
 /// Selects a MIME type for a request based on path and data.
@@ -255,16 +706,19 @@ impl<T> Bucket<T> {
         self.mutex.lock()
     }
 
-    /// Puts new data into the bucket and notifies everyone who's currently
-    /// [waiting](Self::wait).
-    fn put(&self, data: T) {
-        *self.mutex.lock() = data;
+    /// Modifies the data in the bucket and notifies everyone who's currently
+    /// [waiting](Self::wait_while).
+    fn update(&self, f: impl FnOnce(&mut T)) {
+        f(&mut self.mutex.lock());
         self.condvar.notify_all();
     }
 
-    /// Waits for new data in the bucket.
-    fn wait(&self) {
-        self.condvar.wait(&mut self.mutex.lock());
+    /// Waits for updates of the data in the bucket as long as the condition
+    /// holds and then retrieves the data.
+    fn wait_while(&self, mut condition: impl FnMut(&mut T) -> bool) -> MutexGuard<'_, T> {
+        let mut guard = self.mutex.lock();
+        self.condvar.wait_while(&mut guard, &mut condition);
+        guard
     }
 }
 
@@ -300,11 +754,238 @@ const PLACEHOLDER_HTML: &str = "\
 </html>
 ";
 
-/// Reloads the page whenever it receives a "reload" server-sent event
-/// on the `/__events` route.
-const LIVE_RELOAD_SCRIPT: &str = "\
-<script>\
-  new EventSource(\"/__events\")\
-    .addEventListener(\"reload\", () => location.reload())\
-</script>\
+/// Displays the pages of a paged document as SVGs.
+///
+/// Clicks on a page are sent to the `/__click` route.
+const PAGED_VIEWER_HTML: &str = "\
+<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset=\"utf-8\">
+    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+    <title>{TITLE}</title>
+    <style>
+      body {
+        margin: 0;
+        padding: 16px;
+        background: #eff0f3;
+      }
+
+      .page {
+        width: fit-content;
+        margin: 0 auto 16px;
+        background: white;
+        box-shadow: 0 1px 4px rgba(0, 0, 0, 0.2);
+      }
+
+      .page > svg {
+        display: block;
+        max-width: calc(100vw - 32px);
+        height: auto;
+      }
+    </style>
+  </head>
+  <body>
+    {PAGES}
+    <script>
+      document.addEventListener(\"click\", (event) => {
+        const page = event.target.closest(\".page\");
+        if (!page || event.target.closest(\"a\")) return;
+        const svg = page.querySelector(\"svg\");
+        const rect = svg.getBoundingClientRect();
+        const box = svg.viewBox.baseVal;
+        const x = ((event.clientX - rect.left) / rect.width) * box.width;
+        const y = ((event.clientY - rect.top) / rect.height) * box.height;
+        const query = `page=${page.dataset.page}&x=${x}&y=${y}`;
+        fetch(`/__click?${query}`, { method: \"POST\" });
+      });
+    </script>
+  </body>
+</html>
 ";
+
+/// Keeps the page up to date through the events on the `/__events` route.
+///
+/// The page connects with the version it was served with. If it is behind
+/// already, it immediately receives a `reload` event.
+/// - On a `reload` event, the page reloads and restores its scroll position.
+///   If the event lists the changed paths of a bundle, the page only reloads
+///   if it is affected, i.e. if it or a non-HTML file changed.
+/// - On a `patch` event, the page replaces the changed elements in its
+///   `<body>`. If it doesn't display the version that the patches apply to,
+///   it reloads instead.
+/// - On a `scroll` event, the page scrolls to the given position.
+const LIVE_RELOAD_SCRIPT: &str = "
+<script>
+  (() => {
+    let version = {VERSION};
+    const key = `typst-scroll:${location.pathname}`;
+    const saved = sessionStorage.getItem(key);
+    if (saved) {
+      sessionStorage.removeItem(key);
+      window.scrollTo(...JSON.parse(saved));
+    }
+
+    const reload = () => {
+      sessionStorage.setItem(key, JSON.stringify([scrollX, scrollY]));
+      location.reload();
+    };
+
+    const events = new EventSource(`/__events?version=${version}`);
+    events.addEventListener(\"reload\", (event) => {
+      if (!event.data) return reload();
+      const changes = JSON.parse(event.data);
+      const page = location.pathname.replace(/\\/$/, \"/index.html\");
+      const paths = [...changes.added, ...changes.changed, ...changes.removed];
+      if (paths.some((path) => path === page || !path.endsWith(\".html\"))) {
+        reload();
+      }
+    });
+
+    events.addEventListener(\"patch\", (event) => {
+      const { from, to, patches } = JSON.parse(event.data);
+      if (from !== version) return reload();
+      for (const patch of patches) {
+        let node = document.body;
+        for (const i of patch.path) node = node && node.children[i];
+        if (!node) return reload();
+        if (patch.inner) node.innerHTML = patch.html;
+        else node.outerHTML = patch.html;
+      }
+      version = to;
+    });
+
+    events.addEventListener(\"scroll\", (event) => {
+      const position = JSON.parse(event.data);
+      if (position.page) {
+        const selector = `.page[data-page=\"${position.page}\"] > svg`;
+        const svg = document.querySelector(selector);
+        if (!svg) return;
+        const rect = svg.getBoundingClientRect();
+        const y = (position.y / svg.viewBox.baseVal.height) * rect.height;
+        const top = scrollY + rect.top + y - innerHeight / 2;
+        window.scrollTo({ top, behavior: \"smooth\" });
+      } else {
+        let node = document.documentElement;
+        for (const i of position.element) node = node && node.childNodes[i];
+        if (node && node.nodeType !== Node.ELEMENT_NODE) node = node.parentElement;
+        node?.scrollIntoView({ block: \"center\", behavior: \"smooth\" });
+      }
+    });
+  })();
+</script>
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(events: &Events, seen: u64) -> Vec<&'static str> {
+        events.since(seen).iter().map(|event| event.name).collect()
+    }
+
+    /// A server that isn't bound to a port.
+    fn server() -> HttpServer {
+        HttpServer {
+            addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+            bucket: Arc::new(Bucket::new(Live {
+                served: Served {
+                    router: html_single_fs(String::new()),
+                    version: 0,
+                    on_click: None,
+                    on_jump: None,
+                },
+                events: Events::default(),
+            })),
+            previous: Mutex::new(Previous::None),
+        }
+    }
+
+    fn set_pages(server: &HttpServer, pages: &[&str], on_click: Option<ClickHandler>) {
+        let pages = pages.iter().map(|page| page.to_string()).collect();
+        let on_click = on_click.map(|f| move |position| f(position));
+        let on_jump = None::<fn(&Path, usize, usize) -> Option<DocumentPosition>>;
+        server.set_pages("a <b>", pages, on_click, on_jump);
+    }
+
+    fn last_event(server: &HttpServer) -> Event {
+        server.bucket.get().events.recent.back().unwrap().clone()
+    }
+
+    #[test]
+    fn test_events_are_queued() {
+        let mut events = Events::default();
+        let event = |name| Event { name, data: String::new() };
+
+        // A scroll event doesn't replace a patch that was not sent yet.
+        events.push(event("patch"));
+        events.push(event("scroll"));
+        assert_eq!(names(&events, 0), ["patch", "scroll"]);
+        assert_eq!(names(&events, 1), ["scroll"]);
+        assert!(names(&events, 2).is_empty());
+
+        // Browsers that missed dropped events reload.
+        for _ in 0..Events::CAPACITY {
+            events.push(event("patch"));
+        }
+        assert_eq!(names(&events, 1), ["reload"]);
+        assert_eq!(names(&events, 2).len(), Events::CAPACITY);
+    }
+
+    #[test]
+    fn test_parse_version() {
+        let url = |s| url::Url::parse(s).unwrap();
+        assert_eq!(parse_version(&url("http://localhost/__events?version=12")), Some(12));
+        assert_eq!(parse_version(&url("http://localhost/__events?version=x")), None);
+        assert_eq!(parse_version(&url("http://localhost/__events")), None);
+    }
+
+    #[test]
+    fn test_set_pages() {
+        let server = server();
+        set_pages(&server, &["<svg>1</svg>", "<svg>2</svg>"], None);
+        assert_eq!(last_event(&server).name, "reload");
+        {
+            let live = server.bucket.get();
+            assert_eq!(live.served.version, 1);
+            let Some(HttpBody::Html(html)) = (live.served.router)("/") else {
+                panic!("expected HTML at `/`");
+            };
+            assert!(html.contains("<title>a &lt;b&gt;</title>"));
+            assert!(
+                html.contains(r#"<div class="page" data-page="2"><svg>2</svg></div>"#)
+            );
+            assert!(live.served.on_click.is_none());
+        }
+
+        // Only the changed page is patched.
+        set_pages(&server, &["<svg>1</svg>", "<svg>3</svg>"], None);
+        let event = last_event(&server);
+        assert_eq!(event.name, "patch");
+        let data: serde_json::Value = serde_json::from_str(&event.data).unwrap();
+        assert_eq!(
+            data,
+            json!({
+                "from": 1,
+                "to": 2,
+                "patches": [{ "path": [1], "inner": true, "html": "<svg>3</svg>" }],
+            })
+        );
+
+        // A different number of pages requires a reload.
+        let clicked = Arc::new(Mutex::new(None));
+        let clicked2 = clicked.clone();
+        let on_click: ClickHandler = Arc::new(move |position: PagedPosition| {
+            *clicked2.lock() = Some(position.page)
+        });
+        set_pages(&server, &["<svg>1</svg>"], Some(on_click));
+        assert_eq!(last_event(&server).name, "reload");
+        assert_eq!(server.bucket.get().served.version, 3);
+
+        // Clicks are forwarded to the handler.
+        let position = PagedPosition { page: NonZeroUsize::MIN, point: Point::zero() };
+        let on_click = server.bucket.get().served.on_click.clone().unwrap();
+        on_click(position);
+        assert_eq!(*clicked.lock(), Some(NonZeroUsize::MIN));
+    }
+}
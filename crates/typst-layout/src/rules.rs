@@ -18,11 +18,13 @@ use typst_library::math::EquationElem;
 use typst_library::model::{
     Attribution, BibliographyElem, CiteElem, CiteGroup, CslIndentElem, CslLightElem,
     Destination, DirectLinkElem, DividerElem, EmphElem, EnumElem, FigureCaption,
-    FigureElem, FootnoteElem, FootnoteEntry, HeadingElem, LinkElem, LinkMarker, ListElem,
-    OutlineElem, OutlineEntry, ParElem, ParbreakElem, QuoteElem, RefElem, StrongElem,
-    TableCell, TableElem, TermsElem, TitleElem, Works,
+    FigureElem, FootnoteContainer, FootnoteElem, FootnoteEntry, HeadingElem, LinkElem,
+    LinkMarker, ListElem, OutlineElem, OutlineEntry, ParElem, ParbreakElem, QuoteElem,
+    RefElem, StrongElem, TableCell, TableElem, TermsElem, TitleElem, Works,
+};
+use typst_library::pdf::{
+    ArtifactElem, ArtifactKind, AttachElem, NoteElem, PdfMarkerTag,
 };
-use typst_library::pdf::{ArtifactElem, ArtifactKind, AttachElem, PdfMarkerTag};
 use typst_library::text::{
     DecoLine, Decoration, HighlightElem, ItalicToggle, LinebreakElem, LocalName,
     OverlineElem, RawElem, RawLine, ScriptKind, ShiftSettings, Smallcaps, SmallcapsElem,
@@ -56,6 +58,7 @@ pub fn register(rules: &mut NativeRuleMap) {
     rules.register(Paged, QUOTE_RULE);
     rules.register(Paged, FOOTNOTE_RULE);
     rules.register(Paged, FOOTNOTE_ENTRY_RULE);
+    rules.register(Paged, FOOTNOTE_CONTAINER_RULE);
     rules.register(Paged, OUTLINE_RULE);
     rules.register(Paged, OUTLINE_ENTRY_RULE);
     rules.register(Paged, REF_RULE);
@@ -108,6 +111,7 @@ pub fn register(rules: &mut NativeRuleMap) {
     // PDF.
     rules.register(Paged, ATTACH_RULE);
     rules.register(Paged, ARTIFACT_RULE);
+    rules.register(Paged, NOTE_RULE);
     rules.register(Paged, PDF_MARKER_TAG_RULE);
 }
 
@@ -401,6 +405,8 @@ const FOOTNOTE_ENTRY_RULE: ShowFn<FootnoteEntry> = |elem, engine, styles| {
     ]))
 };
 
+const FOOTNOTE_CONTAINER_RULE: ShowFn<FootnoteContainer> = |_, _, _| Ok(Content::empty());
+
 const OUTLINE_RULE: ShowFn<OutlineElem> = |elem, engine, styles| {
     let title = elem.realize_title(styles);
     let entries = elem.realize_flat(engine, styles)?;
@@ -815,4 +821,6 @@ const ATTACH_RULE: ShowFn<AttachElem> = |_, _, _| Ok(Content::empty());
 
 const ARTIFACT_RULE: ShowFn<ArtifactElem> = |elem, _, _| Ok(elem.body.clone());
 
+const NOTE_RULE: ShowFn<NoteElem> = |_, _, _| Ok(Content::empty());
+
 const PDF_MARKER_TAG_RULE: ShowFn<PdfMarkerTag> = |elem, _, _| Ok(elem.body.clone());
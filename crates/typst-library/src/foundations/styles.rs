@@ -18,6 +18,9 @@ use crate::foundations::{
     RefableProperty, Repr, Selector, SettableProperty, Target, cast, ty,
 };
 use crate::introspection::TagElem;
+use crate::model::{
+    AssetElem, FeedElem, SearchIndexElem, SitemapElem, asset_unsupported,
+};
 
 /// A list of style properties.
 #[ty(cast)]
@@ -1027,7 +1030,10 @@ impl NativeRuleMap {
         }
 
         for target in [Target::Paged, Target::Html] {
-            rules.register(target, crate::model::ASSET_UNSUPPORTED_RULE);
+            rules.register(target, asset_unsupported::<AssetElem>());
+            rules.register(target, asset_unsupported::<SitemapElem>());
+            rules.register(target, asset_unsupported::<FeedElem>());
+            rules.register(target, asset_unsupported::<SearchIndexElem>());
             rules.register(target, crate::model::DOCUMENT_UNSUPPORTED_RULE);
         }
 
@@ -4,7 +4,7 @@ use std::str::FromStr;
 
 use typst_utils::{NonZeroExt, Scalar, singleton};
 
-use crate::diag::{HintedStrResult, SourceResult, bail};
+use crate::diag::{HintedStrResult, SourceResult, StrResult, bail};
 use crate::engine::Engine;
 use crate::foundations::{
     Args, Cast, CastInfo, Construct, Content, Dict, Fold, FromValue, IntoValue,
@@ -758,7 +758,7 @@ cast! {
 }
 
 /// A list of page ranges to be exported.
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct PageRanges(Vec<PageRange>);
 
 /// A range of pages to export.
@@ -773,6 +773,24 @@ impl PageRanges {
         Self(ranges)
     }
 
+    /// The individual ranges.
+    pub fn ranges(&self) -> &[PageRange] {
+        &self.0
+    }
+
+    /// Parses comma-separated page numbers and ranges like `1-3,5,8-`.
+    pub fn parse(text: &str) -> StrResult<Self> {
+        text.split(',')
+            .map(Self::parse_range)
+            .collect::<StrResult<_>>()
+            .map(Self)
+    }
+
+    /// Parses a single page number or range like `4`, `1-3`, `5-`, or `-2`.
+    pub fn parse_range(text: &str) -> StrResult<PageRange> {
+        typst_utils::parse_page_range(text).map_err(Into::into)
+    }
+
     /// Check if a page, given its number, should be included when exporting the
     /// document while restricting the exported pages to these page ranges.
     /// This is the one-indexed version of 'includes_page_index'.
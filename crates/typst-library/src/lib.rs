@@ -274,12 +274,13 @@ pub enum Feature {
     Html,
     Bundle,
     A11yExtras,
+    Docx,
 }
 
 impl Feature {
     /// Iterates over all available features.
     pub fn all() -> impl Iterator<Item = Self> {
-        [Self::Html, Self::Bundle, Self::A11yExtras].into_iter()
+        [Self::Html, Self::Bundle, Self::A11yExtras, Self::Docx].into_iter()
     }
 }
 
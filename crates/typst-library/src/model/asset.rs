@@ -1,7 +1,10 @@
-use typst_macros::elem;
+use ecow::EcoString;
+use typst_macros::{elem, scope};
 
 use crate::diag::bail;
-use crate::foundations::{BundlePath, Bytes, ShowFn, Str, cast};
+use crate::foundations::{
+    BundlePath, Bytes, NativeElement, Selector, ShowFn, Smart, Str, cast,
+};
 use crate::introspection::Locatable;
 
 /// Adds a custom file to a bundle.
@@ -49,8 +52,26 @@ use crate::introspection::Locatable;
 /// }
 /// ```
 ///
+/// # Generated assets
+/// Some files that websites typically need are derived from all of the
+/// bundle's documents. Typst can generate them for you with
+/// @asset.sitemap[`asset.sitemap`], @asset.feed[`asset.feed`], and
+/// @asset.search-index[`asset.search-index`]. Since their contents depend on
+/// the compiled documents, they are created once all documents are compiled.
+///
+/// ```typ
+/// #asset.sitemap("sitemap.xml", base: "https://example.com/")
+/// #asset.feed(
+///   "feed.xml",
+///   base: "https://example.com/",
+///   title: "My Blog",
+///   documents: <post>,
+/// )
+/// #asset.search-index("search.json")
+/// ```
+///
 /// This function may only be used in the @reference:bundle[bundle] target.
-#[elem(Locatable)]
+#[elem(scope, Locatable)]
 pub struct AssetElem {
     /// The path in the bundle at which the asset will be placed.
     ///
@@ -66,14 +87,143 @@ pub struct AssetElem {
     pub data: AssetData,
 }
 
-pub const ASSET_UNSUPPORTED_RULE: ShowFn<AssetElem> = |elem, _, _| {
-    bail!(
-        elem.span(),
-        "assets are only supported in the bundle target";
-        // TODO: Support for CLI-specific hints would be nice.
-        hint: "try enabling the bundle target";
-    )
-};
+#[scope]
+impl AssetElem {
+    #[elem]
+    type SitemapElem;
+
+    #[elem]
+    type FeedElem;
+
+    #[elem]
+    type SearchIndexElem;
+}
+
+/// Adds a sitemap of the bundle's documents.
+///
+/// The sitemap lists the URLs of the selected documents in the
+/// [sitemap protocol](https://www.sitemaps.org/protocol.html), which helps
+/// search engines to discover all pages of a website. A document's date is
+/// included as its last modification date.
+///
+/// ```typ
+/// #asset.sitemap("sitemap.xml", base: "https://example.com/")
+/// ```
+#[elem(Locatable)]
+pub struct SitemapElem {
+    /// The path in the bundle at which the sitemap will be placed.
+    #[required]
+    pub path: BundlePath,
+
+    /// The URL at which the bundle is published, like
+    /// `{"https://example.com/"}`.
+    ///
+    /// Sitemaps must contain absolute URLs, so this is required. The paths of
+    /// the documents are appended to it.
+    pub base: Option<EcoString>,
+
+    /// Which documents to list.
+    ///
+    /// If `{auto}`, all documents are listed. Otherwise, only documents
+    /// matching the selector are listed, e.g. ones with a certain label.
+    pub documents: Smart<Selector>,
+}
+
+/// Adds an Atom news feed with the bundle's documents.
+///
+/// Each selected document becomes an entry in the
+/// [Atom feed](https://www.rfc-editor.org/rfc/rfc4287), with its title,
+/// authors, description, and date. The entries are sorted from newest to
+/// oldest. Since feed readers use the date to detect new entries, all
+/// selected documents must have a date.
+///
+/// ```typ
+/// #asset.feed(
+///   "feed.xml",
+///   base: "https://example.com/",
+///   title: "My Blog",
+///   documents: <post>,
+/// )
+///
+/// #document(
+///   "posts/hello.html",
+///   title: [Hello],
+///   date: datetime(year: 2025, month: 3, day: 14),
+/// )[...] <post>
+/// ```
+#[elem(Locatable)]
+pub struct FeedElem {
+    /// The path in the bundle at which the feed will be placed.
+    #[required]
+    pub path: BundlePath,
+
+    /// The URL at which the bundle is published, like
+    /// `{"https://example.com/"}`.
+    ///
+    /// Feeds must contain absolute URLs, so this is required. The paths of
+    /// the documents are appended to it.
+    pub base: Option<EcoString>,
+
+    /// The title of the feed, which is required.
+    pub title: Option<EcoString>,
+
+    /// Which documents to include as entries.
+    ///
+    /// If `{auto}`, all documents are included. Otherwise, only documents
+    /// matching the selector are included, e.g. ones with a certain label.
+    pub documents: Smart<Selector>,
+}
+
+/// Adds a JSON index for client-side full-text search.
+///
+/// The index contains an entry for each selected HTML document with its path
+/// and title. The text of the document is split into sections at each
+/// heading. A section contains the text of the heading, the ID of the
+/// heading element (if it has one), and the text of all paragraphs up until
+/// the next heading:
+///
+/// ```json
+/// [
+///   {
+///     "path": "guide.html",
+///     "title": "Guide",
+///     "sections": [
+///       { "heading": null, "anchor": null, "text": "..." },
+///       { "heading": "Installation", "anchor": "install", "text": "..." }
+///     ]
+///   }
+/// ]
+/// ```
+///
+/// Documents in other formats than HTML are not indexed.
+///
+/// ```typ
+/// #asset.search-index("search.json")
+/// ```
+#[elem(Locatable)]
+pub struct SearchIndexElem {
+    /// The path in the bundle at which the index will be placed.
+    #[required]
+    pub path: BundlePath,
+
+    /// Which documents to index.
+    ///
+    /// If `{auto}`, all HTML documents are indexed. Otherwise, only HTML
+    /// documents matching the selector are indexed.
+    pub documents: Smart<Selector>,
+}
+
+/// Creates a rule that errors for an asset outside of the bundle target.
+pub fn asset_unsupported<T: NativeElement>() -> ShowFn<T> {
+    |elem, _, _| {
+        bail!(
+            elem.span(),
+            "assets are only supported in the bundle target";
+            // TODO: Support for CLI-specific hints would be nice.
+            hint: "try enabling the bundle target";
+        )
+    }
+}
 
 /// The raw data for an asset.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -1,14 +1,21 @@
-use ecow::EcoString;
+use std::num::NonZeroUsize;
+
+use ecow::{EcoString, eco_format};
 use typst_syntax::VirtualPath;
+use typst_utils::Scalar;
 
 use crate::diag::{HintedStrResult, bail, error};
 use crate::foundations::{
-    Array, BundlePath, Cast, Content, Datetime, OneOrMultiple, Packed, ShowFn, ShowSet,
-    Smart, StyleChain, Styles, Target, Value, cast, elem,
+    Array, BundlePath, Cast, CastInfo, Content, Datetime, Dict, Fold, FromValue,
+    IntoValue, OneOrMultiple, Packed, Reflect, ShowFn, ShowSet, Smart, StyleChain,
+    Styles, Target, Value, cast, elem,
 };
 use crate::introspection::Locatable;
+use crate::layout::PageRanges;
 use crate::text::{Locale, TextElem};
 
+pub use typst_utils::PdfStandard;
+
 /// Manages metadata and is used to add a document file to a bundle.
 ///
 /// = Metadata <metadata>
@@ -179,6 +186,39 @@ pub struct DocumentElem {
     /// something other than `{auto}`.
     pub date: Smart<Option<Datetime>>,
 
+    /// Format-specific options for exporting the document.
+    ///
+    /// By default, all documents of a format are exported with the same
+    /// options, which are configured for the whole bundle (e.g. through CLI
+    /// flags). With this dictionary, individual documents can deviate from
+    /// them. It accepts the following keys:
+    ///
+    /// - `standards`: One or multiple PDF standards that Typst will enforce
+    ///   conformance with, like `{"a-2b"}` or `{"ua-1"}`. Only for PDF
+    ///   documents.
+    /// - `tagged`: Whether to write a tagged PDF. Only for PDF documents.
+    /// - `pages`: Which pages to export, as a string of comma-separated page
+    ///   numbers and ranges like `{"1-3,5,8-"}`. Only for PDF documents.
+    /// - `ppi`: The resolution in pixels per inch. Only for PNG, JPEG, and
+    ///   WebP documents.
+    /// - `pretty`: Whether to format the output in a human-readable way. Only
+    ///   for HTML, PDF, and SVG documents.
+    ///
+    /// Setting an option that does not apply to the document's format is an
+    /// error.
+    ///
+    /// ```typ
+    /// #document("thesis.pdf", export: (standards: "a-2b"), thesis)
+    /// #document("draft.pdf", thesis)
+    ///
+    /// #document("preview.png", export: (ppi: 72), cover)
+    /// #document("print.png", export: (ppi: 300), cover)
+    /// ```
+    ///
+    /// This property is only supported in the @reference:bundle[bundle] target.
+    #[fold]
+    pub export: ExportOptions,
+
     /// The content that makes up the document.
     ///
     /// This property is only supported in the @reference:bundle[bundle] target.
@@ -212,6 +252,8 @@ fn determine_format_from_path(path: &VirtualPath) -> Option<DocumentFormat> {
         "pdf" => Some(PagedFormat::Pdf.into()),
         "svg" => Some(PagedFormat::Svg.into()),
         "png" => Some(PagedFormat::Png.into()),
+        "jpg" | "jpeg" => Some(PagedFormat::Jpg.into()),
+        "webp" => Some(PagedFormat::Webp.into()),
         "html" => Some(DocumentFormat::Html),
         _ => None,
     }
@@ -296,6 +338,149 @@ pub enum PagedFormat {
     Png,
     /// The vector graphics format of the web.
     Svg,
+    /// Lossy raster format for photos and thumbnails. Does not support
+    /// transparency.
+    Jpg,
+    /// Raster format of the web. Typst writes losslessly compressed WebP
+    /// files.
+    Webp,
+}
+
+/// Format-specific options for exporting a document in a bundle.
+///
+/// Unset options fall back to the options of the whole bundle.
+#[derive(Debug, Default, Clone, PartialEq, Hash)]
+pub struct ExportOptions {
+    /// PDF standards to enforce conformance with.
+    pub standards: Option<Vec<PdfStandard>>,
+    /// Whether to write a tagged PDF.
+    pub tagged: Option<bool>,
+    /// Which pages to export.
+    pub pages: Option<PageRanges>,
+    /// The resolution of raster images in pixels per inch.
+    pub ppi: Option<Scalar>,
+    /// Whether to format the output in a human-readable way.
+    pub pretty: Option<bool>,
+}
+
+impl Fold for ExportOptions {
+    fn fold(self, outer: Self) -> Self {
+        Self {
+            standards: self.standards.or(outer.standards),
+            tagged: self.tagged.or(outer.tagged),
+            pages: self.pages.or(outer.pages),
+            ppi: self.ppi.or(outer.ppi),
+            pretty: self.pretty.or(outer.pretty),
+        }
+    }
+}
+
+cast! {
+    ExportOptions,
+    self => {
+        let mut dict = Dict::new();
+        if let Some(standards) = self.standards {
+            dict.insert("standards".into(), standards.into_value());
+        }
+        if let Some(tagged) = self.tagged {
+            dict.insert("tagged".into(), tagged.into_value());
+        }
+        if let Some(pages) = &self.pages {
+            dict.insert("pages".into(), format_page_ranges(pages).into_value());
+        }
+        if let Some(ppi) = self.ppi {
+            dict.insert("ppi".into(), ppi.get().into_value());
+        }
+        if let Some(pretty) = self.pretty {
+            dict.insert("pretty".into(), pretty.into_value());
+        }
+        Value::Dict(dict)
+    },
+    mut dict: Dict => {
+        let standards = dict
+            .take("standards")
+            .ok()
+            .map(|v| v.cast::<OneOrMultiple<PdfStandard>>().map(|v| v.0))
+            .transpose()?;
+        let tagged = dict.take("tagged").ok().map(|v| v.cast()).transpose()?;
+        let pages = match dict.take("pages").ok() {
+            Some(v) => Some(PageRanges::parse(&v.cast::<EcoString>()?)?),
+            None => None,
+        };
+        let ppi = match dict.take("ppi").ok() {
+            Some(v) => {
+                let ppi: f64 = v.cast()?;
+                if !(ppi > 0.0 && ppi.is_finite()) {
+                    bail!("ppi must be positive");
+                }
+                Some(Scalar::new(ppi))
+            }
+            None => None,
+        };
+        let pretty = dict.take("pretty").ok().map(|v| v.cast()).transpose()?;
+        dict.finish(&["standards", "tagged", "pages", "ppi", "pretty"])?;
+        Self { standards, tagged, pages, ppi, pretty }
+    },
+}
+
+/// Formats page ranges in the syntax accepted by [`PageRanges::parse`].
+fn format_page_ranges(ranges: &PageRanges) -> EcoString {
+    let page = |page: &Option<NonZeroUsize>| match page {
+        Some(page) => eco_format!("{page}"),
+        None => EcoString::new(),
+    };
+
+    let mut text = EcoString::new();
+    for (i, range) in ranges.ranges().iter().enumerate() {
+        if i > 0 {
+            text.push(',');
+        }
+        if range.start().is_some() && range.start() == range.end() {
+            text.push_str(&page(range.start()));
+        } else {
+            text.push_str(&eco_format!("{}-{}", page(range.start()), page(range.end())));
+        }
+    }
+    text
+}
+
+// The standards are defined in `typst-utils` so that the CLI can share them.
+impl Reflect for PdfStandard {
+    fn input() -> CastInfo {
+        CastInfo::Union(
+            PdfStandard::ALL
+                .into_iter()
+                .map(|standard| {
+                    CastInfo::Value(standard.name().into_value(), standard.display_name())
+                })
+                .collect(),
+        )
+    }
+
+    fn output() -> CastInfo {
+        Self::input()
+    }
+
+    fn castable(value: &Value) -> bool {
+        matches!(value, Value::Str(name) if name.parse::<PdfStandard>().is_ok())
+    }
+}
+
+impl IntoValue for PdfStandard {
+    fn into_value(self) -> Value {
+        self.name().into_value()
+    }
+}
+
+impl FromValue for PdfStandard {
+    fn from_value(value: Value) -> HintedStrResult<Self> {
+        if let Value::Str(name) = &value
+            && let Ok(standard) = name.parse()
+        {
+            return Ok(standard);
+        }
+        Err(Self::error(&value))
+    }
 }
 
 /// A list of authors.
@@ -89,6 +89,9 @@ pub struct FootnoteElem {
 impl FootnoteElem {
     #[elem]
     type FootnoteEntry;
+
+    #[elem]
+    type FootnoteContainer;
 }
 
 impl LocalName for Packed<FootnoteElem> {
@@ -335,10 +338,40 @@ cast! {
     v: Content => v.unpack::<Self>().unwrap_or_else(Self::with_content)
 }
 
-/// In HTML export, this is inserted at the end of the body to display
-/// footnotes. In the future, we can expose this to allow customizing where the
-/// footnotes appear. It could also be exposed for paged export.
-#[elem(Locatable)]
+/// The listing of footnotes in HTML export.
+///
+/// By default, footnotes are listed at the end of the document's body. With
+/// this function, you can decide where they are listed instead. It lists all
+/// footnotes since the previous `footnote.entries` call, so you can use it to
+/// list the footnotes at the end of each chapter or section. Footnotes after
+/// the last call are still listed at the end of the body.
+///
+/// ```typ
+/// = Introduction
+/// Typst is a typesetting system.#footnote[And a markup language.]
+/// #footnote.entries()
+///
+/// = Installation
+/// Typst is available for all major platforms.#footnote[And the web.]
+/// #footnote.entries()
+/// ```
+///
+/// If you create the `body` or `html` element yourself with @html.elem,
+/// footnotes are not listed automatically. In this case, you must place the
+/// listing within your custom element with this function.
+///
+/// ```typ
+/// #html.elem("body")[
+///   #html.elem("main")[
+///     Hello#footnote[World]
+///   ]
+///   #html.elem("aside", footnote.entries())
+/// ]
+/// ```
+///
+/// In paged export, footnotes are always listed at the bottom of the page and
+/// this function has no effect.
+#[elem(name = "entries", title = "Footnote Entries", Locatable)]
 pub struct FootnoteContainer {}
 
 impl FootnoteContainer {
@@ -349,8 +382,8 @@ impl FootnoteContainer {
 }
 
 /// This is an empty element inserted by the HTML footnote rule to indicate the
-/// presence of the default footnote rule. It's only used by the error in
-/// `FootnoteContainer::unsupported_with_custom_dom` and could be removed if
-/// that's not needed anymore.
+/// presence of the default footnote rule. It's only used by the error for
+/// footnotes that are not listed by any `FootnoteContainer` in a custom DOM
+/// and could be removed if that's not needed anymore.
 #[elem(Locatable)]
 pub struct FootnoteMarker {}
@@ -653,6 +653,7 @@ impl EarlyLinkResolver {
 pub struct LateLinkResolver<'a> {
     base: Option<&'a VirtualPath>,
     introspector: &'a dyn Introspector,
+    renamed: Option<&'a FxHashMap<VirtualPath, VirtualPath>>,
 }
 
 impl<'a> LateLinkResolver<'a> {
@@ -665,7 +666,25 @@ impl<'a> LateLinkResolver<'a> {
         base: Option<&'a VirtualPath>,
         introspector: &'a dyn Introspector,
     ) -> Self {
-        Self { base, introspector }
+        Self { base, introspector, renamed: None }
+    }
+
+    /// Makes the resolver point links to files that were moved after
+    /// compilation to their new paths.
+    ///
+    /// This is used in bundle export, where assets can be renamed to include
+    /// a hash of their contents.
+    pub fn with_renamed(
+        mut self,
+        renamed: &'a FxHashMap<VirtualPath, VirtualPath>,
+    ) -> Self {
+        self.renamed = Some(renamed);
+        self
+    }
+
+    /// Returns the new path of a file that may have been renamed.
+    fn renamed<'b>(&'b self, path: &'b VirtualPath) -> &'b VirtualPath {
+        self.renamed.and_then(|renamed| renamed.get(path)).unwrap_or(path)
     }
 }
 
@@ -674,7 +693,7 @@ impl<'a> LateLinkResolver<'a> {
 impl<'a> LateLinkResolver<'a> {
     pub fn resolve(&self, location: Location) -> Option<ResolvedLink> {
         let from = self.base;
-        let to = self.introspector.path(location);
+        let to = self.introspector.path(location).map(|to| self.renamed(to));
         let anchor = self.introspector.anchor(location)?.clone();
 
         // See `EarlyLinkResolver::resolve` for more details.
@@ -691,6 +710,31 @@ impl<'a> LateLinkResolver<'a> {
             (None, Some(_)) => return None,
         })
     }
+
+    /// Rewrites a relative URL if it points to a file that was renamed.
+    ///
+    /// Returns `None` if the URL should be kept as-is, which is always the
+    /// case in single-document export.
+    pub fn rewrite_url(&self, url: &str) -> Option<EcoString> {
+        let renamed = self.renamed?;
+        let parent = self.base?.parent()?;
+
+        // Keep the query and fragment, if any.
+        let end = url.find(['?', '#']).unwrap_or(url.len());
+        let (path, suffix) = url.split_at(end);
+
+        // Skip URLs with a scheme (like `https:` or `mailto:`) and
+        // protocol-relative URLs.
+        let first = path.split('/').next().unwrap_or_default();
+        if path.is_empty() || first.contains(':') || path.starts_with("//") {
+            return None;
+        }
+
+        let decoded = percent_encoding::percent_decode_str(path).decode_utf8().ok()?;
+        let to = renamed.get(&parent.join(&decoded).ok()?)?;
+        let encoded = percent_encode_path(&to.relative_from(&parent));
+        Some(eco_format!("{encoded}{suffix}"))
+    }
 }
 
 /// A resolved internal link.
@@ -749,7 +793,7 @@ impl ResolvedLink {
 
 /// Cross-bundle relative paths may contain characters that are not safe to use
 /// in URIs. This function encodes them with percent encoding.
-fn percent_encode_path(relative_path: &str) -> EcoString {
+pub fn percent_encode_path(relative_path: &str) -> EcoString {
     /// This is the complement of an allow-list of alphanumeric + the listed
     /// chars. The double negation is necessary because `percent_encode`
     /// requires a denylist.
@@ -814,3 +858,60 @@ impl Introspect for LinkAnchorIntrospection {
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::introspection::EmptyIntrospector;
+
+    fn path(path: &str) -> VirtualPath {
+        VirtualPath::new(path).unwrap()
+    }
+
+    #[test]
+    fn test_rewrite_url() {
+        let renamed: FxHashMap<_, _> = [
+            (path("/img/logo.png"), path("/img/logo.1234abcd.png")),
+            (path("/docs/style sheet.css"), path("/docs/style sheet.5678ef90.css")),
+        ]
+        .into_iter()
+        .collect();
+        let base = path("/docs/page.html");
+        let resolver =
+            LateLinkResolver::new(Some(&base), &EmptyIntrospector).with_renamed(&renamed);
+        let rewrite = |url: &str| resolver.rewrite_url(url);
+
+        assert_eq!(
+            rewrite("../img/logo.png").as_deref(),
+            Some("../img/logo.1234abcd.png")
+        );
+        assert_eq!(
+            rewrite("../img/logo.png?v=1#top").as_deref(),
+            Some("../img/logo.1234abcd.png?v=1#top"),
+        );
+        assert_eq!(
+            rewrite("style%20sheet.css").as_deref(),
+            Some("style%20sheet.5678ef90.css"),
+        );
+        assert_eq!(rewrite("../img/other.png"), None);
+        assert_eq!(rewrite("https://example.com/img/logo.png"), None);
+        assert_eq!(rewrite("//example.com/img/logo.png"), None);
+        assert_eq!(rewrite("#top"), None);
+        assert_eq!(rewrite(""), None);
+    }
+
+    #[test]
+    fn test_rewrite_url_without_renamed() {
+        let base = path("/docs/page.html");
+        let resolver = LateLinkResolver::new(Some(&base), &EmptyIntrospector);
+        assert_eq!(resolver.rewrite_url("../img/logo.png"), None);
+
+        let renamed: FxHashMap<_, _> =
+            [(path("/img/logo.png"), path("/img/logo.1234abcd.png"))]
+                .into_iter()
+                .collect();
+        let resolver =
+            LateLinkResolver::new(None, &EmptyIntrospector).with_renamed(&renamed);
+        assert_eq!(resolver.rewrite_url("img/logo.png"), None);
+    }
+}
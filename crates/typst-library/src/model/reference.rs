@@ -1,15 +1,19 @@
-use comemo::Track;
+use std::sync::Arc;
+
+use comemo::{Track, Tracked};
 use ecow::eco_format;
+use typst_syntax::Span;
 
-use crate::diag::{At, Hint, SourceResult, bail};
+use crate::diag::{At, Hint, SourceDiagnostic, SourceResult, StrResult, bail};
 use crate::engine::Engine;
 use crate::foundations::{
-    Cast, Content, Context, Func, IntoValue, Label, NativeElement, Packed, Repr, Smart,
-    StyleChain, Synthesize, cast, elem,
+    Cast, Content, Context, Func, IntoValue, Label, NativeElement, Packed, Repr,
+    Selector, Smart, StyleChain, Synthesize, cast, elem,
 };
 use crate::introspection::{
-    Counter, CounterKey, Locatable, PageNumberingIntrospection,
-    PageSupplementIntrospection, QueryLabelIntrospection, Tagged,
+    Counter, CounterKey, History, Introspect, Introspector, Locatable, Location,
+    PageNumberingIntrospection, PageSupplementIntrospection, QueryLabelIntrospection,
+    Tagged,
 };
 use crate::math::EquationElem;
 use crate::model::{
@@ -135,6 +139,18 @@ use crate::text::TextElem;
 /// In @beginning we prove @pythagoras.
 /// $ a^2 + b^2 = c^2 $ <pythagoras>
 /// ```
+///
+/// = References in bundles <bundles>
+/// In @reference:bundle[bundle export], references can point to elements in
+/// other documents of the bundle. Counters and bibliographies are shared by all
+/// documents, so a reference to a figure in another chapter yields the same
+/// supplement and number as one within the chapter and links to the figure in
+/// the other file. Likewise, citations in all documents are collected into the
+/// bibliographies of the bundle, wherever they are placed.
+///
+/// A label may occur in multiple documents of a bundle, for instance when each
+/// chapter has an `<intro>` section. In this case, a reference resolves to the
+/// labelled element in its own document, if there is exactly one.
 #[elem(title = "Reference", Locatable, Tagged, Synthesize)]
 pub struct RefElem {
     /// The target label that should be referenced.
@@ -205,6 +221,7 @@ impl Synthesize for Packed<RefElem> {
         styles: StyleChain,
     ) -> SourceResult<()> {
         let span = self.span();
+        let location = self.location();
         let citation = to_citation(self, engine, styles)?;
 
         let elem = self.as_mut();
@@ -212,8 +229,11 @@ impl Synthesize for Packed<RefElem> {
         elem.element = Some(None);
 
         if !BibliographyElem::has(engine, elem.target, span)
-            && let Ok(found) =
-                engine.introspect(QueryLabelIntrospection(elem.target, span))
+            && let Ok(found) = engine.introspect(RefTargetIntrospection(
+                elem.target,
+                location,
+                span,
+            ))
         {
             elem.element = Some(Some(found));
             return Ok(());
@@ -231,7 +251,8 @@ impl Packed<RefElem> {
         styles: StyleChain,
     ) -> SourceResult<Content> {
         let span = self.span();
-        let elem = engine.introspect(QueryLabelIntrospection(self.target, span));
+        let elem =
+            engine.introspect(RefTargetIntrospection(self.target, self.location(), span));
 
         let form = self.form.get(styles);
         if form == RefForm::Page {
@@ -378,6 +399,45 @@ fn to_citation(
     Ok(elem)
 }
 
+/// Retrieves the element that a reference points to.
+///
+/// In bundle export, a label that occurs in multiple documents resolves to the
+/// element in the reference's own document if it is unique there.
+#[derive(Debug, Clone, PartialEq, Hash)]
+struct RefTargetIntrospection(Label, Option<Location>, Span);
+
+impl Introspect for RefTargetIntrospection {
+    type Output = StrResult<Content>;
+
+    fn introspect(
+        &self,
+        _: &mut Engine,
+        introspector: Tracked<dyn Introspector + '_>,
+    ) -> Self::Output {
+        let result = introspector.query_label(self.0).cloned();
+        if result.is_ok() || introspector.label_count(self.0) < 2 {
+            return result;
+        }
+
+        let Some(doc_location) = self.1.and_then(|loc| introspector.document(loc)) else {
+            return result;
+        };
+
+        let selector = Selector::Within {
+            selector: Arc::new(Selector::Label(self.0)),
+            ancestor: Arc::new(doc_location.into()),
+        };
+        match introspector.query(&selector).as_slice() {
+            [elem] => Ok(elem.clone()),
+            _ => result,
+        }
+    }
+
+    fn diagnose(&self, history: &History<Self::Output>) -> SourceDiagnostic {
+        QueryLabelIntrospection(self.0, self.2).diagnose(history)
+    }
+}
+
 /// Additional content for a reference.
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum Supplement {
@@ -2,9 +2,11 @@
 
 mod accessibility;
 mod attach;
+mod note;
 
 pub use self::accessibility::*;
 pub use self::attach::*;
+pub use self::note::*;
 
 use crate::foundations::{Module, Scope};
 use crate::{Feature, Features};
@@ -15,6 +17,7 @@ pub fn module(features: &Features) -> Module {
     pdf.start_category(crate::Category::Pdf);
     pdf.define_elem::<AttachElem>();
     pdf.define_elem::<ArtifactElem>();
+    pdf.define_elem::<NoteElem>();
     if features.is_enabled(Feature::A11yExtras) {
         pdf.define_func::<table_summary>();
         pdf.define_func::<header_cell>();
@@ -0,0 +1,45 @@
+use crate::foundations::{Content, elem};
+use crate::introspection::Locatable;
+
+/// Presenter notes for the page the element is placed on.
+///
+/// The notes are not visible in the document itself. Instead, they are
+/// exported for presenter consoles like #link("https://pdfpc.github.io")[pdfpc],
+/// which show the notes of the current slide to the speaker while the
+/// audience sees the slides.
+///
+/// All notes on a page are combined, in order of appearance. Only the plain
+/// text of the notes is exported, so formatting like emphasis is lost.
+///
+/// = Example <example>
+/// ```typ
+/// = Quarterly results
+/// Revenue grew by 12%.
+///
+/// #pdf.note[
+///   Mention that this excludes the
+///   acquisition in March.
+/// ]
+/// ```
+///
+/// = Exporting notes <exporting>
+/// When exporting a PDF with the CLI, pass `--pdfpc` to write the notes to a
+/// `.pdfpc` file next to the PDF, which pdfpc and compatible tools pick up
+/// automatically. With `--pdf-embed-notes`, the same data is additionally
+/// attached to the PDF file itself.
+///
+/// Slide packages often produce multiple pages for a single slide to reveal
+/// its content step by step. With `--pdfpc-overlays`, consecutive pages with
+/// the same page number are marked as overlays of one logical slide, so
+/// that presentation tools can skip over them.
+///
+/// = Notes <notes>
+/// - This element is ignored if exporting to a format other than PDF.
+/// - Notes cannot be embedded into PDF/A-1 and PDF/A-2 files, since these
+///   standards don't permit file attachments.
+#[elem(keywords = ["speaker notes", "presenter notes", "pdfpc"], Locatable)]
+pub struct NoteElem {
+    /// The content of the notes.
+    #[required]
+    pub body: Content,
+}
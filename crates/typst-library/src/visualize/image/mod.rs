@@ -16,16 +16,20 @@ use std::sync::Arc;
 
 use ecow::{EcoString, eco_format};
 use hayro_syntax::LoadPdfError;
-use typst_syntax::{Spanned, VirtualPath};
+use typst_syntax::{Span, Spanned, VirtualPath};
 use typst_utils::{LazyHash, NonZeroExt};
 
 use crate::diag::{At, LoadError, LoadedWithin, SourceResult, StrResult, bail, warning};
 use crate::engine::Engine;
 use crate::foundations::{
-    Bytes, Cast, Derived, Packed, Smart, StyleChain, Synthesize, cast, elem,
+    Array, Bytes, Cast, Content, Derived, IntoValue, NativeElement, Packed, Smart,
+    StyleChain, Styles, Synthesize, cast, dict, elem, func, scope,
 };
 use crate::introspection::{Locatable, Tagged};
-use crate::layout::{Length, Rel, Sizing};
+use crate::layout::{
+    Abs, Alignment, FlushElem, HAlignment, Length, Margin, PageElem, PagebreakElem,
+    PlaceElem, Rel, Sizing, VAlignment,
+};
 use crate::loading::{DataSource, Load, Loaded};
 use crate::model::Figurable;
 use crate::text::{LocalName, Locale, families};
@@ -49,7 +53,7 @@ use crate::visualize::image::pdf::PdfDocument;
 ///   ],
 /// )
 /// ```
-#[elem(Locatable, Tagged, Synthesize, LocalName, Figurable)]
+#[elem(scope, Locatable, Tagged, Synthesize, LocalName, Figurable)]
 pub struct ImageElem {
     /// A path to an image file or raw bytes making up an image in one of the
     /// supported @image.format[formats].
@@ -90,9 +94,11 @@ pub struct ImageElem {
     /// - When exporting to PDF, any PDF image file used must have a version
     ///   equal to or lower than the
     ///   @pdf:pdf-versions[export target PDF version].
-    /// - PDF files as images are currently not supported when exporting with a
-    ///   specific PDF standard, like PDF/A-3 or PDF/UA-1. In these cases, you
-    ///   can instead use SVGs to embed vector images.
+    /// - When exporting with a PDF/A or PDF/UA standard, PDF images are
+    ///   converted into regular PDF content instead of being embedded as-is.
+    ///   Text in them is converted into outlines, colors are converted into
+    ///   sRGB, and they must not use features that the standard forbids, like
+    ///   transparency in PDF/A-1.
     /// - The image file must not be password-protected.
     /// - Tags in your PDF image will not be preserved. Instead, you must
     ///   provide an @image.alt[alternative description] to make the image
@@ -157,6 +163,9 @@ pub struct ImageElem {
 
     /// The page number that should be embedded as an image. This attribute only
     /// has an effect for PDF files.
+    ///
+    /// To include multiple pages of a PDF file as full pages of the document,
+    /// use @image.pages instead.
     #[default(NonZeroUsize::ONE)]
     pub page: NonZeroUsize,
 
@@ -203,6 +212,195 @@ pub struct ImageElem {
     pub locale: Locale,
 }
 
+#[scope]
+impl ImageElem {
+    /// Includes pages of a PDF file as full pages of the document.
+    ///
+    /// Produces one @page per included page of the PDF file, containing the
+    /// page as an image. This is useful for appendices, forms, or other
+    /// documents that were created elsewhere.
+    ///
+    /// ```typ
+    /// // Include all pages of the appendix.
+    /// #image.pages("appendix.pdf")
+    ///
+    /// // Include pages 2 to 5 at their original size.
+    /// #image.pages(
+    ///   "appendix.pdf",
+    ///   pages: range(2, 6),
+    ///   original-size: true,
+    /// )
+    /// ```
+    ///
+    /// The included pages are regular pages of the document. Hence, they can
+    /// receive a @page.background[background] or
+    /// @page.foreground[foreground], for example to stamp them. They have no
+    /// margins, header, or footer, though.
+    ///
+    /// Only the visible area of the original pages (their crop box) is
+    /// included. With `original-boxes`, the trim box of an original page is
+    /// carried over: The produced page then has the size of the trim box and
+    /// the rest of the visible area becomes the page's @page.bleed[bleed].
+    #[func(title = "PDF Pages")]
+    pub fn pages(
+        engine: &mut Engine,
+        span: Span,
+        /// A path to a PDF file or raw bytes making up a PDF file.
+        source: Spanned<DataSource>,
+        /// The page numbers of the pages that should be included, starting at
+        /// one. By default, all pages are included.
+        #[named]
+        #[default(Spanned::detached(Smart::Auto))]
+        pages: Spanned<Smart<Vec<NonZeroUsize>>>,
+        /// Whether each produced page should have the size of the original
+        /// page. If `{false}`, the produced pages have the size configured
+        /// through @page and the original pages are scaled to fit into them.
+        #[named]
+        #[default(false)]
+        original_size: bool,
+        /// Whether to preserve the trim box of the original pages. If
+        /// `{true}`, each produced page has the size of the original page's
+        /// trim box and the visible area around it becomes the page's
+        /// @page.bleed[bleed], so that PDF export writes the same trim box
+        /// again. Pages without a trim box or with a rotation are included at
+        /// their original size. Implies `original-size`.
+        #[named]
+        #[default(false)]
+        original_boxes: bool,
+        /// An alternative description of the included pages. See the image's
+        /// @image.alt[`alt`] parameter for more details.
+        #[named]
+        #[default]
+        alt: Option<EcoString>,
+    ) -> SourceResult<Content> {
+        let loaded = source.load(engine.world)?;
+        let document = load_pdf(&loaded)?;
+        let num_pages = document.num_pages();
+
+        let numbers = match pages.v {
+            Smart::Auto => (1..=num_pages).filter_map(NonZeroUsize::new).collect(),
+            Smart::Custom(numbers) => numbers,
+        };
+
+        let source = Derived::new(source.v, loaded);
+        let mut seq = Vec::with_capacity(numbers.len());
+        for number in numbers {
+            let Some(pdf_image) = PdfImage::new(document.clone(), number.get() - 1)
+            else {
+                let s = if num_pages == 1 { "" } else { "s" };
+                bail!(
+                    pages.span, "page {number} does not exist";
+                    hint: "the document only has {num_pages} page{s}";
+                );
+            };
+
+            let mut image = ImageElem::new(source.clone());
+            image.format.set(Smart::Custom(VectorFormat::Pdf.into()));
+            image.width.set(Smart::Custom(Rel::one()));
+            image.height.set(Sizing::Rel(Rel::one()));
+            image.page.set(number);
+            image.fit.set(ImageFit::Contain);
+            image.alt.set(alt.clone());
+
+            // Like the page constructor, place the image on its own page and
+            // style that page.
+            let mut styles = Styles::new();
+            styles.set(
+                PageElem::margin,
+                Smart::Custom(Margin::splat(Some(Smart::Custom(Rel::zero())))),
+            );
+            styles.set(PageElem::header, Smart::Custom(None));
+            styles.set(PageElem::footer, Smart::Custom(None));
+
+            let width = Abs::pt(pdf_image.width().into());
+            let height = Abs::pt(pdf_image.height().into());
+            let body = if let Some(insets) =
+                pdf_image.trim_insets().filter(|_| original_boxes)
+            {
+                let insets = insets.map(Abs::pt);
+                styles.set(
+                    PageElem::width,
+                    Smart::Custom((width - insets.left - insets.right).into()),
+                );
+                styles.set(
+                    PageElem::height,
+                    Smart::Custom((height - insets.top - insets.bottom).into()),
+                );
+                styles.set(
+                    PageElem::bleed,
+                    Margin {
+                        sides: insets.map(|inset| Some(inset.into())),
+                        two_sided: Some(false),
+                    },
+                );
+
+                // The page's body starts at the trim box, so the image is
+                // moved into the bleed area and given the size of the whole
+                // visible area.
+                image.width.set(Smart::Custom(width.into()));
+                image.height.set(Sizing::Rel(height.into()));
+                let mut place = PlaceElem::new(image.pack().spanned(span));
+                place.alignment.set(Smart::Custom(Alignment::Both(
+                    HAlignment::Left,
+                    VAlignment::Top,
+                )));
+                place.dx.set((-insets.left).into());
+                place.dy.set((-insets.top).into());
+                place.pack().spanned(span)
+            } else {
+                if original_size || original_boxes {
+                    styles.set(PageElem::width, Smart::Custom(width.into()));
+                    styles.set(PageElem::height, Smart::Custom(height.into()));
+                }
+                image.pack().spanned(span)
+            };
+
+            seq.push(
+                Content::sequence([
+                    PagebreakElem::shared_weak().clone(),
+                    FlushElem::new().pack(),
+                    body,
+                    PagebreakElem::shared_boundary().clone(),
+                ])
+                .styled_with_map(styles),
+            );
+        }
+
+        Ok(Content::sequence(seq))
+    }
+
+    /// Retrieves the sizes of the pages of a PDF file.
+    ///
+    /// Returns an array with one dictionary per page, with the keys `width`
+    /// and `height`. The length of the array is the number of pages.
+    ///
+    /// ```typ
+    /// #let sizes = image.page-sizes("appendix.pdf")
+    /// The appendix has #sizes.len() pages.
+    /// The first one is #sizes.first().width wide.
+    /// ```
+    #[func(title = "PDF Page Sizes")]
+    pub fn page_sizes(
+        engine: &mut Engine,
+        /// A path to a PDF file or raw bytes making up a PDF file.
+        source: Spanned<DataSource>,
+    ) -> SourceResult<Array> {
+        let loaded = source.load(engine.world)?;
+        let document = load_pdf(&loaded)?;
+        Ok((0..document.num_pages())
+            .filter_map(|i| PdfImage::new(document.clone(), i))
+            .map(|image| {
+                let size = |v: f32| Length::from(Abs::pt(v.into()));
+                dict! {
+                    "width" => size(image.width()),
+                    "height" => size(image.height()),
+                }
+                .into_value()
+            })
+            .collect())
+    }
+}
+
 impl Synthesize for Packed<ImageElem> {
     fn synthesize(&mut self, _: &mut Engine, styles: StyleChain) -> SourceResult<()> {
         self.locale = Some(Locale::get_in(styles));
@@ -260,33 +458,7 @@ impl Packed<ImageElem> {
                 )
             }
             ImageFormat::Vector(VectorFormat::Pdf) => {
-                let document = match PdfDocument::new(loaded.data.clone()) {
-                    Ok(doc) => doc,
-                    Err(e) => match e {
-                        // TODO: the `DecyptionError` is currently not public
-                        LoadPdfError::Decryption(_) => {
-                            bail!(
-                                LoadError::binary(
-                                    "failed to load PDF",
-                                    "the PDF is encrypted or password-protected",
-                                )
-                                .within(loaded)
-                                .with_hint("such PDFs are currently not supported")
-                                .with_hint("preprocess the PDF to remove the encryption")
-                            );
-                        }
-                        LoadPdfError::Invalid => {
-                            bail!(
-                                LoadError::binary(
-                                    "failed to load PDF",
-                                    "the PDF could not be loaded",
-                                )
-                                .within(loaded)
-                                .with_hint("perhaps the PDF file is malformed")
-                            );
-                        }
-                    },
-                };
+                let document = load_pdf(loaded)?;
 
                 // See https://github.com/LaurenzV/hayro/issues/141.
                 if document.pdf().xref().has_optional_content_groups() {
@@ -345,6 +517,37 @@ impl Packed<ImageElem> {
     }
 }
 
+/// Loads a PDF document, producing helpful errors for unsupported files.
+fn load_pdf(loaded: &Loaded) -> SourceResult<PdfDocument> {
+    match PdfDocument::new(loaded.data.clone()) {
+        Ok(doc) => Ok(doc),
+        Err(e) => match e {
+            // TODO: the `DecyptionError` is currently not public
+            LoadPdfError::Decryption(_) => {
+                bail!(
+                    LoadError::binary(
+                        "failed to load PDF",
+                        "the PDF is encrypted or password-protected",
+                    )
+                    .within(loaded)
+                    .with_hint("such PDFs are currently not supported")
+                    .with_hint("preprocess the PDF to remove the encryption")
+                );
+            }
+            LoadPdfError::Invalid => {
+                bail!(
+                    LoadError::binary(
+                        "failed to load PDF",
+                        "the PDF could not be loaded"
+                    )
+                    .within(loaded)
+                    .with_hint("perhaps the PDF file is malformed")
+                );
+            }
+        },
+    }
+}
+
 /// Derive the image format from the file extension of a path.
 fn determine_format_from_path(path: &VirtualPath) -> Option<ImageFormat> {
     match path.extension()? {
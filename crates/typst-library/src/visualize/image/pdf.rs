@@ -1,10 +1,13 @@
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 
-use hayro_syntax::page::Page;
+use hayro_syntax::object::Rect;
+use hayro_syntax::object::dict::keys::TRIM_BOX;
+use hayro_syntax::page::{Page, Rotation};
 use hayro_syntax::{LoadPdfError, Pdf};
 
 use crate::foundations::Bytes;
+use crate::layout::Sides;
 
 /// A PDF document.
 #[derive(Clone, Hash)]
@@ -88,6 +91,31 @@ impl PdfImage {
     pub fn page_index(&self) -> usize {
         self.0.page_index
     }
+
+    /// Returns how far the page's trim box is inset from its visible area
+    /// (the crop box) on each side, in points.
+    ///
+    /// Returns `None` if the page has no trim box or is rotated.
+    pub fn trim_insets(&self) -> Option<Sides<f64>> {
+        let page = self.page();
+        if !matches!(page.rotation(), Rotation::None) {
+            return None;
+        }
+
+        let crop = page.intersected_crop_box();
+        let trim = page.raw().get::<Rect>(TRIM_BOX)?.intersect(crop);
+        if trim.width() <= 0.0 || trim.height() <= 0.0 {
+            return None;
+        }
+
+        // PDF coordinates grow upwards.
+        Some(Sides::new(
+            trim.x0 - crop.x0,
+            crop.y1 - trim.y1,
+            crop.x1 - trim.x1,
+            trim.y0 - crop.y0,
+        ))
+    }
 }
 
 impl Hash for PdfImageInner {
@@ -21,7 +21,7 @@ use typst_layout::PagedDocument;
 use typst_library::diag::{
     At, ExpectInternal, SourceDiagnostic, SourceResult, bail, error,
 };
-use typst_library::foundations::{NativeElement, Repr};
+use typst_library::foundations::{NativeElement, Repr, Smart};
 use typst_library::introspection::{Introspector, Location, PagedPosition, Tag};
 use typst_library::layout::{Abs, Frame, FrameItem, GroupItem, Sides, Size, Transform};
 use typst_library::model::{HeadingElem, LateLinkResolver};
@@ -29,11 +29,11 @@ use typst_library::text::FontInstance;
 use typst_library::visualize::{Geometry, Paint, SpotColorantName};
 use typst_syntax::Span;
 
-use crate::PdfOptions;
 use crate::attach::attach_files;
-use crate::image::handle_image;
+use crate::image::{ImageOptimizer, handle_image};
 use crate::link::{LinkAnnotation, handle_link};
 use crate::metadata::build_metadata;
+use crate::notes::embed_notes;
 use crate::outline::build_outline;
 use crate::page::PageLabelExt;
 use crate::shape::handle_shape;
@@ -43,6 +43,7 @@ use crate::util::{
     AbsExt, SpotColorantFromNameExt, TransformExt, ValidatorsExt, convert_path,
     display_font,
 };
+use crate::{PdfImageReport, PdfOptions};
 
 #[typst_macros::time(name = "convert document")]
 pub fn convert(
@@ -50,7 +51,7 @@ pub fn convert(
     options: &PdfOptions,
     anchors: &[(Location, EcoString)],
     link_resolver: Option<Tracked<LateLinkResolver>>,
-) -> SourceResult<Vec<u8>> {
+) -> SourceResult<(Vec<u8>, PdfImageReport)> {
     let settings = SerializeSettings {
         compress_content_streams: !options.pretty,
         no_device_cs: true,
@@ -68,6 +69,7 @@ pub fn convert(
     let named_destinations = collect_named_destinations(
         &mut document,
         typst_document,
+        options,
         anchors,
         &page_index_converter,
     );
@@ -85,13 +87,16 @@ pub fn convert(
 
     convert_pages(&mut gc, &mut document)?;
     attach_files(&gc, &mut document)?;
+    embed_notes(&gc, &mut document)?;
     let (doc_lang, tree) = tags::resolve(&mut gc)?;
 
     document.set_outline(build_outline(&gc));
     document.set_metadata(build_metadata(&gc, doc_lang));
     document.set_tag_tree(tree);
 
-    finish(document, gc, options.standards.config)
+    let report = gc.image_optimizer.report;
+    let buffer = finish(document, gc, options.standards.config)?;
+    Ok((buffer, report))
 }
 
 fn convert_pages(gc: &mut GlobalContext, document: &mut Document) -> SourceResult<()> {
@@ -287,6 +292,11 @@ pub(crate) struct GlobalContext<'a> {
     /// The spans of all images that appear in the document. We use this so
     /// we can give more accurate error messages.
     pub(crate) image_spans: FxHashSet<Span>,
+    /// The spans of all PDF images that were converted into regular content
+    /// because the export standards forbid embedding PDFs.
+    pub(crate) pdf_image_spans: FxHashSet<Span>,
+    /// Optimizes raster images according to the export options.
+    pub(crate) image_optimizer: ImageOptimizer,
     /// The document to convert.
     pub(crate) document: &'a PagedDocument,
     /// Options for PDF export.
@@ -318,6 +328,8 @@ impl<'a> GlobalContext<'a> {
             loc_to_names,
             image_to_spans: FxHashMap::default(),
             image_spans: FxHashSet::default(),
+            pdf_image_spans: FxHashSet::default(),
+            image_optimizer: ImageOptimizer::new(document, &options.images),
             page_index_converter,
             tags,
         }
@@ -653,7 +665,14 @@ fn convert_error(
             let hint1 = "try exporting with a different standard that \
                          supports transparency";
             if loc.is_some() {
-                if gc.image_spans.contains(&span) {
+                if gc.pdf_image_spans.contains(&span) {
+                    error!(
+                        span, "{prefix} the PDF image contains transparency";
+                        hint: "{hint1}";
+                        hint: "or flatten the transparency in the PDF file before \
+                               using it as an image";
+                    )
+                } else if gc.image_spans.contains(&span) {
                     error!(
                         span, "{prefix} the image contains transparency";
                         hint: "{hint1}";
@@ -726,10 +745,19 @@ fn convert_error(
         }
         ValidationError::MissingAltText(loc) => {
             let span = to_span(*loc);
-            error!(
-                span, "{prefix} missing alt text";
-                hint: "make sure your images and equations have alt text";
-            )
+            if gc.pdf_image_spans.contains(&span) {
+                error!(
+                    span, "{prefix} the PDF image is missing alt text";
+                    hint: "text in PDF images is converted to outlines in this \
+                           export mode, so it cannot be read by assistive technology";
+                    hint: "describe the image with the `alt` parameter";
+                )
+            } else {
+                error!(
+                    span, "{prefix} missing alt text";
+                    hint: "make sure your images and equations have alt text";
+                )
+            }
         }
         ValidationError::NoDocumentLanguage => error!(
             Span::detached(),
@@ -762,11 +790,13 @@ fn convert_error(
             "{prefix} missing document date";
             hint: "set the date of the document";
         ),
+        // PDF images are converted into regular content when a standard
+        // forbids embedding PDFs, so this should not happen.
         ValidationError::EmbeddedPDF(loc) => {
             error!(
                 to_span(*loc),
-                "embedding PDFs is currently not supported in this export mode";
-                hint: "try converting the PDF to an SVG before embedding it";
+                "{prefix} the PDF contains an embedded PDF";
+                hint: "please report this as a bug";
             )
         }
         ValidationError::RequiresNewerPdfVersion(feature, loc) => {
@@ -845,37 +875,19 @@ pub(crate) fn to_span(loc: Option<krilla::surface::Location>) -> Span {
 fn collect_named_destinations(
     document: &mut Document,
     typst_document: &PagedDocument,
+    options: &PdfOptions,
     anchors: &[(Location, EcoString)],
     pic: &PageIndexConverter,
 ) -> FxHashMap<Location, NamedDestination> {
     let mut locs_to_names = FxHashMap::default();
 
-    // Find all headings that have a label and are the first among other
-    // headings with the same label.
-    let mut seen = FxHashSet::default();
-    let headings = typst_document.introspector().query(&HeadingElem::ELEM.select());
-    let matches = anchors
-        .iter()
-        .map(|(loc, anchor)| (*loc, anchor.to_string()))
-        .chain(
-            headings
-                .iter()
-                .filter_map(|elem| elem.location().zip(elem.label()))
-                .map(|(loc, label)| (loc, label.resolve().to_string())),
-        )
-        .filter(|(_, name)| seen.insert(name.clone()));
-
-    for (loc, name) in matches {
+    for (loc, name, pos) in named_destination_targets(typst_document, options, anchors) {
         // Only add named destination if page belonging to the position is exported.
-        let pos = typst_document
-            .introspector()
-            .position(loc)
-            .unwrap_or(PagedPosition::ORIGIN);
         if let Some(dest) = crate::link::pos_to_xyz(pic, pos) {
-            let named = NamedDestination::new(name, dest);
+            let named = NamedDestination::new(name.to_string(), dest);
             // The option is `None` if the destination is a duplicate which
-            // should not happen because we filtered them on a set insert above,
-            // hence the unwrap.
+            // should not happen because we filtered them on a set insert in
+            // `named_destination_targets`, hence the unwrap.
             document.register_named_destination(named.clone()).unwrap();
             locs_to_names.insert(loc, named);
         }
@@ -884,6 +896,42 @@ fn collect_named_destinations(
     locs_to_names
 }
 
+/// Determines the locations, names, and positions of all elements that
+/// should become named destinations.
+///
+/// These are the explicit `anchors` and all elements matching
+/// [`PdfOptions::named_destinations`] that have a label and are the first
+/// among other such elements with the same label.
+pub(crate) fn named_destination_targets(
+    typst_document: &PagedDocument,
+    options: &PdfOptions,
+    anchors: &[(Location, EcoString)],
+) -> Vec<(Location, EcoString, PagedPosition)> {
+    let introspector = typst_document.introspector();
+    let selector = match &options.named_destinations {
+        Smart::Auto => HeadingElem::ELEM.select(),
+        Smart::Custom(selector) => selector.clone(),
+    };
+
+    let mut seen = FxHashSet::default();
+    let elems = introspector.query(&selector);
+    anchors
+        .iter()
+        .cloned()
+        .chain(
+            elems
+                .iter()
+                .filter_map(|elem| elem.location().zip(elem.label()))
+                .map(|(loc, label)| (loc, label.resolve().as_str().into())),
+        )
+        .filter(|(_, name)| seen.insert(name.clone()))
+        .map(|(loc, name)| {
+            let pos = introspector.position(loc).unwrap_or(PagedPosition::ORIGIN);
+            (loc, name, pos)
+        })
+        .collect()
+}
+
 pub(crate) struct PageIndexConverter {
     page_indices: FxHashMap<usize, usize>,
     skipped_pages: usize,
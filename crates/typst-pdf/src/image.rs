@@ -1,24 +1,30 @@
+use std::borrow::Cow;
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, OnceLock};
 
 use ecow::eco_format;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
 use image::{DynamicImage, EncodableLayout, GenericImageView, Rgba};
 use krilla::image::{BitsPerComponent, CustomImage, ImageColorspace};
 use krilla::pdf::PdfDocument;
 use krilla::surface::Surface;
 use krilla_svg::{SurfaceExt, SvgSettings};
+use rustc_hash::FxHashMap;
+use typst_layout::PagedDocument;
 use typst_library::diag::{At, SourceResult};
-use typst_library::foundations::Smart;
-use typst_library::layout::{Abs, Angle, Ratio, Size, Transform};
+use typst_library::foundations::{Bytes, Smart};
+use typst_library::layout::{Abs, Angle, Frame, FrameItem, Ratio, Size, Transform};
 use typst_library::visualize::{
-    ExchangeFormat, Image, ImageKind, ImageScaling, PdfImage, RasterFormat, RasterImage,
+    ExchangeFormat, Image, ImageKind, ImageScaling, PdfImage, PixelEncoding, PixelFormat,
+    RasterFormat, RasterImage,
 };
 use typst_syntax::Span;
-use typst_utils::defer;
+use typst_utils::{Scalar, defer, hash128};
 
 use crate::convert::{FrameContext, GlobalContext};
-use crate::tags;
 use crate::util::{SizeExt, TransformExt};
+use crate::{PdfImageOptions, PdfImageReport, tags};
 
 #[typst_macros::time(name = "handle image")]
 pub(crate) fn handle_image(
@@ -45,11 +51,21 @@ pub(crate) fn handle_image(
 
     match image.kind() {
         ImageKind::Raster(raster) => {
-            let (exif_transform, new_size) = exif_transform(raster, size);
+            let options = &gc.options.images;
+            let raster = if options.is_enabled() {
+                gc.image_optimizer
+                    .optimize(raster, image.scaling(), options)
+                    .map_err(|err| eco_format!("failed to optimize image ({err})"))
+                    .at(span)?
+            } else {
+                raster.clone()
+            };
+
+            let (exif_transform, new_size) = exif_transform(&raster, size);
             surface.push_transform(&exif_transform.to_krilla());
             let mut surface = defer(surface, |s| s.pop());
 
-            let image = convert_raster(raster.clone(), interpolate)
+            let image = convert_raster(raster, interpolate)
                 .map_err(|err| eco_format!("failed to process image ({err})"))
                 .at(span)?;
 
@@ -72,7 +88,23 @@ pub(crate) fn handle_image(
         }
         ImageKind::Pdf(pdf) => {
             if let Some(size) = size.to_krilla() {
-                surface.draw_pdf_page(&convert_pdf(pdf), size, pdf.page_index());
+                if gc.options.embeds_pdf_images() {
+                    surface.draw_pdf_page(&convert_pdf(pdf), size, pdf.page_index());
+                } else {
+                    // PDF/A and PDF/UA forbid embedding other PDFs, so we
+                    // convert the page into an SVG, which krilla then writes
+                    // as regular content that is validated like everything
+                    // else.
+                    let tree = convert_pdf_to_svg(pdf)
+                        .map_err(|err| eco_format!("failed to convert PDF image ({err})"))
+                        .at(span)?;
+                    gc.pdf_image_spans.insert(span);
+                    surface.draw_svg(
+                        &tree,
+                        size,
+                        SvgSettings { embed_text: true, ..Default::default() },
+                    );
+                }
             }
         }
     }
@@ -210,11 +242,222 @@ fn convert_raster(
     }
 }
 
+/// Optimizes raster images according to [`PdfImageOptions`] and keeps track
+/// of the results.
+#[derive(Default)]
+pub(crate) struct ImageOptimizer {
+    /// The largest size (after transforms) at which each raster image is
+    /// placed in the document, keyed by the hash of the image.
+    placements: FxHashMap<u128, Size>,
+    /// The optimized versions of images, keyed by the hash of the original
+    /// image and whether it uses pixelated scaling.
+    optimized: FxHashMap<(u128, bool), RasterImage>,
+    /// Optimized images keyed by the hash of their pixel data. Used for
+    /// deduplication.
+    by_pixels: FxHashMap<u128, RasterImage>,
+    /// Statistics about the performed optimizations.
+    pub(crate) report: PdfImageReport,
+}
+
+impl ImageOptimizer {
+    /// Prepares optimization of the images in the given document.
+    pub(crate) fn new(document: &PagedDocument, options: &PdfImageOptions) -> Self {
+        let mut optimizer = Self::default();
+        if options.max_ppi.is_some() {
+            for page in document.pages() {
+                optimizer.collect_placements(&page.frame, Transform::identity());
+            }
+        }
+        optimizer
+    }
+
+    /// Records the placed sizes of all raster images in the frame.
+    fn collect_placements(&mut self, frame: &Frame, ts: Transform) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    // Translations don't affect the placed size, so we only
+                    // need to track the group's own transform.
+                    self.collect_placements(&group.frame, ts.pre_concat(group.transform));
+                }
+                FrameItem::Image(image, size, _) => {
+                    let ImageKind::Raster(raster) = image.kind() else { continue };
+                    let scale = |a: Ratio, b: Ratio| {
+                        Scalar::new(a.get()).hypot(Scalar::new(b.get())).get()
+                    };
+                    let placed = Size::new(
+                        size.x * scale(ts.sx, ts.ky),
+                        size.y * scale(ts.kx, ts.sy),
+                    );
+                    let entry = self.placements.entry(hash128(raster)).or_default();
+                    *entry = entry.max(placed);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the raster image that should be embedded in place of the given
+    /// one.
+    fn optimize(
+        &mut self,
+        raster: &RasterImage,
+        scaling: Smart<ImageScaling>,
+        options: &PdfImageOptions,
+    ) -> Result<RasterImage, String> {
+        // Downsampling or compressing pixel art would ruin it.
+        let pixelated = scaling == Smart::Custom(ImageScaling::Pixelated);
+        let hash = hash128(raster);
+        if let Some(optimized) = self.optimized.get(&(hash, pixelated)) {
+            return Ok(optimized.clone());
+        }
+
+        let mut optimized = raster.clone();
+        if !pixelated {
+            let target = options
+                .max_ppi
+                .zip(self.placements.get(&hash))
+                .and_then(|(ppi, &size)| downsampled_size(raster, size, ppi.get()));
+            let (result, reencoded) =
+                optimize_raster(raster.clone(), target, options.jpeg_quality)?;
+            optimized = result;
+            self.report.downsampled += target.is_some() as usize;
+            self.report.reencoded += reencoded as usize;
+        }
+
+        self.report.images += 1;
+        self.report.original_bytes += raster.data().len();
+
+        let mut deduplicated = false;
+        if options.deduplicate {
+            let dynamic = optimized.dynamic();
+            let key = hash128(&(
+                dynamic.dimensions(),
+                dynamic.color(),
+                dynamic.as_bytes(),
+                optimized.icc(),
+            ));
+            if let Some(existing) = self.by_pixels.get(&key) {
+                optimized = existing.clone();
+                deduplicated = true;
+            } else {
+                self.by_pixels.insert(key, optimized.clone());
+            }
+        }
+
+        if deduplicated {
+            self.report.deduplicated += 1;
+        } else {
+            self.report.optimized_bytes += optimized.data().len();
+        }
+
+        self.optimized.insert((hash, pixelated), optimized.clone());
+        Ok(optimized)
+    }
+}
+
+/// Determines the pixel size to which an image placed at the given size must
+/// be downsampled so that its effective resolution doesn't exceed `max_ppi`.
+///
+/// Returns `None` if the image doesn't need to be downsampled.
+fn downsampled_size(
+    raster: &RasterImage,
+    size: Size,
+    max_ppi: f64,
+) -> Option<(u32, u32)> {
+    let width = f64::from(raster.width());
+    let height = f64::from(raster.height());
+    // Keep the aspect ratio and make sure that both axes have enough pixels.
+    let factor =
+        (size.x.to_inches() * max_ppi / width).max(size.y.to_inches() * max_ppi / height);
+    (factor > 0.0 && factor < 1.0).then(|| {
+        (
+            (width * factor).ceil().max(1.0) as u32,
+            (height * factor).ceil().max(1.0) as u32,
+        )
+    })
+}
+
+/// Downsamples an image to the target size, if any, and re-encodes it as JPEG
+/// with the given quality, if any.
+///
+/// Also returns whether the image was re-encoded.
+#[comemo::memoize]
+#[typst_macros::time(name = "optimize raster image")]
+fn optimize_raster(
+    raster: RasterImage,
+    target: Option<(u32, u32)>,
+    jpeg_quality: Option<u8>,
+) -> Result<(RasterImage, bool), String> {
+    let mut dynamic = Cow::Borrowed(raster.dynamic().as_ref());
+    if let Some((width, height)) = target {
+        dynamic = Cow::Owned(dynamic.resize_exact(width, height, FilterType::Lanczos3));
+    }
+
+    // The ICC profile remains valid as long as the channels stay 8-bit.
+    let icc = match raster.dynamic().as_ref() {
+        DynamicImage::ImageLuma8(_)
+        | DynamicImage::ImageLumaA8(_)
+        | DynamicImage::ImageRgb8(_)
+        | DynamicImage::ImageRgba8(_) => raster.icc().cloned(),
+        _ => None,
+    };
+    let icc = icc.map_or(Smart::Auto, Smart::Custom);
+
+    let color = dynamic.color();
+    if let Some(quality) = jpeg_quality
+        && !color.has_alpha()
+    {
+        let converted = if color.has_color() {
+            DynamicImage::ImageRgb8(dynamic.to_rgb8())
+        } else {
+            DynamicImage::ImageLuma8(dynamic.to_luma8())
+        };
+
+        let mut buf = Vec::new();
+        JpegEncoder::new_with_quality(&mut buf, quality.clamp(1, 100))
+            .encode_image(&converted)
+            .map_err(|err| err.to_string())?;
+
+        // Without downsampling, re-encoding only pays off if the result is
+        // smaller than the original.
+        if target.is_some() || buf.len() < raster.data().len() {
+            let image = RasterImage::new(Bytes::new(buf), ExchangeFormat::Jpg, icc)
+                .map_err(|err| err.to_string())?;
+            return Ok((image, true));
+        }
+    }
+
+    let Some((width, height)) = target else { return Ok((raster.clone(), false)) };
+    let (encoding, data) = match (color.has_color(), color.has_alpha()) {
+        (true, true) => (PixelEncoding::Rgba8, dynamic.to_rgba8().into_raw()),
+        (true, false) => (PixelEncoding::Rgb8, dynamic.to_rgb8().into_raw()),
+        (false, true) => (PixelEncoding::Lumaa8, dynamic.to_luma_alpha8().into_raw()),
+        (false, false) => (PixelEncoding::Luma8, dynamic.to_luma8().into_raw()),
+    };
+
+    let format = PixelFormat { encoding, width, height };
+    let image =
+        RasterImage::new(Bytes::new(data), format, icc).map_err(|err| err.to_string())?;
+    Ok((image, false))
+}
+
 #[comemo::memoize]
 fn convert_pdf(pdf: &PdfImage) -> PdfDocument {
     PdfDocument::new(pdf.document().pdf().clone())
 }
 
+/// Converts a PDF page into an SVG tree, for standards that forbid embedding
+/// PDFs.
+#[comemo::memoize]
+#[typst_macros::time(name = "convert pdf to svg")]
+fn convert_pdf_to_svg(pdf: &PdfImage) -> Result<Arc<usvg::Tree>, String> {
+    let svg = typst_svg::convert_pdf_to_svg(pdf);
+    usvg::Tree::from_str(&svg, &usvg::Options::default())
+        .map(Arc::new)
+        .map_err(|err| err.to_string())
+}
+
 fn exif_transform(image: &RasterImage, size: Size) -> (Transform, Size) {
     // For JPEGs, we want to apply the EXIF orientation as a transformation
     // because we don't recode them. For other formats, the transform is already
@@ -264,3 +507,145 @@ fn exif_transform(image: &RasterImage, size: Size) -> (Transform, Size) {
         _ => no_flipping(false, false),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use typst_library::layout::{GroupItem, Point};
+
+    use super::*;
+
+    /// Creates an image with a gradient, encoded as raw pixels.
+    fn gradient(width: u32, height: u32, alpha: bool) -> RasterImage {
+        let encoding = if alpha { PixelEncoding::Rgba8 } else { PixelEncoding::Rgb8 };
+        let data = gradient_pixels(width, height, alpha);
+        let format = PixelFormat { encoding, width, height };
+        RasterImage::plain(Bytes::new(data), format).unwrap()
+    }
+
+    /// The pixel data of a gradient image.
+    fn gradient_pixels(width: u32, height: u32, alpha: bool) -> Vec<u8> {
+        let mut data = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                data.extend([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8]);
+                if alpha {
+                    data.push(128);
+                }
+            }
+        }
+        data
+    }
+
+    /// Places the image into a frame at the given size, scaled by `scale`.
+    fn place(raster: &RasterImage, size: Size, scale: f64) -> Frame {
+        let mut inner = Frame::soft(size);
+        let image = Image::plain(raster.clone());
+        inner.push(Point::zero(), FrameItem::Image(image, size, Span::detached()));
+        let mut group = GroupItem::new(inner);
+        group.transform = Transform::scale(Ratio::new(scale), Ratio::new(scale));
+        let mut frame = Frame::soft(size * scale);
+        frame.push(Point::zero(), FrameItem::Group(group));
+        frame
+    }
+
+    #[test]
+    fn test_downsampled_size() {
+        let raster = gradient(400, 200, false);
+        let size = Size::new(Abs::inches(2.0), Abs::inches(1.0));
+        assert_eq!(downsampled_size(&raster, size, 100.0), Some((200, 100)));
+        assert_eq!(downsampled_size(&raster, size, 300.0), None);
+
+        // The axis that needs more pixels determines the factor.
+        let size = Size::new(Abs::inches(1.0), Abs::inches(1.0));
+        assert_eq!(downsampled_size(&raster, size, 100.0), Some((200, 100)));
+        assert_eq!(downsampled_size(&raster, size, 50.0), Some((100, 50)));
+    }
+
+    #[test]
+    fn test_optimize_downsamples_to_placed_size() {
+        let raster = gradient(400, 200, false);
+        let options = PdfImageOptions {
+            max_ppi: Some(Scalar::new(100.0)),
+            ..Default::default()
+        };
+
+        // Placed at 1x0.5in, but scaled up by two by the surrounding group.
+        let size = Size::new(Abs::inches(1.0), Abs::inches(0.5));
+        let mut optimizer = ImageOptimizer::default();
+        optimizer.collect_placements(&place(&raster, size, 2.0), Transform::identity());
+
+        let optimized = optimizer.optimize(&raster, Smart::Auto, &options).unwrap();
+        assert_eq!((optimized.width(), optimized.height()), (200, 100));
+        assert_eq!(
+            optimizer.report,
+            PdfImageReport {
+                images: 1,
+                downsampled: 1,
+                reencoded: 0,
+                deduplicated: 0,
+                original_bytes: 400 * 200 * 3,
+                optimized_bytes: 200 * 100 * 3,
+            }
+        );
+
+        // Pixelated images are left untouched.
+        let pixelated = Smart::Custom(ImageScaling::Pixelated);
+        let kept = optimizer.optimize(&raster, pixelated, &options).unwrap();
+        assert_eq!((kept.width(), kept.height()), (400, 200));
+        assert_eq!(optimizer.report.images, 2);
+        assert_eq!(optimizer.report.downsampled, 1);
+    }
+
+    #[test]
+    fn test_optimize_reencodes_opaque_images_as_jpeg() {
+        let options = PdfImageOptions { jpeg_quality: Some(50), ..Default::default() };
+        let mut optimizer = ImageOptimizer::default();
+
+        let opaque = gradient(64, 64, false);
+        let optimized = optimizer.optimize(&opaque, Smart::Auto, &options).unwrap();
+        assert_eq!(optimized.format(), RasterFormat::Exchange(ExchangeFormat::Jpg));
+        assert_eq!((optimized.width(), optimized.height()), (64, 64));
+        assert!(optimized.data().len() < opaque.data().len());
+
+        // JPEG doesn't support transparency.
+        let transparent = gradient(64, 64, true);
+        let kept = optimizer.optimize(&transparent, Smart::Auto, &options).unwrap();
+        assert_eq!(kept.data(), transparent.data());
+
+        let report = optimizer.report;
+        assert_eq!((report.images, report.reencoded, report.downsampled), (2, 1, 0));
+        assert_eq!(report.original_bytes, opaque.data().len() + transparent.data().len());
+        assert_eq!(
+            report.optimized_bytes,
+            optimized.data().len() + transparent.data().len()
+        );
+    }
+
+    #[test]
+    fn test_optimize_deduplicates_identical_pixels() {
+        let options = PdfImageOptions { deduplicate: true, ..Default::default() };
+        let raw = gradient(16, 8, false);
+
+        // The same pixels, but encoded as PNG.
+        let mut png = vec![];
+        DynamicImage::ImageRgb8(
+            image::RgbImage::from_raw(16, 8, gradient_pixels(16, 8, false)).unwrap(),
+        )
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+        let encoded = RasterImage::plain(Bytes::new(png), ExchangeFormat::Png).unwrap();
+
+        let mut optimizer = ImageOptimizer::default();
+        let first = optimizer.optimize(&raw, Smart::Auto, &options).unwrap();
+        let second = optimizer.optimize(&encoded, Smart::Auto, &options).unwrap();
+        assert_eq!(first.data(), raw.data());
+        assert_eq!(second.data(), raw.data());
+
+        let report = optimizer.report;
+        assert_eq!((report.images, report.deduplicated), (2, 1));
+        assert_eq!(report.original_bytes, raw.data().len() + encoded.data().len());
+        assert_eq!(report.optimized_bytes, raw.data().len());
+    }
+}
@@ -5,6 +5,7 @@ mod convert;
 mod image;
 mod link;
 mod metadata;
+mod notes;
 mod outline;
 mod page;
 mod paint;
@@ -14,26 +15,41 @@ mod text;
 mod util;
 
 pub use self::metadata::{Timestamp, Timezone};
+pub use typst_library::model::PdfStandard;
 
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
 
 use comemo::Tracked;
 use ecow::{EcoString, eco_format};
 use krilla::configure::Accessibility;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use typst_layout::PagedDocument;
 use typst_library::diag::{HintedStrResult, HintedString, SourceResult, StrResult, bail};
-use typst_library::foundations::Smart;
+use typst_library::foundations::{Selector, Smart};
 use typst_library::introspection::Location;
 use typst_library::layout::PageRanges;
 use typst_library::model::LateLinkResolver;
+use typst_utils::Scalar;
 
 /// Export a document into a PDF file.
 ///
 /// Returns the raw bytes making up the PDF file.
 #[typst_macros::time(name = "pdf")]
 pub fn pdf(document: &PagedDocument, options: &PdfOptions) -> SourceResult<Vec<u8>> {
+    convert::convert(document, options, &[], None).map(|(buffer, _)| buffer)
+}
+
+/// Export a document into a PDF file and report on the embedded images.
+///
+/// Returns the raw bytes making up the PDF file alongside statistics about
+/// how [`PdfOptions::images`] affected the document's raster images.
+#[typst_macros::time(name = "pdf with report")]
+pub fn pdf_with_report(
+    document: &PagedDocument,
+    options: &PdfOptions,
+) -> SourceResult<(Vec<u8>, PdfImageReport)> {
     convert::convert(document, options, &[], None)
 }
 
@@ -51,10 +67,87 @@ pub fn pdf_in_bundle(
     link_resolver: Tracked<LateLinkResolver>,
 ) -> SourceResult<Vec<u8>> {
     convert::convert(document, options, anchors, Some(link_resolver))
+        .map(|(buffer, _)| buffer)
+}
+
+/// Determines the named destinations that PDF export produces for a document.
+///
+/// This can be used to write a sidecar file that enables external systems to
+/// deep link into the PDF. Targets on pages that are excluded through
+/// [`PdfOptions::page_ranges`] are skipped.
+pub fn link_targets(
+    document: &PagedDocument,
+    options: &PdfOptions,
+) -> Vec<PdfLinkTarget> {
+    let pic = convert::PageIndexConverter::new(document, options);
+    convert::named_destination_targets(document, options, &[])
+        .into_iter()
+        .filter_map(|(_, name, pos)| {
+            let index = pic.pdf_page_index(pos.page.get() - 1)?;
+            Some(PdfLinkTarget {
+                name,
+                page: NonZeroUsize::new(index + 1)?,
+                x: pos.point.x.to_pt(),
+                y: pos.point.y.to_pt(),
+            })
+        })
+        .collect()
+}
+
+/// Collects the presenter notes of a document in the format of a `.pdfpc`
+/// file.
+///
+/// When placed next to the PDF with the same file stem, the file is picked up
+/// by the pdfpc presenter console and compatible tools. Pages that are
+/// excluded through [`PdfOptions::page_ranges`] are skipped.
+pub fn pdfpc(document: &PagedDocument, options: &PdfOptions) -> Pdfpc {
+    notes::collect_pdfpc(document, options)
+}
+
+/// The contents of a `.pdfpc` file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pdfpc {
+    /// The version of the file format.
+    pub pdfpc_format: u32,
+    /// The exported pages.
+    pub pages: Vec<PdfpcPage>,
+}
+
+/// A page in a `.pdfpc` file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PdfpcPage {
+    /// The index of the page in the PDF, starting at 0.
+    pub idx: usize,
+    /// The label of the logical slide the page belongs to.
+    pub label: EcoString,
+    /// The index of the page within its logical slide, starting at 0.
+    pub overlay: usize,
+    /// The presenter notes for the page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<EcoString>,
+}
+
+/// A named destination in an exported PDF.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PdfLinkTarget {
+    /// The name of the destination, i.e. the label of the element.
+    pub name: EcoString,
+    /// The number of the PDF page the destination is on, starting at 1.
+    ///
+    /// This differs from the page number in the document if some pages are
+    /// not exported.
+    pub page: NonZeroUsize,
+    /// The horizontal position of the element on the page in points, measured
+    /// from the left edge.
+    pub x: f64,
+    /// The vertical position of the element on the page in points, measured
+    /// from the top edge.
+    pub y: f64,
 }
 
 /// Settings for PDF export.
-#[derive(Debug, Hash)]
+#[derive(Debug, Clone, Hash)]
 pub struct PdfOptions {
     /// If not `Smart::Auto`, shall be a string that uniquely and stably
     /// identifies the document. It should not change between compilations of
@@ -86,6 +179,15 @@ pub struct PdfOptions {
     pub tagged: bool,
     /// Whether to format the PDF in a human-readable way.
     pub pretty: bool,
+    /// How to optimize raster images for a smaller file size.
+    pub images: PdfImageOptions,
+    /// Which elements become named destinations, so that they can be linked
+    /// to from outside of the PDF. Only elements with a label are considered
+    /// and the label is used as the destination's name. When `Smart::Auto`,
+    /// labelled headings become named destinations.
+    pub named_destinations: Smart<Selector>,
+    /// How to export presenter notes.
+    pub presenter: PdfPresenterOptions,
 }
 
 impl PdfOptions {
@@ -94,6 +196,12 @@ impl PdfOptions {
     pub(crate) fn accessibility_validator(&self) -> Option<Accessibility> {
         self.standards.config.validators().accessibility()
     }
+
+    /// Whether PDF images can be embedded as-is. This is not the case when
+    /// conforming to a PDF/A or PDF/UA standard.
+    pub(crate) fn embeds_pdf_images(&self) -> bool {
+        self.standards.config.validators().into_iter().next().is_none()
+    }
 }
 
 impl Default for PdfOptions {
@@ -106,10 +214,81 @@ impl Default for PdfOptions {
             standards: PdfStandards::default(),
             tagged: true,
             pretty: false,
+            images: PdfImageOptions::default(),
+            named_destinations: Smart::Auto,
+            presenter: PdfPresenterOptions::default(),
         }
     }
 }
 
+/// Settings for exporting the presenter notes of a document.
+///
+/// Notes are always available through [`pdfpc`]. These settings control how
+/// they are grouped into slides and whether they are also embedded into the
+/// PDF.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdfPresenterOptions {
+    /// Whether to attach the notes to the PDF as a `.pdfpc` file.
+    pub embed: bool,
+    /// Whether consecutive pages with the same logical page number are
+    /// grouped into one slide, with each page being an overlay of it. Slide
+    /// packages typically keep the page number constant while revealing the
+    /// content of a slide step by step.
+    pub overlays: bool,
+}
+
+/// Settings for reducing the size of raster images embedded into a PDF.
+///
+/// By default, images are embedded as-is.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdfImageOptions {
+    /// If not `None`, raster images whose effective resolution exceeds this
+    /// number of pixels per inch are downsampled to it. The effective
+    /// resolution is determined from the largest size at which an image is
+    /// placed in the document. Images with `pixelated` scaling are left
+    /// untouched.
+    pub max_ppi: Option<Scalar>,
+    /// If not `None`, raster images without transparency are re-encoded as
+    /// JPEG with this quality (from 1 to 100), provided that this makes them
+    /// smaller.
+    pub jpeg_quality: Option<u8>,
+    /// Whether to embed images that have identical pixel data only once, even
+    /// if they were loaded from differently encoded files.
+    pub deduplicate: bool,
+}
+
+impl PdfImageOptions {
+    /// Whether any optimization is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.max_ppi.is_some() || self.jpeg_quality.is_some() || self.deduplicate
+    }
+}
+
+/// Statistics about the raster images embedded into a PDF.
+///
+/// Produced by [`pdf_with_report`] to inform users about the effect of
+/// [`PdfImageOptions`].
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct PdfImageReport {
+    /// The number of distinct raster images in the document.
+    pub images: usize,
+    /// The number of images that were downsampled.
+    pub downsampled: usize,
+    /// The number of images that were re-encoded as JPEG.
+    pub reencoded: usize,
+    /// The number of images that were embedded by reusing an image with
+    /// identical pixel data.
+    pub deduplicated: usize,
+    /// The total size of the encoded image data before optimization, in
+    /// bytes.
+    pub original_bytes: usize,
+    /// The total size of the encoded image data after optimization, in bytes.
+    ///
+    /// Images that are not stored as JPEG are compressed further when writing
+    /// the PDF, so this is only an approximation of their final size.
+    pub optimized_bytes: usize,
+}
+
 /// Encapsulates a list of compatible PDF standards.
 #[derive(Clone)]
 pub struct PdfStandards {
@@ -176,6 +355,7 @@ impl PdfStandards {
                 PdfStandard::A_4f => set_archival_validator(Archival::A4F)?,
                 PdfStandard::A_4e => set_archival_validator(Archival::A4E)?,
                 PdfStandard::Ua_1 => set_accessibility_validator(Accessibility::UA1)?,
+                _ => bail!("{} is not supported", standard.display_name()),
             }
         }
 
@@ -269,63 +449,3 @@ impl Hash for PdfStandards {
         }
     }
 }
-
-/// A PDF standard that Typst can enforce conformance with.
-///
-/// Support for more standards is planned.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[allow(non_camel_case_types)]
-#[non_exhaustive]
-pub enum PdfStandard {
-    /// PDF 1.4.
-    #[serde(rename = "1.4")]
-    V_1_4,
-    /// PDF 1.5.
-    #[serde(rename = "1.5")]
-    V_1_5,
-    /// PDF 1.5.
-    #[serde(rename = "1.6")]
-    V_1_6,
-    /// PDF 1.7.
-    #[serde(rename = "1.7")]
-    V_1_7,
-    /// PDF 2.0.
-    #[serde(rename = "2.0")]
-    V_2_0,
-    /// PDF/A-1b.
-    #[serde(rename = "a-1b")]
-    A_1b,
-    /// PDF/A-1a.
-    #[serde(rename = "a-1a")]
-    A_1a,
-    /// PDF/A-2b.
-    #[serde(rename = "a-2b")]
-    A_2b,
-    /// PDF/A-2u.
-    #[serde(rename = "a-2u")]
-    A_2u,
-    /// PDF/A-2a.
-    #[serde(rename = "a-2a")]
-    A_2a,
-    /// PDF/A-3b.
-    #[serde(rename = "a-3b")]
-    A_3b,
-    /// PDF/A-3u.
-    #[serde(rename = "a-3u")]
-    A_3u,
-    /// PDF/A-3a.
-    #[serde(rename = "a-3a")]
-    A_3a,
-    /// PDF/A-4.
-    #[serde(rename = "a-4")]
-    A_4,
-    /// PDF/A-4f.
-    #[serde(rename = "a-4f")]
-    A_4f,
-    /// PDF/A-4e.
-    #[serde(rename = "a-4e")]
-    A_4e,
-    /// PDF/UA-1.
-    #[serde(rename = "ua-1")]
-    Ua_1,
-}
@@ -35,7 +35,9 @@ pub(crate) fn handle_link(
 ) -> SourceResult<()> {
     let target = match dest {
         Destination::Url(u) => {
-            Target::Action(Action::Link(LinkAction::new(u.to_string())))
+            let rewritten = gc.link_resolver.and_then(|resolver| resolver.rewrite_url(u));
+            let uri = rewritten.map_or_else(|| u.to_string(), Into::into);
+            Target::Action(Action::Link(LinkAction::new(uri)))
         }
         Destination::Position(p) => {
             let Some(dest) = pos_to_xyz(&gc.page_index_converter, *p) else {
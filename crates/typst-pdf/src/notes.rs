@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use ecow::EcoString;
+use krilla::Document;
+use krilla::embed::{AssociationKind, EmbeddedFile, MimeType};
+use typst_layout::PagedDocument;
+use typst_library::diag::{SourceResult, bail};
+use typst_library::foundations::NativeElement;
+use typst_library::introspection::Introspector;
+use typst_library::pdf::NoteElem;
+use typst_syntax::Span;
+
+use crate::convert::{GlobalContext, PageIndexConverter};
+use crate::{PdfOptions, Pdfpc, PdfpcPage, metadata};
+
+/// Collects the presenter notes of all exported pages.
+pub(crate) fn collect_pdfpc(document: &PagedDocument, options: &PdfOptions) -> Pdfpc {
+    let pic = PageIndexConverter::new(document, options);
+    let introspector = document.introspector();
+
+    // Notes are assigned to pages in order of appearance, which is the order
+    // of the query results.
+    let mut notes = vec![None::<EcoString>; document.pages().len()];
+    for elem in introspector.query(&NoteElem::ELEM.select()) {
+        let Some(pos) = elem.location().and_then(|loc| introspector.position(loc)) else {
+            continue;
+        };
+        let elem = elem.to_packed::<NoteElem>().unwrap();
+        let text = elem.body.plain_text();
+        let note = notes[pos.page.get() - 1].get_or_insert_default();
+        if !note.is_empty() {
+            note.push_str("\n\n");
+        }
+        note.push_str(text.trim());
+    }
+
+    let mut pages: Vec<PdfpcPage> = Vec::new();
+    for (i, (page, note)) in document.pages().iter().zip(notes).enumerate() {
+        let Some(idx) = pic.pdf_page_index(i) else { continue };
+        let label: EcoString = if options.presenter.overlays {
+            page.number.to_string().into()
+        } else {
+            (idx + 1).to_string().into()
+        };
+        let overlay = match pages.last() {
+            Some(prev) if prev.label == label => prev.overlay + 1,
+            _ => 0,
+        };
+        pages.push(PdfpcPage { idx, label, overlay, note });
+    }
+
+    Pdfpc { pdfpc_format: 2, pages }
+}
+
+/// Attaches the presenter notes to the PDF as a `.pdfpc` file.
+pub(crate) fn embed_notes(
+    gc: &GlobalContext,
+    document: &mut Document,
+) -> SourceResult<()> {
+    if !gc.options.presenter.embed {
+        return Ok(());
+    }
+
+    // Only embed something if there are any notes at all. The span of the
+    // first note is used for errors, e.g. when a PDF standard doesn't permit
+    // attachments.
+    let notes = gc.document.introspector().query(&NoteElem::ELEM.select());
+    let Some(span) = notes.first().map(|elem| elem.span()) else { return Ok(()) };
+
+    let pdfpc = collect_pdfpc(gc.document, gc.options);
+    let Ok(json) = serde_json::to_vec_pretty(&pdfpc) else {
+        bail!(Span::detached(), "failed to serialize presenter notes");
+    };
+
+    let data: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(json);
+    let file = EmbeddedFile {
+        path: "notes.pdfpc".into(),
+        mime_type: MimeType::new("application/json"),
+        description: Some("Presenter notes".into()),
+        association_kind: AssociationKind::Supplement,
+        data: data.into(),
+        compress: None,
+        location: Some(span.into_raw()),
+        modification_date: metadata::creation_date(gc),
+    };
+
+    if document.embed_file(file).is_none() {
+        bail!(span, "attempted to attach file notes.pdfpc twice");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use ecow::eco_format;
+use image::codecs::jpeg::{JpegEncoder, PixelDensity};
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder};
+use tiny_skia as sk;
+use typst_library::diag::StrResult;
+
+use crate::RenderOptions;
+
+/// A file format for rendered pages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RasterFormat {
+    /// Lossless compression with transparency. Channels always have 8 bits,
+    /// which is the precision pages are rendered with.
+    Png,
+    /// Lossy compression without transparency. Transparent areas are
+    /// composited onto white.
+    Jpg,
+    /// Lossless compression with transparency. Lossy WebP is not supported.
+    Webp,
+}
+
+impl RasterFormat {
+    /// The name of the format, for use in error messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpg => "JPEG",
+            Self::Webp => "WebP",
+        }
+    }
+}
+
+/// Encode a rendered page into an image file.
+///
+/// Besides the format-specific settings, this respects
+/// [`RenderOptions::embed_ppi`] and [`RenderOptions::srgb_profile`].
+#[typst_macros::time(name = "encode raster")]
+pub fn encode(
+    pixmap: &sk::Pixmap,
+    format: RasterFormat,
+    opts: &RenderOptions,
+) -> StrResult<Vec<u8>> {
+    match format {
+        RasterFormat::Png => encode_png(pixmap, opts),
+        RasterFormat::Jpg => encode_jpg(pixmap, opts),
+        RasterFormat::Webp => encode_webp(pixmap, opts),
+    }
+    .map_err(|err| eco_format!("failed to encode {} file ({err})", format.name()))
+}
+
+fn encode_png(pixmap: &sk::Pixmap, opts: &RenderOptions) -> Result<Vec<u8>, String> {
+    let mut data = pixmap.encode_png().map_err(|err| err.to_string())?;
+
+    let mut chunks = Vec::new();
+    if opts.embed_ppi {
+        // The PNG resolution is given in pixels per meter.
+        let ppm = (ppi(opts) / 0.0254).round() as u32;
+        let mut phys = Vec::with_capacity(9);
+        phys.extend_from_slice(&ppm.to_be_bytes());
+        phys.extend_from_slice(&ppm.to_be_bytes());
+        phys.push(1);
+        write_png_chunk(&mut chunks, b"pHYs", &phys);
+    }
+    if opts.srgb_profile {
+        // The sRGB chunk is a compact replacement for an embedded sRGB ICC
+        // profile. The payload is the perceptual rendering intent.
+        write_png_chunk(&mut chunks, b"sRGB", &[0]);
+    }
+
+    // The chunks must precede the image data, so we insert them right after
+    // the signature and the header chunk, which always comes first.
+    let header_len = data
+        .get(8..16)
+        .filter(|chunk| &chunk[4..] == b"IHDR")
+        .map(|chunk| u32::from_be_bytes(chunk[..4].try_into().unwrap()))
+        .ok_or("missing header chunk")?;
+    let header_end = 8 + 12 + header_len as usize;
+    data.splice(header_end..header_end, chunks);
+    Ok(data)
+}
+
+fn encode_jpg(pixmap: &sk::Pixmap, opts: &RenderOptions) -> Result<Vec<u8>, String> {
+    // Composite onto white. With premultiplied alpha, this is just an
+    // addition.
+    let rgb: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let rest = 255 - p.alpha();
+            [p.red() + rest, p.green() + rest, p.blue() + rest]
+        })
+        .collect();
+
+    let mut buf = Vec::new();
+    let mut encoder =
+        JpegEncoder::new_with_quality(&mut buf, opts.jpeg_quality.clamp(1, 100));
+    if opts.embed_ppi {
+        encoder.set_pixel_density(PixelDensity::dpi(ppi(opts).round() as u16));
+    }
+    if opts.srgb_profile {
+        encoder
+            .set_icc_profile(typst_assets::icc::S_RGB_V4.to_vec())
+            .map_err(|err| err.to_string())?;
+    }
+    encoder
+        .write_image(&rgb, pixmap.width(), pixmap.height(), ExtendedColorType::Rgb8)
+        .map_err(|err| err.to_string())?;
+    Ok(buf)
+}
+
+fn encode_webp(pixmap: &sk::Pixmap, opts: &RenderOptions) -> Result<Vec<u8>, String> {
+    let rgba: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+
+    // The WebP encoder only supports lossless compression. Lossy compression
+    // would require binding to libwebp, so `jpeg_quality` does not apply here.
+    let mut buf = Vec::new();
+    let mut encoder = WebPEncoder::new_lossless(&mut buf);
+    if opts.srgb_profile {
+        encoder
+            .set_icc_profile(typst_assets::icc::S_RGB_V4.to_vec())
+            .map_err(|err| err.to_string())?;
+    }
+    encoder
+        .write_image(&rgba, pixmap.width(), pixmap.height(), ExtendedColorType::Rgba8)
+        .map_err(|err| err.to_string())?;
+    Ok(buf)
+}
+
+/// The resolution of the rendered image in pixels per inch.
+fn ppi(opts: &RenderOptions) -> f64 {
+    opts.pixel_per_pt.get() * 72.0
+}
+
+/// Append a PNG chunk with the given type and data to `out`.
+fn write_png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(kind.iter().chain(data));
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// The CRC-32 checksum used by PNG.
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GenericImageView, ImageFormat};
+    use typst_utils::Scalar;
+
+    use super::*;
+
+    /// A pixmap whose left half is opaque red and right half is transparent.
+    fn pixmap() -> sk::Pixmap {
+        let mut pixmap = sk::Pixmap::new(4, 2).unwrap();
+        let paint = sk::Paint {
+            shader: sk::Shader::SolidColor(sk::Color::from_rgba8(255, 0, 0, 255)),
+            ..Default::default()
+        };
+        let rect = sk::Rect::from_xywh(0.0, 0.0, 2.0, 2.0).unwrap();
+        pixmap.fill_rect(rect, &paint, sk::Transform::identity(), None);
+        pixmap
+    }
+
+    fn options() -> RenderOptions {
+        RenderOptions {
+            pixel_per_pt: Scalar::new(4.0),
+            embed_ppi: true,
+            srgb_profile: true,
+            ..Default::default()
+        }
+    }
+
+    /// Split a PNG file into its chunks.
+    fn png_chunks(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        assert_eq!(&data[..8], b"\x89PNG\r\n\x1a\n");
+        let mut chunks = Vec::new();
+        let mut rest = &data[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = rest[4..8].try_into().unwrap();
+            let body = &rest[8..8 + len];
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(kind.iter().chain(body)));
+            chunks.push((kind, body));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+    }
+
+    #[test]
+    fn test_encode_png_metadata() {
+        let data = encode(&pixmap(), RasterFormat::Png, &options()).unwrap();
+        let chunks = png_chunks(&data);
+        let kinds: Vec<_> = chunks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds[..3], [b"IHDR", b"pHYs", b"sRGB"]);
+        assert!(kinds[3..].contains(&b"IDAT"));
+
+        // 288 PPI are 11339 pixels per meter.
+        let mut phys = 11339_u32.to_be_bytes().repeat(2);
+        phys.push(1);
+        assert_eq!(chunks[1].1, phys);
+        assert_eq!(chunks[2].1, [0]);
+
+        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 0).0[3], 0);
+    }
+
+    #[test]
+    fn test_encode_png_without_metadata() {
+        let data = encode(&pixmap(), RasterFormat::Png, &RenderOptions::default());
+        let data = data.unwrap();
+        let chunks = png_chunks(&data);
+        assert!(chunks.iter().all(|(kind, _)| kind != b"pHYs" && kind != b"sRGB"));
+        assert_eq!(data, pixmap().encode_png().unwrap());
+    }
+
+    #[test]
+    fn test_encode_jpg_metadata() {
+        let data = encode(&pixmap(), RasterFormat::Jpg, &options()).unwrap();
+
+        // The JFIF segment records the density in dots per inch.
+        assert_eq!(&data[..4], [0xFF, 0xD8, 0xFF, 0xE0]);
+        assert_eq!(&data[6..11], b"JFIF\0");
+        assert_eq!(data[13], 1);
+        assert_eq!(&data[14..18], [1, 32, 1, 32]);
+        assert!(data.windows(12).any(|w| w == b"ICC_PROFILE\0"));
+
+        // Transparent areas are composited onto white.
+        let image =
+            image::load_from_memory_with_format(&data, ImageFormat::Jpeg).unwrap();
+        assert_eq!(image.dimensions(), (4, 2));
+        let [r, g, b, _] = image.get_pixel(3, 1).0;
+        assert!(r > 240 && g > 240 && b > 240);
+    }
+
+    #[test]
+    fn test_encode_webp_metadata() {
+        let data = encode(&pixmap(), RasterFormat::Webp, &options()).unwrap();
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(&data[8..12], b"WEBP");
+        assert!(data.windows(4).any(|w| w == b"ICCP"));
+
+        // The compression is lossless.
+        let image =
+            image::load_from_memory_with_format(&data, ImageFormat::WebP).unwrap();
+        assert_eq!(image.get_pixel(0, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 1).0[3], 0);
+    }
+}
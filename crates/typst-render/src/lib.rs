@@ -1,14 +1,19 @@
 //! Rendering of Typst documents into raster images.
 
+mod encode;
 mod image;
 mod paint;
 mod shape;
 mod text;
 
+pub use self::encode::{RasterFormat, encode};
+
 use tiny_skia as sk;
 use typst_layout::{Page, PagedDocument};
+use typst_library::foundations::Smart;
 use typst_library::layout::{
-    Abs, Axes, Frame, FrameItem, FrameKind, GroupItem, Point, Sides, Size, Transform,
+    Abs, Axes, Frame, FrameItem, FrameKind, GroupItem, Point, Rect, Sides, Size,
+    Transform,
 };
 use typst_library::visualize::{Color, Geometry, Paint};
 use typst_utils::Scalar;
@@ -27,11 +32,16 @@ pub fn render(page: &Page, opts: &RenderOptions) -> sk::Pixmap {
     let pxh = (pixel_per_pt * size.y.to_f32()).round().max(1.0) as u32;
 
     let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt);
-    let state = State::new(size, ts, pixel_per_pt);
+    let state = State::new(size, ts, pixel_per_pt).with_thumbnail(opts.thumbnail);
 
     let mut canvas = sk::Pixmap::new(pxw, pxh).unwrap();
 
-    if let Some(fill) = page.fill_or_white() {
+    let fill = match &opts.background {
+        Smart::Auto => page.fill_or_white(),
+        Smart::Custom(color) => color.clone().map(Paint::Solid),
+    };
+
+    if let Some(fill) = fill {
         if let Paint::Solid(color) = fill {
             canvas.fill(paint::to_sk_color(color.to_process()));
         } else {
@@ -47,6 +57,50 @@ pub fn render(page: &Page, opts: &RenderOptions) -> sk::Pixmap {
     canvas
 }
 
+/// Render a rectangular region of a frame into an existing pixmap.
+///
+/// The region is given in the frame's coordinate system and rendered at the
+/// given number of pixels per point, with its top-left corner at the
+/// top-left corner of the pixmap. Content outside of the region is clipped.
+/// The pixmap is not cleared beforehand, so that the caller can decide on the
+/// background. This way, a large page can be rendered as tiles on demand, for
+/// example when zooming into it.
+///
+/// The options' bleed and background settings are ignored.
+#[typst_macros::time(name = "render region")]
+pub fn render_region(
+    canvas: &mut sk::Pixmap,
+    frame: &Frame,
+    region: Rect,
+    opts: &RenderOptions,
+) {
+    let pixel_per_pt = opts.pixel_per_pt.get() as f32;
+    let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt)
+        .pre_translate(-region.min.x.to_f32(), -region.min.y.to_f32());
+    let state = State::new(frame.size(), ts, pixel_per_pt).with_thumbnail(opts.thumbnail);
+
+    // A mask is only needed if the region doesn't span the whole pixmap.
+    let size = region.size();
+    let pxw = pixel_per_pt * size.x.to_f32();
+    let pxh = pixel_per_pt * size.y.to_f32();
+    if pxw >= canvas.width() as f32 && pxh >= canvas.height() as f32 {
+        render_frame(canvas, state, frame);
+        return;
+    }
+
+    let Some(rect) = sk::Rect::from_xywh(0.0, 0.0, pxw, pxh) else { return };
+    let Some(mut mask) = sk::Mask::new(canvas.width(), canvas.height()) else {
+        return;
+    };
+    mask.fill_path(
+        &sk::PathBuilder::from_rect(rect),
+        sk::FillRule::default(),
+        false,
+        sk::Transform::default(),
+    );
+    render_frame(canvas, state.with_mask(Some(&mask)), frame);
+}
+
 /// Export a document with potentially multiple pages into a single raster image.
 pub fn render_merged(
     document: &PagedDocument,
@@ -101,6 +155,21 @@ pub struct RenderOptions {
     /// margins. This field allows expanding the rendered area to include such
     /// bleed.
     pub render_bleed: bool,
+    /// Renders quickly at the expense of fidelity, which is useful for small
+    /// previews of many pages. Text that would be too small to be legible at
+    /// the chosen resolution is skipped.
+    pub thumbnail: bool,
+    /// Overrides the background of rendered pages. By default, the page's
+    /// fill is used. `None` results in a transparent background.
+    pub background: Smart<Option<Color>>,
+    /// The quality of JPEG files, from 1 to 100.
+    pub jpeg_quality: u8,
+    /// Whether to record the resolution in encoded PNG and JPEG files, so
+    /// that they are printed at the document's physical size.
+    pub embed_ppi: bool,
+    /// Whether to tag encoded files as being in the sRGB color space, with an
+    /// `sRGB` chunk in PNG files and an ICC profile in JPEG and WebP files.
+    pub srgb_profile: bool,
 }
 
 impl Default for RenderOptions {
@@ -108,6 +177,11 @@ impl Default for RenderOptions {
         Self {
             pixel_per_pt: Scalar::new(2.0),
             render_bleed: false,
+            thumbnail: false,
+            background: Smart::Auto,
+            jpeg_quality: 90,
+            embed_ppi: false,
+            srgb_profile: false,
         }
     }
 }
@@ -125,6 +199,8 @@ struct State<'a> {
     pixel_per_pt: f32,
     /// The size of the first hard frame in the hierarchy.
     size: Size,
+    /// Whether we are rendering a thumbnail.
+    thumbnail: bool,
 }
 
 impl<'a> State<'a> {
@@ -168,6 +244,11 @@ impl<'a> State<'a> {
         State { mask: mask.or(self.mask), ..self }
     }
 
+    /// Sets whether we are rendering a thumbnail.
+    fn with_thumbnail(self, thumbnail: bool) -> Self {
+        Self { thumbnail, ..self }
+    }
+
     /// Sets the size of the first hard frame in the hierarchy.
     fn with_size(self, size: Size) -> Self {
         Self { size, ..self }
@@ -284,3 +365,77 @@ impl AbsExt for Abs {
         self.to_pt() as f32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use typst_syntax::Span;
+
+    use super::*;
+
+    /// A 20pt × 20pt frame with a black square in its bottom-right quarter.
+    fn frame() -> Frame {
+        let mut frame = Frame::soft(Size::splat(Abs::pt(20.0)));
+        let square = Geometry::Rect(Size::splat(Abs::pt(10.0))).filled(Color::BLACK);
+        frame.push(
+            Point::splat(Abs::pt(10.0)),
+            FrameItem::Shape(square, Span::detached()),
+        );
+        frame
+    }
+
+    fn region(x: f64, y: f64, w: f64, h: f64) -> Rect {
+        Rect::new(
+            Point::new(Abs::pt(x), Abs::pt(y)),
+            Point::new(Abs::pt(x + w), Abs::pt(y + h)),
+        )
+    }
+
+    fn alpha(pixmap: &sk::Pixmap, x: u32, y: u32) -> u8 {
+        pixmap.pixel(x, y).unwrap().alpha()
+    }
+
+    #[test]
+    fn test_render_region_offset() {
+        let opts = RenderOptions {
+            pixel_per_pt: Scalar::new(1.0),
+            ..Default::default()
+        };
+
+        // The region's top-left corner ends up at the pixmap's origin.
+        let mut canvas = sk::Pixmap::new(10, 10).unwrap();
+        render_region(&mut canvas, &frame(), region(5.0, 5.0, 10.0, 10.0), &opts);
+        assert_eq!(alpha(&canvas, 2, 2), 0);
+        assert_eq!(alpha(&canvas, 4, 7), 0);
+        assert_eq!(alpha(&canvas, 7, 7), 255);
+        assert_eq!(alpha(&canvas, 9, 9), 255);
+
+        // The scale applies to the region's offset as well.
+        let opts = RenderOptions {
+            pixel_per_pt: Scalar::new(2.0),
+            ..Default::default()
+        };
+        let mut canvas = sk::Pixmap::new(20, 20).unwrap();
+        render_region(&mut canvas, &frame(), region(5.0, 5.0, 10.0, 10.0), &opts);
+        assert_eq!(alpha(&canvas, 9, 9), 0);
+        assert_eq!(alpha(&canvas, 10, 10), 255);
+    }
+
+    #[test]
+    fn test_render_region_clipping() {
+        let opts = RenderOptions {
+            pixel_per_pt: Scalar::new(1.0),
+            ..Default::default()
+        };
+
+        // The region is smaller than the pixmap, so the rest of the square
+        // must be clipped. The pixmap is not cleared.
+        let mut canvas = sk::Pixmap::new(10, 10).unwrap();
+        canvas.fill(sk::Color::from_rgba8(0, 0, 255, 128));
+        render_region(&mut canvas, &frame(), region(5.0, 5.0, 8.0, 8.0), &opts);
+        assert_eq!(alpha(&canvas, 2, 2), 128);
+        assert_eq!(alpha(&canvas, 7, 7), 255);
+        assert_eq!(alpha(&canvas, 8, 8), 128);
+        assert_eq!(alpha(&canvas, 9, 5), 128);
+        assert_eq!(alpha(&canvas, 5, 9), 128);
+    }
+}
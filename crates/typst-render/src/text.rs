@@ -11,8 +11,18 @@ use typst_library::visualize::{FixedStroke, Paint};
 use crate::paint::{self, GradientSampler, PaintSampler, TilingSampler};
 use crate::{AbsExt, State, shape};
 
+/// Text with fewer pixels per em than this is skipped in thumbnails.
+const THUMBNAIL_MIN_PPEM: f32 = 4.0;
+
 /// Render a text run into the canvas.
 pub fn render_text(canvas: &mut sk::Pixmap, state: State, text: &TextItem) {
+    if state.thumbnail {
+        let (sx, sy) = state.transform.get_scale();
+        if text.size.to_f32() * sx.max(sy) < THUMBNAIL_MIN_PPEM {
+            return;
+        }
+    }
+
     let mut x = Abs::zero();
     let mut y = Abs::zero();
     for glyph in &text.glyphs {
@@ -279,3 +289,68 @@ fn alpha_mul(color: u32, scale: u32) -> u32 {
     let ag = ((color >> 8) & mask) * scale;
     (rb & mask) | (ag & !mask)
 }
+
+#[cfg(test)]
+mod tests {
+    use typst_library::foundations::Bytes;
+    use typst_library::layout::Em;
+    use typst_library::text::{Font, FontVariant, FontVariations, Glyph, Lang};
+    use typst_library::visualize::Color;
+    use typst_syntax::Span;
+
+    use super::*;
+
+    /// A text item with a single "H" glyph.
+    fn text(size: Abs) -> TextItem {
+        let font = typst_dev_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::new(data)))
+            .find(|font| font.info().coverage.contains('H' as u32))
+            .unwrap()
+            .instantiate(FontVariant::default(), size, &FontVariations::default());
+        let id = font.ttf().glyph_index('H').unwrap().0;
+        TextItem {
+            font,
+            size,
+            fill: Paint::Solid(Color::BLACK),
+            stroke: None,
+            lang: Lang::ENGLISH,
+            region: None,
+            text: "H".into(),
+            glyphs: vec![Glyph {
+                id,
+                x_advance: Em::one(),
+                x_offset: Em::zero(),
+                y_advance: Em::zero(),
+                y_offset: Em::zero(),
+                range: 0..1,
+                span: (Span::detached(), 0),
+            }],
+        }
+    }
+
+    /// Whether rendering the text at the given scale produces any pixels.
+    fn renders(text: &TextItem, pixel_per_pt: f32, thumbnail: bool) -> bool {
+        let mut canvas = sk::Pixmap::new(40, 40).unwrap();
+        let ts = sk::Transform::from_scale(pixel_per_pt, pixel_per_pt)
+            .pre_translate(0.0, text.size.to_f32() * 1.5);
+        let size = Size::splat(Abs::pt(40.0));
+        let state = State::new(size, ts, pixel_per_pt).with_thumbnail(thumbnail);
+        render_text(&mut canvas, state, text);
+        canvas.pixels().iter().any(|pixel| pixel.alpha() > 0)
+    }
+
+    #[test]
+    fn test_thumbnail_skips_small_text() {
+        let text = text(Abs::pt(10.0));
+        let small = 0.9 * THUMBNAIL_MIN_PPEM / 10.0;
+        assert!(renders(&text, 1.0, true));
+        assert!(renders(&text, small, false));
+        assert!(!renders(&text, small, true));
+    }
+
+    #[test]
+    fn test_thumbnail_keeps_text_at_threshold() {
+        let text = text(Abs::pt(f64::from(THUMBNAIL_MIN_PPEM)));
+        assert!(renders(&text, 1.0, true));
+    }
+}
@@ -0,0 +1,554 @@
+//! Embedding of fonts as WOFF2 web fonts for selectable text.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use brotli::enc::BrotliEncoderParams;
+use brotli::enc::backward_references::BrotliEncoderMode;
+use subsetter::GlyphRemapper;
+use ttf_parser::{Permissions, RawFace, Tag};
+use typst_library::text::{Font, FontInstance};
+
+/// A font that is referenced by `<text>` elements and embedded into the SVG
+/// as a web font.
+pub struct EmbeddedFont {
+    /// The font to subset.
+    font: Font,
+    /// Maps the characters written to the SVG to the glyphs in the original
+    /// font they must be displayed with.
+    cmap: BTreeMap<char, u16>,
+}
+
+impl EmbeddedFont {
+    /// Create a new embedded font without any glyphs.
+    pub fn new(font: Font) -> Self {
+        Self { font, cmap: BTreeMap::new() }
+    }
+
+    /// Try to display `c` with `glyph`.
+    ///
+    /// Since the embedded font is not shaped by the viewer, every character
+    /// can only map to a single glyph. Returns `false` if the character is
+    /// already displayed with a different glyph elsewhere, for example because
+    /// of contextual alternates.
+    pub fn map(&mut self, c: char, glyph: u16) -> bool {
+        *self.cmap.entry(c).or_insert(glyph) == glyph
+    }
+
+    /// Subset the font to the mapped glyphs and encode it as WOFF2.
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        let mut remapper = GlyphRemapper::new();
+        let mapping: Vec<(char, u16)> = self
+            .cmap
+            .iter()
+            .map(|(&c, &glyph)| (c, remapper.remap(glyph)))
+            .collect();
+
+        let subset =
+            subsetter::subset(self.font.data(), self.font.index(), &remapper).ok()?;
+        let subset_face = RawFace::parse(&subset, 0).ok()?;
+        let original_face = RawFace::parse(self.font.data(), self.font.index()).ok()?;
+
+        // The subsetter only keeps what is needed for PDF embedding. Web fonts
+        // additionally need a character map and a few tables that browsers
+        // check during font sanitization.
+        let mut tables: Vec<(Tag, Cow<[u8]>)> = subset_face
+            .table_records
+            .into_iter()
+            .filter(|record| ![CMAP, POST].contains(&record.tag))
+            .filter_map(|record| {
+                Some((record.tag, subset_face.table(record.tag)?.into()))
+            })
+            .collect();
+
+        for tag in [OS2, NAME, HHEA] {
+            if tables.iter().all(|(t, _)| *t != tag)
+                && let Some(data) = original_face.table(tag)
+            {
+                tables.push((tag, data.into()));
+            }
+        }
+
+        // The copied tables describe the glyphs and characters of the
+        // original font, so they must be updated to match the subset.
+        let num_h_metrics = num_h_metrics(&subset_face)?;
+        for (tag, data) in &mut tables {
+            match *tag {
+                HHEA => data
+                    .to_mut()
+                    .get_mut(34..36)?
+                    .copy_from_slice(&num_h_metrics.to_be_bytes()),
+                OS2 => patch_os2(data.to_mut(), &mapping),
+                _ => {}
+            }
+        }
+
+        tables.push((CMAP, write_cmap(&mapping).into()));
+        tables.push((POST, write_post(original_face.table(POST)).into()));
+        tables.sort_by_key(|(tag, _)| *tag);
+
+        let flavor = u32::from_be_bytes(subset.get(..4)?.try_into().ok()?);
+        write_woff2(flavor, &tables)
+    }
+}
+
+/// Whether the glyphs of a font instance can be written as text backed by an
+/// embedded web font.
+///
+/// This is not the case for fonts whose license disallows embedding or
+/// subsetting and for instances of variable fonts, since the subset would
+/// lose their variation coordinates.
+pub fn can_embed(font: &FontInstance) -> bool {
+    let ttf = font.ttf();
+    let tables = ttf.tables();
+    font.variations().0.is_empty()
+        && ttf.permissions() != Some(Permissions::Restricted)
+        && ttf.is_subsetting_allowed()
+        && ttf.is_outline_embedding_allowed()
+        && (tables.glyf.is_some() || tables.cff.is_some())
+}
+
+const CMAP: Tag = Tag::from_bytes(b"cmap");
+const GLYF: Tag = Tag::from_bytes(b"glyf");
+const HHEA: Tag = Tag::from_bytes(b"hhea");
+const HMTX: Tag = Tag::from_bytes(b"hmtx");
+const LOCA: Tag = Tag::from_bytes(b"loca");
+const MAXP: Tag = Tag::from_bytes(b"maxp");
+const NAME: Tag = Tag::from_bytes(b"name");
+const OS2: Tag = Tag::from_bytes(b"OS/2");
+const POST: Tag = Tag::from_bytes(b"post");
+
+/// Write a `cmap` table for the given sorted character to glyph mapping.
+///
+/// Contains a format 4 subtable for the Basic Multilingual Plane, which some
+/// font sanitizers require, and a format 12 subtable for all characters.
+fn write_cmap(mapping: &[(char, u16)]) -> Vec<u8> {
+    let bmp: Vec<_> = mapping.iter().copied().filter(|&(c, _)| c < '\u{FFFF}').collect();
+    let bmp_ranges = ranges(&bmp);
+    let all_ranges = ranges(mapping);
+
+    // Format 4 lengths are 16-bit, so it must be skipped for very large
+    // subsets.
+    let seg_count = bmp_ranges.len() + 1;
+    let format_4_len = 16 + 8 * seg_count;
+    let has_format_4 = format_4_len <= usize::from(u16::MAX);
+
+    let mut out = Vec::new();
+    let num_tables = if has_format_4 { 2 } else { 1 };
+    push_u16(&mut out, 0);
+    push_u16(&mut out, num_tables);
+
+    let mut offset = 4 + 8 * u32::from(num_tables);
+    if has_format_4 {
+        push_u16(&mut out, 3);
+        push_u16(&mut out, 1);
+        push_u32(&mut out, offset);
+        offset += format_4_len as u32;
+    }
+    push_u16(&mut out, 3);
+    push_u16(&mut out, 10);
+    push_u32(&mut out, offset);
+
+    if has_format_4 {
+        let exponent = seg_count.ilog2();
+        let search_range = 2 * (1 << exponent) as u16;
+        push_u16(&mut out, 4);
+        push_u16(&mut out, format_4_len as u16);
+        push_u16(&mut out, 0);
+        push_u16(&mut out, 2 * seg_count as u16);
+        push_u16(&mut out, search_range);
+        push_u16(&mut out, exponent as u16);
+        push_u16(&mut out, 2 * seg_count as u16 - search_range);
+
+        // The final segment must map `0xFFFF` to the missing glyph.
+        let segments = bmp_ranges
+            .iter()
+            .map(|&(start, end, glyph)| (start as u16, end as u16, glyph))
+            .chain(std::iter::once((0xFFFF, 0xFFFF, 0)));
+        let segments: Vec<_> = segments.collect();
+        for &(_, end, _) in &segments {
+            push_u16(&mut out, end);
+        }
+        push_u16(&mut out, 0);
+        for &(start, _, _) in &segments {
+            push_u16(&mut out, start);
+        }
+        for &(start, _, glyph) in &segments {
+            push_u16(&mut out, glyph.wrapping_sub(start));
+        }
+        for _ in &segments {
+            push_u16(&mut out, 0);
+        }
+    }
+
+    push_u16(&mut out, 12);
+    push_u16(&mut out, 0);
+    push_u32(&mut out, 16 + 12 * all_ranges.len() as u32);
+    push_u32(&mut out, 0);
+    push_u32(&mut out, all_ranges.len() as u32);
+    for &(start, end, glyph) in &all_ranges {
+        push_u32(&mut out, start);
+        push_u32(&mut out, end);
+        push_u32(&mut out, glyph.into());
+    }
+
+    out
+}
+
+/// Group a sorted mapping into ranges of consecutive characters that map to
+/// consecutive glyphs.
+fn ranges(mapping: &[(char, u16)]) -> Vec<(u32, u32, u16)> {
+    let mut ranges: Vec<(u32, u32, u16)> = Vec::new();
+    for &(c, glyph) in mapping {
+        let c = c as u32;
+        match ranges.last_mut() {
+            Some((start, end, first))
+                if *end + 1 == c
+                    && u32::from(*first) + (c - *start) == u32::from(glyph) =>
+            {
+                *end = c;
+            }
+            _ => ranges.push((c, c, glyph)),
+        }
+    }
+    ranges
+}
+
+/// Determine the number of advance widths in the `hmtx` table of a font,
+/// which the `hhea` table must specify as `numberOfHMetrics`.
+fn num_h_metrics(face: &RawFace) -> Option<u16> {
+    let num_glyphs = face.table(MAXP)?.get(4..6)?;
+    let num_glyphs = usize::from(u16::from_be_bytes(num_glyphs.try_into().ok()?));
+    // Every glyph has a two byte side bearing and the first
+    // `numberOfHMetrics` glyphs additionally a two byte advance.
+    let len = face.table(HMTX)?.len();
+    let count = len.checked_sub(2 * num_glyphs)? / 2;
+    u16::try_from(count)
+        .ok()
+        .filter(|&count| count >= 1 && usize::from(count) <= num_glyphs)
+}
+
+/// Update the character range of an `OS/2` table to the given sorted
+/// character to glyph mapping.
+///
+/// The Unicode range bits are cleared instead of being recomputed. They only
+/// hint at the coverage and the ones of the original font would claim scripts
+/// that are not part of the subset.
+fn patch_os2(data: &mut [u8], mapping: &[(char, u16)]) {
+    let to_u16 = |c: char| (c as u32).min(0xFFFF) as u16;
+    let first = mapping.first().map_or(0xFFFF, |&(c, _)| to_u16(c));
+    let last = mapping.last().map_or(0, |&(c, _)| to_u16(c));
+    if let Some(ranges) = data.get_mut(42..58) {
+        ranges.fill(0);
+    }
+    if let Some(range) = data.get_mut(64..68) {
+        range[..2].copy_from_slice(&first.to_be_bytes());
+        range[2..].copy_from_slice(&last.to_be_bytes());
+    }
+}
+
+/// Write a version 3 `post` table, which doesn't contain glyph names that
+/// would be invalidated by subsetting.
+fn write_post(original: Option<&[u8]>) -> Vec<u8> {
+    let mut out = vec![0; 32];
+    out[..4].copy_from_slice(&0x0003_0000_u32.to_be_bytes());
+    // Keep the italic angle, underline metrics and fixed pitch flag.
+    if let Some(metrics) = original.and_then(|data| data.get(4..16)) {
+        out[4..16].copy_from_slice(metrics);
+    }
+    out
+}
+
+/// Write a WOFF2 file with the given sorted tables.
+///
+/// All tables are stored with the null transform, so the font data is only
+/// compressed, but not restructured.
+fn write_woff2(flavor: u32, tables: &[(Tag, Cow<[u8]>)]) -> Option<Vec<u8>> {
+    let mut directory = Vec::new();
+    let mut data = Vec::new();
+    let mut sfnt_size = 12 + 16 * tables.len();
+    for (tag, table) in tables {
+        // The flags use `63` to denote that an arbitrary tag follows. For
+        // `glyf` and `loca`, transform version 3 is the null transform, while
+        // it is version 0 for all other tables.
+        let transform = if [GLYF, LOCA].contains(tag) { 3 << 6 } else { 0 };
+        directory.push(63 | transform);
+        directory.extend_from_slice(&tag.to_bytes());
+        push_base128(&mut directory, table.len() as u32);
+        data.extend_from_slice(table);
+        sfnt_size += table.len().next_multiple_of(4);
+    }
+
+    let params = BrotliEncoderParams {
+        quality: 9,
+        mode: BrotliEncoderMode::BROTLI_MODE_FONT,
+        size_hint: data.len(),
+        ..Default::default()
+    };
+    let mut compressed = Vec::new();
+    brotli::BrotliCompress(&mut data.as_slice(), &mut compressed, &params).ok()?;
+
+    let header_len = 48;
+    let len = (header_len + directory.len() + compressed.len()).next_multiple_of(4);
+    let mut out = Vec::with_capacity(len);
+    out.extend_from_slice(b"wOF2");
+    push_u32(&mut out, flavor);
+    push_u32(&mut out, len as u32);
+    push_u16(&mut out, tables.len() as u16);
+    push_u16(&mut out, 0);
+    push_u32(&mut out, sfnt_size as u32);
+    push_u32(&mut out, compressed.len() as u32);
+    push_u16(&mut out, 1);
+    push_u16(&mut out, 0);
+    // No metadata and private data blocks.
+    out.extend_from_slice(&[0; 20]);
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&compressed);
+    out.resize(len, 0);
+    Some(out)
+}
+
+/// Write a `UIntBase128` as used by WOFF2.
+fn push_base128(out: &mut Vec<u8>, value: u32) {
+    let len = (1..5).find(|&i| value >> (7 * i) == 0).unwrap_or(5);
+    for i in (0..len).rev() {
+        let byte = ((value >> (7 * i)) & 0x7F) as u8;
+        out.push(if i > 0 { byte | 0x80 } else { byte });
+    }
+}
+
+fn push_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use ttf_parser::cmap::{Format, Subtable};
+    use ttf_parser::{Face, GlyphId};
+    use typst_library::foundations::Bytes;
+
+    use super::*;
+
+    const MAPPING: &[(char, u16)] =
+        &[('A', 3), ('B', 4), ('C', 5), ('a', 9), ('é', 2), ('😀', 7)];
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(
+            ranges(MAPPING),
+            [(0x41, 0x43, 3), (0x61, 0x61, 9), (0xE9, 0xE9, 2), (0x1F600, 0x1F600, 7)]
+        );
+        assert_eq!(ranges(&[('x', 1), ('y', 5)]), [(0x78, 0x78, 1), (0x79, 0x79, 5)]);
+        assert!(ranges(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_cmap_round_trip() {
+        let data = write_cmap(MAPPING);
+        let cmap = ttf_parser::cmap::Table::parse(&data).unwrap();
+        let subtables: Vec<Subtable> = cmap.subtables.into_iter().collect();
+        assert_eq!(subtables.len(), 2);
+        assert!(matches!(subtables[0].format, Format::SegmentMappingToDeltaValues(_)));
+        assert!(matches!(subtables[1].format, Format::SegmentedCoverage(_)));
+
+        for subtable in &subtables {
+            assert!(subtable.is_unicode());
+            for &(c, glyph) in MAPPING {
+                let expected = Some(GlyphId(glyph));
+                if matches!(subtable.format, Format::SegmentMappingToDeltaValues(_))
+                    && c > '\u{FFFF}'
+                {
+                    assert_eq!(subtable.glyph_index(c as u32), None);
+                } else {
+                    assert_eq!(subtable.glyph_index(c as u32), expected);
+                }
+            }
+            assert_eq!(subtable.glyph_index('D' as u32), None);
+        }
+    }
+
+    #[test]
+    fn test_cmap_without_format_4() {
+        // Every other character, so that each needs its own segment.
+        let mapping: Vec<_> = (0..10_000_u16)
+            .map(|i| (char::from_u32(0x4E00 + 2 * u32::from(i)).unwrap(), i + 1))
+            .collect();
+        let data = write_cmap(&mapping);
+        let cmap = ttf_parser::cmap::Table::parse(&data).unwrap();
+        let subtables: Vec<Subtable> = cmap.subtables.into_iter().collect();
+        assert_eq!(subtables.len(), 1);
+        assert!(matches!(subtables[0].format, Format::SegmentedCoverage(_)));
+        for &(c, glyph) in &mapping {
+            assert_eq!(subtables[0].glyph_index(c as u32), Some(GlyphId(glyph)));
+        }
+    }
+
+    #[test]
+    fn test_post_keeps_metrics() {
+        let font = test_font();
+        let face = RawFace::parse(font.data(), font.index()).unwrap();
+        let data = face.table(POST).unwrap();
+        let original = ttf_parser::post::Table::parse(data).unwrap();
+
+        let post = ttf_parser::post::Table::parse(&write_post(Some(data))).unwrap();
+        assert_eq!(post.italic_angle, original.italic_angle);
+        assert_eq!(post.underline_metrics, original.underline_metrics);
+        assert_eq!(post.is_monospaced, original.is_monospaced);
+
+        let post = ttf_parser::post::Table::parse(&write_post(None)).unwrap();
+        assert_eq!(post.italic_angle, 0.0);
+    }
+
+    #[test]
+    fn test_woff2_round_trip() {
+        let tables: Vec<(Tag, Cow<[u8]>)> = vec![
+            (CMAP, vec![1, 2, 3].into()),
+            (GLYF, vec![4; 200].into()),
+            (NAME, vec![5; 5].into()),
+        ];
+        let data = write_woff2(0x0001_0000, &tables).unwrap();
+        assert_eq!(&data[..4], b"wOF2");
+        assert_eq!(read_u32(&data, 4), 0x0001_0000);
+        assert_eq!(read_u32(&data, 8) as usize, data.len());
+        assert_eq!(data.len() % 4, 0);
+        assert_eq!(read_u32(&data, 16) as usize, 12 + 16 * 3 + 4 + 200 + 8);
+        assert_eq!(decode_woff2(&data).1, tables);
+    }
+
+    #[test]
+    fn test_base128() {
+        for (value, bytes) in [
+            (0, vec![0x00]),
+            (127, vec![0x7F]),
+            (128, vec![0x81, 0x00]),
+            (16_384, vec![0x81, 0x80, 0x00]),
+            (u32::MAX, vec![0x8F, 0xFF, 0xFF, 0xFF, 0x7F]),
+        ] {
+            let mut out = Vec::new();
+            push_base128(&mut out, value);
+            assert_eq!(out, bytes);
+        }
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let font = test_font();
+        let original = Face::parse(font.data(), font.index()).unwrap();
+        let mut embedded = EmbeddedFont::new(font.clone());
+        let chars = ['T', 'y', 'p', 's', 't', '!'];
+        for c in chars {
+            assert!(embedded.map(c, original.glyph_index(c).unwrap().0));
+        }
+        assert!(!embedded.map('T', 0));
+
+        let data = embedded.encode().unwrap();
+        let (flavor, tables) = decode_woff2(&data);
+        let sfnt = write_sfnt(flavor, &tables);
+        let face = Face::parse(&sfnt, 0).unwrap();
+        assert!(face.number_of_glyphs() < original.number_of_glyphs());
+        for c in chars {
+            let glyph = face.glyph_index(c).unwrap();
+            let original_glyph = original.glyph_index(c).unwrap();
+            assert_eq!(
+                face.glyph_hor_advance(glyph),
+                original.glyph_hor_advance(original_glyph),
+            );
+        }
+
+        // The copied `hhea` and `OS/2` tables match the subset.
+        let hhea = face.tables().hhea;
+        assert!(hhea.number_of_metrics <= face.number_of_glyphs());
+        let os2 = face.raw_face().table(OS2).unwrap();
+        assert_eq!(read_u16(os2, 64), '!' as u16);
+        assert_eq!(read_u16(os2, 66), 'y' as u16);
+        assert!(os2[42..58].iter().all(|&b| b == 0));
+    }
+
+    /// A font from the development assets that can be subset.
+    fn test_font() -> Font {
+        typst_dev_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::new(data)))
+            .find(|font| {
+                Face::parse(font.data(), font.index()).is_ok_and(|face| {
+                    face.tables().glyf.is_some()
+                        && "Typst!".chars().all(|c| face.glyph_index(c).is_some())
+                })
+            })
+            .unwrap()
+    }
+
+    /// Decode a WOFF2 file with null transforms into its flavor and tables.
+    fn decode_woff2(data: &[u8]) -> (u32, Vec<(Tag, Cow<'static, [u8]>)>) {
+        let num_tables = read_u16(data, 12);
+        let compressed_len = read_u32(data, 20) as usize;
+
+        let mut cursor = 48;
+        let mut entries = Vec::new();
+        for _ in 0..num_tables {
+            assert_eq!(data[cursor] & 63, 63);
+            let tag = Tag::from_bytes(data[cursor + 1..cursor + 5].try_into().unwrap());
+            cursor += 5;
+            let mut len = 0_u32;
+            loop {
+                let byte = data[cursor];
+                cursor += 1;
+                len = (len << 7) | u32::from(byte & 0x7F);
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            entries.push((tag, len as usize));
+        }
+
+        let mut decompressed = Vec::new();
+        let mut compressed = &data[cursor..cursor + compressed_len];
+        brotli::BrotliDecompress(&mut compressed, &mut decompressed).unwrap();
+
+        let mut offset = 0;
+        let tables = entries
+            .into_iter()
+            .map(|(tag, len)| {
+                let table = decompressed[offset..offset + len].to_vec();
+                offset += len;
+                (tag, Cow::Owned(table))
+            })
+            .collect();
+        assert_eq!(offset, decompressed.len());
+        (read_u32(data, 4), tables)
+    }
+
+    /// Write an OpenType file with the given sorted tables.
+    fn write_sfnt(flavor: u32, tables: &[(Tag, Cow<[u8]>)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        push_u32(&mut out, flavor);
+        push_u16(&mut out, tables.len() as u16);
+        // Search range, entry selector and range shift are not checked.
+        out.extend_from_slice(&[0; 6]);
+
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, table) in tables {
+            out.extend_from_slice(&tag.to_bytes());
+            push_u32(&mut out, 0);
+            push_u32(&mut out, offset as u32);
+            push_u32(&mut out, table.len() as u32);
+            offset += table.len().next_multiple_of(4);
+        }
+        for (_, table) in tables {
+            out.extend_from_slice(table);
+            out.resize(out.len().next_multiple_of(4), 0);
+        }
+        out
+    }
+
+    fn read_u16(data: &[u8], at: usize) -> u16 {
+        u16::from_be_bytes(data[at..at + 2].try_into().unwrap())
+    }
+
+    fn read_u32(data: &[u8], at: usize) -> u32 {
+        u32::from_be_bytes(data[at..at + 4].try_into().unwrap())
+    }
+}
@@ -124,7 +124,7 @@ impl WebImage {
             },
             ImageKind::Svg(svg) => (WebImageFormat::Svg, svg.data().clone()),
             ImageKind::Pdf(pdf) => {
-                (WebImageFormat::Svg, Bytes::from_string(pdf_to_svg(pdf)))
+                (WebImageFormat::Svg, Bytes::from_string(convert_pdf_to_svg(pdf)))
             }
         };
         Self { format, data }
@@ -142,8 +142,12 @@ impl WebImage {
     }
 }
 
+/// Converts a PDF page into an SVG.
+///
+/// Text is converted into outlines. This is also used by PDF export when a
+/// standard forbids embedding PDFs as-is.
 // Keep this in sync with `typst-png`!
-fn pdf_to_svg(pdf: &PdfImage) -> String {
+pub fn convert_pdf_to_svg(pdf: &PdfImage) -> String {
     let select_standard_font = move |font: StandardFont| -> Option<(FontData, u32)> {
         let bytes = match font {
             StandardFont::Helvetica => typst_assets::pdf::SANS,
@@ -1,6 +1,8 @@
 //! Rendering of Typst documents into SVG images.
 
+mod font;
 mod image;
+mod link;
 mod paint;
 mod path;
 mod shape;
@@ -8,12 +10,14 @@ mod text;
 mod write;
 
 use comemo::Tracked;
-pub use image::{WebImage, convert_image_scaling};
+pub use image::{WebImage, convert_image_scaling, convert_pdf_to_svg};
 use indexmap::IndexMap;
+pub use link::SvgLinks;
 use rustc_hash::FxBuildHasher;
 use typst_library::model::{Destination, LateLinkResolver};
 
 use std::hash::Hash;
+use std::num::NonZeroUsize;
 
 use ecow::EcoString;
 use typst_layout::{Page, PagedDocument};
@@ -21,18 +25,20 @@ use typst_library::layout::{
     Abs, Frame, FrameItem, FrameKind, GroupItem, Point, Ratio, Sides, Size, Transform,
 };
 use typst_library::visualize::{Geometry, Gradient, Tiling};
+use typst_utils::NonZeroExt;
 use xmlwriter::XmlWriter;
 
+use crate::font::EmbeddedFont;
 use crate::paint::{GradientRef, SVGSubGradient, TilingRef};
 use crate::text::RenderedGlyph;
-use crate::write::{SvgDisplay, SvgElem, SvgTransform, SvgUrl, SvgWrite};
+use crate::write::{SvgDisplay, SvgElem, SvgEscaped, SvgTransform, SvgUrl, SvgWrite};
 
 /// Export a frame into an SVG file.
 #[typst_macros::time(name = "svg")]
 pub fn svg(page: &Page, opts: &SvgOptions) -> String {
     let (size, ts) = page_bleed(page, opts);
 
-    let mut renderer = SVGRenderer::new();
+    let mut renderer = SVGRenderer::new(opts.text);
     let mut xml = XmlWriter::new(xml_options(opts.pretty));
     let mut svg = svg_header(&mut xml, size);
 
@@ -42,6 +48,36 @@ pub fn svg(page: &Page, opts: &SvgOptions) -> String {
     xml.end_document()
 }
 
+/// Export a page of a document into an SVG file, resolving links to other
+/// places in the document through `links`.
+///
+/// The `number` is the page's physical page number, starting at one.
+#[typst_macros::time(name = "svg")]
+pub fn svg_with_links(
+    page: &Page,
+    number: NonZeroUsize,
+    opts: &SvgOptions,
+    links: &SvgLinks,
+) -> String {
+    let (size, ts) = page_bleed(page, opts);
+
+    let mut renderer = SVGRenderer::new(opts.text);
+    renderer.links = Some(links);
+    renderer.page = number;
+    let mut xml = XmlWriter::new(xml_options(opts.pretty));
+    let mut svg = svg_header(&mut xml, size);
+
+    let state = State::new(size);
+    renderer.render_page(&mut svg, &state, ts, page);
+
+    for (pos, id) in links.anchors(number) {
+        renderer.render_anchor(&mut svg, pos.transform(ts), &id);
+    }
+
+    renderer.finalize(svg);
+    xml.end_document()
+}
+
 /// Export a page into an SVG file as part of a bundle.
 ///
 /// Takes additional `anchor` locations that will be serialized as linkable
@@ -57,7 +93,7 @@ pub fn svg_in_bundle(
 ) -> String {
     let (size, ts) = page_bleed(page, opts);
 
-    let mut renderer = SVGRenderer::with_options(Some(link_resolver));
+    let mut renderer = SVGRenderer::with_options(opts.text, Some(link_resolver));
     let mut xml = XmlWriter::new(xml_options(opts.pretty));
     let mut svg = svg_header(&mut xml, size);
 
@@ -88,7 +124,8 @@ pub fn svg_in_html(
     anchors: &[(Point, EcoString)],
     link_resolver: Tracked<LateLinkResolver>,
 ) -> String {
-    let mut renderer = SVGRenderer::with_options(Some(link_resolver));
+    let mut renderer =
+        SVGRenderer::with_options(SvgTextMode::default(), Some(link_resolver));
     let mut xml = XmlWriter::new(xmlwriter::Options {
         indent: xmlwriter::Indent::None,
         ..xml_options(pretty)
@@ -124,7 +161,8 @@ pub fn svg_in_html(
 
 /// Export a document with potentially multiple pages into a single SVG file.
 ///
-/// The gap will be added between the individual pages.
+/// The gap will be added between the individual pages. Links between the
+/// pages are written as fragment links.
 pub fn svg_merged(document: &PagedDocument, opts: &SvgOptions, gap: Abs) -> String {
     let num_gaps = document.pages().len().saturating_sub(1) as f64;
     let mut size = Size::new(Abs::zero(), num_gaps * gap);
@@ -134,20 +172,24 @@ pub fn svg_merged(document: &PagedDocument, opts: &SvgOptions, gap: Abs) -> Stri
         size.y += page_size.y;
     }
 
-    let mut renderer = SVGRenderer::new();
+    // All pages end up in the same file.
+    let links = SvgLinks::new(document, |_| Some(EcoString::new()));
+
+    let mut renderer = SVGRenderer::new(opts.text);
+    renderer.links = Some(&links);
     let mut xml = XmlWriter::new(xml_options(opts.pretty));
     let mut svg = svg_header(&mut xml, size);
 
     let mut y = Abs::zero();
-    for page in document.pages() {
+    for (i, page) in document.pages().iter().enumerate() {
         let (page_size, bleed_ts) = page_bleed(page, opts);
         let state = State::new(page_size);
-        renderer.render_page(
-            &mut svg,
-            &state,
-            Transform::translate(Abs::zero(), y).pre_concat(bleed_ts),
-            page,
-        );
+        let ts = Transform::translate(Abs::zero(), y).pre_concat(bleed_ts);
+        renderer.page = NonZeroUsize::new(i + 1).unwrap();
+        renderer.render_page(&mut svg, &state, ts, page);
+        for (pos, id) in links.anchors(renderer.page) {
+            renderer.render_anchor(&mut svg, pos.transform(ts), &id);
+        }
         y += page_size.y + gap;
     }
 
@@ -185,12 +227,41 @@ pub struct SvgOptions {
     pub render_bleed: bool,
     /// Whether to format the SVG in a human-readable way.
     pub pretty: bool,
+    /// How to write text.
+    pub text: SvgTextMode,
+}
+
+/// How text is written to an SVG file.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SvgTextMode {
+    /// Writes all glyphs as outlines. The text looks the same in every viewer,
+    /// but it can't be searched, selected, copied, or read by screen readers.
+    #[default]
+    Outlines,
+    /// Writes text as `<text>` elements and embeds subsets of the used fonts
+    /// as WOFF2 web fonts.
+    ///
+    /// Glyphs that can't be displayed this way fall back to outlines. This is
+    /// the case for colour glyphs, fonts whose license disallows embedding,
+    /// instances of variable fonts, and ligatures or other glyphs that don't
+    /// correspond to exactly one character. The text of such glyphs is still
+    /// written, but invisible.
+    Text,
 }
 
 /// Renders one or multiple frames to an SVG file.
 struct SVGRenderer<'a> {
     /// The document's introspector, if we're writing an HTML frame.
     link_resolver: Option<Tracked<'a, LateLinkResolver<'a>>>,
+    /// Resolves links within a paged document, if we're writing one.
+    links: Option<&'a SvgLinks>,
+    /// The physical number of the page that is currently rendered, for
+    /// resolving links through `links`.
+    page: NonZeroUsize,
+    /// How to write text.
+    text_mode: SvgTextMode,
+    /// Fonts that are embedded for text written as `<text>` elements.
+    fonts: Deduplicator<EmbeddedFont>,
     /// Prepared glyphs.
     glyphs: Deduplicator<Option<RenderedGlyph>>,
     /// Clip paths are used to clip a group. A clip path is a path that defines
@@ -267,14 +338,21 @@ impl State {
 
 impl<'a> SVGRenderer<'a> {
     /// Create a new SVG renderer with empty glyph and clip path.
-    fn new() -> Self {
-        Self::with_options(None)
+    fn new(text_mode: SvgTextMode) -> Self {
+        Self::with_options(text_mode, None)
     }
 
     /// Create a new SVG renderer with the given configuration.
-    fn with_options(link_resolver: Option<Tracked<'a, LateLinkResolver<'a>>>) -> Self {
+    fn with_options(
+        text_mode: SvgTextMode,
+        link_resolver: Option<Tracked<'a, LateLinkResolver<'a>>>,
+    ) -> Self {
         SVGRenderer {
             link_resolver,
+            links: None,
+            page: NonZeroUsize::ONE,
+            text_mode,
+            fonts: Deduplicator::new('w'),
             glyphs: Deduplicator::new('g'),
             clip_paths: Deduplicator::new('c'),
             gradients: Deduplicator::new('f'),
@@ -320,7 +398,9 @@ impl<'a> SVGRenderer<'a> {
                 FrameItem::Image(image, size, _) => {
                     self.render_image(svg, &state, image, size)
                 }
-                FrameItem::Link(dest, size) => self.render_link(svg, &state, dest, *size),
+                FrameItem::Link(dest, size) => {
+                    self.render_link(svg, &state, dest, *size, frame)
+                }
                 FrameItem::Tag(_) => {}
             };
         }
@@ -362,38 +442,44 @@ impl<'a> SVGRenderer<'a> {
         self.render_frame(svg.lazy(), &state, &group.frame);
     }
 
-    /// Render a link element.
+    /// Render a link element. The frame is the one that contains the link,
+    /// i.e. the link's body.
     fn render_link(
         &mut self,
         svg: &mut SvgElem,
         state: &State,
         dest: &Destination,
         size: Size,
+        frame: &Frame,
     ) {
+        let href = match (dest, self.link_resolver) {
+            (Destination::Url(url), resolver) => Some(
+                resolver
+                    .and_then(|resolver| resolver.rewrite_url(url))
+                    .unwrap_or_else(|| url.as_str().into()),
+            ),
+            (Destination::Location(loc), Some(resolver)) => {
+                resolver.resolve(*loc).and_then(|link| link.into_relative_uri().ok())
+            }
+            _ => self.links.and_then(|links| links.resolve(dest, self.page)),
+        };
+
+        // A link without a target would only be a confusing hit area.
+        let Some(href) = href else { return };
+
         let mut a = svg.elem("a");
         if !state.transform.is_identity() {
             a.attr("transform", SvgTransform(state.transform));
         }
 
-        match dest {
-            Destination::Url(url) => {
-                a.attr("href", url.as_str());
-                a.attr("xlink:href", url.as_str());
-            }
-            Destination::Position(_) => {
-                // TODO: Links on the same page could be supported.
-            }
-            Destination::Location(loc) => {
-                // TODO: Location links on the same page could also be supported
-                // outside of HTML.
-                if let Some(resolver) = self.link_resolver
-                    && let Some(link) = resolver.resolve(*loc)
-                    && let Ok(uri) = link.into_relative_uri()
-                {
-                    a.attr("href", &uri);
-                    a.attr("xlink:href", &uri);
-                }
-            }
+        a.attr("href", SvgEscaped(&href))
+            .attr("xlink:href", SvgEscaped(&href));
+
+        // The hit area has no content of its own, so it is labelled with the
+        // body of the link to be announced meaningfully by screen readers.
+        let label = link_label(frame);
+        if !label.is_empty() {
+            a.attr("aria-label", SvgEscaped(&label));
         }
 
         a.elem("rect")
@@ -403,7 +489,8 @@ impl<'a> SVGRenderer<'a> {
             .attr("stroke", "none");
     }
 
-    /// Renders a linkable point that can be used to link into an HTML frame.
+    /// Renders a linkable point that can be used to link into an HTML frame or
+    /// to a position in a paged document.
     fn render_anchor(&mut self, svg: &mut SvgElem, pos: Point, id: &str) {
         svg.elem("g")
             .attr("id", id)
@@ -412,6 +499,7 @@ impl<'a> SVGRenderer<'a> {
 
     /// Finalize the SVG file. This must be called after all rendering is done.
     fn finalize(mut self, mut svg: SvgElem) {
+        self.write_font_defs(&mut svg);
         self.write_glyph_defs(&mut svg);
         self.write_clip_path_defs(&mut svg);
         self.write_gradients(&mut svg);
@@ -436,6 +524,31 @@ impl<'a> SVGRenderer<'a> {
     }
 }
 
+/// The accessible name of a link: The text and image descriptions in the
+/// frame that contains the link, with whitespace collapsed.
+fn link_label(frame: &Frame) -> String {
+    fn collect(frame: &Frame, label: &mut String) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => collect(&group.frame, label),
+                FrameItem::Text(text) => label.push_str(&text.text),
+                FrameItem::Image(image, _, _) => {
+                    if let Some(alt) = image.alt() {
+                        label.push(' ');
+                        label.push_str(alt);
+                        label.push(' ');
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut label = String::new();
+    collect(frame, &mut label);
+    label.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 /// Write the default SVG header, including a `typst-doc` class, the
 /// `viewBox` and `width` and `height` attributes.
 fn svg_header(xml: &mut XmlWriter, size: Size) -> SvgElem<'_> {
@@ -0,0 +1,136 @@
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+
+use ecow::EcoString;
+use rustc_hash::FxHashMap;
+use typst_layout::{PagedDocument, PagedIntrospector};
+use typst_library::introspection::{Location, PagedPosition};
+use typst_library::layout::{Frame, FrameItem, Point};
+use typst_library::model::{AnchorGenerator, Destination};
+
+/// Link targets within a paged document that is exported to SVG.
+///
+/// An SVG file only contains a single page (or all pages merged), so links to
+/// positions and locations in the document can't be resolved while rendering
+/// a page on its own. This collects all link targets in the document and
+/// assigns an anchor to each of them. Links then become fragment links to
+/// these anchors, prefixed with the URL of the target page's file if it is
+/// different from the linking page's.
+pub struct SvgLinks {
+    /// The URL of each page's file, relative to the other pages' files. `None`
+    /// for pages that aren't exported.
+    page_hrefs: Vec<Option<EcoString>>,
+    /// Indices into `anchors` by target location.
+    locations: FxHashMap<Location, usize>,
+    /// Indices into `anchors` by target position.
+    positions: FxHashMap<PagedPosition, usize>,
+    /// The positions and IDs of all anchors.
+    anchors: Vec<(PagedPosition, EcoString)>,
+}
+
+impl SvgLinks {
+    /// Assign anchors to all link targets in the document.
+    ///
+    /// The `page_href` function determines the URL of the file a page is
+    /// exported to, relative to the files of the other pages. It should return
+    /// `None` for pages that are not exported. Links to them are dropped.
+    pub fn new(
+        document: &PagedDocument,
+        page_href: impl Fn(NonZeroUsize) -> Option<EcoString>,
+    ) -> Self {
+        let introspector = document.introspector();
+        let mut links = Self {
+            page_hrefs: (1..=document.pages().len())
+                .map(|i| page_href(NonZeroUsize::new(i).unwrap()))
+                .collect(),
+            locations: FxHashMap::default(),
+            positions: FxHashMap::default(),
+            anchors: Vec::new(),
+        };
+
+        let mut generator = AnchorGenerator::new(introspector.as_ref());
+        for page in document.pages() {
+            links.collect(&page.frame, introspector, &mut generator);
+        }
+
+        links
+    }
+
+    /// Assign anchors to the targets of links in the frame, in order of
+    /// appearance.
+    fn collect(
+        &mut self,
+        frame: &Frame,
+        introspector: &PagedIntrospector,
+        generator: &mut AnchorGenerator,
+    ) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => {
+                    self.collect(&group.frame, introspector, generator)
+                }
+                FrameItem::Link(Destination::Location(loc), _) => {
+                    if self.locations.contains_key(loc) {
+                        continue;
+                    }
+                    let Some(pos) = introspector.position(*loc) else { continue };
+                    let label = introspector
+                        .elements()
+                        .get_by_loc(loc)
+                        .and_then(|elem| elem.label());
+                    self.locations.insert(*loc, self.anchors.len());
+                    self.anchors.push((pos, generator.identify(label)));
+                }
+                FrameItem::Link(Destination::Position(pos), _) => {
+                    if self.positions.contains_key(pos) {
+                        continue;
+                    }
+                    self.positions.insert(*pos, self.anchors.len());
+                    self.anchors.push((*pos, generator.identify(None)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The anchors that must be written to the given page.
+    pub(crate) fn anchors(&self, page: NonZeroUsize) -> Vec<(Point, EcoString)> {
+        self.anchors
+            .iter()
+            .filter(|(pos, _)| pos.page == page)
+            .map(|(pos, id)| (pos.point, id.clone()))
+            .collect()
+    }
+
+    /// Resolve an intra-document link on the given page to a URL.
+    pub(crate) fn resolve(
+        &self,
+        dest: &Destination,
+        page: NonZeroUsize,
+    ) -> Option<EcoString> {
+        let index = match dest {
+            Destination::Url(_) => return None,
+            Destination::Position(pos) => self.positions.get(pos)?,
+            Destination::Location(loc) => self.locations.get(loc)?,
+        };
+
+        let (pos, id) = &self.anchors[*index];
+        let mut href = if pos.page == page {
+            EcoString::new()
+        } else {
+            self.page_hrefs.get(pos.page.get() - 1)?.clone()?
+        };
+        href.push('#');
+        href.push_str(id);
+        Some(href)
+    }
+}
+
+/// Hashes everything that influences how links are written, so that it can be
+/// used to determine whether a previously exported page is still up to date.
+impl Hash for SvgLinks {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.page_hrefs.hash(state);
+        self.anchors.hash(state);
+    }
+}
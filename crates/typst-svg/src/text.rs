@@ -1,3 +1,4 @@
+use base64::Engine;
 use ecow::EcoString;
 use ttf_parser::GlyphId;
 use typst_library::layout::{Abs, Ratio, Size, Transform};
@@ -7,9 +8,22 @@ use typst_library::text::color::{
 };
 use typst_library::visualize::{FillRule, Paint, RelativeTo};
 
+use crate::font::{EmbeddedFont, can_embed};
 use crate::path::SvgPathBuilder;
-use crate::write::{SvgElem, SvgIdRef, SvgTransform};
-use crate::{DedupId, SVGRenderer, State};
+use crate::write::{SvgElem, SvgFormatter, SvgIdRef, SvgTransform, SvgWrite};
+use crate::{DedupId, SVGRenderer, State, SvgTextMode};
+
+/// A run of characters in a `<text>` element that are either all displayed
+/// or all invisible.
+struct TextRun {
+    /// Whether the characters are displayed with the embedded font. If not,
+    /// their glyphs are rendered as outlines instead.
+    visible: bool,
+    /// The characters.
+    text: String,
+    /// The position of each character's baseline origin.
+    positions: Vec<(Abs, Abs)>,
+}
 
 /// Represents a glyph to be rendered.
 #[derive(Clone)]
@@ -32,6 +46,11 @@ impl SVGRenderer<'_> {
         state: &State,
         text: &TextItem,
     ) {
+        if self.text_mode == SvgTextMode::Text && can_write_as_text(text) {
+            self.render_selectable_text(svg, state, text);
+            return;
+        }
+
         let svg = &mut svg.elem("g");
 
         // Flip the transform since fonts use a Y-Up coordinate system.
@@ -52,6 +71,125 @@ impl SVGRenderer<'_> {
         }
     }
 
+    /// Render a text item as a `<text>` element backed by an embedded font.
+    /// Glyphs that can't be displayed with the embedded font are rendered as
+    /// outlines instead, with their text being written invisibly.
+    fn render_selectable_text(
+        &mut self,
+        svg: &mut SvgElem,
+        state: &State,
+        text: &TextItem,
+    ) {
+        let (font_id, font) = self.fonts.insert_with_val(text.font.font(), || {
+            EmbeddedFont::new(text.font.font().clone())
+        });
+
+        let mut runs: Vec<TextRun> = Vec::new();
+        let mut outlined = Vec::new();
+        let mut x = Abs::pt(0.0);
+        let mut y = Abs::pt(0.0);
+        for cluster in text.glyphs.chunk_by(|a, b| a.range == b.range) {
+            let str = &text.text[cluster[0].range()];
+            let mut chars = str.chars().filter(|&c| is_xml_char(c));
+            let visible = match (cluster, chars.next(), chars.next()) {
+                ([glyph], Some(c), None) => {
+                    should_outline(&text.font, GlyphId(glyph.id)) && font.map(c, glyph.id)
+                }
+                _ => false,
+            };
+
+            // Text is written in a Y-down coordinate system, while glyphs use
+            // a Y-up one.
+            let start = (x + cluster[0].x_offset.at(text.size), -y);
+            let mut width = Abs::zero();
+            for glyph in cluster {
+                if !visible {
+                    let x_offset = x + width + glyph.x_offset.at(text.size);
+                    let y_offset = y + glyph.y_offset.at(text.size);
+                    outlined.push((GlyphId(glyph.id), x_offset, y_offset));
+                }
+                width += glyph.x_advance.at(text.size);
+                y += glyph.y_advance.at(text.size);
+            }
+            x += width;
+
+            let run = match runs.last_mut() {
+                Some(run) if run.visible == visible => run,
+                _ => {
+                    runs.push(TextRun {
+                        visible,
+                        text: String::new(),
+                        positions: vec![],
+                    });
+                    runs.last_mut().unwrap()
+                }
+            };
+
+            // Distribute the characters of clusters with multiple characters
+            // evenly across the cluster's width, so that they can be selected
+            // individually.
+            let count = str.chars().filter(|&c| is_xml_char(c)).count();
+            for (i, c) in str.chars().filter(|&c| is_xml_char(c)).enumerate() {
+                run.text.push(c);
+                run.positions
+                    .push((start.0 + width * i as f64 / count as f64, start.1));
+            }
+        }
+
+        if !outlined.is_empty() {
+            let mut group = svg.elem("g");
+            let state = state.pre_concat(Transform::scale(Ratio::one(), -Ratio::one()));
+            group.attr("transform", SvgTransform(state.transform));
+            for (id, x_offset, y_offset) in outlined {
+                self.render_glyph(&mut group, &state, text, id, x_offset, y_offset);
+            }
+        }
+
+        if runs.iter().all(|run| run.text.is_empty()) {
+            return;
+        }
+
+        let mut elem = svg.elem("text");
+        if !state.transform.is_identity() {
+            elem.attr("transform", SvgTransform(state.transform));
+        }
+        elem.attr("font-family", font_id)
+            .attr("font-size", text.size.to_pt())
+            .attr("xml:space", "preserve")
+            .attr("xml:lang", text.lang.as_str());
+
+        // Only solid paints are written as text, so the paint transform
+        // doesn't matter.
+        self.write_fill(
+            &mut elem,
+            &text.fill,
+            FillRule::default(),
+            Ratio::one(),
+            Transform::identity(),
+        );
+        if let Some(stroke) = &text.stroke {
+            self.write_stroke(&mut elem, stroke, Ratio::one(), Transform::identity());
+        }
+
+        // Pretty-printing must not add whitespace to the text.
+        elem.set_preserve_whitespace(true);
+        for run in runs.iter().filter(|run| !run.text.is_empty()) {
+            let mut tspan = elem.elem("tspan");
+            tspan.attr_with("x", |attr| {
+                attr.push_nums(run.positions.iter().map(|(x, _)| x.to_pt()))
+            });
+            tspan.attr_with("y", |attr| {
+                attr.push_nums(run.positions.iter().map(|(_, y)| y.to_pt()))
+            });
+            if !run.visible {
+                tspan.attr("fill", "transparent").attr("stroke", "none");
+            }
+            tspan.text(&run.text);
+        }
+        drop(elem);
+        svg.set_preserve_whitespace(false);
+    }
+
     fn render_glyph(
         &mut self,
         svg: &mut SvgElem,
@@ -178,6 +316,29 @@ impl SVGRenderer<'_> {
         }
     }
 
+    /// Build the `@font-face` rules for fonts embedded for text.
+    pub(super) fn write_font_defs(&mut self, svg: &mut SvgElem) {
+        let mut css = String::new();
+        for (id, font) in self.fonts.iter() {
+            // If the font can't be encoded, viewers will fall back to another
+            // font, which is preferable to losing the text.
+            let Some(data) = font.encode() else { continue };
+            let mut family = EcoString::new();
+            SvgFormatter::new(&mut family).push(id);
+            css.push_str("@font-face { font-family: ");
+            css.push_str(&family);
+            css.push_str("; src: url(\"data:font/woff2;base64,");
+            css.push_str(&base64::engine::general_purpose::STANDARD.encode(data));
+            css.push_str("\") format(\"woff2\"); }");
+        }
+
+        if !css.is_empty() {
+            svg.elem("defs").with(|defs| {
+                defs.elem("style").text(&css);
+            });
+        }
+    }
+
     /// Build the glyph definitions.
     pub(super) fn write_glyph_defs(&mut self, svg: &mut SvgElem) {
         if self.glyphs.iter().all(|(_, g)| g.is_none()) {
@@ -216,3 +377,24 @@ impl SVGRenderer<'_> {
         assert!(self.glyphs.is_empty());
     }
 }
+
+/// Whether a text item is eligible for being written as a `<text>` element.
+///
+/// The positions of characters are computed from the glyphs, which only works
+/// for left-to-right text. Gradients and tilings are relative to the glyph
+/// outlines, so those are also excluded.
+fn can_write_as_text(text: &TextItem) -> bool {
+    matches!(text.fill, Paint::Solid(_))
+        && text
+            .stroke
+            .as_ref()
+            .is_none_or(|stroke| matches!(stroke.paint, Paint::Solid(_)))
+        && text.glyphs.windows(2).all(|w| w[0].range.start <= w[1].range.start)
+        && can_embed(&text.font)
+}
+
+/// Whether a character may appear in XML text.
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}')
+        || c >= '\u{10000}'
+}
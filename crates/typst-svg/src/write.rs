@@ -45,6 +45,25 @@ impl<'a> SvgElem<'a> {
         f(self);
         self
     }
+
+    /// Write text content.
+    pub fn text(&mut self, text: &str) -> &mut Self {
+        // `xmlwriter` only escapes `<` in text nodes.
+        if text.contains('&') {
+            self.xml.write_text(&text.replace('&', "&amp;"));
+        } else {
+            self.xml.write_text(text);
+        }
+        self
+    }
+
+    /// Whether to suppress indentation and line breaks when pretty-printing,
+    /// so that they don't end up in text content. This affects all following
+    /// writes until it is reset, including the closing tag of this element.
+    pub fn set_preserve_whitespace(&mut self, preserve: bool) -> &mut Self {
+        self.xml.set_preserve_whitespaces(preserve);
+        self
+    }
 }
 
 impl Drop for SvgElem<'_> {
@@ -267,3 +286,21 @@ impl<S: AsRef<str>> SvgDisplay for SvgIdRef<S> {
         f.push_str(self.0.as_ref());
     }
 }
+
+/// Displays arbitrary user-provided text, escaping characters that are not
+/// allowed verbatim in XML attribute values.
+pub struct SvgEscaped<'a>(pub &'a str);
+
+impl SvgDisplay for SvgEscaped<'_> {
+    fn fmt(&self, f: &mut impl SvgWrite) {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.push_str("&amp;"),
+                '<' => f.push_str("&lt;"),
+                '>' => f.push_str("&gt;"),
+                '"' => f.push_str("&quot;"),
+                c => f.push_char(c),
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Export settings that are shared by the library and the CLI, whose argument
+//! parsing can't depend on the library.
+
+use std::num::NonZeroUsize;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// A PDF standard that Typst can enforce conformance with.
+///
+/// Support for more standards is planned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(non_camel_case_types)]
+#[non_exhaustive]
+pub enum PdfStandard {
+    /// PDF 1.4.
+    #[cfg_attr(feature = "serde", serde(rename = "1.4"))]
+    V_1_4,
+    /// PDF 1.5.
+    #[cfg_attr(feature = "serde", serde(rename = "1.5"))]
+    V_1_5,
+    /// PDF 1.6.
+    #[cfg_attr(feature = "serde", serde(rename = "1.6"))]
+    V_1_6,
+    /// PDF 1.7.
+    #[cfg_attr(feature = "serde", serde(rename = "1.7"))]
+    V_1_7,
+    /// PDF 2.0.
+    #[cfg_attr(feature = "serde", serde(rename = "2.0"))]
+    V_2_0,
+    /// PDF/A-1b.
+    #[cfg_attr(feature = "serde", serde(rename = "a-1b"))]
+    A_1b,
+    /// PDF/A-1a.
+    #[cfg_attr(feature = "serde", serde(rename = "a-1a"))]
+    A_1a,
+    /// PDF/A-2b.
+    #[cfg_attr(feature = "serde", serde(rename = "a-2b"))]
+    A_2b,
+    /// PDF/A-2u.
+    #[cfg_attr(feature = "serde", serde(rename = "a-2u"))]
+    A_2u,
+    /// PDF/A-2a.
+    #[cfg_attr(feature = "serde", serde(rename = "a-2a"))]
+    A_2a,
+    /// PDF/A-3b.
+    #[cfg_attr(feature = "serde", serde(rename = "a-3b"))]
+    A_3b,
+    /// PDF/A-3u.
+    #[cfg_attr(feature = "serde", serde(rename = "a-3u"))]
+    A_3u,
+    /// PDF/A-3a.
+    #[cfg_attr(feature = "serde", serde(rename = "a-3a"))]
+    A_3a,
+    /// PDF/A-4.
+    #[cfg_attr(feature = "serde", serde(rename = "a-4"))]
+    A_4,
+    /// PDF/A-4f.
+    #[cfg_attr(feature = "serde", serde(rename = "a-4f"))]
+    A_4f,
+    /// PDF/A-4e.
+    #[cfg_attr(feature = "serde", serde(rename = "a-4e"))]
+    A_4e,
+    /// PDF/UA-1.
+    #[cfg_attr(feature = "serde", serde(rename = "ua-1"))]
+    Ua_1,
+}
+
+impl PdfStandard {
+    /// All standards, in the order in which they are listed.
+    pub const ALL: [Self; 17] = [
+        Self::V_1_4,
+        Self::V_1_5,
+        Self::V_1_6,
+        Self::V_1_7,
+        Self::V_2_0,
+        Self::A_1b,
+        Self::A_1a,
+        Self::A_2b,
+        Self::A_2u,
+        Self::A_2a,
+        Self::A_3b,
+        Self::A_3u,
+        Self::A_3a,
+        Self::A_4,
+        Self::A_4f,
+        Self::A_4e,
+        Self::Ua_1,
+    ];
+
+    /// The name by which the standard is selected, like `a-2b`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::V_1_4 => "1.4",
+            Self::V_1_5 => "1.5",
+            Self::V_1_6 => "1.6",
+            Self::V_1_7 => "1.7",
+            Self::V_2_0 => "2.0",
+            Self::A_1b => "a-1b",
+            Self::A_1a => "a-1a",
+            Self::A_2b => "a-2b",
+            Self::A_2u => "a-2u",
+            Self::A_2a => "a-2a",
+            Self::A_3b => "a-3b",
+            Self::A_3u => "a-3u",
+            Self::A_3a => "a-3a",
+            Self::A_4 => "a-4",
+            Self::A_4f => "a-4f",
+            Self::A_4e => "a-4e",
+            Self::Ua_1 => "ua-1",
+        }
+    }
+
+    /// The full name of the standard, like `PDF/A-2b`.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::V_1_4 => "PDF 1.4",
+            Self::V_1_5 => "PDF 1.5",
+            Self::V_1_6 => "PDF 1.6",
+            Self::V_1_7 => "PDF 1.7",
+            Self::V_2_0 => "PDF 2.0",
+            Self::A_1b => "PDF/A-1b",
+            Self::A_1a => "PDF/A-1a",
+            Self::A_2b => "PDF/A-2b",
+            Self::A_2u => "PDF/A-2u",
+            Self::A_2a => "PDF/A-2a",
+            Self::A_3b => "PDF/A-3b",
+            Self::A_3u => "PDF/A-3u",
+            Self::A_3a => "PDF/A-3a",
+            Self::A_4 => "PDF/A-4",
+            Self::A_4f => "PDF/A-4f",
+            Self::A_4e => "PDF/A-4e",
+            Self::Ua_1 => "PDF/UA-1",
+        }
+    }
+}
+
+impl FromStr for PdfStandard {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|standard| standard.name() == name)
+            .ok_or_else(|| format!("unknown PDF standard `{name}`"))
+    }
+}
+
+/// Parses a single page number or range of pages like `4`, `1-3`, `5-`, or
+/// `-2`. Page numbers are one-based and the range is inclusive.
+pub fn parse_page_range(
+    text: &str,
+) -> Result<RangeInclusive<Option<NonZeroUsize>>, String> {
+    fn page(value: &str) -> Result<NonZeroUsize, String> {
+        match value.parse() {
+            Ok(page) => Ok(page),
+            Err(_) if value == "0" => Err("page numbers start at one".into()),
+            Err(_) => Err(format!("`{value}` is not a valid page number")),
+        }
+    }
+
+    let bounds: Vec<_> = text.split('-').map(str::trim).collect();
+    match bounds[..] {
+        [] | [""] => Err("page range must not be empty".into()),
+        [single] => Ok(Some(page(single)?)..=Some(page(single)?)),
+        ["", ""] => Err("page range must have start or end".into()),
+        [start, ""] => Ok(Some(page(start)?)..=None),
+        ["", end] => Ok(None..=Some(page(end)?)),
+        [start, end] => {
+            let (start, end) = (page(start)?, page(end)?);
+            if start > end {
+                return Err("page range must end at a page after the start".into());
+            }
+            Ok(Some(start)..=Some(end))
+        }
+        _ => Err("page range must have a single hyphen".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_standard_names() {
+        for standard in PdfStandard::ALL {
+            assert_eq!(standard.name().parse(), Ok(standard));
+        }
+        assert!("a-5".parse::<PdfStandard>().is_err());
+    }
+
+    #[test]
+    fn test_parse_page_range() {
+        let page = NonZeroUsize::new;
+        assert_eq!(parse_page_range("4"), Ok(page(4)..=page(4)));
+        assert_eq!(parse_page_range(" 1 - 3 "), Ok(page(1)..=page(3)));
+        assert_eq!(parse_page_range("5-"), Ok(page(5)..=None));
+        assert_eq!(parse_page_range("-2"), Ok(None..=page(2)));
+        assert_eq!(parse_page_range("0").unwrap_err(), "page numbers start at one");
+        assert_eq!(parse_page_range("x").unwrap_err(), "`x` is not a valid page number");
+        assert!(parse_page_range("").is_err());
+        assert!(parse_page_range("-").is_err());
+        assert!(parse_page_range("3-1").is_err());
+        assert!(parse_page_range("1-2-3").is_err());
+    }
+}
@@ -7,6 +7,7 @@ mod macros;
 mod bitset;
 mod deferred;
 mod duration;
+mod export;
 mod hash;
 mod listset;
 mod pico;
@@ -19,6 +20,7 @@ mod version_;
 pub use self::bitset::{BitSet, SmallBitSet};
 pub use self::deferred::Deferred;
 pub use self::duration::format_duration;
+pub use self::export::{PdfStandard, parse_page_range};
 pub use self::hash::{HashLock, LazyHash, ManuallyHash, hash128};
 pub use self::listset::ListSet;
 pub use self::pico::{PicoStr, ResolvedPicoStr};
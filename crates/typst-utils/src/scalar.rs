@@ -41,6 +41,14 @@ impl Scalar {
         Self::new(self.get().sqrt())
     }
 
+    /// Computes the length of the hypotenuse of a right-angle triangle with
+    /// the two scalars as its legs.
+    ///
+    /// Unlike `f64::hypot`, this is deterministic across platforms.
+    pub fn hypot(self, other: Self) -> Self {
+        (self * self + other * other).sqrt()
+    }
+
     /// Raises a number to an integer power.
     pub fn powi(self, mut b: i32) -> Self {
         // Ported from https://github.com/llvm/llvm-project/blob/0ee439b/compiler-rt/lib/builtins/powidf2.c
@@ -16,15 +16,34 @@ With Typst's bundle export, you can emit multiple output files from a single Typ
 == Command Line <command-line>
 Pass `--format bundle` to the `compile` or `watch` subcommand. Note that you must also pass `--features bundle` or set `TYPST_FEATURES=bundle` to enable this experimental export target.
 
+By default, the bundle's files are written into the output directory. To instead write them into a single archive, provide an output path ending in `.zip`, `.tar`, `.tar.gz`, or `.tgz`, or pass `--bundle-archive` with one of `zip`, `tar`, or `tar-gz`. With `--bundle-archive`, you can also write the archive to standard output by passing `-` as the output. Archives are reproducible: Their entries are sorted by path and all carry the same timestamp, which you can set with `--creation-timestamp` or the `SOURCE_DATE_EPOCH` environment variable. Without one, the Unix epoch is used.
+
 When using `typst watch`, Typst will launch a live-reloading HTTP server serving your files. You can configure it as follows:
 
 - Pass `--port` to change the port. (Defaults to the first free port in the range 3000-3005.)
 - Pass `--no-reload` to disable injection of a live reload script into HTML pages. (The HTML that is written to disk isn't affected either way.) Non-HTML documents do not support live reload.
 - Pass `--no-serve` to disable the server altogether.
 
+When a bundle is exported to a directory, files whose contents did not change are not written again. If you pass `--bundle-prune`, Typst also deletes files that an earlier compilation produced, but that are no longer part of the bundle. To know which files it produced, even across `typst watch` sessions, Typst records them in a `.typst-bundle.json` file in the output directory. Files that Typst did not produce are never deleted. With `--verbose`, Typst lists the files that were added, changed, and removed. Live reload uses the same information: Open pages only reload if they or one of the non-HTML files in the bundle changed.
+
+To let browsers cache assets indefinitely, pass `--bundle-fingerprint`. Typst then renames each asset to include a hash of its contents, turning `style.css` into something like `style.3f9a1c2b.css`. Since the name changes whenever the contents do, browsers never use an outdated copy. References to the assets in `src` and `href` attributes of HTML documents and links in PDF and SVG documents are rewritten to the new paths. This applies to links to an asset's label as well as to relative URLs like `{link("style.css")}`. Documents keep their paths. Typst also adds a `manifest.json` that maps the original path of each asset to its fingerprinted path, for use by other tools.
+
+To catch broken links before publishing, pass `--check-links`. Typst then warns about relative links, including `src` and `href` attributes in HTML documents, that point to files which are not part of the bundle, and about links to anchors that do not exist in the linked-to HTML document. Links to a directory are considered valid if the directory contains an `index.html`. Links to labels need no check since Typst already reports an error for missing labels. Add `--check-file-links` to also check that `file://` links point to existing files on your machine.
+
 == Web App <web-app>
 Not currently available.
 
+= Exporting as EPUB <exporting-as-epub>
+A bundle can also be packaged into an EPUB 3 publication for e-book readers. Pass `--format epub` or provide an output file name that ends with `.epub`. Since the chapters of the publication are HTML documents, you must enable both the `bundle` and the `html` feature.
+
+The HTML documents of the bundle become the chapters of the publication, in the order in which they appear in the bundle. They are written as XHTML, the XML-based variant of HTML that EPUB requires. All other files of the bundle, like images, fonts, stylesheets, and other documents, are packaged alongside them, so that the chapters can refer to them with links.
+
+The publication's metadata, like its title, authors, and language, is taken from the first HTML document. The title is required, so make sure to set it with `{set document(title: [...])}`. The table of contents is generated from the headings in all chapters. Chapters without any headings are listed with their title instead.
+
+Each publication needs a unique identifier. By default, Typst derives one from the title and authors. Pass `--epub-identifier` to use your own, for example the book's ISBN in the form `urn:isbn:...`. The modification date of the publication is the current time unless you provide a `--creation-timestamp`.
+
+E-book readers display the chapters in a font of their choice. To ship the fonts of your book with the publication, pass `--epub-font` with a font family, once for each family. All fonts of these families are packaged into the publication's `fonts` directory and declared in a stylesheet that all chapters link to. The families also become the default font of the chapters, in the order you passed them. Make sure that the licenses of the fonts allow embedding them.
+
 = Creating files <creating-files>
 A bundle is a collection of files. Each of these bundle files falls into one of two categories: Document or asset. A @document takes @content[content] that is exported with one of Typst's other export formats. Meanwhile, an @asset takes raw @bytes[byte data] of your choice that will be written to disk as-is. Both elements take the desired output path as their first argument.
 
@@ -60,6 +79,27 @@ In the example, we create two HTML documents: A home page and a blog. The home p
 
 Documents and assets are normal elements, so you can use them with Typst's usual scripting, styling, and introspection mechanisms. For more details, refer to the @document and @asset documentation.
 
+All documents of a format are exported with the same options, for instance the PPI and PDF standards you pass on the command line. Individual documents can deviate from them through their @document.export[`export`] property. This way, one PDF can conform to PDF/A-2b while another is a plain PDF, or a low-resolution PNG preview can sit next to a high-resolution one for print.
+
+```typ
+#document("thesis.pdf", export: (standards: "a-2b"), thesis)
+#document("preview.png", export: (ppi: 72), cover)
+#document("print.png", export: (ppi: 300), cover)
+```
+
+Some files that websites commonly need are derived from all of their pages. Typst can generate them for you: @asset.sitemap[`asset.sitemap`] lists your documents for search engines, @asset.feed[`asset.feed`] creates an Atom news feed from selected documents (for instance, all documents with a `<post>` label), and @asset.search-index[`asset.search-index`] creates a JSON index of the headings and paragraphs in your HTML documents for client-side search. They draw on the titles, descriptions, authors, and dates that you configure for your documents.
+
+```typ
+#asset.sitemap("sitemap.xml", base: "https://example.com/")
+#asset.feed(
+  "blog/feed.xml",
+  base: "https://example.com/",
+  title: "My Blog",
+  documents: <post>,
+)
+#asset.search-index("search.json")
+```
+
 = Introspection <introspection>
 Introspections always observe the full bundle rather than individual documents. For instance, querying for headings will give you all headings in all documents rather than the ones in the current document. Similarly, labels are global to the bundle—you can locate and @link:links-in-bundle-export[link to labels in other documents]. Counters and states are likewise global. #footnote[An exception to this forms the page counter, which is (naturally) per document.] In particular, if you enable things like heading numbering, the numbering will progress consecutively across the full bundle.
 
@@ -0,0 +1,41 @@
+#import "../../../components/index.typ": docs-category, info
+
+#show: docs-category.with(
+  title: "DOCX",
+  description: "Documentation for Typst's DOCX export target.",
+  category: "docx",
+)
+
+#info[
+  DOCX export is only available for experimentation behind a feature flag. Do not use this feature for production use cases. In the CLI, you can experiment with it by passing `--features html,docx` or setting the `TYPST_FEATURES` environment variable to `html,docx`. Both features are required because DOCX export builds on HTML export. In the web app, DOCX export is not available at this time.
+]
+
+With Typst's DOCX export, you can create Word documents that others can continue to edit in a word processor like Microsoft Word or LibreOffice. Unlike @pdf[PDF] and the image formats, DOCX export does not use Typst's layout. Instead, Typst produces the same semantic structure as for @html[HTML export] and translates it into native Word paragraphs, lists, and tables. The word processor then lays out the document itself, so the result will not look exactly like the PDF version of your document.
+
+= Exporting as DOCX <exporting-as-docx>
+== Command Line <command-line>
+Pass `--format docx` to the `compile` or `watch` subcommand or provide an output file name that ends with `.docx`. Note that you must also pass `--features html,docx` or set `TYPST_FEATURES=html,docx` to enable this experimental export target.
+
+== Web App <web-app>
+Not currently available.
+
+= Supported elements <supported-elements>
+Typst converts the following elements into their Word equivalents:
+
+- @heading[Headings] become paragraphs with the built-in "Heading" styles, so that they show up in Word's navigation pane. A @title becomes a paragraph with the "Title" style.
+- @par[Paragraphs] become Word paragraphs. @strong[Strong] and @emph[emphasized] text, @raw[raw text], @sub[subscript], @super[superscript], @strike[strikethrough], @underline[underlined], and @highlight[highlighted] text keep their formatting.
+- @list[Bullet lists] and @enum[numbered lists] become Word lists. Numbered lists start at the configured number.
+- @terms[Term lists] become paragraphs with bold terms followed by indented descriptions.
+- @table[Tables] become Word tables. Cells spanning multiple columns or rows are merged.
+- @footnote[Footnotes] become Word footnotes.
+- @image[Images] are embedded with their size. SVG images are embedded along with a PNG version for word processors that do not support SVG.
+- @link[Links] become hyperlinks. Links to labels jump to the linked-to element.
+- @bibliography[Bibliography] entries and the @outline become paragraphs with the respective Word styles.
+- @math.equation[Equations] become native Word equations that remain editable.
+
+Anything you place in an @html.frame is laid out by Typst and embedded as an image. This is useful for content that can't be represented in Word, like complex equations or drawings. Make sure to use @target to check whether you are exporting to HTML before using HTML-specific functions. For DOCX export, `{target()}` returns `{"html"}` since the document is realized just like for HTML export.
+
+= Page setup <page-setup>
+The page size, margins, and number of columns are taken from @page[page set rules] at the top level of the document. The font, font size, and justification of the text are taken from top-level @text[text] and @par[paragraph] set rules and become the document's default style. If you do not set a page width or height, A4 dimensions are used.
+
+All other page properties, like headers, footers, and page numbering, are currently not exported. You can adjust these and the styles of the document in your word processor after exporting.
@@ -59,11 +59,21 @@ In the `html` export format, Typst will output a single HTML file. You can also
 
 Typst currently does not output CSS style sheets, instead focussing on emitting semantic markup. You can of course write your own CSS styles and still benefit from sharing your _content_ between PDF and HTML. For the future, we plan to give you the option of automatically emitting CSS, taking more of your existing set rules into account.
 
+Footnotes are listed at the end of the document's body by default. With @footnote.entries, you can list them elsewhere, for example at the end of each chapter. If you create the `<html>` or `<body>` element yourself, Typst cannot add the listing for you, so you must then place @footnote.entries within your custom element.
+
 = Exporting as HTML <exporting-as-html>
 == Command Line <command-line>
 Pass `--format html` to the `compile` or `watch` subcommand or provide an output file name that ends with `.html`. Note that you must also pass `--features html` or set `TYPST_FEATURES=html` to enable this experimental export target.
 
-When using `typst watch`, Typst will launch a live-reloading HTTP server. You can configure it as follows:
+To embed Typst's output into an existing web page, for example in a content management system, pass `--html-fragment`. Typst then only outputs the contents of the `<body>` element, without the surrounding `<html>`, `<head>`, and `<body>` tags. Some content, like equations, requires styles that Typst would normally place into the `<head>`. Pass `--html-styles` followed by a path to write them to a CSS file that the host page can include.
+
+By default, styles that Typst generates, for example for the layout of equations, are written as inline `style` attributes. Pass `--html-style-mode classes` to instead collect them into CSS classes that are shared by all elements with the same styles. The classes are defined in a `<style>` element in the document's head, or, in @bundle[bundle export], in a `typst.css` file that all HTML documents link to. Since the classes are defined in a regular stylesheet, your own stylesheets can override them.
+
+If your pages are styled by an external design system, pass `--html-profile semantic`. Typst then emits purely semantic markup: It omits all styles it would otherwise generate, sizes derived from the layout, like the width of an image, and presentational `<span>` elements from text styling, like underlines and small capitals. Attributes such as `alt` and `role` that are relevant for accessibility are still emitted.
+
+Pass `--check-links` to get warnings for links to anchors that do not exist in the document. With `--check-file-links`, Typst additionally checks that `file://` links point to existing files on your machine.
+
+When using `typst watch`, Typst will launch a live-reloading HTTP server. When the document changes, open browser tabs only replace the parts of the page that changed, keeping their scroll position. (If much of the page changed, they reload instead.) To scroll the browser to the output of a place in the source code, for example from a key binding in your editor, send a `POST` request to `/__jump?file=<path>&line=<line>&column=<column>` with the path of the source file and a one-based line and column. You can configure the server as follows:
 
 - Pass `--port` to change the port. (Defaults to the first free port in the range 3000-3005.)
 - Pass `--no-reload` to disable injection of a live reload script. (The HTML that is written to disk isn't affected either way.)
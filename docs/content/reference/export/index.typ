@@ -7,3 +7,4 @@
 #include "png.typ"
 #include "svg.typ"
 #include "bundle.typ"
+#include "docx.typ"
@@ -19,6 +19,16 @@ PDF is Typst's default export format. Running the `compile` or `watch` subcomman
 
 - Which pages to export by specifying `--pages` followed by a comma-separated list of numbers or dash-separated number ranges. Ranges can be half-open. Example: `2,3,7-9,11-`.
 
+- How to reduce the size of raster images by specifying `--pdf-optimize` followed by a profile. With `screen`, `ebook`, or `print`, images whose resolution exceeds 96, 150, or 300 pixels per inch at the largest size they are placed at are downsampled. Images without transparency are then re-encoded as JPEG with low, medium, or high quality and images with identical pixel data are only embedded once. Images with `{scaling: "pixelated"}` are left untouched. By default (`none`), images are embedded as-is. Add `--verbose` to print how many images were optimized.
+
+- Which labelled elements become _named destinations_ by specifying `--pdf-destinations` followed by a selector, like for `typst query`. Named destinations allow other documents and systems to link to a specific element in the PDF, e.g. through `file.pdf#name`, where `name` is the element's label. By default, labelled headings become named destinations. Example: `--pdf-destinations "selector(heading).or(figure).or(math.equation)"`.
+
+- Where to write a JSON file that lists the named destinations with their page number and position by specifying `--pdf-link-targets` followed by a path.
+
+- Whether to write the document's @pdf.note[presenter notes] to a `.pdfpc` file for presenter consoles by passing `--pdfpc`. By default, the file is placed next to the PDF, but you can also pass a path. Add `--pdfpc-overlays` to group consecutive pages with the same page number into one slide and `--pdf-embed-notes` to additionally attach the notes to the PDF. Notes are not attached by default, since PDF/A-1 and PDF/A-2 don't permit attachments. These options are only available for PDF output.
+
+When using `typst watch`, pass `--serve` to preview the pages in your browser. Typst then launches an HTTP server that displays the pages and updates those that changed whenever the document changes, keeping the scroll position. Pass `--port` to change the port. (Defaults to the first free port in the range 3000-3005.) To jump from a click on a page to the corresponding place in the source code, pass `--editor-command` followed by a command that opens a file in your editor. The placeholders `{file}`, `{line}`, and `{column}` are replaced with the clicked position. Example: `--editor-command "code --goto {file}:{line}:{column}"`. Conversely, the preview scrolls to a place in the source code when you send a `POST` request to `/__jump?file=<path>&line=<line>&column=<column>` with the path of the source file and a one-based line and column.
+
 == Web App <web-app>
 Click the quick download button at the top right to export a PDF with default settings. For further configuration, click "File" > "Export as" > "PDF" or click the downwards-facing arrow next to the quick download button and select "Export as PDF". When exporting to PDF, you have the following configuration options:
 
@@ -19,10 +19,14 @@ Because PNGs only contain a pixel raster, the text within cannot be extracted au
 
 PNGs can have transparent backgrounds. By default, Typst will output a PNG with an opaque white background. You can make the background transparent using `[#set page(fill: none)]`. Learn more on the @page.fill[`page` function's reference page].
 
+Typst can also render pages to JPEG and WebP files. JPEGs are compressed lossily, which makes them much smaller than PNGs, but can introduce artifacts around text and sharp edges. They are a good fit for thumbnails and previews on the web. JPEGs cannot be transparent, so transparent areas become white. WebP files are compressed losslessly and, like PNGs, can have transparent backgrounds, but are usually smaller.
+
 = Exporting as PNG <exporting-as-png>
 == Command Line <command-line>
 Pass `--format png` to the `compile` or `watch` subcommand or provide an output file name that ends with `.png`.
 
+Likewise, pass `--format jpg` or `--format webp` or use an output file name ending in `.jpg`, `.jpeg`, or `.webp` to export JPEG or WebP files. For JPEGs, you can additionally configure the quality with `--jpeg-quality` followed by a number from 1 to 100. The default is `90`. PNG and WebP files are always compressed losslessly, so passing `--jpeg-quality` for any output other than JPEG or a bundle is an error. PNG, JPEG, and WebP files always use 8 bits per color channel, which is the precision Typst renders pages with.
+
 If your document has more than one page, Typst will create multiple image files. The output file name must then be a template string containing at least one of
 - `[{p}]`, which will be replaced by the page number
 - `[{0p}]`, which will be replaced by the zero-padded page number (so that all numbers have the same length)
@@ -32,8 +36,16 @@ When exporting to PNG, you have the following configuration options:
 
 - Which resolution to render at by specifying `--ppi` followed by a number of pixels per inch. The default is `144`.
 
+- Which background to render the pages on by specifying `--background` followed by either `transparent` or a hex color like `#ffeedd`. By default, each page's @page.fill[fill] is used.
+
+- Whether to record the resolution in the file by passing `--embed-ppi`. Image editors and printing software then display the image at the document's physical size.
+
+- Whether to tag the image as being in the sRGB color space by passing `--srgb-profile`. This helps color-managed software, such as print workflows, to display colors correctly.
+
 - Which pages to export by specifying `--pages` followed by a comma-separated list of numbers or dash-separated number ranges. Ranges can be half-open. Example: `2,3,7-9,11-`.
 
+When using `typst watch`, you can preview the pages in your browser by passing `--serve`, just like @pdf:command-line[for PDF].
+
 == Web App <web-app>
 Click "File" > "Export as" > "PNG" or click the downwards-facing arrow next to the quick download button and select "Export as PNG". When exporting to PNG, you have the following configuration options:
 
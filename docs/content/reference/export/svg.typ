@@ -8,7 +8,9 @@
 
 Instead of creating a PDF, Typst can also directly render pages to scalable vector graphics (SVGs), which are the preferred format for embedding vector graphics in web pages. Like PDF files, SVGs display your document exactly how you have laid it out in Typst. Likewise, they share the benefit of not being bound to a specific resolution. Hence, you can print or view SVG files on any device without incurring a loss of quality. (Note that font printing quality may be better with a PDF.) In contrast to a PDF, an SVG cannot contain multiple pages. When exporting a multi-page document, Typst will emit multiple SVGs.
 
-SVGs can represent text in two ways: By embedding the text itself and rendering it with the fonts available on the viewer's computer or by embedding the shapes of each glyph in the font used to create the document. To ensure that the SVG file looks the same across all devices it is viewed on, Typst chooses the latter method. This means that the text in the SVG cannot be extracted automatically, for example by copy/paste or a screen reader. If you need the text to be accessible, export a PDF or HTML file instead.
+SVGs can represent text in two ways: By embedding the text itself and rendering it with the fonts available on the viewer's computer or by embedding the shapes of each glyph in the font used to create the document. To ensure that the SVG file looks the same across all devices it is viewed on, Typst chooses the latter method by default. This means that the text in the SVG cannot be extracted automatically, for example by copy/paste or a screen reader.
+
+If you need the text to be accessible, you can instead let Typst write the text itself alongside subsets of the used fonts, embedded as WOFF2 web fonts. Each character is positioned individually, so the SVG still looks like your Typst document. Glyphs that cannot be represented this way are still embedded as shapes, with their text being written invisibly on top. This applies to colored glyphs like emoji, to fonts whose license forbids embedding, to variable fonts, and to ligatures. Right-to-left text and text filled with a gradient or tiling is always embedded as shapes.
 
 SVGs can have transparent backgrounds. By default, Typst will output an SVG with an opaque white background. You can make the background transparent using `[#set page(fill: none)]`. Learn more on the @page.fill[`page` function's reference page].
 
@@ -21,10 +23,16 @@ If your document has more than one page, Typst will create multiple image files.
 - `[{0p}]`, which will be replaced by the zero-padded page number (so that all numbers have the same length)
 - `[{t}]`, which will be replaced by the total number of pages
 
+Links to other places in your document keep working across the exported files: A link to a heading on another page points to that page's SVG file, with a fragment identifying the heading. Links to pages that are not exported are dropped. This requires all pages to be exported into the same directory.
+
 When exporting to SVG, you have the following configuration options:
 
 - Which pages to export by specifying `--pages` followed by a comma-separated list of numbers or dash-separated number ranges. Ranges can be half-open. Example: `2,3,7-9,11-`.
 
+- How to write text by specifying `--svg-text` followed by `outlines` (the default) or `text`. With `text`, the text in the SVG can be searched, selected, copied, and read by screen readers.
+
+When using `typst watch`, you can preview the pages in your browser by passing `--serve`, just like @pdf:command-line[for PDF].
+
 == Web App <web-app>
 Click "File" > "Export as" > "SVG" or click the downwards-facing arrow next to the quick download button and select "Export as SVG". When exporting to SVG, you have the following configuration options:
 
@@ -161,7 +161,7 @@ fn trim_page(page: &mut Page, Zoom { x, y, w, h }: &Zoom, styles: StyleChain) {
 fn page_to_image(page: Page) -> Content {
     let opts = RenderOptions {
         pixel_per_pt: Scalar::new(2.0),
-        render_bleed: false,
+        ..Default::default()
     };
     let pixmap = typst_render::render(&page, &opts);
     let format = ImageFormat::Raster(RasterFormat::Pixel(PixelFormat {
@@ -8,14 +8,14 @@ mod world;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, Mutex};
 
 use clap::Parser;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use typst::diag::{At, SourceResult, Warned};
 use typst::foundations::Bytes;
 use typst::syntax::Span;
-use typst_bundle::{Bundle, BundleFile, BundleOptions, VirtualFs};
+use typst_bundle::{Bundle, BundleChanges, BundleFile, BundleOptions, VirtualFs};
 use typst_kit::diagnostics::{self, termcolor};
 use typst_kit::server::HttpServer;
 use typst_kit::timer::Timer;
@@ -118,6 +118,8 @@ struct Config {
     server: Option<HttpServer>,
     /// Whether to open the output after compilation.
     open: bool,
+    /// The files of the previous export, to determine what changed.
+    previous: Mutex<Option<VirtualFs>>,
 }
 
 impl Config {
@@ -134,6 +136,7 @@ impl Config {
             server: (serve && args.format == OutputFormat::Website)
                 .then(|| HttpServer::new("docs", None, true).unwrap()),
             open: args.open,
+            previous: Mutex::new(None),
         }
     }
 }
@@ -190,7 +193,10 @@ fn export_website(mut bundle: Bundle, config: &Config) -> SourceResult<()> {
     }
 
     if let Some(server) = &config.server {
-        server.set_bundle(bundle, fs);
+        let mut previous = config.previous.lock().unwrap();
+        let changes = BundleChanges::between(previous.as_ref(), &fs);
+        *previous = Some(fs.clone());
+        server.set_bundle(bundle, fs, &changes);
     }
 
     Ok(())
@@ -70,7 +70,7 @@ cast! {
 pub fn build_search_index(bundle: &Bundle) -> SourceResult<SearchIndex> {
     let mut indexer = Indexer::new();
     for file in bundle.files.values() {
-        let BundleFile::Document(BundleDocument::Html(doc)) = file else { continue };
+        let BundleFile::Document(BundleDocument::Html(doc, _)) = file else { continue };
         walk_html(&mut indexer, doc.root(), bundle.introspector.as_ref())?;
     }
     Ok(indexer.finish())
@@ -164,6 +164,7 @@ pub enum TestStage {
     Svg,
     Html,
     Bundle,
+    Docx,
 }
 
 impl From<TestStage> for TestStages {
@@ -177,6 +178,7 @@ impl From<TestStage> for TestStages {
             TestStage::Svg => TestStages::SVG,
             TestStage::Html => TestStages::HTML,
             TestStage::Bundle => TestStages::BUNDLE,
+            TestStage::Docx => TestStages::DOCX,
         }
     }
 }
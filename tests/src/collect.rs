@@ -87,6 +87,7 @@ bitflags! {
     struct AttrFlags: u16 {
         const LARGE = 1 << 0;
         const EMPTY = 1 << 1;
+        const SVG_TEXT = 1 << 2;
     }
 }
 
@@ -96,6 +97,8 @@ pub struct Attrs {
     pub large: bool,
     pub empty: bool,
     pub pdf_standard: Vec<PdfStandard>,
+    /// Whether SVGs are written with selectable text instead of outlines.
+    pub svg_text: bool,
     /// Tolerance for image comparisons. Render tests are not 100% reproducible.
     /// By default, we allow a byte difference of 1, but in rare cases, we need
     /// to increase it. This can for example happen due to cross-platform
@@ -130,11 +133,12 @@ bitflags! {
     ///                  ╭─> render
     ///       ╭─> paged ─┼─> pdf ───> pdftags
     /// eval ─┤          ╰─> svg
-    ///       ├─> html -───> html
+    ///       ├─> html ──┬─> html
+    ///       │          ╰─> docx
     ///       ╰─> bundle ──> bundle
     /// ```
     #[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
-    pub struct TestStages: u8 {
+    pub struct TestStages: u16 {
         const EVAL = 1 << 0;
         const PAGED = 1 << 1;
         const RENDER = 1 << 2;
@@ -143,6 +147,7 @@ bitflags! {
         const SVG = 1 << 5;
         const HTML = 1 << 6;
         const BUNDLE = 1 << 7;
+        const DOCX = 1 << 8;
     }
 }
 
@@ -162,6 +167,7 @@ impl TestStages {
                 TestStages::SVG => TestStages::empty(),
                 TestStages::HTML => TestStages::empty(),
                 TestStages::BUNDLE => TestStages::empty(),
+                TestStages::DOCX => TestStages::empty(),
                 _ => unreachable!(),
             });
         }
@@ -184,6 +190,7 @@ impl TestStages {
                 TestStages::SVG => TestStages::EVAL | TestStages::PAGED,
                 TestStages::HTML => TestStages::EVAL,
                 TestStages::BUNDLE => TestStages::EVAL,
+                TestStages::DOCX => TestStages::EVAL | TestStages::HTML,
                 _ => unreachable!(),
             });
         }
@@ -206,6 +213,7 @@ impl TestStages {
                 TestStages::SVG => TestStages::RENDER | TestStages::PDF | TestStages::SVG,
 
                 TestStages::PDFTAGS => TestStages::PDFTAGS,
+                TestStages::DOCX => TestStages::DOCX,
                 _ => unreachable!("{flag}"),
             });
         }
@@ -228,6 +236,7 @@ impl Display for TestStages {
                 TestStages::SVG => Display::fmt(&TestOutput::Svg, f),
                 TestStages::HTML => Display::fmt(&TestTarget::Html, f),
                 TestStages::BUNDLE => Display::fmt(&TestTarget::Bundle, f),
+                TestStages::DOCX => Display::fmt(&TestOutput::Docx, f),
                 _ => unreachable!(),
             })?;
         }
@@ -254,7 +263,7 @@ impl Display for TestEval {
 
 /// A compilation target, analog to [`typst::Target`].
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[repr(u8)]
+#[repr(u16)]
 pub enum TestTarget {
     Paged = TestStages::PAGED.bits(),
     Html = TestStages::HTML.bits(),
@@ -265,7 +274,7 @@ impl TestStage for TestTarget {}
 
 impl From<TestTarget> for TestStages {
     fn from(value: TestTarget) -> Self {
-        TestStages::from_bits(value as u8).unwrap()
+        TestStages::from_bits(value as u16).unwrap()
     }
 }
 
@@ -304,7 +313,7 @@ impl From<Target> for TestTarget {
 
 /// A test output format.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
-#[repr(u8)]
+#[repr(u16)]
 pub enum TestOutput {
     Render = TestStages::RENDER.bits(),
     Pdf = TestStages::PDF.bits(),
@@ -312,11 +321,19 @@ pub enum TestOutput {
     Svg = TestStages::SVG.bits(),
     Html = TestStages::HTML.bits(),
     Bundle = TestStages::BUNDLE.bits(),
+    Docx = TestStages::DOCX.bits(),
 }
 
 impl TestOutput {
-    pub const ALL: [Self; 6] =
-        [Self::Render, Self::Svg, Self::Pdf, Self::Pdftags, Self::Html, Self::Bundle];
+    pub const ALL: [Self; 7] = [
+        Self::Render,
+        Self::Svg,
+        Self::Pdf,
+        Self::Pdftags,
+        Self::Html,
+        Self::Bundle,
+        Self::Docx,
+    ];
 
     fn from_sub_dir(dir: &str) -> Option<Self> {
         Self::ALL.into_iter().find(|o| o.sub_dir() == dir)
@@ -331,6 +348,7 @@ impl TestOutput {
             Self::Svg => "svg",
             Self::Html => "html",
             Self::Bundle => "bundle",
+            Self::Docx => "docx",
         }
     }
 
@@ -343,13 +361,14 @@ impl TestOutput {
             Self::Svg => "svg",
             Self::Html => "html",
             Self::Bundle => "tar",
+            Self::Docx => "docx",
         }
     }
 
     /// The file extension used for file references.
     pub const fn ref_extension(&self) -> &'static str {
         match self {
-            Self::Bundle => "txt",
+            Self::Bundle | Self::Docx => "txt",
             _ => self.live_extension(),
         }
     }
@@ -384,7 +403,9 @@ impl TestOutput {
     /// The output kind.
     pub fn kind(&self) -> TestOutputKind {
         match self {
-            TestOutput::Render | TestOutput::Bundle => TestOutputKind::File,
+            TestOutput::Render | TestOutput::Bundle | TestOutput::Docx => {
+                TestOutputKind::File
+            }
             TestOutput::Pdf => TestOutputKind::Hash(output::Pdf::INDEX),
             TestOutput::Pdftags => TestOutputKind::Hash(output::Pdftags::INDEX),
             TestOutput::Svg => TestOutputKind::Hash(output::Svg::INDEX),
@@ -397,7 +418,7 @@ impl TestStage for TestOutput {}
 
 impl From<TestOutput> for TestStages {
     fn from(value: TestOutput) -> Self {
-        TestStages::from_bits(value as u8).unwrap()
+        TestStages::from_bits(value as u16).unwrap()
     }
 }
 
@@ -718,10 +739,8 @@ impl<'a> Parser<'a> {
                         .split(',')
                         .map(str::trim)
                         .filter_map(|s| {
-                            serde_yaml::from_str(s)
-                                .inspect_err(|e| {
-                                    self.error(format!("unknown pdf standard `{s}`: {e}"))
-                                })
+                            s.parse::<PdfStandard>()
+                                .inspect_err(|e| self.error(e.clone()))
                                 .ok()
                         })
                         .collect();
@@ -738,8 +757,10 @@ impl<'a> Parser<'a> {
                 }
                 "html" => self.set_attr(attr_name, &mut stages, TestStages::HTML),
                 "bundle" => self.set_attr(attr_name, &mut stages, TestStages::BUNDLE),
+                "docx" => self.set_attr(attr_name, &mut stages, TestStages::DOCX),
                 "large" => self.set_attr(attr_name, &mut flags, AttrFlags::LARGE),
                 "empty" => self.set_attr(attr_name, &mut flags, AttrFlags::EMPTY),
+                "svgtext" => self.set_attr(attr_name, &mut flags, AttrFlags::SVG_TEXT),
 
                 found => {
                     self.error(format!(
@@ -775,6 +796,7 @@ impl<'a> Parser<'a> {
             large: flags.contains(AttrFlags::LARGE),
             empty: flags.contains(AttrFlags::EMPTY),
             pdf_standard,
+            svg_text: flags.contains(AttrFlags::SVG_TEXT),
             stages,
             tolerance,
         }
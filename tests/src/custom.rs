@@ -1,11 +1,20 @@
 use std::fmt::Write;
+use std::io::{Cursor, Read};
+use std::num::NonZeroUsize;
+use std::path::Path;
 
+use ecow::eco_format;
 use typst::World;
-use typst::foundations::Smart;
+use typst::foundations::{NativeElement, Smart};
 use typst::introspection::{Location, Tag};
-use typst::layout::{Frame, FrameItem};
-use typst::model::{Document, DocumentInfo};
+use typst::layout::{Abs, Frame, FrameItem, PageRanges};
+use typst::model::{Document, DocumentInfo, FigureElem, HeadingElem};
+use typst::text::Font;
+use typst_bundle::{Bundle, BundleOptions, EpubOptions, LinkCheckOptions};
 use typst_layout::PagedDocument;
+use typst_pdf::PdfOptions;
+use typst_svg::{SvgLinks, SvgOptions};
+use typst_utils::NonZeroExt;
 
 use crate::collect::Test;
 use crate::world::TestWorld;
@@ -47,16 +56,674 @@ pub fn check(test: &Test, world: &TestWorld, doc: Option<&PagedDocument>) -> Str
                 sink.push_str("missing document");
             }
         }
+        "link-svg-internal" => {
+            if let Some(doc) = doc {
+                check_svg_links(&mut sink, doc);
+            } else {
+                sink.push_str("missing document");
+            }
+        }
+        "pdf-note-pdfpc" => {
+            if let Some(doc) = doc {
+                check_pdfpc(&mut sink, doc);
+            } else {
+                sink.push_str("missing document");
+            }
+        }
+        "link-pdf-named-destinations" => {
+            if let Some(doc) = doc {
+                check_link_targets(&mut sink, doc);
+            } else {
+                sink.push_str("missing document");
+            }
+        }
         _ => {}
     }
     sink
 }
 
+/// Run special checks for specific bundle tests.
+pub fn check_bundle(test: &Test, world: &TestWorld, bundle: Option<&Bundle>) -> String {
+    let mut sink = String::new();
+    let check: fn(&mut String, &TestWorld, &Bundle) = match test.name.as_str() {
+        "asset-generated" => check_generated_assets,
+        "bundle-check-links" => check_bundle_links,
+        "ref-bundle-label-own-document" => |sink, _, bundle| {
+            let Some(fs) = export_bundle(sink, bundle, &BundleOptions::default()) else {
+                return;
+            };
+            let a = html_links(&bundle_file(&fs, "a.html"));
+            let b = html_links(&bundle_file(&fs, "b.html"));
+            test_eq!(sink, a, [("".to_string(), "Section 1".to_string())]);
+            test_eq!(sink, b, [("".to_string(), "Section 3".to_string())]);
+        },
+        "ref-bundle-figure" => |sink, _, bundle| {
+            let Some(fs) = export_bundle(sink, bundle, &BundleOptions::default()) else {
+                return;
+            };
+            let links = html_links(&bundle_file(&fs, "b.html"));
+            test_eq!(
+                sink,
+                links,
+                [
+                    ("a.html".to_string(), "Figure 1".to_string()),
+                    ("".to_string(), "Figure 2".to_string()),
+                ]
+            );
+        },
+        "ref-bundle-counter-bibliography" => |sink, _, bundle| {
+            let Some(fs) = export_bundle(sink, bundle, &BundleOptions::default()) else {
+                return;
+            };
+            let a = html_text(&bundle_file(&fs, "a.html"));
+            let b = html_text(&bundle_file(&fs, "b.html"));
+            test_eq!(sink, a.contains("Cite [1]."), true);
+            test_eq!(sink, b.contains("Count: 2"), true);
+            test_eq!(sink, b.contains("Cite [2]."), true);
+            test_eq!(sink, b.contains("The First Book"), true);
+            test_eq!(sink, b.contains("The Second Book"), true);
+        },
+        "bundle-fingerprint" => check_fingerprint,
+        "document-export-options" => check_export_options,
+        "epub-package" => check_epub_package,
+        "epub-missing-title" => |sink, _, bundle| {
+            let errors = epub_errors(bundle, &BundleOptions::default());
+            test_eq!(sink, errors, ["EPUB export requires a document title"]);
+        },
+        "epub-reserved-path" => |sink, _, bundle| {
+            let errors = epub_errors(bundle, &BundleOptions::default());
+            test_eq!(sink, errors, ["bundle already contains a file at /nav.xhtml"]);
+        },
+        _ => return sink,
+    };
+
+    match bundle {
+        Some(bundle) => check(&mut sink, world, bundle),
+        None => sink.push_str("missing bundle"),
+    }
+    sink
+}
+
 /// Extract the document information.
 fn info(doc: Option<&PagedDocument>) -> DocumentInfo {
     doc.map(|doc| doc.info().clone()).unwrap_or_default()
 }
 
+/// Checks that intra-document links in SVGs point to the anchors of their
+/// targets, both when all pages are merged and when they are separate files,
+/// and that they are labelled with their body.
+fn check_svg_links(sink: &mut String, doc: &PagedDocument) {
+    let options = SvgOptions::default();
+    let merged = typst_svg::svg_merged(doc, &options, Abs::pt(1.0));
+    test_eq!(sink, merged.contains(r##"href="#target""##), true);
+    test_eq!(sink, merged.contains(r##"href="#loc-1""##), true);
+    test_eq!(sink, merged.contains(r#"id="target""#), true);
+    test_eq!(sink, merged.contains(r#"id="loc-1""#), true);
+    test_eq!(sink, merged.contains(r#"aria-label="To target""#), true);
+    test_eq!(sink, merged.contains(r#"aria-label="To position""#), true);
+
+    let [first, second] = doc.pages() else {
+        sink.push_str("expected two pages");
+        return;
+    };
+    let links = SvgLinks::new(doc, |n| Some(eco_format!("page-{n}.svg")));
+    let two = NonZeroUsize::new(2).unwrap();
+    let first = typst_svg::svg_with_links(first, NonZeroUsize::ONE, &options, &links);
+    let second = typst_svg::svg_with_links(second, two, &options, &links);
+    test_eq!(sink, first.contains(r##"href="page-2.svg#target""##), true);
+    test_eq!(sink, first.contains(r##"href="page-2.svg#loc-1""##), true);
+    test_eq!(sink, first.contains(r#"id="target""#), false);
+    test_eq!(sink, second.contains(r#"id="target""#), true);
+    test_eq!(sink, second.contains(r#"id="loc-1""#), true);
+}
+
+/// Checks the named destinations of a PDF export with a custom selector and
+/// the JSON that is written for them with `--pdf-link-targets`.
+fn check_link_targets(sink: &mut String, doc: &PagedDocument) {
+    let selector = HeadingElem::ELEM.select().or(vec![FigureElem::ELEM.select()]);
+    let mut options = PdfOptions {
+        named_destinations: Smart::Custom(selector),
+        ..Default::default()
+    };
+
+    // Unlabelled elements and later elements with a duplicate label are
+    // skipped, the equation is not selected.
+    let targets = typst_pdf::link_targets(doc, &options);
+    let names: Vec<_> = targets.iter().map(|t| (t.name.as_str(), t.page.get())).collect();
+    test_eq!(sink, names, [("intro", 1), ("fig", 1), ("late", 2)]);
+    if let [intro, fig, _] = targets.as_slice() {
+        test_eq!(sink, intro.y < fig.y, true);
+    }
+
+    // Pages are counted in the exported PDF, so skipped pages shift them.
+    options.page_ranges = Some(PageRanges::new(vec![NonZeroUsize::new(2)..=None]));
+    let targets = typst_pdf::link_targets(doc, &options);
+    let json = serde_json::to_value(&targets).unwrap();
+    test_eq!(sink, json.as_array().map(Vec::len), Some(1));
+    if let Some(late) = targets.first() {
+        test_eq!(sink, json[0]["name"], "late");
+        test_eq!(sink, json[0]["page"], 1);
+        test_eq!(sink, json[0]["x"], late.x);
+        test_eq!(sink, json[0]["y"], late.y);
+    }
+}
+
+/// Checks the `.pdfpc` JSON written for presenter notes, with and without
+/// overlay grouping, and its attachment to the PDF.
+fn check_pdfpc(sink: &mut String, doc: &PagedDocument) {
+    let json = |options: &PdfOptions| {
+        serde_json::to_string(&typst_pdf::pdfpc(doc, options)).unwrap()
+    };
+
+    let mut options = PdfOptions::default();
+    test_eq!(
+        sink,
+        json(&options),
+        concat!(
+            r#"{"pdfpcFormat":2,"pages":["#,
+            r#"{"idx":0,"label":"1","overlay":0,"note":"First slide."},"#,
+            r#"{"idx":1,"label":"2","overlay":0,"note":"Overlay."},"#,
+            r#"{"idx":2,"label":"3","overlay":0,"note":"Second slide.\n\nSecond note."}"#,
+            r#"]}"#,
+        )
+    );
+
+    options.presenter.overlays = true;
+    test_eq!(
+        sink,
+        json(&options),
+        concat!(
+            r#"{"pdfpcFormat":2,"pages":["#,
+            r#"{"idx":0,"label":"1","overlay":0,"note":"First slide."},"#,
+            r#"{"idx":1,"label":"1","overlay":1,"note":"Overlay."},"#,
+            r#"{"idx":2,"label":"2","overlay":0,"note":"Second slide.\n\nSecond note."}"#,
+            r#"]}"#,
+        )
+    );
+
+    // Indices refer to the exported pages.
+    options.page_ranges = Some(PageRanges::new(vec![NonZeroUsize::new(2)..=None]));
+    test_eq!(
+        sink,
+        json(&options),
+        concat!(
+            r#"{"pdfpcFormat":2,"pages":["#,
+            r#"{"idx":0,"label":"1","overlay":0,"note":"Overlay."},"#,
+            r#"{"idx":1,"label":"2","overlay":0,"note":"Second slide.\n\nSecond note."}"#,
+            r#"]}"#,
+        )
+    );
+
+    // The notes are only attached to the PDF on request.
+    let attached = |options: &PdfOptions| {
+        typst_pdf::pdf(doc, options)
+            .is_ok_and(|pdf| pdf.windows(11).any(|window| window == b"notes.pdfpc"))
+    };
+    let mut options = PdfOptions::default();
+    test_eq!(sink, attached(&options), false);
+    options.presenter.embed = true;
+    test_eq!(sink, attached(&options), true);
+}
+
+/// Checks the warnings for broken links along with the calls they point to.
+fn check_bundle_links(sink: &mut String, world: &TestWorld, bundle: &Bundle) {
+    let file_exists = |path: &Path| path.ends_with("exists.txt");
+    let options = LinkCheckOptions { file_exists: Some(&file_exists) };
+    let warnings: Vec<_> = typst_bundle::check_links(bundle, &options)
+        .iter()
+        .map(|warning| {
+            let span = warning.span;
+            let source = world.source(span.id().unwrap()).unwrap();
+            let text = &source.text()[source.range(span).unwrap()];
+            let call = text.split('[').next().unwrap().to_string();
+            (call, warning.message.to_string())
+        })
+        .collect();
+
+    // File URLs without a drive letter are not local paths on Windows.
+    let missing_file = if cfg!(windows) {
+        "link destination `file:///missing%20file.txt` is not a valid local file URL"
+    } else {
+        "file `/missing file.txt` does not exist"
+    };
+    let expected = [
+        (
+            r#"link("missing.html")"#,
+            "link destination `missing.html` does not exist in the bundle",
+        ),
+        (
+            r#"link("about.html#nobody")"#,
+            "anchor `#nobody` does not exist in `about.html`",
+        ),
+        (
+            r#"link("../outside.html")"#,
+            "link destination `../outside.html` is outside of the bundle",
+        ),
+        (r##"link("#top")"##, "anchor `#top` does not exist in this document"),
+        (r#"link("file:///missing%20file.txt")"#, missing_file),
+        (
+            r#"html.img(src: "img/missing.png")"#,
+            "link destination `img/missing.png` does not exist in the bundle",
+        ),
+        (
+            r#"link("index.html#nowhere")"#,
+            "anchor `#nowhere` does not exist in `index.html`",
+        ),
+    ];
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(call, message)| (call.to_string(), message.to_string()))
+        .collect();
+    test_eq!(sink, warnings, expected);
+}
+
+/// Checks that assets are renamed with a hash of their contents, that
+/// references to them are rewritten, and that the manifest lists them.
+fn check_fingerprint(sink: &mut String, _: &TestWorld, bundle: &Bundle) {
+    let options = BundleOptions { fingerprint: true, ..Default::default() };
+    let Some(fs) = export_bundle(sink, bundle, &options) else { return };
+
+    // Documents and generated assets keep their paths.
+    let hash = &format!("{:032x}", typst_utils::hash128(b"logo"))[..8];
+    let logo = format!("img/logo.{hash}.png");
+    let paths: Vec<_> = fs.keys().map(|path| path.get_without_slash()).collect();
+    test_eq!(
+        sink,
+        paths,
+        ["index.html", "about.html", logo.as_str(), "sitemap.xml", "manifest.json"]
+    );
+
+    let file = |path: &str| bundle_file(&fs, path);
+
+    // Query and fragment are kept, external URLs and documents are untouched.
+    let index = file("index.html");
+    test_eq!(sink, index.contains(&format!(r#"src="{logo}""#)), true);
+    test_eq!(sink, index.contains(&format!(r#"href="{logo}?v=1#top""#)), true);
+    test_eq!(sink, index.contains(r#"href="https://example.com/img/logo.png""#), true);
+    test_eq!(sink, index.contains(r#"href="about.html""#), true);
+    test_eq!(sink, file(&logo), "logo");
+    test_eq!(
+        sink,
+        file("manifest.json"),
+        format!("{{\n  \"img/logo.png\": \"{logo}\"\n}}\n")
+    );
+}
+
+/// Checks that the export options of individual documents are applied: PDF
+/// standards, the resolution of PNGs, and pretty-printing of HTML.
+fn check_export_options(sink: &mut String, _: &TestWorld, bundle: &Bundle) {
+    let Some(fs) = export_bundle(sink, bundle, &BundleOptions::default()) else {
+        return;
+    };
+
+    // Only the document that asks for PDF/A declares conformance with it.
+    test_eq!(sink, bundle_file(&fs, "a.pdf").contains("pdfaid:part"), true);
+    test_eq!(sink, bundle_file(&fs, "b.pdf").contains("pdfaid:part"), false);
+
+    // The width of a PNG is stored in bytes 16 to 20 of its header.
+    let png_width = |path: &str| {
+        let data = bundle_bytes(&fs, path);
+        data.get(16..20)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+    };
+    test_eq!(sink, png_width("c.png"), Some(72));
+    test_eq!(sink, png_width("d.png"), Some(144));
+
+    // Only pretty HTML is indented.
+    test_eq!(sink, bundle_file(&fs, "e.html").contains("\n  <"), true);
+    test_eq!(sink, bundle_file(&fs, "f.html").contains("\n  <"), false);
+}
+
+/// Checks the contents of a sitemap, an Atom feed, and a search index.
+fn check_generated_assets(sink: &mut String, _: &TestWorld, bundle: &Bundle) {
+    let Some(fs) = export_bundle(sink, bundle, &BundleOptions::default()) else {
+        return;
+    };
+
+    // All documents are listed, but only those with a date have a `lastmod`.
+    let sitemap = bundle_file(&fs, "sitemap.xml");
+    let sitemap = roxmltree::Document::parse(&sitemap).unwrap();
+    let urls: Vec<_> = sitemap
+        .descendants()
+        .filter(|node| node.has_tag_name("url"))
+        .map(|url| (child_text(url, "loc"), child_text(url, "lastmod")))
+        .collect();
+    test_eq!(
+        sink,
+        urls,
+        [
+            ("https://a.b/index.html", ""),
+            ("https://a.b/posts/first%20post.html", "2024-01-02"),
+            ("https://a.b/posts/second.html", "2024-03-04"),
+            ("https://a.b/print.pdf", ""),
+        ]
+    );
+
+    // Only the selected documents are entries, with the newest first.
+    let feed = bundle_file(&fs, "feed.xml");
+    let feed = roxmltree::Document::parse(&feed).unwrap();
+    let root = feed.root_element();
+    test_eq!(sink, child_text(root, "title"), "Blog");
+    test_eq!(sink, child_text(root, "id"), "https://a.b/feed.xml");
+    test_eq!(sink, child_text(root, "updated"), "2024-03-04T05:06:07Z");
+    let self_link = root.children().find(|node| node.has_tag_name("link"));
+    test_eq!(sink, self_link.and_then(|link| link.attribute("rel")), Some("self"));
+    let entries: Vec<_> = root
+        .children()
+        .filter(|node| node.has_tag_name("entry"))
+        .map(|entry| {
+            let author = entry
+                .children()
+                .find(|node| node.has_tag_name("author"))
+                .map(|author| child_text(author, "name"))
+                .unwrap_or_default();
+            [
+                child_text(entry, "title"),
+                child_text(entry, "id"),
+                child_text(entry, "updated"),
+                author,
+                child_text(entry, "summary"),
+            ]
+        })
+        .collect();
+    test_eq!(
+        sink,
+        entries,
+        [
+            [
+                "Second",
+                "https://a.b/posts/second.html",
+                "2024-03-04T05:06:07Z",
+                "",
+                "The sequel"
+            ],
+            [
+                "First",
+                "https://a.b/posts/first%20post.html",
+                "2024-01-02T00:00:00Z",
+                "Ann",
+                ""
+            ],
+        ]
+    );
+
+    // Paged documents are not indexed. Paragraphs are grouped by heading.
+    let index: serde_json::Value =
+        serde_json::from_str(&bundle_file(&fs, "search.json")).unwrap();
+    test_eq!(
+        sink,
+        index,
+        serde_json::json!([
+            {
+                "path": "index.html",
+                "title": "Home",
+                "sections": [
+                    { "heading": null, "anchor": null, "text": "Hello world." },
+                ],
+            },
+            {
+                "path": "posts/first post.html",
+                "title": "First",
+                "sections": [
+                    { "heading": "Intro", "anchor": null, "text": "Some text. More text." },
+                    { "heading": "Outro", "anchor": "outro", "text": "Bye." },
+                ],
+            },
+            {
+                "path": "posts/second.html",
+                "title": "Second",
+                "sections": [
+                    { "heading": null, "anchor": null, "text": "Again." },
+                ],
+            },
+        ])
+    );
+}
+
+/// Checks the layout, package document, and navigation document of an EPUB
+/// publication, as well as the packaged fonts.
+fn check_epub_package(sink: &mut String, world: &TestWorld, bundle: &Bundle) {
+    let fonts: Vec<Font> = world
+        .book()
+        .select_family("libertinus serif")
+        .filter_map(|id| world.font(id))
+        .collect();
+    let options = BundleOptions {
+        epub: EpubOptions { fonts, ..Default::default() },
+        ..Default::default()
+    };
+    let epub = match typst_bundle::epub(bundle, &options) {
+        Ok(epub) => epub,
+        Err(errors) => {
+            writeln!(sink, "EPUB export failed: {}", errors[0].message).unwrap();
+            return;
+        }
+    };
+
+    let files = unzip(&epub);
+    let names: Vec<_> = files.iter().map(|(name, _, _)| name.as_str()).collect();
+    let file = |name: &str| {
+        files
+            .iter()
+            .find(|(other, _, _)| other == name)
+            .map(|(_, _, data)| String::from_utf8_lossy(data).into_owned())
+            .unwrap_or_default()
+    };
+
+    // The `mimetype` must come first and be stored uncompressed.
+    test_eq!(
+        sink,
+        names[..names.len().min(6)],
+        [
+            "mimetype",
+            "META-INF/container.xml",
+            "EPUB/content.opf",
+            "EPUB/nav.xhtml",
+            "EPUB/b.html",
+            "EPUB/a.html",
+        ]
+    );
+    test_eq!(sink, files[0].1, zip::CompressionMethod::Stored);
+    test_eq!(sink, file("mimetype"), "application/epub+zip");
+    test_eq!(
+        sink,
+        file("META-INF/container.xml").contains(r#"full-path="EPUB/content.opf""#),
+        true
+    );
+
+    // The metadata is taken from the first HTML document and the spine lists
+    // the chapters in bundle order.
+    let package = file("EPUB/content.opf");
+    let Ok(opf) = roxmltree::Document::parse(&package) else {
+        sink.push_str("invalid package document");
+        return;
+    };
+    let metadata = |name: &str| -> Vec<&str> {
+        opf.descendants()
+            .filter(|node| node.has_tag_name(name))
+            .filter_map(|node| node.text())
+            .collect()
+    };
+    test_eq!(sink, metadata("title"), ["Book"]);
+    test_eq!(sink, metadata("creator"), ["Jane", "John"]);
+    test_eq!(sink, metadata("description"), ["About the book"]);
+    test_eq!(sink, metadata("subject"), ["typst"]);
+    test_eq!(sink, metadata("language"), ["en"]);
+    test_eq!(sink, metadata("date"), ["2024-05-06"]);
+    test_eq!(sink, metadata("meta"), ["2024-05-06T00:00:00Z"]);
+    test_eq!(
+        sink,
+        metadata("identifier")
+            .first()
+            .is_some_and(|id| id.starts_with("urn:uuid:")),
+        true
+    );
+
+    let item = |id: &str| {
+        opf.descendants()
+            .find(|node| node.has_tag_name("item") && node.attribute("id") == Some(id))
+            .map(|node| (node.attribute("href"), node.attribute("media-type")))
+    };
+    let spine: Vec<_> = opf
+        .descendants()
+        .filter(|node| node.has_tag_name("itemref"))
+        .filter_map(|node| item(node.attribute("idref")?)?.0)
+        .collect();
+    test_eq!(sink, spine, ["b.html", "a.html"]);
+
+    let media_types: Vec<_> = opf
+        .descendants()
+        .filter(|node| node.has_tag_name("item"))
+        .filter_map(|node| Some((node.attribute("href")?, node.attribute("media-type")?)))
+        .filter(|(href, _)| !href.starts_with("fonts/"))
+        .collect();
+    test_eq!(
+        sink,
+        media_types,
+        [
+            ("nav.xhtml", "application/xhtml+xml"),
+            ("b.html", "application/xhtml+xml"),
+            ("a.html", "application/xhtml+xml"),
+            ("fig.pdf", "application/pdf"),
+            ("data.txt", "application/octet-stream"),
+            ("typst.css", "text/css"),
+        ]
+    );
+
+    // Headings nest by level and chapters without headings are listed with
+    // their title.
+    let nav = file("EPUB/nav.xhtml");
+    match roxmltree::Document::parse(&nav) {
+        Ok(nav) => match nav.descendants().find(|node| node.has_tag_name("ol")) {
+            Some(ol) => test_eq!(
+                sink,
+                nav_outline(ol),
+                "Part [Section [Subsection] Other] Second Appendix"
+            ),
+            None => sink.push_str("missing table of contents\n"),
+        },
+        Err(_) => sink.push_str("invalid navigation document\n"),
+    }
+
+    // Each font is packaged and declared in the stylesheet that all chapters
+    // link to.
+    let regular = "fonts/LibertinusSerif-Regular.otf";
+    test_eq!(
+        sink,
+        opf.descendants().any(|node| node.attribute("href") == Some(regular)
+            && node.attribute("media-type") == Some("font/otf")),
+        true
+    );
+    test_eq!(sink, names.contains(&"EPUB/fonts/LibertinusSerif-Regular.otf"), true);
+    let css = file("EPUB/typst.css");
+    test_eq!(
+        sink,
+        css.contains(&format!(
+            r#"@font-face {{ font-family: "Libertinus Serif"; font-style: normal; font-weight: 400; src: url("{regular}"); }}"#
+        )),
+        true
+    );
+    test_eq!(sink, css.contains(r#"body { font-family: "Libertinus Serif"; }"#), true);
+
+    let chapter = file("EPUB/b.html");
+    test_eq!(sink, chapter.starts_with("<?xml"), true);
+    test_eq!(
+        sink,
+        chapter.contains(r#"<link rel="stylesheet" href="typst.css"/>"#),
+        true
+    );
+}
+
+/// Summarizes the entries of a table of contents with their nesting.
+fn nav_outline(ol: roxmltree::Node) -> String {
+    let mut entries = vec![];
+    for li in ol.children().filter(|node| node.has_tag_name("li")) {
+        let mut entry = String::new();
+        for child in li.children() {
+            if child.has_tag_name("a") {
+                entry.push_str(child.text().unwrap_or_default());
+            } else if child.has_tag_name("ol") {
+                write!(entry, " [{}]", nav_outline(child)).unwrap();
+            }
+        }
+        entries.push(entry);
+    }
+    entries.join(" ")
+}
+
+/// Exports a bundle, reporting a failure to the sink.
+fn export_bundle(
+    sink: &mut String,
+    bundle: &Bundle,
+    options: &BundleOptions,
+) -> Option<typst_bundle::VirtualFs> {
+    match typst_bundle::export(bundle, options) {
+        Ok(fs) => Some(fs),
+        Err(errors) => {
+            writeln!(sink, "bundle export failed: {}", errors[0].message).unwrap();
+            None
+        }
+    }
+}
+
+/// The links in an exported HTML document, as the file part of the
+/// destination and the text of the link.
+fn html_links(html: &str) -> Vec<(String, String)> {
+    let re =
+        regex::Regex::new(r##"(?s)<a href="([^"#]*)[^"]*"[^>]*>(.*?)</a>"##).unwrap();
+    re.captures_iter(html)
+        .map(|caps| (caps[1].to_string(), html_text(&caps[2])))
+        .collect()
+}
+
+/// The text of exported HTML, without tags and with normal spaces.
+fn html_text(html: &str) -> String {
+    let re = regex::Regex::new("<[^>]*>").unwrap();
+    re.replace_all(html, "").replace('\u{a0}', " ")
+}
+
+/// The contents of a file in an exported bundle as text.
+fn bundle_file(fs: &typst_bundle::VirtualFs, path: &str) -> String {
+    String::from_utf8_lossy(&bundle_bytes(fs, path)).into_owned()
+}
+
+/// The contents of a file in an exported bundle.
+fn bundle_bytes(fs: &typst_bundle::VirtualFs, path: &str) -> Vec<u8> {
+    fs.iter()
+        .find(|(other, _)| other.get_without_slash() == path)
+        .map(|(_, data)| data.to_vec())
+        .unwrap_or_default()
+}
+
+/// The text of the first child element with the given name.
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> &'a str {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+        .unwrap_or_default()
+}
+
+/// The messages of the errors of an EPUB export.
+fn epub_errors(bundle: &Bundle, options: &BundleOptions) -> Vec<String> {
+    match typst_bundle::epub(bundle, options) {
+        Ok(_) => vec![],
+        Err(errors) => errors.iter().map(|error| error.message.to_string()).collect(),
+    }
+}
+
+/// Reads the files of a ZIP archive in order, with their compression method.
+fn unzip(data: &[u8]) -> Vec<(String, zip::CompressionMethod, Vec<u8>)> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    (0..archive.len())
+        .map(|i| {
+            let mut file = archive.by_index(i).unwrap();
+            let mut data = Vec::new();
+            file.read_to_end(&mut data).unwrap();
+            (file.name().into(), file.compression(), data)
+        })
+        .collect()
+}
+
 /// Naive check for whether tags are balanced in the document.
 ///
 /// This is kept minimal for now: It does not handle groups with parents and
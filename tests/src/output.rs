@@ -1,4 +1,5 @@
 use std::fmt::{Display, Write as _};
+use std::io::{Cursor, Read};
 use std::option::Option;
 use std::path::Path;
 use std::str::FromStr;
@@ -17,12 +18,13 @@ use typst::layout::{Abs, Frame, FrameItem, Transform};
 use typst::model::ParbreakElem;
 use typst::text::SpaceElem;
 use typst::visualize::Color;
-use typst_bundle::{BundleOptions, VirtualFs};
-use typst_html::{HtmlDocument, HtmlOptions};
+use typst_bundle::{BundleOptions, EpubOptions, VirtualFs};
+use typst_docx::DocxDocument;
+use typst_html::{HtmlDocument, HtmlNode, HtmlOptions, tag};
 use typst_layout::PagedDocument;
 use typst_pdf::{PdfOptions, PdfStandard, PdfStandards};
 use typst_render::RenderOptions;
-use typst_svg::SvgOptions;
+use typst_svg::{SvgOptions, SvgTextMode};
 use typst_syntax::Span;
 use typst_utils::Scalar;
 
@@ -404,8 +406,10 @@ impl OutputType for Svg {
         is_empty_paged_document(doc)
     }
 
-    fn make_live(_: &Test, doc: &Self::Doc) -> SourceResult<Self::Live> {
-        let options = SvgOptions { pretty: true, ..Default::default() };
+    fn make_live(test: &Test, doc: &Self::Doc) -> SourceResult<Self::Live> {
+        let text =
+            if test.attrs.svg_text { SvgTextMode::Text } else { SvgTextMode::Outlines };
+        let options = SvgOptions { pretty: true, text, ..Default::default() };
         Ok(typst_svg::svg_merged(doc, &options, Abs::pt(1.0)))
     }
 
@@ -454,7 +458,7 @@ impl OutputType for Html {
     }
 
     fn make_live(_: &Test, doc: &Self::Doc) -> SourceResult<Self::Live> {
-        let options = HtmlOptions { pretty: true };
+        let options = HtmlOptions { pretty: true, ..Default::default() };
         typst_html::html(doc, &options)
     }
 
@@ -494,13 +498,15 @@ impl OutputType for Bundle {
     fn make_live(test: &Test, doc: &Self::Doc) -> SourceResult<Self::Live> {
         let standards = test.attrs.pdf_standard.as_slice();
         let options = BundleOptions {
-            html: HtmlOptions { pretty: true },
+            html: HtmlOptions { pretty: true, ..Default::default() },
             pdf: pdf_options(standards)?,
             png: RenderOptions {
                 pixel_per_pt: Scalar::new(1.0),
                 ..Default::default()
             },
             svg: SvgOptions { pretty: true, ..Default::default() },
+            epub: EpubOptions::default(),
+            fingerprint: false,
         };
         typst_bundle::export(doc, &options)
     }
@@ -543,6 +549,56 @@ impl FileOutputType for Bundle {
     }
 }
 
+pub struct Docx;
+
+impl OutputType for Docx {
+    type Doc = DocxDocument;
+    type Live = Vec<u8>;
+
+    const OUTPUT: TestOutput = TestOutput::Docx;
+
+    fn is_empty(doc: &Self::Doc, _: &Self::Live) -> bool {
+        doc.html().root().children.iter().all(|node| match node {
+            HtmlNode::Element(elem) if elem.tag == tag::body => elem.children.is_empty(),
+            _ => true,
+        })
+    }
+
+    fn make_live(_: &Test, doc: &Self::Doc) -> SourceResult<Self::Live> {
+        typst_docx::docx(doc)
+    }
+
+    fn save_live(_: &Self::Doc, live: &Self::Live) -> impl AsRef<[u8]> {
+        live
+    }
+
+    fn make_hash(live: &Self::Live) -> HashedRef {
+        HashedRef(typst_utils::hash128(live))
+    }
+
+    fn make_report(
+        a: Option<(&Path, Old<&[u8]>)>,
+        b: Result<(&Path, &[u8]), ()>,
+    ) -> ReportFile {
+        // The live output is the package itself, so it is listed the same way
+        // as the reference to produce a meaningful diff.
+        let listing = b.map(|(path, bytes)| (path, docx_parts(bytes)));
+        let listed = listing.as_ref().map(|(path, text)| (*path, text.as_bytes()));
+        let diffs = [text_diff(a, listed.map_err(|_| ()))];
+        file_report(Self::OUTPUT, a, b, diffs)
+    }
+}
+
+impl FileOutputType for Docx {
+    fn save_ref(live: &Self::Live) -> impl AsRef<[u8]> {
+        docx_parts(live)
+    }
+
+    fn matches(old: &[u8], new: &Self::Live, _: u8) -> bool {
+        old == docx_parts(new).as_bytes()
+    }
+}
+
 fn text_diff(a: Option<(&Path, Old<&[u8]>)>, b: Result<(&Path, &[u8]), ()>) -> Diff {
     let a = a.map(|(_, old)| old.map(|bytes| std::str::from_utf8(bytes).unwrap()));
     let b = b.map(|(_, bytes)| std::str::from_utf8(bytes).unwrap());
@@ -594,7 +650,7 @@ fn render(document: &PagedDocument, pixel_per_pt: f32) -> sk::Pixmap {
     let gap = Abs::pt(1.0);
     let opts = typst_render::RenderOptions {
         pixel_per_pt: Scalar::new(pixel_per_pt as f64),
-        render_bleed: false,
+        ..Default::default()
     };
     let mut pixmap =
         typst_render::render_merged(document, &opts, gap, Some(Color::BLACK));
@@ -672,6 +728,113 @@ fn hashed_fs_list(fs: &VirtualFs) -> String {
     output
 }
 
+/// Lists the parts of a DOCX package. Most XML parts are listed with their
+/// indented contents, while other parts are listed with their hash.
+fn docx_parts(data: &[u8]) -> String {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).unwrap();
+    let mut output = String::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).unwrap();
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).unwrap();
+
+        // The styles and settings barely depend on the document, so they are
+        // only hashed to keep the references small.
+        let name = file.name();
+        let listed = !matches!(name, "word/styles.xml" | "word/settings.xml")
+            && (name.ends_with(".xml") || name.ends_with(".rels"));
+        match std::str::from_utf8(&bytes) {
+            Ok(xml) if listed => {
+                writeln!(output, "--- {name} ---").unwrap();
+                indent_xml(&mut output, xml);
+            }
+            _ => {
+                let hash = HashedRef(typst_utils::hash128(&bytes));
+                writeln!(output, "{hash} {name}").unwrap();
+            }
+        }
+    }
+    output
+}
+
+/// Writes XML with one tag per line, indented by nesting depth. Elements that
+/// only contain text are kept on a single line.
+fn indent_xml(output: &mut String, xml: &str) {
+    enum Token<'a> {
+        Open(&'a str),
+        Close(&'a str),
+        Single(&'a str),
+        Text(&'a str),
+    }
+
+    let mut tokens = vec![];
+    let mut s = unscanny::Scanner::new(xml);
+    while !s.done() {
+        let start = s.cursor();
+        if s.eat_if('<') {
+            let mut quote = None;
+            s.eat_until(|c| match quote {
+                Some(q) if c == q => {
+                    quote = None;
+                    false
+                }
+                Some(_) => false,
+                None if c == '"' || c == '\'' => {
+                    quote = Some(c);
+                    false
+                }
+                None => c == '>',
+            });
+            s.eat_if('>');
+            let tag = s.from(start);
+            tokens.push(if tag.starts_with("</") {
+                Token::Close(tag)
+            } else if tag.ends_with("/>") || tag.starts_with("<?") {
+                Token::Single(tag)
+            } else {
+                Token::Open(tag)
+            });
+        } else {
+            s.eat_until('<');
+            tokens.push(Token::Text(s.from(start)));
+        }
+    }
+
+    let mut depth = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let indent = "  ".repeat(depth);
+        match (&tokens[i], tokens.get(i + 1), tokens.get(i + 2)) {
+            (Token::Open(open), Some(Token::Text(text)), Some(Token::Close(close))) => {
+                writeln!(output, "{indent}{open}{text}{close}").unwrap();
+                i += 3;
+            }
+            (Token::Open(open), Some(Token::Close(close)), _) => {
+                writeln!(output, "{indent}{open}{close}").unwrap();
+                i += 2;
+            }
+            (Token::Open(open), ..) => {
+                writeln!(output, "{indent}{open}").unwrap();
+                depth += 1;
+                i += 1;
+            }
+            (Token::Close(close), ..) => {
+                depth = depth.saturating_sub(1);
+                writeln!(output, "{}{close}", "  ".repeat(depth)).unwrap();
+                i += 1;
+            }
+            (Token::Single(tag), ..) => {
+                writeln!(output, "{indent}{tag}").unwrap();
+                i += 1;
+            }
+            (Token::Text(text), ..) => {
+                writeln!(output, "{indent}{text}").unwrap();
+                i += 1;
+            }
+        }
+    }
+}
+
 /// Whether this content can be considered empty.
 pub fn is_empty_content(content: &Content) -> bool {
     if let Some(sequence) = content.to_packed::<SequenceElem>() {
@@ -548,6 +548,7 @@ fn sidebar(parent: &mut HtmlElem, reports: &[TestReport]) {
                 TestOutput::Svg => ("Filter SVG", icons::SVG),
                 TestOutput::Html => ("Filter HTML", icons::HTML),
                 TestOutput::Bundle => ("Filter bundle", icons::BUNDLE),
+                TestOutput::Docx => ("Filter DOCX", icons::DOCX),
             };
             parent.label().class("icon-toggle-button").with(|label| {
                 label
@@ -587,6 +588,7 @@ fn sidebar(parent: &mut HtmlElem, reports: &[TestReport]) {
                     TestOutput::Svg => ("Show SVG diffs", icons::SVG),
                     TestOutput::Html => ("Show HTML diffs", icons::HTML),
                     TestOutput::Bundle => ("Show bundle diffs", icons::BUNDLE),
+                    TestOutput::Docx => ("Show DOCX diffs", icons::DOCX),
                 };
 
                 let enabled = (reports.iter())
@@ -733,6 +735,7 @@ fn test_report_header(
                     TestOutput::Svg => ("View SVG", icons::SVG),
                     TestOutput::Html => ("View HTML", icons::HTML),
                     TestOutput::Bundle => ("View bundle", icons::BUNDLE),
+                    TestOutput::Docx => ("View DOCX", icons::DOCX),
                 };
                 let report_file_tab = |parent: &mut HtmlElem| {
                     tab_icon_button(
@@ -1352,6 +1355,7 @@ mod icons {
     pub static SVG: SvgIcon = SvgIcon("M2.93 12v2h2v-2zm0-10v2h2V2Zm.46 2v8h1.2V4Zm9.68 0c-1.46 0-3.741-.01-5.726.438-.993.223-1.922.557-2.647 1.12l-.107.088v4.708q.052.046.107.087c.725.564 1.654.898 2.647 1.121 1.985.447 4.266.438 5.726.438v-1.2c-1.461 0-3.678-.006-5.463-.407-.892-.201-1.666-.506-2.171-.899-.506-.393-.764-.82-.764-1.494 0-.673.258-1.101.764-1.494.505-.393 1.28-.698 2.171-.899C9.392 5.206 11.61 5.2 13.07 5.2Z");
     pub static HTML: SvgIcon = SvgIcon("M8 1.4A6.61 6.61 0 0 0 1.4 8c0 3.638 2.962 6.6 6.6 6.6s6.6-2.962 6.6-6.6S11.638 1.4 8 1.4m1.824.883c-.106.149-.2.31-.285.47-.166.316-.281.63-.281.84 0 .287.21.594.37.772a.3.3 0 0 0 .224.094.33.33 0 0 0 .332-.332v-.1c0-.182.086-.373.189-.533a.35.35 0 0 1 .37-.15q.137.08.27.17l.003.002a.46.46 0 0 1 .093.279v.121c0 .323-.21.609-.52.705l-.868.272-.496.23c-.286.133-.565.302-.729.57-.093.152-.166.328-.166.496 0 .433.465.866.928.866.2 0 .409-.17.584-.371.253-.291.548-.56.916-.674.504-.157 1.145-.052 1.262.463q.016.075.017.148c0 .216-.117.217-.232.217-.116 0-.23 0-.23.217 0 .456.668.285 1.04.021.193-.137.348-.282.348-.455 0-.12.105-.199.209-.187a5.4 5.4 0 0 1 .201 2.119 2.7 2.7 0 0 0-1.146-.475c-.563-.089-1.133-.158-1.53-.158-.26 0-.553-.03-.845-.059a8 8 0 0 0-.942-.054c-.472.014-.816.142-.816.572 0 .325-.085.695-.166 1.043-.148.634-.279 1.193.166 1.252 1.216.161 1.96 1.033 2.31 2.164-.723.36-1.54.562-2.404.562a5.4 5.4 0 0 1-2.084-.414l-.014.035q.029-.079.05-.162c.04-.171.075-.3.1-.367.065-.164.208-.297.376-.453.237-.22.524-.486.705-.95.15-.383-.487-.76-1.229-1.048a2.1 2.1 0 0 0-.818-.146c-.598.015-1.157.297-1.47.843q-.07.121-.128.235a5.4 5.4 0 0 1-.767-1.825l.736.49A.35.35 0 0 0 4 9.349c0-.193.159-.341.34-.276.194.07.432.2.66.428q.121.119.244.17c.417.18.495-.431.174-.752a.77.77 0 0 1-.133-.938c.203-.34.466-.73.715-.98.32-.32-.195-1.092-.67-1.643a2.4 2.4 0 0 0-.766-.574l-.222-.113a3 3 0 0 0-.45-.182A5.4 5.4 0 0 1 5 3.506C5.005 4.29 5.15 5 5.627 5c.206 0 .395-.023.564-.059.636-.134.84-.827.682-1.457l-.125-.494a1 1 0 0 0-.088-.224A5.4 5.4 0 0 1 8 2.6c.522 0 1.027.073 1.504.21.01-.019.025-.037.035-.056.085-.161.178-.322.285-.47M8.415 3.967a.25.25 0 0 0-.233.32l.138.47a.418.418 0 1 0 .645-.456l-.4-.285a.25.25 0 0 0-.15-.05m2.503 2.756a.4.4 0 0 0-.145.039c-.36.18-.233.724.17.724a.383.383 0 1 0-.025-.764m-5.102 6.502-.035.066z");
     pub static BUNDLE: SvgIcon = SvgIcon("M1.898 2.592v10.816h12.204V5.592H9.31l.046.146a4.605 4.605 0 0 0-4.363-3.146ZM3.1 3.793h1.894c1.466 0 2.763.934 3.227 2.324l.224.676H12.9v5.414H3.1Z");
+    pub static DOCX: SvgIcon = SvgIcon("M3 1v14h10V4.5L9.5 1Zm1.2 1.2h4.6v3h3v8.6H4.2Zm1.3 5.3v1.2h5V7.5Zm0 2.5v1.2h5V10Z");
     pub static SOURCE: SvgIcon = SvgIcon("M5.559 3.559 1.115 8l4.444 4.441.882-.882L2.883 8 6.44 4.441Zm4.882 0-.882.882L13.117 8 9.56 11.559l.882.882L14.885 8Z");
 
     pub static VISUAL: SvgIcon = SvgIcon("m13.152 3.152-1.586 1.586C10.606 4.242 9.416 3.9 8 3.9c-1.93 0-3.44.634-4.52 1.43-1.078.797-1.73 1.73-2.03 2.434l-.12.275.149.258c.384.673 1.127 1.593 2.222 2.379C4.797 11.46 6.254 12.1 8 12.1s3.203-.639 4.299-1.424c1.095-.786 1.838-1.706 2.222-2.38l.149-.257-.12-.275c-.292-.685-.917-1.587-1.945-2.37L14 4ZM8 5.1c1.67 0 2.91.533 3.807 1.195.774.572 1.164 1.202 1.418 1.678-.33.498-.814 1.144-1.625 1.726-.938.673-2.146 1.201-3.6 1.201s-2.662-.528-3.6-1.2c-.81-.583-1.294-1.23-1.625-1.727.254-.476.644-1.106 1.418-1.678C5.09 5.633 6.33 5.1 8 5.1M8 6a2 2 0 0 0-2 2 2 2 0 0 0 2 2 2 2 0 0 0 2-2 2 2 0 0 0-2-2");
@@ -6,6 +6,7 @@ use typst::diag::{SourceDiagnostic, SourceResult, Warned};
 use typst::foundations::{Content, Output, Repr};
 use typst::model::Document;
 use typst_bundle::Bundle;
+use typst_docx::DocxDocument;
 use typst_html::HtmlDocument;
 use typst_layout::PagedDocument;
 use typst_syntax::Spanned;
@@ -167,12 +168,7 @@ impl<'a> Runner<'a> {
         if self.test.should_run(TestTarget::Paged) {
             let mut doc = self.compile::<PagedDocument>(evaluated.clone());
             let errors = custom::check(self.test, &self.world, doc.as_ref());
-            if !errors.is_empty() {
-                log!(self, "custom check failed");
-                for line in errors.lines() {
-                    log!(self, "  {line}");
-                }
-            }
+            self.log_custom_errors(&errors);
 
             if let Some(doc) = &mut doc
                 && doc.info().title.is_none()
@@ -196,13 +192,26 @@ impl<'a> Runner<'a> {
 
         // Only compile html document when the html target is specified.
         if self.test.should_run(TestTarget::Html) {
-            let doc = self.compile::<HtmlDocument>(evaluated.clone());
-            self.run_hash_test::<output::Html>(doc.as_ref());
+            // DOCX export realizes the document for the HTML target on its
+            // own, as it needs the page setup from the top-level set rules. So
+            // the HTML document is only compiled when its output is needed, to
+            // not report the same diagnostics twice.
+            let docx = self.test.should_run(TestOutput::Docx);
+            if !docx || self.test.should_check(TestOutput::Html) {
+                let doc = self.compile::<HtmlDocument>(evaluated.clone());
+                self.run_hash_test::<output::Html>(doc.as_ref());
+            }
+            if docx {
+                let docx = self.compile::<DocxDocument>(evaluated.clone());
+                self.run_file_test::<output::Docx>(docx.as_ref());
+            }
         }
 
         // Only compile bundle when the bundle target is specified.
         if self.test.should_run(TestTarget::Bundle) {
             let bundle = self.compile::<Bundle>(evaluated.clone());
+            let errors = custom::check_bundle(self.test, &self.world, bundle.as_ref());
+            self.log_custom_errors(&errors);
             self.run_file_test::<output::Bundle>(bundle.as_ref());
         }
 
@@ -212,6 +221,16 @@ impl<'a> Runner<'a> {
         self.result
     }
 
+    /// Logs the failures of a custom check.
+    fn log_custom_errors(&mut self, errors: &str) {
+        if !errors.is_empty() {
+            log!(self, "custom check failed");
+            for line in errors.lines() {
+                log!(self, "  {line}");
+            }
+        }
+    }
+
     fn handle_empty(&mut self) {
         match &self.unexpected_non_empty {
             UnexpectedNonEmpty::None => (),
@@ -0,0 +1,18 @@
+--- bundle-check-links bundle ---
+#document("index.html")[
+  #link("about.html")[Fine]
+  #link("about.html#team")[Fine]
+  #link("docs/")[Index]
+  #link("docs")[Index]
+  #link("https://example.com/missing.html")[External]
+  #link("file:///exists.txt")[Existing file]
+  #link("missing.html")[Missing]
+  #link("about.html#nobody")[Missing anchor]
+  #link("../outside.html")[Outside]
+  #link("#top")[Missing own anchor]
+  #link("file:///missing%20file.txt")[Missing file]
+  #html.img(src: "img/missing.png")
+]
+#document("about.html", html.div(id: "team")[Team])
+#document("docs/index.html")[Docs]
+#document("print.pdf", link("index.html#nowhere")[Paged])
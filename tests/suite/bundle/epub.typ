@@ -0,0 +1,28 @@
+// Tests for EPUB export. The publication itself is checked in
+// `tests/src/custom.rs`, the bundle outputs are checked as usual.
+
+--- epub-package bundle ---
+#document(
+  "b.html",
+  title: [Book],
+  author: ("Jane", "John"),
+  description: [About the book],
+  keywords: ("typst",),
+  date: datetime(year: 2024, month: 5, day: 6),
+)[
+  = Part
+  == Section
+  === Subsection
+  == Other
+  = Second
+]
+#document("a.html", title: [Appendix])[No headings]
+#document("fig.pdf")[A figure]
+#asset("data.txt", bytes("data"))
+
+--- epub-missing-title bundle ---
+#document("a.html")[Hello]
+
+--- epub-reserved-path bundle ---
+#document("a.html", title: [Book])[Hello]
+#asset("nav.xhtml", bytes("<nav/>"))
@@ -0,0 +1,13 @@
+// Tests for fingerprinted bundle export. The renamed files are checked in
+// `tests/src/custom.rs`.
+
+--- bundle-fingerprint bundle ---
+#document("index.html", title: [Home])[
+  #html.img(src: "img/logo.png", alt: "Logo")
+  #html.a(href: "img/logo.png?v=1#top")[Logo]
+  #html.a(href: "https://example.com/img/logo.png")[External]
+  #html.a(href: "about.html")[About]
+]
+#document("about.html", title: [About])[About]
+#asset("img/logo.png", bytes("logo"))
+#asset.sitemap("sitemap.xml", base: "https://a.b")
@@ -0,0 +1,90 @@
+--- docx-headings docx ---
+#set page(width: 15cm, margin: 2cm)
+#set text(font: "Libertinus Serif", size: 11pt)
+#set heading(numbering: "1.")
+
+= Introduction
+Typst is a *markup-based* typesetting system with _beautiful_ output.
+
+== Motivation
+It is as easy to learn as `Markdown`.
+
+--- docx-lists docx ---
+- Apples
+- Oranges
+  + Blood orange
+  + Navel orange
+- Pears
+
+/ Term: Description
+
+--- docx-table-spans docx ---
+#table(
+  columns: 3,
+  table.header([A], [B], [C]),
+  table.cell(colspan: 2)[Wide], [1],
+  table.cell(rowspan: 2)[Tall], [2], [3],
+  [4], [5],
+)
+
+--- docx-footnotes docx ---
+Typst#footnote[A typesetting system.] is fast.#footnote[_Very_ fast.]
+
+--- docx-bookmarks docx ---
+#set heading(numbering: "1.")
+
+= Setup <setup>
+Install it first.
+
+= Usage
+Follow the steps from @setup or #link(<setup>)[the first section].
+Visit #link("https://typst.app")[the website].
+
+--- docx-math docx ---
+The formula $a^2 + b^2 = c^2$ is inline.
+
+$ sum_(k=1)^n k = (n (n + 1)) / 2 quad sqrt(x) != root(3, x) quad hat(v) $
+
+--- docx-image docx ---
+#image(
+  bytes(
+    ```
+    <svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">
+      <rect width="20" height="10" fill="teal"/>
+    </svg>
+    ```.text,
+  ),
+  alt: "A teal rectangle",
+  width: 2cm,
+)
+
+--- docx-scoped-text docx ---
+// Only the set rules at the top level determine the default text style, not
+// those scoped to a block.
+#set text(size: 12pt)
+#[
+  #set text(font: "Libertinus Serif", size: 20pt)
+  Large
+]
+
+Normal
+
+--- docx-template docx ---
+// The page and text setup of a template's show rule applies to the whole
+// document. A page set rule further down is ignored.
+#let template(title, doc) = {
+  set page(width: 15cm, margin: 2cm)
+  set text(font: "Libertinus Serif", size: 11pt)
+  align(center, title)
+  doc
+}
+
+#show: template.with[Report]
+
+Typst is a markup-based typesetting system.
+
+// Warning: 4-25 page set rule was ignored during DOCX export
+// Hint: 4-25 the page setup is only taken from set rules at the start of the document
+#[#set page(width: 10cm)
+  Narrow
+]
@@ -159,3 +159,8 @@ $ a/sqrt(b) $
     Hello $x$
   ]
 })
+
+--- mathml-custom-html-without-head html ---
+// Error: 2-33 equations require a `<head>` element
+// Hint: 2-33 add a `head` element to the custom `html` element
+#html.html(html.body[Hello $x$])
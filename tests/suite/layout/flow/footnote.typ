@@ -394,12 +394,48 @@ C
 #html.html({
   html.head()
   html.body[
-    // Error: 12-32 footnotes are not currently supported in combination with a custom `<html>` or `<body>` element
-    // Hint: 12-32 you can still use footnotes with a custom footnote show rule
+    // Error: 12-32 footnote is not listed anywhere in the custom `<html>` or `<body>` element
+    // Hint: 12-32 use `footnote.entries()` within the custom element to list the footnotes
+    // Hint: 12-32 you can also use footnotes with a custom footnote show rule
     Hello #footnote[Footnote 1]
   ]
 })
 
+--- footnote-custom-head-html-entries html ---
+#html.html({
+  html.head()
+  html.body[
+    #html.main[Hello #footnote[Footnote 1]]
+    #html.aside(footnote.entries())
+  ]
+})
+
+--- footnote-custom-body-html-unlisted html ---
+#html.body[
+  Hello #footnote[Footnote 1]
+  #footnote.entries()
+  // Error: 10-30 footnote is not listed anywhere in the custom `<html>` or `<body>` element
+  // Hint: 10-30 use `footnote.entries()` within the custom element to list the footnotes
+  // Hint: 10-30 you can also use footnotes with a custom footnote show rule
+  World #footnote[Footnote 2]
+]
+
+--- footnote-entries-per-section html ---
+= Introduction
+Typst#footnote[A] is a system.#footnote[B]
+#footnote.entries()
+
+= Usage
+No notes here.
+#footnote.entries()
+
+= Installation
+It runs everywhere.#footnote[C]
+
+--- footnote-entries-paged paged ---
+A#footnote[B]
+#footnote.entries()
+
 --- footnote-custom-head-html-show-none html ---
 #show footnote: none
 #html.html({
@@ -48,3 +48,65 @@
   "log.txt",
   query(<log>).map(it => it.value).join("\n", default: ""),
 )
+
+--- asset-generated-outside-of-bundle paged ---
+// Error: 2-51 assets are only supported in the bundle target
+// Hint: 2-51 try enabling the bundle target
+#asset.sitemap("sitemap.xml", base: "https://a.b")
+
+--- asset-sitemap-without-base bundle ---
+#document("index.html")[Hi]
+// Error: 2-30 sitemap requires a base URL
+// Hint: 2-30 try setting `base` to the URL at which the bundle is published
+#asset.sitemap("sitemap.xml")
+
+--- asset-feed-without-title bundle ---
+// Error: 2-50 feed requires a title
+// Hint: 2-50 try setting `title` to the name of the website
+#asset.feed("feed.xml", base: "https://a.b/blog")
+
+--- asset-feed-without-date bundle ---
+#asset.feed("feed.xml", base: "https://a.b", title: "Blog", documents: <post>)
+#document("index.html")[Home]
+// Error: 2-31 document `post.html` has no date
+// Hint: 2-31 documents in a feed must have a date
+// Hint: 2-31 try setting the `date` of the document
+#document("post.html")[A post] <post>
+
+--- asset-generated-path-collision bundle ---
+// Hint: 2-28 path is already in use here
+#asset("search.json", "[]")
+// Error: 2-35 path `search.json` occurs multiple times in the bundle
+// Hint: 2-35 search-index paths must be unique in the bundle
+#asset.search-index("search.json")
+
+--- asset-generated bundle ---
+#asset.sitemap("sitemap.xml", base: "https://a.b/")
+#asset.feed("feed.xml", base: "https://a.b", title: "Blog", documents: <post>)
+#asset.search-index("search.json")
+
+#document("index.html", title: [Home])[Hello world.]
+
+#document(
+  "posts/first post.html",
+  title: [First],
+  author: "Ann",
+  date: datetime(year: 2024, month: 1, day: 2),
+)[
+  = Intro
+  Some text.
+
+  More text.
+
+  = Outro <outro>
+  Bye.
+] <post>
+
+#document(
+  "posts/second.html",
+  title: [Second],
+  description: [The sequel],
+  date: datetime(year: 2024, month: 3, day: 4, hour: 5, minute: 6, second: 7),
+)[Again.] <post>
+
+#document("print.pdf")[Paged.]
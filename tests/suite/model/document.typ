@@ -114,6 +114,31 @@ Hello
 // Hint: 2-30 documents exported to an image format only support a single page
 #document("image.png", multi)
 
+--- document-export-options bundle ---
+#set page(width: 72pt, height: 36pt)
+#document("a.pdf", title: [A], export: (standards: "a-2b"))[A]
+#document("b.pdf", title: [B])[B]
+#document("c.png", export: (ppi: 72))[C]
+#document("d.png", export: (ppi: 144))[D]
+#document("e.html", export: (pretty: true))[= E]
+#document("f.html", export: (pretty: false))[= F]
+
+--- document-export-options-unsupported bundle ---
+// Error: 2-50 export option `ppi` is not supported for HTML documents
+// Hint: 2-50 it is only supported for PNG, JPEG, and WebP documents
+#document("index.html", export: (ppi: 72))[Hello]
+
+// Error: 2-61 choose at most one PDF/A standard
+#document("a.pdf", export: (standards: ("a-2b", "a-3b")))[A]
+
+--- document-export-options-bad-pages bundle ---
+// Error: 28-42 page range must end at a page after the start
+#document("a.pdf", export: (pages: "3-1"))[A]
+
+--- document-export-options-bad-key bundle ---
+// Error: 28-37 unexpected key "dpi", valid keys are "standards", "tagged", "pages", "ppi", and "pretty"
+#document("a.pdf", export: (dpi: 72))[A]
+
 --- document-realization-errors bundle ---
 // This test ensures that we show errors from all document realizations at once.
 
@@ -267,6 +267,27 @@ See #metadata(none) <t8>
 
 // Not testing PNG since it does not support named destinations.
 
+--- link-svg-internal paged ---
+// Intra-document links become fragment links to anchors in SVG export. The
+// custom check also exports the pages into separate files.
+#set page(height: 60pt)
+#link(<target>)[To target] \
+#link((page: 2, x: 10pt, y: 20pt))[To position]
+#pagebreak()
+= Target <target>
+
+--- link-pdf-named-destinations pdf ---
+// The custom check exports this with `heading` and `figure` as named
+// destinations and checks the resulting link targets.
+#set page(height: 120pt)
+= Intro <intro>
+#figure(rect(width: 10pt, height: 10pt)) <fig>
+$ x $ <eq>
+#pagebreak()
+#figure(rect(width: 10pt, height: 10pt))
+= Intro again <intro>
+#figure(rect(width: 10pt, height: 10pt)) <late>
+
 --- link-bundle-html-frame bundle ---
 // Test combination of bundle and frame.
 #document("index.html")[
@@ -47,6 +47,65 @@ $ A = 1 $ <eq2>
 #set ref(supplement: none)
 @fig1, @fig2, @eq1, @eq2
 
+--- ref-bundle-label-duplicate bundle ---
+// A label that occurs in multiple other documents is ambiguous.
+#set heading(numbering: "1.")
+#document("a.html")[= A <intro>]
+#document("b.html")[= B <intro>]
+#document("c.html")[
+  // Error: 3-9 label `<intro>` occurs multiple times in the document
+  @intro
+]
+
+--- ref-bundle-label-own-document bundle ---
+// A label that occurs in multiple documents resolves to the one in the
+// referencing document. Headings are numbered across the bundle.
+#set heading(numbering: "1.")
+#document("a.html")[
+  = A <intro>
+  See @intro.
+]
+#document("b.html")[
+  = B
+  = C <intro>
+  See @intro.
+]
+
+--- ref-bundle-figure bundle ---
+// A reference to a figure in another document has the same supplement and
+// number as within that document and links to the other file.
+#document("a.html")[
+  #figure([A], caption: [First]) <first>
+]
+#document("b.html")[
+  #figure([B], caption: [Second]) <second>
+  See @first and @second.
+]
+
+--- ref-bundle-counter-bibliography bundle ---
+// Counters and the bibliography are shared by all documents.
+#let src = ```yaml
+first:
+  type: Book
+  title: The First Book
+  author: Doe, Jane
+second:
+  type: Book
+  title: The Second Book
+  author: Roe, Rick
+```
+#let c = counter("shared")
+#document("a.html")[
+  #c.step()
+  Cite @first.
+]
+#document("b.html")[
+  #c.step()
+  #context [Count: #c.get().first()]
+  Cite @second.
+  #bibliography(bytes(src.text))
+]
+
 --- ref-ambiguous paged ---
 // Test ambiguous reference.
 = Introduction <arrgh>
@@ -0,0 +1,21 @@
+// Test presenter notes. They are invisible in all export formats, so only the
+// absence of visual output is tested here.
+
+--- pdf-note paged empty ---
+#pdf.note[Mention the *acquisition*.]
+#pdf.note[Second note on the same page.]
+
+--- pdf-note-missing-body eval ---
+// Error: 2-12 missing argument: body
+#pdf.note()
+
+--- pdf-note-pdfpc paged empty ---
+// The custom check writes the notes of these pages as pdfpc JSON. The first
+// two pages share a page number, so they form one slide with overlays.
+#pdf.note[First *slide*.]
+#pagebreak()
+#counter(page).update(1)
+#pdf.note[Overlay.]
+#pagebreak()
+#pdf.note[Second slide.]
+#pdf.note[Second note.]
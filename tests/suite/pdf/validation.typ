@@ -52,6 +52,15 @@ https://typst.app/
   ```.text
 ), alt: "Geʽez letter")
 
+--- pdf-validation-pdf-image pdf pdfstandard(a-2b) ---
+#image("/assets/images/star.pdf", alt: "Star")
+
+--- pdf-validation-pdf-image-alt pdf pdfstandard(ua-1) ---
+// Error: 2-34 PDF/UA-1 error: the PDF image is missing alt text
+// Hint: 2-34 text in PDF images is converted to outlines in this export mode, so it cannot be read by assistive technology
+// Hint: 2-34 describe the image with the `alt` parameter
+#image("/assets/images/star.pdf")
+
 --- pdf-validation-bundle bundle pdfstandard(ua-1) ---
 #document(
   "hi.pdf",
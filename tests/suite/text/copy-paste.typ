@@ -6,3 +6,11 @@ The after fira 🏳️‍🌈!
 
 #set text(lang: "ar", font: "Noto Sans Arabic")
 مرحبًا
+
+--- text-copy-paste-svg-text paged svgtext ---
+// Test selectable text in SVG export. The ligatures and the colour glyphs fall
+// back to outlines.
+#set page(width: 120pt, height: auto)
+The after fira 🏳️‍🌈!
+
+#text(font: "New Computer Modern")[Typst *bold* _italic_]
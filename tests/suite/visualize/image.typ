@@ -434,6 +434,29 @@ A #box(image("/assets/images/tiger.jpg", height: 1cm, width: 80%)) B
 // Hint: 8-39 the document only has 1 page
 #image("/assets/images/matplotlib.pdf", page: 2)
 
+--- image-pdf-page-sizes eval ---
+#let sizes = image.page-sizes("/assets/images/matplotlib.pdf")
+#test(sizes.len(), 1)
+#test(type(sizes.first().width), length)
+
+--- image-pdf-pages paged ---
+// Each included page becomes a page of its own, between the surrounding
+// content.
+#set page(width: 120pt, height: 100pt, fill: eastern.lighten(80%))
+Before
+#image.pages("/assets/images/diagrams.pdf", pages: (1, 3))
+After
+#context test(counter(page).final(), (4,))
+
+--- image-pdf-pages-original-size paged ---
+#set page(width: 120pt, height: 100pt)
+#image.pages("/assets/images/matplotlib.pdf", original-size: true)
+
+--- image-pdf-pages-invalid-page eval ---
+// Error: 53-57 page 2 does not exist
+// Hint: 53-57 the document only has 1 page
+#image.pages("/assets/images/matplotlib.pdf", pages: (2,))
+
 --- issue-6869-image-zero-sized paged ---
 // Primarily to ensure that it does not crash in PDF export.
 #image("/assets/images/f2t.jpg", width: 0pt, height: 0pt)